@@ -0,0 +1,146 @@
+//! How many movement phases the adjudicator resolves per second, over
+//! mid-game positions: one written out with a full set of orders, and
+//! the positions a few years of [`HoldAndGrab`] reach, each with orders
+//! drawn at random from every unit's legal orders, supports and convoys
+//! included.
+//!
+//! Run with `cargo bench`. Bot search wants well over 10,000
+//! adjudications a second from this on commodity hardware.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use dipboy::adjudicator::adjudicate;
+use dipboy::bot::{HoldAndGrab, Strategist};
+use dipboy::history::History;
+use dipboy::map::Map;
+use dipboy::order::Order;
+use dipboy::parser::parse_order;
+use dipboy::phase::PhaseKind;
+use dipboy::power::Power;
+use dipboy::state::GameState;
+
+const FALL_1905: &str = r#"{
+  "phase": "Fall 1905 Movement",
+  "units": {
+    "Austria": ["A Vie", "A Bud", "A Ser", "A Gal"],
+    "England": ["F Nwy", "F Nth", "F Nwg", "A Yor", "F Eng"],
+    "France": ["A Bel", "A Bur", "F Mao", "A Pic", "F Bre", "A Spa"],
+    "Germany": ["A Ruh", "A Mun", "F Hol", "A Kie", "A Den", "F Hel"],
+    "Italy": ["A Tyr", "A Tri", "F Ion", "F Tun", "A Ven"],
+    "Russia": ["A War", "A Ukr", "F Sev", "F Swe", "A StP", "A Mos"],
+    "Turkey": ["F Bla", "A Bul", "F Aeg", "A Arm", "A Con"]
+  },
+  "centers": "home"
+}"#;
+
+const FALL_1905_ORDERS: &[(Power, &str)] = &[
+    (Power::Austria, "A Vie H"),
+    (Power::Austria, "A Bud S A Vie"),
+    (Power::Austria, "A Ser - Tri"),
+    (Power::Austria, "A Gal - War"),
+    (Power::England, "A Yor - Bel"),
+    (Power::England, "F Nth C A Yor - Bel"),
+    (Power::England, "F Eng S A Yor - Bel"),
+    (Power::England, "F Nwy - Swe"),
+    (Power::England, "F Nwg - Bar"),
+    (Power::France, "A Bel H"),
+    (Power::France, "A Pic S A Bel"),
+    (Power::France, "A Bur - Mun"),
+    (Power::France, "F Mao - Eng"),
+    (Power::France, "F Bre S F Mao - Eng"),
+    (Power::France, "A Spa - Por"),
+    (Power::Germany, "A Ruh S A Mun"),
+    (Power::Germany, "A Mun H"),
+    (Power::Germany, "F Hol S A Bel"),
+    (Power::Germany, "F Hel - Nth"),
+    (Power::Germany, "A Den - Swe"),
+    (Power::Germany, "A Kie - Ber"),
+    (Power::Italy, "A Tyr - Vie"),
+    (Power::Italy, "A Ven S A Tyr"),
+    (Power::Italy, "A Tri S A Tyr - Vie"),
+    (Power::Italy, "F Ion - Aeg"),
+    (Power::Italy, "F Tun S F Ion - Aeg"),
+    (Power::Russia, "A War S A Ukr - Gal"),
+    (Power::Russia, "A Ukr - Gal"),
+    (Power::Russia, "F Sev - Bla"),
+    (Power::Russia, "F Swe H"),
+    (Power::Russia, "A StP - Nwy"),
+    (Power::Russia, "A Mos - Sev"),
+    (Power::Turkey, "F Bla - Sev"),
+    (Power::Turkey, "A Con H"),
+    (Power::Turkey, "F Aeg - Gre"),
+    (Power::Turkey, "A Arm S F Bla - Sev"),
+    (Power::Turkey, "A Bul - Ser"),
+];
+
+type Phase = (GameState<'static>, HashMap<Power, Vec<Order<'static>>>);
+
+fn main() {
+    let map = Map::standard();
+    let mut phases: Vec<Phase> = Vec::new();
+
+    let state = GameState::from_scenario(map, FALL_1905).expect("a valid position");
+    let mut orders: HashMap<Power, Vec<Order>> = HashMap::new();
+    for &(power, order) in FALL_1905_ORDERS {
+        let order = parse_order(map, order).expect("a valid order");
+        orders.entry(power).or_default().push(order);
+    }
+    phases.push((state, orders));
+
+    let mut history = History::new(GameState::standard());
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    while history.current().phase().year < 1906 {
+        let state = history.current().clone();
+        if state.phase().kind == PhaseKind::Movement && state.phase().year >= 1903 {
+            for _ in 0..8 {
+                let orders = random_orders(&state, &mut seed);
+                phases.push((state.clone(), orders));
+            }
+        }
+        let orders = Power::ALL
+            .iter()
+            .map(|&power| (power, HoldAndGrab.orders(&state, power)))
+            .collect();
+        history.play(orders).expect("no one wins by 1906");
+    }
+
+    let units: usize = phases.iter().map(|(state, _)| state.units().count()).sum();
+    println!(
+        "{} positions, {:.1} units each",
+        phases.len(),
+        units as f64 / phases.len() as f64
+    );
+    let mut adjudications = 0u64;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(3) {
+        for (state, orders) in &phases {
+            std::hint::black_box(adjudicate(orders.clone(), state));
+            adjudications += 1;
+        }
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "{} adjudications in {:.2}s: {:.0} per second",
+        adjudications,
+        elapsed,
+        adjudications as f64 / elapsed
+    );
+}
+
+/// An order for every unit, chosen with a xorshift generator from its
+/// legal orders.
+fn random_orders<'m>(state: &GameState<'m>, seed: &mut u64) -> HashMap<Power, Vec<Order<'m>>> {
+    let mut orders: HashMap<Power, Vec<Order>> = HashMap::new();
+    let mut units: Vec<_> = state.units().collect();
+    units.sort_by_key(|(province, _)| province.abbreviation());
+    for (province, unit) in units {
+        let legal = state.legal_orders(province);
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        let order = legal[(*seed % legal.len() as u64) as usize].clone();
+        orders.entry(unit.power()).or_default().push(order);
+    }
+    orders
+}
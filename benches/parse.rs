@@ -0,0 +1,56 @@
+//! How many orders a second the parser reads, with the default options,
+//! over the corpus of orders players have written in `corpus/`, and how
+//! many of them it reads at all.
+//!
+//! Run with `cargo bench --bench parse`.
+
+use std::time::{Duration, Instant};
+
+use dipboy::map::Map;
+use dipboy::parser::parse_order;
+
+const CORPUS: &[(&str, &str)] = &[
+    ("chat", include_str!("../corpus/chat.txt")),
+    ("face_to_face", include_str!("../corpus/face_to_face.txt")),
+    ("forum", include_str!("../corpus/forum.txt")),
+    ("judge", include_str!("../corpus/judge.txt")),
+];
+
+fn main() {
+    let map = Map::standard();
+    let mut all = Vec::new();
+    for &(name, text) in CORPUS {
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .collect();
+        let parsed = lines
+            .iter()
+            .filter(|line| parse_order(map, line).is_ok())
+            .count();
+        println!(
+            "{}: {} of {} lines parsed ({:.1}%)",
+            name,
+            parsed,
+            lines.len(),
+            100.0 * parsed as f64 / lines.len() as f64
+        );
+        all.extend(lines);
+    }
+
+    let mut lines = 0u64;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(3) {
+        for line in &all {
+            std::hint::black_box(parse_order(map, line)).ok();
+            lines += 1;
+        }
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "{} lines in {:.2}s: {:.0} per second",
+        lines,
+        elapsed,
+        lines as f64 / elapsed
+    );
+}
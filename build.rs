@@ -0,0 +1,265 @@
+//! Compiles the standard map into the crate.
+//!
+//! `Map::standard` includes the file this writes to `OUT_DIR`: a `Map`
+//! expression whose provinces, adjacencies and name trie all borrow from
+//! static tables, read from `src/map/standard.map` as `Map::parse` would
+//! read them. The map test `standard_map_is_compiled_in` checks that the
+//! two agree; the data file stays the only place the map is written down.
+
+extern crate alloc;
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// What `trie.rs` expects of the crate it is compiled into.
+mod prelude {
+    pub(crate) use std::vec;
+}
+
+#[allow(dead_code)]
+#[path = "src/map/trie.rs"]
+mod trie;
+#[allow(dead_code)]
+#[path = "src/map/unicode.rs"]
+mod unicode;
+
+const MAP: &str = "src/map/standard.map";
+
+struct Province<'t> {
+    abbreviation: &'t str,
+    name: &'t str,
+    kind: &'t str,
+    sc: &'t str,
+    coasts: Vec<&'t str>,
+    aliases: Vec<&'t str>,
+    regions: Vec<&'t str>,
+}
+
+fn main() {
+    for path in [MAP, "src/map/trie.rs", "src/map/unicode.rs"] {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+    let text = fs::read_to_string(MAP).expect("src/map/standard.map is readable");
+    let out = Path::new(&env::var("OUT_DIR").expect("cargo sets OUT_DIR")).join("standard_map.rs");
+    fs::write(out, standard_map(&text)).expect("OUT_DIR is writable");
+}
+
+/// A `Map` expression for the map definition `text`, which must be valid.
+fn standard_map(text: &str) -> String {
+    let mut provinces: Vec<Province> = Vec::new();
+    let mut army: Vec<Vec<usize>> = Vec::new();
+    let mut fleet: Vec<Vec<String>> = Vec::new();
+    let mut positions: Vec<Option<(u16, u16)>> = Vec::new();
+    let mut regions: Vec<&str> = Vec::new();
+    let mut unit_kinds: Vec<String> = Vec::new();
+    let mut section = "";
+    for (i, line) in text.lines().enumerate() {
+        let fail = |message: &str| -> ! { panic!("{}:{}: {}", MAP, i + 1, message) };
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name;
+            continue;
+        }
+        let location = |provinces: &[Province], text: &str| {
+            location(provinces, text).unwrap_or_else(|message| fail(&message))
+        };
+        match section {
+            "provinces" => {
+                let columns: Vec<&str> = line.split('|').map(str::trim).collect();
+                let column = |n: usize| columns.get(n).copied().unwrap_or("");
+                provinces.push(Province {
+                    abbreviation: column(0),
+                    name: column(1),
+                    kind: column(2),
+                    sc: column(3),
+                    coasts: column(4).split_whitespace().collect(),
+                    aliases: column(5)
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|alias| !alias.is_empty())
+                        .collect(),
+                    regions: Vec::new(),
+                });
+                army.push(Vec::new());
+                fleet.push(Vec::new());
+                positions.push(None);
+            }
+            "adjacencies" => {
+                let (head, neighbours) = line.split_once(':').unwrap_or_else(|| fail("no colon"));
+                let (unit, from) = head.trim().split_once(' ').unwrap_or(("", head));
+                let (from, from_coast) = location(&provinces, from.trim());
+                for neighbour in neighbours.split_whitespace() {
+                    let (to, to_coast) = location(&provinces, neighbour);
+                    match unit {
+                        "army" => army[from].push(to),
+                        "fleet" => fleet[from].push(format!(
+                            "FleetEdge {{ from_coast: {}, to: {}, to_coast: {} }}",
+                            from_coast, to, to_coast
+                        )),
+                        other => fail(&format!("unknown unit kind {:?}", other)),
+                    }
+                }
+            }
+            "positions" => {
+                let (province, xy) = line.split_once(':').unwrap_or_else(|| fail("no colon"));
+                let xy: Vec<u16> = xy
+                    .split_whitespace()
+                    .map(|n| n.parse().unwrap_or_else(|_| fail("expected x and y")))
+                    .collect();
+                positions[location(&provinces, province.trim()).0] = Some((xy[0], xy[1]));
+            }
+            "regions" => {
+                let (region, members) = line.split_once(':').unwrap_or_else(|| fail("no colon"));
+                let region = region.trim();
+                for member in members.split_whitespace() {
+                    let id = location(&provinces, member).0;
+                    provinces[id].regions.push(region);
+                }
+                regions.push(region);
+            }
+            "units" => {
+                let columns: Vec<&str> = line.split('|').map(str::trim).collect();
+                let column = |n: usize| columns.get(n).copied().unwrap_or("");
+                unit_kinds.push(format!(
+                    "UnitKind {{ id: {}, abbreviation: Cow::Borrowed({:?}), name: Cow::Borrowed({:?}), moves_as: UnitType::{}, mobile: {} }}",
+                    unit_kinds.len(),
+                    column(0),
+                    column(1),
+                    if column(2) == "fleet" { "Fleet" } else { "Army" },
+                    column(3) != "fixed"
+                ));
+            }
+            other => fail(&format!("unknown section {:?}", other)),
+        }
+    }
+
+    let mut matcher = trie::Trie::new();
+    for (index, province) in provinces.iter().enumerate() {
+        matcher.insert(province.name, index);
+        matcher.insert(province.abbreviation, index);
+        for alias in &province.aliases {
+            matcher.insert(alias, index);
+        }
+    }
+
+    let mut map = String::from("// Written by build.rs from src/map/standard.map.\nMap {\n");
+    map.push_str("    provinces: Cow::Borrowed(&[\n");
+    for (id, province) in provinces.iter().enumerate() {
+        let home = match province.sc {
+            "" | "neutral" => "None".to_owned(),
+            power => format!("Some(Power::{})", power_variant(power)),
+        };
+        let _ = writeln!(
+            map,
+            "        Province {{ id: {}, name: Cow::Borrowed({:?}), abbreviation: Cow::Borrowed({:?}), aliases: {}, kind: ProvinceKind::{}, sc: {}, home: {}, coasts: {}, regions: {} }},",
+            id,
+            province.name,
+            province.abbreviation,
+            table(province.aliases.iter().map(|alias| format!("Cow::Borrowed({:?})", alias))),
+            capitalized(province.kind),
+            !province.sc.is_empty(),
+            home,
+            table(province.coasts.iter().map(|&coast| coast_variant(coast))),
+            table(province.regions.iter().map(|region| format!("Cow::Borrowed({:?})", region))),
+        );
+    }
+    map.push_str("    ]),\n");
+    let _ = writeln!(
+        map,
+        "    army: {},",
+        table(
+            army.iter()
+                .map(|moves| table(moves.iter().map(usize::to_string)))
+        )
+    );
+    let _ = writeln!(
+        map,
+        "    fleet: {},",
+        table(fleet.iter().map(|moves| table(moves.iter().cloned())))
+    );
+    let _ = writeln!(
+        map,
+        "    positions: {},",
+        table(positions.iter().map(|xy| format!("{:?}", xy)))
+    );
+    let _ = writeln!(
+        map,
+        "    regions: {},",
+        table(
+            regions
+                .iter()
+                .map(|region| format!("Cow::Borrowed({:?})", region))
+        )
+    );
+    let _ = writeln!(map, "    unit_kinds: {},", table(unit_kinds.into_iter()));
+    let _ = writeln!(
+        map,
+        "    matcher: Trie {{ nodes: {} }},",
+        table(matcher.nodes.iter().map(|node| format!(
+            "trie::Node {{ children: {}, value: {:?} }}",
+            table(node.children.iter().map(|edge| format!("{:?}", edge))),
+            node.value
+        )))
+    );
+    map.push_str("}\n");
+    map
+}
+
+/// The id of the province written `text`, and its coast as a Rust
+/// expression, as in `Spa/nc`.
+fn location(provinces: &[Province], text: &str) -> Result<(usize, String), String> {
+    let (abbreviation, coast) = match text.split_once('/') {
+        Some((abbreviation, coast)) => (abbreviation, Some(coast)),
+        None => (text, None),
+    };
+    let id = provinces
+        .iter()
+        .position(|p| p.abbreviation.eq_ignore_ascii_case(abbreviation))
+        .ok_or_else(|| format!("unknown province {:?}", abbreviation))?;
+    Ok((id, option(coast.map(coast_variant))))
+}
+
+/// A borrowed `Cow` slice of `items`, which are Rust expressions.
+fn table(items: impl Iterator<Item = String>) -> String {
+    format!("Cow::Borrowed(&[{}])", items.collect::<Vec<_>>().join(", "))
+}
+
+/// `Some(item)`, or `None`, as a Rust expression.
+fn option(item: Option<String>) -> String {
+    item.map_or_else(|| "None".to_owned(), |item| format!("Some({})", item))
+}
+
+fn coast_variant(coast: &str) -> String {
+    let variant = match coast.to_ascii_lowercase().as_str() {
+        "nc" => "North",
+        "sc" => "South",
+        "ec" => "East",
+        "wc" => "West",
+        other => panic!("{}: unknown coast {:?}", MAP, other),
+    };
+    format!("Coast::{}", variant)
+}
+
+fn power_variant(power: &str) -> &'static str {
+    [
+        "Austria", "England", "France", "Germany", "Italy", "Russia", "Turkey",
+    ]
+    .iter()
+    .copied()
+    .find(|name| name.eq_ignore_ascii_case(power))
+    .unwrap_or_else(|| panic!("{}: unknown power {:?}", MAP, power))
+}
+
+/// `word` with its first letter capitalized, as in "land" to "Land".
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
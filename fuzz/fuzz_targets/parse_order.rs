@@ -0,0 +1,20 @@
+//! Feeds arbitrary text to the order parser and the province scanner,
+//! which must never panic.
+
+#![no_main]
+
+use dipboy::combinators::Parser;
+use dipboy::map::Map;
+use dipboy::parser::{parse_order_with, parse_orders_lossy, ParseProvince, ParserOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    let map = Map::standard();
+    let _ = parse_order_with(map, &input, &ParserOptions::default());
+    let _ = parse_order_with(map, &input, &ParserOptions::strict());
+    let _ = parse_orders_lossy(map, &input);
+    for (_, rest) in ParseProvince::new(map).parse(&input) {
+        assert!(input.ends_with(rest));
+    }
+});
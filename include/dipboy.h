@@ -0,0 +1,181 @@
+/* Declarations for dipboy's C-ABI exports, built with the `ffi` feature.
+ *
+ * Kept in step with src/ffi by a test, and can be regenerated with
+ *
+ *     cbindgen --config cbindgen.toml --crate dipboy --output include/dipboy.h
+ */
+
+#ifndef DIPBOY_H
+#define DIPBOY_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/* No province. */
+#define DIPBOY_NO_PROVINCE 65535
+
+/* No unit type, coast or reason. */
+#define DIPBOY_NONE 255
+
+#define DIPBOY_HOLD 0
+
+#define DIPBOY_MOVE 1
+
+#define DIPBOY_SUPPORT 2
+
+#define DIPBOY_CONVOY 3
+
+#define DIPBOY_ARMY 0
+
+#define DIPBOY_FLEET 1
+
+#define DIPBOY_NORTH_COAST 0
+
+#define DIPBOY_SOUTH_COAST 1
+
+#define DIPBOY_EAST_COAST 2
+
+#define DIPBOY_WEST_COAST 3
+
+#define DIPBOY_AUSTRIA 0
+
+#define DIPBOY_ENGLAND 1
+
+#define DIPBOY_FRANCE 2
+
+#define DIPBOY_GERMANY 3
+
+#define DIPBOY_ITALY 4
+
+#define DIPBOY_RUSSIA 5
+
+#define DIPBOY_TURKEY 6
+
+#define DIPBOY_ORDERED 0
+
+#define DIPBOY_UNORDERED 1
+
+#define DIPBOY_ILLEGAL 2
+
+/* An order. Fields that do not apply to its kind are `DIPBOY_NONE` or
+ * `DIPBOY_NO_PROVINCE`, and a unit type of `DIPBOY_NONE` means the order
+ * did not name one. */
+typedef struct DipboyOrder {
+  uint8_t kind;
+  uint8_t unit;
+  uint16_t province;
+  /* Where a move goes. */
+  uint16_t destination;
+  /* The coast a move asks for. */
+  uint8_t coast;
+  /* Whether a move asks to go by convoy. */
+  bool via_convoy;
+  /* The unit type in the order a support or convoy is for. */
+  uint8_t target_unit;
+  /* Where the unit a support or convoy is for is. */
+  uint16_t target_province;
+  /* Where that unit is going, or `DIPBOY_NO_PROVINCE` for a support
+   * to hold. */
+  uint16_t target_destination;
+} DipboyOrder;
+
+/* A unit on the board. */
+typedef struct DipboyUnit {
+  uint8_t power;
+  uint8_t unit;
+  /* For fleets on bicoastal provinces; otherwise `DIPBOY_NONE`. */
+  uint8_t coast;
+  uint16_t province;
+} DipboyUnit;
+
+/* An order given by a power. */
+typedef struct DipboyPowerOrder {
+  uint8_t power;
+  struct DipboyOrder order;
+} DipboyPowerOrder;
+
+/* What became of one unit's order. */
+typedef struct DipboyResolution {
+  uint16_t province;
+  uint8_t power;
+  /* `DIPBOY_ORDERED`, `DIPBOY_UNORDERED` or `DIPBOY_ILLEGAL`. Units
+   * that were not ordered, or were ordered illegally, hold. */
+  uint8_t status;
+  /* Why an illegal order was illegal, numbered from 0 in the order of
+   * `IllegalOrder`'s variants; otherwise `DIPBOY_NONE`. */
+  uint8_t reason;
+  bool succeeded;
+  /* The province the unit was dislodged from, or `DIPBOY_NO_PROVINCE`. */
+  uint16_t dislodged_by;
+} DipboyResolution;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/* The message for the last call on this thread that failed. It is valid
+ * until the next call that fails. */
+const char *dipboy_last_error(void);
+
+/* The number of provinces on the standard map. */
+size_t dipboy_province_count(void);
+
+/* The abbreviation of `province`, or null if there is no such province.
+ * The string lives as long as the library is loaded. */
+const char *dipboy_province_abbreviation(uint16_t province);
+
+/* The province with the name or abbreviation `name`, or -1. */
+int32_t dipboy_find_province(const char *name);
+
+/* Parses `text` as an order on the standard map into `order`. Returns 0,
+ * or -1 if it is not an order. */
+int32_t dipboy_parse_into(const char *text, struct DipboyOrder *order);
+
+/* Writes `order` as the parser would display it into `buffer`, which
+ * holds `len` bytes, cutting it short if it does not fit. Returns the
+ * length of the whole text, like `snprintf`, or -1 if the order is not
+ * valid. */
+ptrdiff_t dipboy_format_order(const struct DipboyOrder *order, char *buffer, size_t len);
+
+/* Adjudicates a movement phase on the standard map.
+ *
+ * Writes one resolution for each of the `unit_count` units into
+ * `resolutions`, which has room for `capacity`, and returns how many it
+ * wrote; or returns -1 if a unit or order is not valid, or there is not
+ * room. Orders for provinces without one of the power's units are
+ * ignored. */
+ptrdiff_t dipboy_adjudicate_into(const struct DipboyUnit *units,
+                                size_t unit_count,
+                                const struct DipboyPowerOrder *orders,
+                                size_t order_count,
+                                struct DipboyResolution *resolutions,
+                                size_t capacity);
+
+/* Makes room for `len` bytes of UTF-8 in input `slot`, 0 or 1, and
+ * returns where to write them. */
+uint8_t *dipboy_input(size_t slot, size_t len);
+
+/* Where the result of the last call is, valid until the next call. */
+const uint8_t *dipboy_output(void);
+
+/* `map`. Returns the length of the output. */
+size_t dipboy_map(void);
+
+/* `standard_state`. Returns the length of the output. */
+size_t dipboy_standard_state(void);
+
+/* `parse_order` on input slot 0. Returns the length of the output. */
+size_t dipboy_parse_order(void);
+
+/* `adjudicate` on the state in input slot 0 and the orders in slot 1.
+ * Returns the length of the output. */
+size_t dipboy_adjudicate(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* DIPBOY_H */
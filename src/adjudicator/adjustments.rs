@@ -0,0 +1,256 @@
+//! Adjudication of an adjustment phase, in which powers build units in
+//! their home centers or disband units they can no longer supply.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use super::{RemovalTies, RngSource, Rules, SeededRng};
+use crate::map::{Coast, Map, Province, ProvinceKind};
+use crate::order::UnitType;
+use crate::parser::parse_adjustment;
+use crate::power::Power;
+use crate::state::{GameState, Unit};
+
+/// An order given in an adjustment phase.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Adjustment<'m> {
+    /// `Build A Par`, or `Build F St. Petersburg (nc)`: puts a new unit in
+    /// an empty home center, naming the coast for a fleet in a bicoastal
+    /// one.
+    Build {
+        unit: UnitType,
+        province: &'m Province,
+        coast: Option<Coast>,
+    },
+    /// `Disband Paris`: takes the unit in the province off the board.
+    Disband(&'m Province),
+}
+
+impl<'m> Adjustment<'m> {
+    pub fn province(&self) -> &'m Province {
+        match self {
+            Adjustment::Build { province, .. } | Adjustment::Disband(province) => province,
+        }
+    }
+
+    /// Reads an adjustment as it is displayed, like `Build F St.
+    /// Petersburg (nc)` or `Disband Paris`, or abbreviated, like `build f
+    /// stp/nc`, ignoring case. See [`parse_adjustment`] for the forms
+    /// accepted.
+    pub fn parse(map: &'m Map, text: &str) -> Option<Self> {
+        parse_adjustment(map, text).ok()
+    }
+}
+
+impl<'m> fmt::Display for Adjustment<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Adjustment::Build {
+                unit,
+                province,
+                coast: Some(coast),
+            } => write!(f, "Build {} {} ({})", unit, province, coast),
+            Adjustment::Build { unit, province, .. } => write!(f, "Build {} {}", unit, province),
+            Adjustment::Disband(province) => write!(f, "Disband {}", province),
+        }
+    }
+}
+
+/// Why an adjustment could not be made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AdjustmentError {
+    /// The power has no more builds or disbands to make.
+    TooMany,
+    /// A build was ordered outside the power's home supply centers.
+    NotHomeCenter,
+    /// A build was ordered in a home center the power does not own.
+    NotOwned,
+    /// A build was ordered in a province that already has a unit.
+    Occupied,
+    /// A fleet was to be built inland.
+    WrongUnitType,
+    /// A build named a coast its province does not have, or named one for
+    /// an army or where there is no choice.
+    InvalidCoast,
+    /// A disband was ordered for a province without a unit.
+    NoUnit,
+    /// A disband was ordered for another power's unit.
+    ForeignUnit,
+    /// A fleet was to be built in a bicoastal province without naming
+    /// which coast.
+    MissingCoast,
+}
+
+impl fmt::Display for AdjustmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            AdjustmentError::TooMany => "the power has no more adjustments to make",
+            AdjustmentError::NotHomeCenter => "units can only be built in home supply centers",
+            AdjustmentError::NotOwned => "the power does not own that center",
+            AdjustmentError::Occupied => "there is already a unit there",
+            AdjustmentError::WrongUnitType => "fleets can only be built on the coast",
+            AdjustmentError::InvalidCoast => "the province has no such coast to build on",
+            AdjustmentError::NoUnit => "there is no unit there",
+            AdjustmentError::ForeignUnit => "the unit belongs to another power",
+            AdjustmentError::MissingCoast => "the build must name one of the province's coasts",
+        })
+    }
+}
+
+impl Error for AdjustmentError {}
+
+/// An adjustment that was not made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RejectedAdjustment<'m> {
+    pub power: Power,
+    pub adjustment: Adjustment<'m>,
+    pub reason: AdjustmentError,
+}
+
+/// The outcome of an adjustment phase.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdjustmentResult<'m> {
+    builds: Vec<(&'m Province, Unit)>,
+    disbands: Vec<(&'m Province, Unit)>,
+    removals: Vec<(&'m Province, Unit)>,
+    rejected: Vec<RejectedAdjustment<'m>>,
+}
+
+impl<'m> AdjustmentResult<'m> {
+    /// The units built, in the order they were given.
+    pub fn builds(&self) -> &[(&'m Province, Unit)] {
+        &self.builds
+    }
+
+    /// The units disbanded as their powers ordered.
+    pub fn disbands(&self) -> &[(&'m Province, Unit)] {
+        &self.disbands
+    }
+
+    /// The units removed because their powers did not disband enough, or
+    /// are in civil disorder.
+    pub fn removals(&self) -> &[(&'m Province, Unit)] {
+        &self.removals
+    }
+
+    pub fn rejected(&self) -> &[RejectedAdjustment<'m>] {
+        &self.rejected
+    }
+}
+
+/// Adjudicates an adjustment phase.
+///
+/// Each power may build as many units as it has supply centers beyond its
+/// units, and must disband as many as it has units beyond its centers.
+/// Adjustments beyond those, or that cannot be made, are rejected. A power
+/// that builds fewer units than it may waives the rest; one that disbands
+/// too few has the rest removed for it, as the rulebook removes the units
+/// of a power in civil disorder, whose adjustments are ignored.
+///
+/// Under [`RemovalTies::Random`], ties between units to remove are
+/// drawn from a [`SeededRng`] seeded with the position's
+/// [`zobrist`](GameState::zobrist) hash, so that a position always has
+/// the same units removed. See [`adjudicate_adjustments_with`] to give
+/// the numbers.
+pub fn adjudicate_adjustments<'m>(
+    adjustments: HashMap<Power, Vec<Adjustment<'m>>>,
+    state: &GameState<'m>,
+    rules: &Rules,
+) -> AdjustmentResult<'m> {
+    let mut rng = SeededRng::new(state.zobrist());
+    adjudicate_adjustments_with(adjustments, state, rules, &mut rng)
+}
+
+/// Adjudicates an adjustment phase like [`adjudicate_adjustments`],
+/// taking any random numbers the rules call for from `rng`.
+pub fn adjudicate_adjustments_with<'m>(
+    adjustments: HashMap<Power, Vec<Adjustment<'m>>>,
+    state: &GameState<'m>,
+    rules: &Rules,
+    rng: &mut dyn RngSource,
+) -> AdjustmentResult<'m> {
+    let mut result = AdjustmentResult::default();
+    let mut after = state.clone();
+    for &power in &Power::ALL {
+        let mut allowed = state.adjustment(power);
+        if !state.in_civil_disorder(power) {
+            for adjustment in adjustments.get(&power).into_iter().flatten() {
+                let made = match *adjustment {
+                    Adjustment::Build {
+                        unit,
+                        province,
+                        coast,
+                    } if allowed > 0 => build(&after, power, unit, province, coast).map(|unit| {
+                        after.place_unit(province, unit);
+                        result.builds.push((province, unit));
+                        allowed -= 1;
+                    }),
+                    Adjustment::Disband(province) if allowed < 0 => match after.unit_at(province) {
+                        None => Err(AdjustmentError::NoUnit),
+                        Some(unit) if unit.power != power => Err(AdjustmentError::ForeignUnit),
+                        Some(_) => {
+                            let unit = after.remove_unit(province).expect("the unit is there");
+                            result.disbands.push((province, unit));
+                            allowed += 1;
+                            Ok(())
+                        }
+                    },
+                    _ => Err(AdjustmentError::TooMany),
+                };
+                if let Err(reason) = made {
+                    result.rejected.push(RejectedAdjustment {
+                        power,
+                        adjustment: adjustment.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+        if allowed < 0 {
+            let removals = match rules.removal_ties {
+                RemovalTies::Rulebook => after.civil_disorder_removals(power, rules.removals),
+                RemovalTies::Random => {
+                    after.civil_disorder_removals_with(power, rules.removals, rng)
+                }
+            };
+            for province in removals {
+                let unit = *after.unit_at(province).expect("removals are of units");
+                result.removals.push((province, unit));
+            }
+        }
+    }
+    result
+}
+
+/// Checks a build of `unit` in `province` by `power`.
+fn build(
+    state: &GameState,
+    power: Power,
+    unit: UnitType,
+    province: &Province,
+    coast: Option<Coast>,
+) -> Result<Unit, AdjustmentError> {
+    if !province.is_supply_center() || province.home_power() != Some(power) {
+        return Err(AdjustmentError::NotHomeCenter);
+    }
+    if state.owner(province) != Some(power) {
+        return Err(AdjustmentError::NotOwned);
+    }
+    if state.unit_at(province).is_some() {
+        return Err(AdjustmentError::Occupied);
+    }
+    match unit {
+        UnitType::Army if coast.is_some() => Err(AdjustmentError::InvalidCoast),
+        UnitType::Army => Ok(Unit::army(power)),
+        UnitType::Fleet if province.kind() != ProvinceKind::Coast => {
+            Err(AdjustmentError::WrongUnitType)
+        }
+        UnitType::Fleet => match coast {
+            None if !province.is_bicoastal() => Ok(Unit::fleet(power)),
+            None => Err(AdjustmentError::MissingCoast),
+            Some(coast) if province.coasts().contains(&coast) => Ok(Unit::fleet_on(power, coast)),
+            _ => Err(AdjustmentError::InvalidCoast),
+        },
+    }
+}
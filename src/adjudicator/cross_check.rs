@@ -0,0 +1,514 @@
+//! A second, plainer adjudicator that checks the resolver's answers.
+//!
+//! With the `cross-check` feature, every movement phase [`adjudicate`]
+//! resolves is adjudicated again by iterating to a fixed point: each
+//! decision starts unknown, and strengths are worked out as ranges from
+//! the decisions known so far, until a pass settles nothing more. What is
+//! settled must be true however the rest turns out, so it can be compared
+//! with the resolver's answer without trusting its guesses or backup
+//! rule. Decisions caught in circular movement or a paradox stay unknown,
+//! and are not compared.
+//!
+//! [`adjudicate`]: super::adjudicate
+
+use std::fmt;
+
+use super::resolve::{Entry, Kind, Resolver};
+use super::{AdjudicationResult, ParadoxRule};
+use crate::map::Province;
+use crate::order::Order;
+use crate::power::Power;
+use crate::state::GameState;
+
+/// A decision on which the fixed-point adjudicator and the resolver
+/// disagree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence<'m> {
+    pub province: &'m Province,
+    pub decision: Decision,
+    /// The resolver's answer, which the fixed-point adjudicator reversed.
+    pub resolved: bool,
+}
+
+/// What the adjudicators were deciding about a unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Decision {
+    /// Whether its move succeeded, its support was given, or, as a
+    /// convoying fleet, it held its ground.
+    Succeeded,
+    /// Whether it was dislodged.
+    Dislodged,
+}
+
+impl<'m> fmt::Display for Divergence<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (did, did_not) = match self.decision {
+            Decision::Succeeded => ("succeeded", "did not succeed"),
+            Decision::Dislodged => ("was dislodged", "was not dislodged"),
+        };
+        let (resolved, fixed) = if self.resolved {
+            (did, did_not)
+        } else {
+            (did_not, did)
+        };
+        write!(
+            f,
+            "{}: the resolver found it {}, the fixed point that it {}",
+            self.province.abbreviation(),
+            resolved,
+            fixed
+        )
+    }
+}
+
+/// Adjudicates the movement phase `state` is in again from the orders
+/// `result` adjudicated, and lists the decisions on which the two
+/// disagree, in map order. Other phases have no decisions to compare.
+pub fn cross_check<'m>(
+    state: &GameState<'m>,
+    result: &AdjudicationResult<'m>,
+) -> Vec<Divergence<'m>> {
+    let map = state.map();
+    let mut units = vec![None; map.provinces().count()];
+    for (province, resolution) in result.resolutions() {
+        let kind = match resolution.order() {
+            Order::Hold(_) => Kind::Hold,
+            Order::Move(move_) => Kind::Move {
+                to: move_.destination().id(),
+                convoy: resolution.convoyed(),
+            },
+            Order::Support(support) => Kind::Support {
+                from: support.from().id(),
+                to: support.to().map(Province::id),
+            },
+            Order::Convoy(convoy) => Kind::Convoy {
+                from: convoy.from().id(),
+                to: convoy.to().id(),
+            },
+        };
+        units[province.id()] = Some(Entry {
+            power: resolution.power(),
+            kind,
+        });
+    }
+    let mut fixed = FixedPoint::new(state, units, result.rules().paradox);
+    fixed.run();
+
+    let mut divergences = Vec::new();
+    for (province, resolution) in result.resolutions() {
+        let p = province.id();
+        let mut check = |decision, fixed: Option<bool>, resolved| {
+            if fixed.is_some_and(|fixed| fixed != resolved) {
+                divergences.push(Divergence {
+                    province,
+                    decision,
+                    resolved,
+                });
+            }
+        };
+        if fixed.kind(p) != Some(Kind::Hold) {
+            check(
+                Decision::Succeeded,
+                fixed.success[p],
+                resolution.succeeded(),
+            );
+        }
+        check(
+            Decision::Dislodged,
+            fixed.dislodged(p),
+            resolution.is_dislodged(),
+        );
+    }
+    divergences
+}
+
+/// Panics, listing the divergences, if the resolver's `result` does not
+/// stand up to [`cross_check`].
+pub(super) fn assert_agrees(state: &GameState, result: &AdjudicationResult) {
+    let divergences = cross_check(state, result);
+    if !divergences.is_empty() {
+        let list: Vec<String> = divergences.iter().map(Divergence::to_string).collect();
+        panic!(
+            "adjudicator cross-check failed in {}: {}",
+            state.phase(),
+            list.join("; ")
+        );
+    }
+}
+
+/// The least and greatest a strength could be.
+type Range = (usize, usize);
+
+struct FixedPoint<'m> {
+    units: Vec<Option<Entry>>,
+    attackers: Vec<Vec<usize>>,
+    supporters: Vec<Vec<usize>>,
+    convoys: Vec<Vec<usize>>,
+    /// Each unit's decision, if settled.
+    success: Vec<Option<bool>>,
+    paradox: ParadoxRule,
+    /// Only asked for convoy routes.
+    routes: Resolver<'m>,
+}
+
+impl<'m> FixedPoint<'m> {
+    fn new(state: &GameState<'m>, units: Vec<Option<Entry>>, paradox: ParadoxRule) -> Self {
+        let mut attackers = vec![Vec::new(); units.len()];
+        let mut supporters = vec![Vec::new(); units.len()];
+        let mut convoys = vec![Vec::new(); units.len()];
+        for (province, entry) in units.iter().enumerate() {
+            match entry.map(|entry| entry.kind) {
+                Some(Kind::Move { to, .. }) => attackers[to].push(province),
+                Some(Kind::Support { from, .. }) => supporters[from].push(province),
+                Some(Kind::Convoy { from, .. }) => convoys[from].push(province),
+                Some(Kind::Hold) | None => {}
+            }
+        }
+        let success = units
+            .iter()
+            .map(|entry| match entry.map(|entry| entry.kind) {
+                Some(Kind::Hold) => Some(true),
+                _ => None,
+            })
+            .collect();
+        FixedPoint {
+            routes: Resolver::new(state.map(), units.clone(), paradox),
+            units,
+            attackers,
+            supporters,
+            convoys,
+            success,
+            paradox,
+        }
+    }
+
+    /// Settles decisions until a whole pass settles none.
+    fn run(&mut self) {
+        loop {
+            let mut changed = false;
+            for province in 0..self.units.len() {
+                if self.units[province].is_some() && self.success[province].is_none() {
+                    if let Some(decided) = self.decide(province) {
+                        self.success[province] = Some(decided);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    fn kind(&self, province: usize) -> Option<Kind> {
+        self.units[province].map(|entry| entry.kind)
+    }
+
+    fn power(&self, province: usize) -> Option<Power> {
+        self.units[province].map(|entry| entry.power)
+    }
+
+    fn decide(&self, province: usize) -> Option<bool> {
+        match self.kind(province)? {
+            Kind::Hold => Some(true),
+            Kind::Move { to, .. } => self.move_succeeds(province, to),
+            Kind::Support { to, .. } => self.support_given(province, to),
+            Kind::Convoy { .. } => self.dislodged(province).map(|dislodged| !dislodged),
+        }
+    }
+
+    /// Whether the unit in `province` is dislodged, if that is settled.
+    fn dislodged(&self, province: usize) -> Option<bool> {
+        self.units[province]?;
+        let moved = match self.kind(province) {
+            Some(Kind::Move { .. }) => self.success[province],
+            _ => Some(false),
+        };
+        if moved == Some(true) {
+            return Some(false);
+        }
+        let attacked = self.any(&self.attackers[province]);
+        match (moved, attacked) {
+            (_, Some(false)) => Some(false),
+            (Some(false), Some(true)) => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Whether any of `units` succeeds, if that is settled.
+    fn any(&self, units: &[usize]) -> Option<bool> {
+        let mut unknown = false;
+        for &unit in units {
+            match self.success[unit] {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => unknown = true,
+            }
+        }
+        if unknown {
+            None
+        } else {
+            Some(false)
+        }
+    }
+
+    fn move_succeeds(&self, province: usize, to: usize) -> Option<bool> {
+        let attack = self.attack_strength(province);
+        let resisted = if self.head_to_head(province) {
+            self.defend_strength(to)
+        } else {
+            self.hold_strength(to)
+        };
+        let prevents: Vec<Range> = self.attackers[to]
+            .iter()
+            .filter(|&&other| other != province)
+            .map(|&other| self.prevent_strength(other))
+            .collect();
+        if attack.1 <= resisted.0 || prevents.iter().any(|prevent| attack.1 <= prevent.0) {
+            Some(false)
+        } else if attack.0 > resisted.1 && prevents.iter().all(|prevent| attack.0 > prevent.1) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    fn support_given(&self, province: usize, to: Option<usize>) -> Option<bool> {
+        if !self.support_matches(province) {
+            return Some(false);
+        }
+        let dislodged = self.dislodged(province);
+        if dislodged == Some(true) {
+            return Some(false);
+        }
+        let mut unknown = dislodged.is_none();
+        let power = self.power(province);
+        for &attacker in &self.attackers[province] {
+            if Some(attacker) == to || self.power(attacker) == power {
+                continue;
+            }
+            if self.paradox == ParadoxRule::Dptg && to.is_some_and(|to| self.convoys(to, attacker))
+            {
+                continue;
+            }
+            match self.path(attacker) {
+                Some(true) => return Some(false),
+                Some(false) => {}
+                None => unknown = true,
+            }
+        }
+        if unknown {
+            None
+        } else {
+            Some(true)
+        }
+    }
+
+    fn support_matches(&self, province: usize) -> bool {
+        match self.kind(province) {
+            Some(Kind::Support { from, to: Some(to) }) => {
+                matches!(self.kind(from), Some(Kind::Move { to: dest, .. }) if dest == to)
+            }
+            Some(Kind::Support { from, to: None }) => {
+                matches!(self.kind(from), Some(kind) if !matches!(kind, Kind::Move { .. }))
+            }
+            _ => false,
+        }
+    }
+
+    fn convoys(&self, fleet: usize, army: usize) -> bool {
+        match self.kind(army) {
+            Some(Kind::Move { to, convoy: true }) => {
+                self.kind(fleet) == Some(Kind::Convoy { from: army, to })
+            }
+            _ => false,
+        }
+    }
+
+    fn head_to_head(&self, province: usize) -> bool {
+        match self.kind(province) {
+            Some(Kind::Move { to, convoy: false }) => matches!(
+                self.kind(to),
+                Some(Kind::Move { to: back, convoy: false }) if back == province
+            ),
+            _ => false,
+        }
+    }
+
+    /// Whether the unit in `province` gets to its destination, if that is
+    /// settled.
+    fn path(&self, province: usize) -> Option<bool> {
+        let to = match self.kind(province) {
+            Some(Kind::Move { to, convoy: true }) => to,
+            Some(Kind::Move { .. }) => return Some(true),
+            _ => return Some(false),
+        };
+        let fleets: Vec<usize> = self.convoys[province]
+            .iter()
+            .copied()
+            .filter(|&fleet| self.kind(fleet) == Some(Kind::Convoy { from: province, to }))
+            .collect();
+        let surviving: Vec<usize> = fleets
+            .iter()
+            .copied()
+            .filter(|&fleet| self.success[fleet] == Some(true))
+            .collect();
+        let standing: Vec<usize> = fleets
+            .iter()
+            .copied()
+            .filter(|&fleet| self.success[fleet] != Some(false))
+            .collect();
+        if self.routes.convoy_route(province, to, &surviving) {
+            Some(true)
+        } else if !self.routes.convoy_route(province, to, &standing) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// The supports for the unit in `province` that are, and that might
+    /// be, given, ignoring those of `excluding`.
+    fn supports(&self, province: usize, excluding: Option<Power>) -> Range {
+        let mut range = (0, 0);
+        for &supporter in &self.supporters[province] {
+            if !self.support_matches(supporter)
+                || (excluding.is_some() && self.power(supporter) == excluding)
+            {
+                continue;
+            }
+            match self.success[supporter] {
+                Some(true) => {
+                    range.0 += 1;
+                    range.1 += 1;
+                }
+                Some(false) => {}
+                None => range.1 += 1,
+            }
+        }
+        range
+    }
+
+    fn hold_strength(&self, province: usize) -> Range {
+        match self.kind(province) {
+            None => (0, 0),
+            Some(Kind::Move { .. }) => match self.success[province] {
+                Some(true) => (0, 0),
+                Some(false) => (1, 1),
+                None => (0, 1),
+            },
+            Some(_) => plus_one(self.supports(province, None)),
+        }
+    }
+
+    fn attack_strength(&self, province: usize) -> Range {
+        let to = match self.kind(province) {
+            Some(Kind::Move { to, .. }) => to,
+            _ => return (0, 0),
+        };
+        let path = self.path(province);
+        if path == Some(false) {
+            return (0, 0);
+        }
+        let strength = match self.units[to] {
+            None => plus_one(self.supports(province, None)),
+            Some(defender) => {
+                let leaving = match defender.kind {
+                    Kind::Move { .. } if !self.head_to_head(province) => self.success[to],
+                    _ => Some(false),
+                };
+                let left = plus_one(self.supports(province, None));
+                let stayed = if Some(defender.power) == self.power(province) {
+                    (0, 0)
+                } else {
+                    plus_one(self.supports(province, Some(defender.power)))
+                };
+                match leaving {
+                    Some(true) => left,
+                    Some(false) => stayed,
+                    None => (left.0.min(stayed.0), left.1.max(stayed.1)),
+                }
+            }
+        };
+        match path {
+            Some(_) => strength,
+            None => (0, strength.1),
+        }
+    }
+
+    fn defend_strength(&self, province: usize) -> Range {
+        plus_one(self.supports(province, None))
+    }
+
+    fn prevent_strength(&self, province: usize) -> Range {
+        let to = match self.kind(province) {
+            Some(Kind::Move { to, .. }) => to,
+            _ => return (0, 0),
+        };
+        let path = self.path(province);
+        if path == Some(false) {
+            return (0, 0);
+        }
+        let lost = if self.head_to_head(province) {
+            self.success[to]
+        } else {
+            Some(false)
+        };
+        let strength = plus_one(self.supports(province, None));
+        match (path, lost) {
+            (_, Some(true)) => (0, 0),
+            (Some(true), Some(false)) => strength,
+            _ => (0, strength.1),
+        }
+    }
+}
+
+fn plus_one((least, most): Range) -> Range {
+    (least + 1, most + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::adjudicator::adjudicate_with;
+    use crate::map::Map;
+    use crate::parser::parse_order;
+
+    #[test]
+    fn agrees_and_catches_divergences() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let state = GameState::standard();
+        let orders = HashMap::from([
+            (
+                Power::France,
+                vec![order("A Par - Bur"), order("A Mar S A Par - Bur")],
+            ),
+            (Power::Germany, vec![order("A Mun - Bur")]),
+        ]);
+        let mut result = adjudicate_with(orders, &state, &Default::default());
+        assert_eq!(cross_check(&state, &result), []);
+
+        let paris = map.find_by_abbreviation("Par").unwrap();
+        let i = result
+            .resolutions
+            .iter()
+            .position(|(province, _)| *province == paris)
+            .unwrap();
+        result.resolutions[i].1.succeeded = false;
+        assert_eq!(
+            cross_check(&state, &result),
+            [Divergence {
+                province: paris,
+                decision: Decision::Succeeded,
+                resolved: false,
+            }]
+        );
+        assert_eq!(
+            cross_check(&state, &result)[0].to_string(),
+            "Par: the resolver found it did not succeed, the fixed point that it succeeded"
+        );
+    }
+}
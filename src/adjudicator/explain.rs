@@ -0,0 +1,298 @@
+//! Adjudication explained step by step, as data: the strengths that
+//! decided each move, what became of each support, and the cycles the
+//! backup rule had to settle. Enough to walk a beginner through why a
+//! move bounced.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::resolve::{Fate, Kind};
+use super::{adjudicate_movement, adjudicate_with, AdjudicationResult, Rules};
+use crate::map::Province;
+use crate::order::Order;
+use crate::phase::PhaseKind;
+use crate::power::Power;
+use crate::state::GameState;
+
+/// How a phase was adjudicated.
+///
+/// Only movement phases have steps to explain; for the others, there is
+/// just the result.
+#[derive(Clone, Debug)]
+pub struct Explanation<'m> {
+    result: AdjudicationResult<'m>,
+    moves: Vec<MoveStep<'m>>,
+    supports: Vec<SupportStep<'m>>,
+    cycles: Vec<Cycle<'m>>,
+}
+
+impl<'m> Explanation<'m> {
+    /// The result, exactly as [`adjudicate_with`] gives it.
+    pub fn result(&self) -> &AdjudicationResult<'m> {
+        &self.result
+    }
+
+    pub fn into_result(self) -> AdjudicationResult<'m> {
+        self.result
+    }
+
+    /// Every move adjudicated, in map order of where it started.
+    pub fn moves(&self) -> &[MoveStep<'m>] {
+        &self.moves
+    }
+
+    /// The move of the unit in `province`, if it was ordered to move.
+    pub fn move_from(&self, province: &Province) -> Option<&MoveStep<'m>> {
+        self.moves.iter().find(|step| step.from == province)
+    }
+
+    /// Every support adjudicated, in map order of the supporter.
+    pub fn supports(&self) -> &[SupportStep<'m>] {
+        &self.supports
+    }
+
+    /// The supports given to the unit in `province`.
+    pub fn supports_of<'e>(
+        &'e self,
+        province: &'e Province,
+    ) -> impl Iterator<Item = &'e SupportStep<'m>> + 'e {
+        self.supports
+            .iter()
+            .filter(move |step| step.supported == province)
+    }
+
+    /// The cycles of decisions that depended on each other with no
+    /// consistent answer, in the order the backup rule settled them.
+    pub fn cycles(&self) -> &[Cycle<'m>] {
+        &self.cycles
+    }
+}
+
+/// One line per move, support and cycle, like
+///
+/// ```text
+/// Paris -> Burgundy: attack 1 against hold 1: fails
+/// Marseilles supporting Paris to Burgundy: cut by Gascony
+/// ```
+impl<'m> fmt::Display for Explanation<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.moves {
+            writeln!(f, "{}", step)?;
+        }
+        for step in &self.supports {
+            writeln!(f, "{}", step)?;
+        }
+        for cycle in &self.cycles {
+            writeln!(f, "{}", cycle)?;
+        }
+        Ok(())
+    }
+}
+
+/// The strengths that decided one move.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveStep<'m> {
+    pub from: &'m Province,
+    pub to: &'m Province,
+    /// Its attack strength: one, plus the supports that count for it, or
+    /// none if it could not get there or would dislodge a unit of its own
+    /// power.
+    pub attack: usize,
+    /// Whether the unit in `to` was moving directly into `from`.
+    pub head_to_head: bool,
+    /// What the attack had to beat in `to`: the defend strength of a unit
+    /// met head to head, and otherwise the hold strength of the province.
+    pub resistance: usize,
+    /// The prevent strength of every other move into `to`, by where it
+    /// came from.
+    pub prevents: Vec<(&'m Province, usize)>,
+    pub succeeded: bool,
+}
+
+impl<'m> fmt::Display for MoveStep<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let against = if self.head_to_head { "defend" } else { "hold" };
+        write!(
+            f,
+            "{} -> {}: attack {} against {} {}",
+            self.from, self.to, self.attack, against, self.resistance
+        )?;
+        for (from, prevent) in &self.prevents {
+            write!(f, ", prevent {} from {}", prevent, from)?;
+        }
+        f.write_str(if self.succeeded {
+            ": succeeds"
+        } else {
+            ": fails"
+        })
+    }
+}
+
+/// What became of a support.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SupportOutcome<'m> {
+    /// It was given, and counted.
+    Given,
+    /// The supported unit was not ordered to do what the support said.
+    Mismatched,
+    /// The supporter was caught up in a convoy paradox, and held by the
+    /// [`ParadoxRule`](super::ParadoxRule) in force.
+    Held,
+    /// The supporter was attacked from this province.
+    Cut(&'m Province),
+    /// The supporter was dislodged by the unit from this province.
+    Dislodged(&'m Province),
+}
+
+/// One support and what became of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SupportStep<'m> {
+    /// Where the supporting unit is.
+    pub from: &'m Province,
+    /// Where the supported unit is.
+    pub supported: &'m Province,
+    /// Where the supported unit was to move, or `None` for a support to
+    /// hold.
+    pub to: Option<&'m Province>,
+    pub outcome: SupportOutcome<'m>,
+}
+
+impl<'m> fmt::Display for SupportStep<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} supporting {}", self.from, self.supported)?;
+        match self.to {
+            Some(to) => write!(f, " to {}: ", to)?,
+            None => f.write_str(" to hold: ")?,
+        }
+        match self.outcome {
+            SupportOutcome::Given => f.write_str("given"),
+            SupportOutcome::Mismatched => f.write_str("does not match the order given"),
+            SupportOutcome::Held => f.write_str("held by the paradox rule"),
+            SupportOutcome::Cut(by) => write!(f, "cut by {}", by),
+            SupportOutcome::Dislodged(by) => write!(f, "dislodged by {}", by),
+        }
+    }
+}
+
+/// A cycle of decisions that depended on each other with no consistent
+/// answer, or more than one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle<'m> {
+    /// The provinces of the units whose orders made up the cycle.
+    pub provinces: Vec<&'m Province>,
+    pub kind: CycleKind,
+}
+
+impl<'m> fmt::Display for Cycle<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<String> = self.provinces.iter().map(|p| p.to_string()).collect();
+        match self.kind {
+            CycleKind::CircularMovement => write!(f, "circular movement: {}", names.join(", ")),
+            CycleKind::ConvoyParadox => write!(f, "convoy paradox: {}", names.join(", ")),
+        }
+    }
+}
+
+/// How the backup rule settled a [`Cycle`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CycleKind {
+    /// Units moving in a ring, which all move.
+    CircularMovement,
+    /// A convoy that its own army's attack would disrupt.
+    ConvoyParadox,
+}
+
+/// Adjudicates the phase `state` is in, as [`adjudicate`](super::adjudicate)
+/// does, explaining how.
+pub fn explain<'m>(
+    orders: HashMap<Power, Vec<Order<'m>>>,
+    state: &GameState<'m>,
+) -> Explanation<'m> {
+    explain_with(orders, state, &Rules::default())
+}
+
+/// Adjudicates the phase `state` is in, as [`adjudicate_with`] does,
+/// explaining how.
+pub fn explain_with<'m>(
+    orders: HashMap<Power, Vec<Order<'m>>>,
+    state: &GameState<'m>,
+    rules: &Rules,
+) -> Explanation<'m> {
+    if state.phase().kind != PhaseKind::Movement {
+        return Explanation {
+            result: adjudicate_with(orders, state, rules),
+            moves: Vec::new(),
+            supports: Vec::new(),
+            cycles: Vec::new(),
+        };
+    }
+    let map = state.map();
+    let (result, mut resolver) = adjudicate_movement(&orders, state, rules);
+    let mut moves = Vec::new();
+    let mut supports = Vec::new();
+    for (from, _) in state.units() {
+        let id = from.id();
+        match resolver.kind(id) {
+            Some(Kind::Move { to, .. }) => {
+                let head_to_head = resolver.head_to_head(id);
+                let resistance = if head_to_head {
+                    resolver.defend_strength(to)
+                } else {
+                    resolver.hold_strength(to)
+                };
+                let others = resolver.moving_to(to).to_vec();
+                let prevents = others
+                    .into_iter()
+                    .filter(|&other| other != id)
+                    .map(|other| (map.province(other), resolver.prevent_strength(other)))
+                    .collect();
+                moves.push(MoveStep {
+                    from,
+                    to: map.province(to),
+                    attack: resolver.attack_strength(id),
+                    head_to_head,
+                    resistance,
+                    prevents,
+                    succeeded: resolver.resolve(id),
+                });
+            }
+            Some(Kind::Support {
+                from: supported,
+                to,
+            }) => {
+                let outcome = match resolver.fate(id) {
+                    Fate::Given => SupportOutcome::Given,
+                    Fate::Mismatched => SupportOutcome::Mismatched,
+                    Fate::Held => SupportOutcome::Held,
+                    Fate::Cut(by) => SupportOutcome::Cut(map.province(by)),
+                    Fate::Dislodged(by) => SupportOutcome::Dislodged(map.province(by)),
+                };
+                supports.push(SupportStep {
+                    from,
+                    supported: map.province(supported),
+                    to: to.map(|to| map.province(to)),
+                    outcome,
+                });
+            }
+            _ => {}
+        }
+    }
+    let cycles = resolver
+        .cycles()
+        .iter()
+        .map(|(cycle, circular)| Cycle {
+            provinces: cycle.iter().map(|&id| map.province(id)).collect(),
+            kind: if *circular {
+                CycleKind::CircularMovement
+            } else {
+                CycleKind::ConvoyParadox
+            },
+        })
+        .collect();
+    Explanation {
+        result,
+        moves,
+        supports,
+        cycles,
+    }
+}
@@ -0,0 +1,1831 @@
+//! Adjudication of the phases of a game.
+//!
+//! [`adjudicate`] takes every power's orders at once, as a game server
+//! would at a deadline, and works out what happened to each unit on the
+//! board, or in a retreat phase to each dislodged unit.
+//! [`adjudicate_adjustments`] does the same for the builds and disbands of
+//! an adjustment phase.
+//!
+//! Bots searching ahead adjudicate many phases in a row, so a movement
+//! phase of a mid-game position should take well under a tenth of a
+//! millisecond: more than 10,000 adjudications a second on commodity
+//! hardware. `cargo bench` measures it.
+//!
+//! With the `cross-check` feature, each movement phase is adjudicated a
+//! second time by a slower, plainer method, and a disagreement panics.
+//! See [`cross_check`].
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::map::{Coast, Map, Province, ProvinceKind, ProvinceSet};
+use crate::order::{Hold, Move, Order, UnitType};
+use crate::phase::PhaseKind;
+use crate::power::Power;
+use crate::state::{GameState, Unit};
+
+mod adjustments;
+#[cfg(feature = "cross-check")]
+mod cross_check;
+mod explain;
+mod resolve;
+mod retreats;
+mod rules;
+mod what_if;
+
+pub use adjustments::{
+    adjudicate_adjustments, adjudicate_adjustments_with, Adjustment, AdjustmentError,
+    AdjustmentResult, RejectedAdjustment,
+};
+#[cfg(feature = "cross-check")]
+pub use cross_check::{cross_check, Decision, Divergence};
+pub use explain::{
+    explain, explain_with, Cycle, CycleKind, Explanation, MoveStep, SupportOutcome, SupportStep,
+};
+use resolve::{Entry, Kind, Resolver};
+pub use rules::{ConvoyRules, ParadoxRule, RemovalRules, RemovalTies, RngSource, Rules, SeededRng};
+pub use what_if::WhatIf;
+
+/// Why an order could not be carried out as given.
+///
+/// Orders for units that exist are replaced with holds; orders for units
+/// that don't are rejected outright.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IllegalOrder {
+    /// There is no unit in the ordered province.
+    NoUnit,
+    /// The unit in the ordered province belongs to another power.
+    ForeignUnit,
+    /// The order named the wrong type of unit.
+    WrongUnitType,
+    /// The unit cannot reach the province it was ordered to move to or
+    /// support into.
+    Unreachable,
+    /// A fleet was ordered to a bicoastal province it can reach on more
+    /// than one coast, without saying which.
+    AmbiguousCoast,
+    /// A convoy was ordered from a fleet that is not at sea.
+    NotAtSea,
+    /// An army was ordered to move by convoy, but no chain of fleets was
+    /// ordered to carry it.
+    NoConvoyRoute,
+    /// The order cannot be given in the phase the game is in, like a
+    /// support in a retreat phase, or any order in an adjustment phase.
+    WrongPhase,
+    /// A unit of a kind the map fixes in place, like a garrison, was
+    /// ordered to move or retreat.
+    Immobile,
+}
+
+impl fmt::Display for IllegalOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            IllegalOrder::NoUnit => "there is no unit there",
+            IllegalOrder::ForeignUnit => "the unit belongs to another power",
+            IllegalOrder::WrongUnitType => "the order names the wrong type of unit",
+            IllegalOrder::Unreachable => "the unit cannot reach that province",
+            IllegalOrder::AmbiguousCoast => "the order must say which coast",
+            IllegalOrder::NotAtSea => "only fleets at sea can convoy",
+            IllegalOrder::NoConvoyRoute => "no fleets were ordered to convoy the army",
+            IllegalOrder::WrongPhase => "that order cannot be given in this phase",
+            IllegalOrder::Immobile => "the unit cannot move",
+        })
+    }
+}
+
+impl Error for IllegalOrder {}
+
+/// What becomes of an order, judged on its own before the phase is
+/// adjudicated.
+///
+/// The rulebook has an order for a unit the power does not have refused
+/// when it is given, but an order its unit cannot carry out stands, and
+/// the unit holds instead. Whether an army's convoy route was ordered
+/// depends on the other orders, so a move that needs one is valid here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OrderLegality {
+    Valid,
+    /// The unit will hold, or in a retreat phase be disbanded, instead.
+    ConvertsToHold(IllegalOrder),
+    /// The order applies to no unit of the power's, or cannot be given in
+    /// the phase at all, and is refused.
+    Rejected(IllegalOrder),
+}
+
+/// Judges `power`'s `order` in the phase `state` is in, following `rules`.
+///
+/// The adjudicator rejects and replaces orders the same way.
+pub fn legality(state: &GameState, power: Power, order: &Order, rules: &Rules) -> OrderLegality {
+    trace_span!("legality", %power, %order);
+    let unit = match state.phase().kind {
+        PhaseKind::Movement => state.unit_at(order.province()),
+        PhaseKind::Retreats => state.dislodged_unit(order.province()),
+        PhaseKind::Adjustments => return OrderLegality::Rejected(IllegalOrder::WrongPhase),
+    };
+    let unit = match unit {
+        None => return OrderLegality::Rejected(IllegalOrder::NoUnit),
+        Some(unit) if unit.power != power => {
+            return OrderLegality::Rejected(IllegalOrder::ForeignUnit)
+        }
+        Some(unit) => unit,
+    };
+    let checked = match state.phase().kind {
+        PhaseKind::Retreats => retreats::check(state, unit, order).map(drop),
+        _ => check(state.map(), unit, order, rules).map(drop),
+    };
+    let legality = match checked {
+        Ok(()) => OrderLegality::Valid,
+        Err(reason) => OrderLegality::ConvertsToHold(reason),
+    };
+    trace_event!(?legality);
+    legality
+}
+
+/// How the order that was adjudicated for a unit came about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OrderStatus {
+    /// The unit's power gave it a legal order.
+    Ordered,
+    /// The unit was given no order, and held.
+    Unordered,
+    /// The unit was given an illegal order, and held instead.
+    Illegal(IllegalOrder),
+}
+
+/// What happened to one unit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Resolution<'m> {
+    power: Power,
+    unit: UnitType,
+    order: Order<'m>,
+    submitted: Option<Order<'m>>,
+    status: OrderStatus,
+    succeeded: bool,
+    convoyed: bool,
+    dislodged_by: Option<&'m Province>,
+}
+
+impl<'m> Resolution<'m> {
+    pub fn power(&self) -> Power {
+        self.power
+    }
+
+    pub fn unit(&self) -> UnitType {
+        self.unit
+    }
+
+    /// The order that was adjudicated, with the unit type and any coast
+    /// filled in. This is a hold for unordered units and illegal orders.
+    pub fn order(&self) -> &Order<'m> {
+        &self.order
+    }
+
+    /// The order the unit's power gave it, if any.
+    pub fn submitted(&self) -> Option<&Order<'m>> {
+        self.submitted.as_ref()
+    }
+
+    pub fn status(&self) -> OrderStatus {
+        self.status
+    }
+
+    /// Whether the order succeeded: the unit moved, its support was not
+    /// cut, or, for holds and convoys, it was not dislodged.
+    pub fn succeeded(&self) -> bool {
+        self.succeeded
+    }
+
+    /// Whether the unit was ordered to move by convoy, and a route was
+    /// ordered for it. A unit dislodged by a convoyed army may retreat to
+    /// where the army came from.
+    pub fn convoyed(&self) -> bool {
+        self.convoyed
+    }
+
+    /// The province the dislodging unit came from, if the unit was
+    /// dislodged.
+    pub fn dislodged_by(&self) -> Option<&'m Province> {
+        self.dislodged_by
+    }
+
+    pub fn is_dislodged(&self) -> bool {
+        self.dislodged_by.is_some()
+    }
+}
+
+/// An order that did not apply to any unit of the power that gave it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RejectedOrder<'m> {
+    pub power: Power,
+    pub order: Order<'m>,
+    pub reason: IllegalOrder,
+}
+
+/// The outcome of a movement or retreat phase, keyed by the province each
+/// unit started in.
+///
+/// Adjudicating the same orders in the same position always gives the
+/// same result, down to the order its resolutions are listed in: by
+/// province, in map order.
+///
+/// In a retreat phase, each dislodged unit's order is a move if it was
+/// ordered to retreat somewhere it may, and a hold, standing for its
+/// disbanding, otherwise. A retreat succeeds unless another unit retreats
+/// to the same province.
+#[derive(Clone, Debug)]
+pub struct AdjudicationResult<'m> {
+    /// Sorted by province id.
+    resolutions: Vec<(&'m Province, Resolution<'m>)>,
+    rejected: Vec<RejectedOrder<'m>>,
+    standoffs: ProvinceSet<'m>,
+    rules: Rules,
+}
+
+impl<'m> AdjudicationResult<'m> {
+    /// What happened to the unit that started in `province`.
+    pub fn get(&self, province: &Province) -> Option<&Resolution<'m>> {
+        self.resolutions
+            .binary_search_by_key(&province.id(), |(province, _)| province.id())
+            .ok()
+            .map(|i| &self.resolutions[i].1)
+    }
+
+    /// Every unit's resolution, with the province it started in, in map
+    /// order.
+    pub fn resolutions(&self) -> impl Iterator<Item = (&'m Province, &Resolution<'m>)> {
+        self.resolutions.iter().map(|(province, r)| (*province, r))
+    }
+
+    /// The units that were dislodged, by the province they were
+    /// dislodged from.
+    pub fn dislodged(&self) -> impl Iterator<Item = (&'m Province, &Resolution<'m>)> {
+        self.resolutions().filter(|(_, r)| r.is_dislodged())
+    }
+
+    /// Orders given for provinces without a unit of the ordering power.
+    pub fn rejected(&self) -> &[RejectedOrder<'m>] {
+        &self.rejected
+    }
+
+    /// Provinces left empty by a standoff, which dislodged units may not
+    /// retreat to.
+    pub fn standoffs(&self) -> ProvinceSet<'m> {
+        self.standoffs
+    }
+
+    /// The rules the phase was adjudicated by.
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+}
+
+/// Adjudicates the phase `state` is in.
+///
+/// In a retreat phase, the orders are the dislodged units' retreats, given
+/// as moves; units ordered to do anything else, or nothing, are disbanded,
+/// as are the units of powers in civil disorder, and units that retreat to
+/// the same province. In an adjustment phase, every order is rejected:
+/// builds and disbands are made by [`adjudicate_adjustments`].
+///
+/// In a movement phase, every power's orders are merged. Units without an
+/// order hold, as do all the units of powers in civil disorder, whose
+/// orders are ignored. If a power orders a unit more than once, the last
+/// order counts. Orders that the unit cannot carry out are treated as
+/// holds, per the rulebook, and orders for provinces without one of the
+/// power's units are rejected.
+///
+/// An army moves by convoy if it was ordered to (`via convoy`) or if its
+/// destination is not adjacent, as in the 2000 rulebook. Convoy paradoxes
+/// are settled with the Szykman rule. [`adjudicate_with`] follows other
+/// rules.
+///
+/// Supports go by province, as in section 6.B of the DATC: a fleet may
+/// support a move to a coast it could not reach itself, so long as it can
+/// reach the province, and a coast named for a supported unit need not be
+/// the one it is on.
+pub fn adjudicate<'m>(
+    orders: HashMap<Power, Vec<Order<'m>>>,
+    state: &GameState<'m>,
+) -> AdjudicationResult<'m> {
+    adjudicate_with(orders, state, &Rules::default())
+}
+
+/// Adjudicates the phase `state` is in, following `rules` where rulebooks
+/// differ.
+pub fn adjudicate_with<'m>(
+    orders: HashMap<Power, Vec<Order<'m>>>,
+    state: &GameState<'m>,
+    rules: &Rules,
+) -> AdjudicationResult<'m> {
+    trace_span!("adjudicate", phase = %state.phase());
+    match state.phase().kind {
+        PhaseKind::Movement => {
+            let result = adjudicate_movement(&orders, state, rules).0;
+            #[cfg(feature = "cross-check")]
+            cross_check::assert_agrees(state, &result);
+            result
+        }
+        PhaseKind::Retreats => retreats::adjudicate(orders, state, rules),
+        PhaseKind::Adjustments => {
+            let mut orders: Vec<_> = orders.into_iter().collect();
+            orders.sort_by_key(|(power, _)| *power);
+            AdjudicationResult {
+                resolutions: Vec::new(),
+                rejected: orders
+                    .into_iter()
+                    .flat_map(|(power, orders)| {
+                        orders.into_iter().map(move |order| RejectedOrder {
+                            power,
+                            order,
+                            reason: IllegalOrder::WrongPhase,
+                        })
+                    })
+                    .collect(),
+                standoffs: ProvinceSet::new(state.map()),
+                rules: *rules,
+            }
+        }
+    }
+}
+
+/// Adjudicates a movement phase, returning the resolver along with the
+/// result, for [`explain`] to question.
+fn adjudicate_movement<'m>(
+    orders: &HashMap<Power, Vec<Order<'m>>>,
+    state: &GameState<'m>,
+    rules: &Rules,
+) -> (AdjudicationResult<'m>, Resolver<'m>) {
+    let map = state.map();
+    let provinces = map.provinces().count();
+    let mut submitted: Vec<Option<Order<'m>>> = vec![None; provinces];
+    let mut rejected = Vec::new();
+    for &power in &Power::ALL {
+        if state.in_civil_disorder(power) {
+            continue;
+        }
+        for order in orders.get(&power).into_iter().flatten() {
+            match legality(state, power, order, rules) {
+                OrderLegality::Rejected(reason) => rejected.push(RejectedOrder {
+                    power,
+                    order: order.clone(),
+                    reason,
+                }),
+                _ => submitted[order.province().id()] = Some(order.clone()),
+            }
+        }
+    }
+
+    let mut checked: Vec<Option<(Order<'m>, OrderStatus)>> = vec![None; provinces];
+    for (province, unit) in state.units() {
+        let hold = || {
+            Order::Hold(Hold {
+                unit: Some(unit.unit_type),
+                province,
+                coast: None,
+            })
+        };
+        let checked_order = match &submitted[province.id()] {
+            None => (hold(), OrderStatus::Unordered),
+            Some(order) => match check(map, unit, order, rules) {
+                Ok(order) => (order, OrderStatus::Ordered),
+                Err(reason) => (hold(), OrderStatus::Illegal(reason)),
+            },
+        };
+        checked[province.id()] = Some(checked_order);
+    }
+
+    let mut units = vec![None; provinces];
+    for (province, unit) in state.units() {
+        let kind = match &checked[province.id()]
+            .as_ref()
+            .expect("every unit was checked")
+            .0
+        {
+            Order::Hold(_) => Kind::Hold,
+            Order::Move(move_) => Kind::Move {
+                to: move_.destination.id(),
+                convoy: is_convoyed(map, unit, move_, rules)
+                    || (rules.convoys == ConvoyRules::Rules1982
+                        && convoyed_by_own_fleet(state, &checked, province, move_)),
+            },
+            Order::Support(support) => Kind::Support {
+                from: support.from().id(),
+                to: support.to().map(Province::id),
+            },
+            Order::Convoy(convoy) => Kind::Convoy {
+                from: convoy.from.id(),
+                to: convoy.to.id(),
+            },
+        };
+        units[province.id()] = Some(Entry {
+            power: unit.power,
+            kind,
+        });
+    }
+
+    // A convoyed move needs fleets to carry it before anything else.
+    let routes = Resolver::new(map, units.clone(), rules.paradox);
+    for (province, unit) in state.units() {
+        if let Some(Entry {
+            kind: Kind::Move { to, convoy: true },
+            ..
+        }) = units[province.id()]
+        {
+            let fleets = routes.convoying(province.id(), to);
+            if !routes.convoy_route(province.id(), to, &fleets) {
+                units[province.id()] = Some(Entry {
+                    power: unit.power,
+                    kind: Kind::Hold,
+                });
+                checked[province.id()] = Some((
+                    Order::Hold(Hold {
+                        unit: Some(unit.unit_type),
+                        province,
+                        coast: None,
+                    }),
+                    OrderStatus::Illegal(IllegalOrder::NoConvoyRoute),
+                ));
+            }
+        }
+    }
+
+    let convoyed: Vec<bool> = units
+        .iter()
+        .map(|entry| {
+            matches!(
+                entry,
+                Some(Entry {
+                    kind: Kind::Move { convoy: true, .. },
+                    ..
+                })
+            )
+        })
+        .collect();
+    let mut resolver = Resolver::new(map, units, rules.paradox);
+    trace_span!("resolve");
+    let mut resolutions = Vec::with_capacity(state.occupied().len());
+    let mut resolved = vec![None; provinces];
+    for (province, unit) in state.units() {
+        let (order, status) = checked[province.id()]
+            .take()
+            .expect("every unit was checked");
+        let succeeded = resolver.resolve(province.id());
+        let dislodged_by = resolver
+            .dislodged_by(province.id())
+            .map(|id| map.province(id));
+        resolved[province.id()] = Some(order.is_move() && succeeded);
+        resolutions.push((
+            province,
+            Resolution {
+                power: unit.power,
+                unit: unit.unit_type,
+                order,
+                submitted: submitted[province.id()].take(),
+                status,
+                succeeded,
+                convoyed: convoyed[province.id()],
+                dislodged_by,
+            },
+        ));
+    }
+    let mut standoffs = ProvinceSet::new(map);
+    standoffs.extend(
+        map.provinces()
+            .filter(|&province| resolved[province.id()].unwrap_or(true))
+            .filter(|province| resolver.standoff(province.id())),
+    );
+
+    let result = AdjudicationResult {
+        resolutions,
+        rejected,
+        standoffs,
+        rules: *rules,
+    };
+    (result, resolver)
+}
+
+/// Checks `order` against the map and the unit it was given to, filling
+/// in the unit type and, for fleet moves, the coast.
+fn check<'m>(
+    map: &Map,
+    unit: &Unit,
+    order: &Order<'m>,
+    rules: &Rules,
+) -> Result<Order<'m>, IllegalOrder> {
+    if order
+        .unit()
+        .is_some_and(|unit_type| unit_type != unit.unit_type)
+    {
+        return Err(IllegalOrder::WrongUnitType);
+    }
+    let mut order = order.clone();
+    match &mut order {
+        Order::Hold(hold) => hold.unit = Some(unit.unit_type),
+        Order::Move(move_) => {
+            if !unit.is_mobile(map) {
+                return Err(IllegalOrder::Immobile);
+            }
+            move_.unit = Some(unit.unit_type);
+            move_.coast = match unit.unit_type {
+                UnitType::Army => {
+                    if move_.destination.kind() == ProvinceKind::Sea {
+                        return Err(IllegalOrder::Unreachable);
+                    }
+                    if is_convoyed(map, unit, move_, rules)
+                        && (move_.province.kind() != ProvinceKind::Coast
+                            || move_.destination.kind() != ProvinceKind::Coast)
+                    {
+                        return Err(IllegalOrder::Unreachable);
+                    }
+                    None
+                }
+                UnitType::Fleet => {
+                    if move_.via_convoy {
+                        return Err(IllegalOrder::Unreachable);
+                    }
+                    fleet_coast(map, unit, move_)?
+                }
+            };
+        }
+        Order::Support(support) => {
+            support.unit = Some(unit.unit_type);
+            let target = support.to().unwrap_or_else(|| support.from());
+            let reachable = match unit.unit_type {
+                UnitType::Army => map.army_can_move(support.province, target),
+                UnitType::Fleet => map.fleet_can_reach(support.province, unit.coast, target),
+            };
+            if !reachable {
+                return Err(IllegalOrder::Unreachable);
+            }
+        }
+        Order::Convoy(convoy) => {
+            convoy.unit = Some(unit.unit_type);
+            if unit.unit_type != UnitType::Fleet {
+                return Err(IllegalOrder::WrongUnitType);
+            }
+            if convoy.province.kind() != ProvinceKind::Sea {
+                return Err(IllegalOrder::NotAtSea);
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// The coast a fleet move arrives on, checking that it can get there.
+fn fleet_coast(map: &Map, unit: &Unit, move_: &Move) -> Result<Option<Coast>, IllegalOrder> {
+    let coasts: Vec<Option<Coast>> = map
+        .fleet_neighbours(move_.province, unit.coast)
+        .filter(|(province, _)| *province == move_.destination)
+        .map(|(_, coast)| coast)
+        .collect();
+    match (move_.coast, coasts.as_slice()) {
+        (_, []) => Err(IllegalOrder::Unreachable),
+        (Some(coast), _) if move_.destination.is_bicoastal() => {
+            if coasts.contains(&Some(coast)) {
+                Ok(Some(coast))
+            } else {
+                Err(IllegalOrder::Unreachable)
+            }
+        }
+        (_, [coast]) => Ok(*coast),
+        (_, _) => Err(IllegalOrder::AmbiguousCoast),
+    }
+}
+
+/// Whether an army's move goes by convoy.
+fn is_convoyed(map: &Map, unit: &Unit, move_: &Move, rules: &Rules) -> bool {
+    unit.unit_type == UnitType::Army
+        && if map.army_can_move(move_.province, move_.destination) {
+            move_.via_convoy && rules.convoys != ConvoyRules::Rules1971
+        } else {
+            true
+        }
+}
+
+/// Whether a fleet of the same power as the army in `province` was
+/// ordered to convoy its move.
+fn convoyed_by_own_fleet(
+    state: &GameState,
+    checked: &[Option<(Order, OrderStatus)>],
+    province: &Province,
+    move_: &Move,
+) -> bool {
+    let power = state.unit_at(province).map(|unit| unit.power);
+    checked.iter().flatten().any(|(order, _)| match order {
+        Order::Convoy(convoy) => {
+            convoy.from == province
+                && convoy.to == move_.destination
+                && state.unit_at(convoy.province).map(|unit| unit.power) == power
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_order;
+    use Power::*;
+
+    fn province(abbreviation: &str) -> &'static Province {
+        Map::standard()
+            .find_by_abbreviation(abbreviation)
+            .expect("known province")
+    }
+
+    /// Sets up units written as "A Par" or "F Spa/nc".
+    fn state(units: &[(Power, &str)]) -> GameState<'static> {
+        let mut state = GameState::new(Map::standard());
+        for &(power, unit) in units {
+            let (unit_type, location) = unit.split_at(2);
+            let (abbreviation, coast) = match location.split_once('/') {
+                Some((abbreviation, coast)) => (abbreviation, Some(coast.parse().unwrap())),
+                None => (location, None),
+            };
+            let unit = match (unit_type, coast) {
+                ("A ", _) => Unit::army(power),
+                ("F ", None) => Unit::fleet(power),
+                (_, Some(coast)) => Unit::fleet_on(power, coast),
+                _ => panic!("bad unit {:?}", unit),
+            };
+            state.place_unit(province(abbreviation), unit);
+        }
+        state
+    }
+
+    fn orders(orders: &[(Power, &str)]) -> HashMap<Power, Vec<Order<'static>>> {
+        let mut map: HashMap<Power, Vec<Order<'static>>> = HashMap::new();
+        for &(power, order) in orders {
+            let order = parse_order(Map::standard(), order).expect("valid order");
+            map.entry(power).or_default().push(order);
+        }
+        map
+    }
+
+    fn run(units: &[(Power, &str)], given: &[(Power, &str)]) -> AdjudicationResult<'static> {
+        adjudicate(orders(given), &state(units))
+    }
+
+    fn run_with(
+        units: &[(Power, &str)],
+        given: &[(Power, &str)],
+        rules: Rules,
+    ) -> AdjudicationResult<'static> {
+        adjudicate_with(orders(given), &state(units), &rules)
+    }
+
+    fn succeeded(result: &AdjudicationResult, abbreviation: &str) -> bool {
+        result.get(province(abbreviation)).unwrap().succeeded()
+    }
+
+    fn dislodged_by(
+        result: &AdjudicationResult<'static>,
+        abbreviation: &str,
+    ) -> Option<&'static str> {
+        result
+            .get(province(abbreviation))
+            .unwrap()
+            .dislodged_by()
+            .map(Province::abbreviation)
+    }
+
+    #[test]
+    fn standoff() {
+        let result = run(
+            &[(France, "A Par"), (Germany, "A Mun")],
+            &[(France, "A Par - Bur"), (Germany, "A Mun - Bur")],
+        );
+        assert!(!succeeded(&result, "Par"));
+        assert!(!succeeded(&result, "Mun"));
+        assert_eq!(
+            result.standoffs().iter().collect::<Vec<_>>(),
+            [province("Bur")]
+        );
+    }
+
+    #[test]
+    fn order_legality() {
+        let state = state(&[(France, "A Par"), (England, "A Lon"), (Germany, "A Mun")]);
+        let legality = |power, text| {
+            let order = parse_order(Map::standard(), text).unwrap();
+            legality(&state, power, &order, &Rules::default())
+        };
+        assert_eq!(legality(France, "A Par - Bur"), OrderLegality::Valid);
+        // Whether a convoy is ordered depends on the other orders.
+        assert_eq!(legality(England, "A Lon - Bre"), OrderLegality::Valid);
+        assert_eq!(
+            legality(France, "A Par - Mun"),
+            OrderLegality::ConvertsToHold(IllegalOrder::Unreachable)
+        );
+        assert_eq!(
+            legality(France, "F Par H"),
+            OrderLegality::ConvertsToHold(IllegalOrder::WrongUnitType)
+        );
+        assert_eq!(
+            legality(France, "A Mun H"),
+            OrderLegality::Rejected(IllegalOrder::ForeignUnit)
+        );
+        assert_eq!(
+            legality(France, "A Mar H"),
+            OrderLegality::Rejected(IllegalOrder::NoUnit)
+        );
+
+        let mut winter = state.clone();
+        winter.set_phase("Winter 1901 Adjustments".parse().unwrap());
+        let order = parse_order(Map::standard(), "A Par H").unwrap();
+        assert_eq!(
+            super::legality(&winter, France, &order, &Rules::default()),
+            OrderLegality::Rejected(IllegalOrder::WrongPhase)
+        );
+    }
+
+    #[test]
+    fn supported_attack_dislodges() {
+        let result = run(
+            &[(France, "A Bur"), (Germany, "A Mun"), (Germany, "A Ruh")],
+            &[(Germany, "A Mun - Bur"), (Germany, "A Ruh S A Mun - Bur")],
+        );
+        assert!(succeeded(&result, "Mun"));
+        assert_eq!(dislodged_by(&result, "Bur"), Some("Mun"));
+        assert_eq!(
+            result.get(province("Bur")).unwrap().status(),
+            OrderStatus::Unordered
+        );
+    }
+
+    #[test]
+    fn support_is_cut() {
+        let result = run(
+            &[
+                (France, "A Bur"),
+                (France, "A Bel"),
+                (Germany, "A Mun"),
+                (Germany, "A Ruh"),
+            ],
+            &[
+                (France, "A Bel - Ruh"),
+                (Germany, "A Mun - Bur"),
+                (Germany, "A Ruh S A Mun - Bur"),
+            ],
+        );
+        assert!(!succeeded(&result, "Ruh"));
+        assert!(!succeeded(&result, "Mun"));
+        assert_eq!(dislodged_by(&result, "Bur"), None);
+    }
+
+    #[test]
+    fn cannot_dislodge_own_unit() {
+        let result = run(
+            &[(Germany, "A Bur"), (Germany, "A Mun"), (Germany, "A Ruh")],
+            &[(Germany, "A Mun - Bur"), (Germany, "A Ruh S A Mun - Bur")],
+        );
+        assert!(!succeeded(&result, "Mun"));
+        assert_eq!(dislodged_by(&result, "Bur"), None);
+    }
+
+    #[test]
+    fn head_to_head() {
+        let units = [(France, "A Bur"), (Germany, "A Mun"), (Germany, "A Ruh")];
+        let result = run(&units, &[(France, "A Bur - Mun"), (Germany, "A Mun - Bur")]);
+        assert!(!succeeded(&result, "Bur"));
+        assert!(!succeeded(&result, "Mun"));
+
+        let result = run(
+            &units,
+            &[
+                (France, "A Bur - Mun"),
+                (Germany, "A Mun - Bur"),
+                (Germany, "A Ruh S A Mun - Bur"),
+            ],
+        );
+        assert!(succeeded(&result, "Mun"));
+        assert_eq!(dislodged_by(&result, "Bur"), Some("Mun"));
+    }
+
+    #[test]
+    fn circular_movement() {
+        let result = run(
+            &[(Turkey, "F Ank"), (Turkey, "A Con"), (Turkey, "A Smy")],
+            &[
+                (Turkey, "F Ank - Con"),
+                (Turkey, "A Con - Smy"),
+                (Turkey, "A Smy - Ank"),
+            ],
+        );
+        assert!(succeeded(&result, "Ank"));
+        assert!(succeeded(&result, "Con"));
+        assert!(succeeded(&result, "Smy"));
+    }
+
+    #[test]
+    fn convoy() {
+        let result = run(
+            &[(England, "A Lon"), (England, "F Nth")],
+            &[(England, "A Lon - Bel"), (England, "F Nth C A Lon - Bel")],
+        );
+        assert!(succeeded(&result, "Lon"));
+    }
+
+    #[test]
+    fn convoy_paradox_fails_the_convoyed_army() {
+        // DATC 6.F.14.
+        let result = run(
+            &[
+                (England, "F Lon"),
+                (England, "F Wal"),
+                (France, "A Bre"),
+                (France, "F Eng"),
+            ],
+            &[
+                (England, "F Lon S F Wal - Eng"),
+                (England, "F Wal - Eng"),
+                (France, "A Bre - Lon"),
+                (France, "F Eng C A Bre - Lon"),
+            ],
+        );
+        assert!(succeeded(&result, "Wal"));
+        assert!(!succeeded(&result, "Bre"));
+        assert_eq!(dislodged_by(&result, "Eng"), Some("Wal"));
+    }
+
+    #[test]
+    fn identical_inputs_give_identical_results() {
+        // A convoy paradox, a three-way rotation, a standoff and a
+        // dislodgement, each rebuilt from scratch for every run so that
+        // nothing carries over between runs but the inputs.
+        let units = [
+            (England, "F Lon"),
+            (England, "F Wal"),
+            (France, "A Bre"),
+            (France, "F Eng"),
+            (Turkey, "F Ank"),
+            (Turkey, "A Con"),
+            (Turkey, "A Smy"),
+            (Austria, "A Vie"),
+            (Italy, "A Ven"),
+            (Germany, "A Mun"),
+            (Germany, "A Sil"),
+            (Russia, "A War"),
+        ];
+        let given = [
+            (England, "F Lon S F Wal - Eng"),
+            (England, "F Wal - Eng"),
+            (France, "A Bre - Lon"),
+            (France, "F Eng C A Bre - Lon"),
+            (Turkey, "F Ank - Con"),
+            (Turkey, "A Con - Smy"),
+            (Turkey, "A Smy - Ank"),
+            (Austria, "A Vie - Tyr"),
+            (Italy, "A Ven - Tyr"),
+            (Germany, "A Mun S A Sil - War"),
+            (Germany, "A Sil - War"),
+            (Russia, "A War H"),
+        ];
+        let serialized = || {
+            let before = state(&units);
+            let result = adjudicate(orders(&given), &before);
+            let after = before.apply(&result);
+            format!("{:?}\n{}", result, after.to_json())
+        };
+        let first = serialized();
+        assert!(first.contains("Tyr"));
+        for _ in 0..100 {
+            assert_eq!(serialized(), first);
+        }
+    }
+
+    #[test]
+    fn explains_strengths_supports_and_cycles() {
+        let units = [
+            (France, "A Par"),
+            (France, "A Mar"),
+            (Germany, "A Bur"),
+            (Germany, "A Gas"),
+            (Turkey, "F Ank"),
+            (Turkey, "A Con"),
+            (Turkey, "A Smy"),
+        ];
+        let given = [
+            (France, "A Par - Bur"),
+            (France, "A Mar S A Par - Bur"),
+            (Germany, "A Bur H"),
+            (Germany, "A Gas - Mar"),
+            (Turkey, "F Ank - Con"),
+            (Turkey, "A Con - Smy"),
+            (Turkey, "A Smy - Ank"),
+        ];
+        let explanation = explain(orders(&given), &state(&units));
+        let step = explanation.move_from(province("Par")).unwrap();
+        assert_eq!((step.attack, step.resistance), (1, 1));
+        assert!(!step.head_to_head && !step.succeeded);
+        let supports: Vec<_> = explanation.supports_of(province("Par")).collect();
+        assert_eq!(supports.len(), 1);
+        assert_eq!(supports[0].outcome, SupportOutcome::Cut(province("Gas")));
+        assert_eq!(explanation.cycles().len(), 1);
+        assert_eq!(explanation.cycles()[0].kind, CycleKind::CircularMovement);
+        assert_eq!(explanation.cycles()[0].provinces.len(), 3);
+        assert!(explanation
+            .to_string()
+            .contains("Marseilles supporting Paris to Burgundy: cut by Gascony"));
+        assert!(explanation
+            .result()
+            .get(province("Con"))
+            .unwrap()
+            .succeeded());
+    }
+
+    #[test]
+    fn convoys_to_adjacent_provinces_by_rulebook() {
+        // DATC 6.G.1, under each rulebook, with and without "via convoy".
+        let units = [(England, "A Nwy"), (England, "F Ska"), (Russia, "A Swe")];
+        let swaps = |convoys, order| {
+            let rules = Rules {
+                convoys,
+                ..Rules::default()
+            };
+            let given = [
+                (England, order),
+                (England, "F Ska C A Nwy - Swe"),
+                (Russia, "A Swe - Nwy"),
+            ];
+            let result = run_with(&units, &given, rules);
+            succeeded(&result, "Nwy") && succeeded(&result, "Swe")
+        };
+        assert!(!swaps(ConvoyRules::Rules1971, "A Nwy - Swe"));
+        assert!(!swaps(ConvoyRules::Rules1971, "A Nwy - Swe via convoy"));
+        assert!(swaps(ConvoyRules::Rules1982, "A Nwy - Swe"));
+        assert!(swaps(ConvoyRules::Rules1982, "A Nwy - Swe via convoy"));
+        assert!(!swaps(ConvoyRules::Rules2000, "A Nwy - Swe"));
+        assert!(swaps(ConvoyRules::Rules2000, "A Nwy - Swe via convoy"));
+    }
+
+    #[test]
+    fn paradox_rules() {
+        // DATC 6.F.14 comes out the same either way. With a second route
+        // the army is in no paradox, and only under the DPTG rule does it
+        // leave the support against its other fleet uncut.
+        let simple = [
+            (England, "F Lon"),
+            (England, "F Wal"),
+            (France, "A Bre"),
+            (France, "F Eng"),
+        ];
+        let simple_orders = [
+            (England, "F Lon S F Wal - Eng"),
+            (England, "F Wal - Eng"),
+            (France, "A Bre - Lon"),
+            (France, "F Eng C A Bre - Lon"),
+        ];
+        let two_routes = [
+            (France, "A Tun"),
+            (France, "F Tys"),
+            (France, "F Ion"),
+            (Italy, "F Rom"),
+            (Italy, "F Nap"),
+        ];
+        let two_routes_orders = [
+            (France, "A Tun - Nap"),
+            (France, "F Tys C A Tun - Nap"),
+            (France, "F Ion C A Tun - Nap"),
+            (Italy, "F Rom - Tys"),
+            (Italy, "F Nap S F Rom - Tys"),
+        ];
+        for (paradox, tys_dislodged) in [(ParadoxRule::Szykman, false), (ParadoxRule::Dptg, true)] {
+            let rules = Rules {
+                paradox,
+                ..Rules::default()
+            };
+            let result = run_with(&simple, &simple_orders, rules);
+            assert!(!succeeded(&result, "Bre"));
+            assert_eq!(dislodged_by(&result, "Eng"), Some("Wal"));
+
+            let result = run_with(&two_routes, &two_routes_orders, rules);
+            assert!(!succeeded(&result, "Tun"));
+            assert_eq!(dislodged_by(&result, "Tys").is_some(), tys_dislodged);
+        }
+    }
+
+    #[test]
+    fn datc_section_f() {
+        let szykman = Rules::default();
+        let all_hold = Rules {
+            paradox: ParadoxRule::AllHold,
+            ..Rules::default()
+        };
+        let both = [szykman, all_hold];
+
+        // 6.F.1: fleets in coastal provinces cannot convoy.
+        for rules in both {
+            let result = run_with(
+                &[
+                    (Turkey, "A Gre"),
+                    (Turkey, "F Aeg"),
+                    (Turkey, "F Con"),
+                    (Turkey, "F Bla"),
+                ],
+                &[
+                    (Turkey, "A Gre - Sev"),
+                    (Turkey, "F Aeg C A Gre - Sev"),
+                    (Turkey, "F Con C A Gre - Sev"),
+                    (Turkey, "F Bla C A Gre - Sev"),
+                ],
+                rules,
+            );
+            assert_eq!(
+                result.get(province("Gre")).unwrap().status(),
+                OrderStatus::Illegal(IllegalOrder::NoConvoyRoute)
+            );
+        }
+
+        // 6.F.2 and 6.F.3: a convoyed army bounces as usual, and can be
+        // supported.
+        let units = [
+            (England, "F Eng"),
+            (England, "A Lon"),
+            (England, "F Mao"),
+            (France, "A Par"),
+        ];
+        let orders = [
+            (England, "F Eng C A Lon - Bre"),
+            (England, "A Lon - Bre"),
+            (France, "A Par - Bre"),
+        ];
+        let mut supported = orders.to_vec();
+        supported.push((England, "F Mao S A Lon - Bre"));
+        for rules in both {
+            let result = run_with(&units, &orders, rules);
+            assert!(!succeeded(&result, "Lon"));
+            assert!(!succeeded(&result, "Par"));
+            let result = run_with(&units, &supported, rules);
+            assert!(succeeded(&result, "Lon"));
+            assert!(!succeeded(&result, "Par"));
+        }
+
+        // 6.F.4 and 6.F.5: a convoy is only disrupted by dislodging the
+        // fleet, not by attacking it.
+        let units = [
+            (England, "F Nth"),
+            (England, "A Lon"),
+            (France, "F Eng"),
+            (France, "F Bel"),
+            (Germany, "F Ska"),
+            (Germany, "F Den"),
+        ];
+        let attacked = [
+            (England, "F Nth C A Lon - Hol"),
+            (England, "A Lon - Hol"),
+            (Germany, "F Ska - Nth"),
+        ];
+        let mut beleaguered = attacked.to_vec();
+        beleaguered.extend([
+            (France, "F Eng - Nth"),
+            (France, "F Bel S F Eng - Nth"),
+            (Germany, "F Den S F Ska - Nth"),
+        ]);
+        for rules in both {
+            for orders in [&attacked[..], &beleaguered] {
+                let result = run_with(&units, orders, rules);
+                assert!(succeeded(&result, "Lon"));
+                assert_eq!(dislodged_by(&result, "Nth"), None);
+            }
+        }
+
+        // 6.F.6: a dislodged convoy does not cut support.
+        for rules in both {
+            let result = run_with(
+                &[
+                    (England, "F Nth"),
+                    (England, "A Lon"),
+                    (Germany, "A Hol"),
+                    (Germany, "A Bel"),
+                    (Germany, "F Hel"),
+                    (Germany, "F Ska"),
+                    (France, "A Pic"),
+                    (France, "A Bur"),
+                ],
+                &[
+                    (England, "F Nth C A Lon - Hol"),
+                    (England, "A Lon - Hol"),
+                    (Germany, "A Hol S A Bel"),
+                    (Germany, "A Bel S A Hol"),
+                    (Germany, "F Hel S F Ska - Nth"),
+                    (Germany, "F Ska - Nth"),
+                    (France, "A Pic - Bel"),
+                    (France, "A Bur S A Pic - Bel"),
+                ],
+                rules,
+            );
+            assert_eq!(dislodged_by(&result, "Nth"), Some("Ska"));
+            assert!(!succeeded(&result, "Lon"));
+            assert!(succeeded(&result, "Hol"));
+            assert!(!succeeded(&result, "Pic"));
+            assert_eq!(dislodged_by(&result, "Bel"), None);
+        }
+
+        // 6.F.7 and 6.F.8: nor does it make a standoff where the army was
+        // going, so the fleet may retreat there, or another unit move in.
+        let units = [
+            (England, "F Nth"),
+            (England, "A Lon"),
+            (Germany, "F Hel"),
+            (Germany, "F Ska"),
+            (Germany, "A Bel"),
+        ];
+        let orders = [
+            (England, "F Nth C A Lon - Hol"),
+            (England, "A Lon - Hol"),
+            (Germany, "F Hel S F Ska - Nth"),
+            (Germany, "F Ska - Nth"),
+        ];
+        let mut bel = orders.to_vec();
+        bel.push((Germany, "A Bel - Hol"));
+        for rules in both {
+            let before = state(&units);
+            let result = adjudicate_with(self::orders(&orders), &before, &rules);
+            assert_eq!(dislodged_by(&result, "Nth"), Some("Ska"));
+            assert!(result.standoffs().is_empty());
+            let position = before.apply(&result);
+            assert!(position
+                .retreats(province("Nth"))
+                .contains(&(province("Hol"), None)));
+            let retreat =
+                adjudicate_with(self::orders(&[(England, "F Nth - Hol")]), &position, &rules);
+            assert!(succeeded(&retreat, "Nth"));
+
+            let result = run_with(&units, &bel, rules);
+            assert!(succeeded(&result, "Bel"));
+        }
+
+        // 6.F.9 to 6.F.13: while one route is left, dislodging a fleet on
+        // another does not disrupt the convoy, whosever fleets they are.
+        type Placed<'s> = &'s [(Power, &'s str)];
+        let cases: [(Placed, Placed); 5] = [
+            (
+                &[
+                    (England, "F Eng"),
+                    (England, "F Nth"),
+                    (England, "A Lon"),
+                    (France, "F Bre"),
+                    (France, "F Mao"),
+                ],
+                &[
+                    (England, "F Eng C A Lon - Bel"),
+                    (England, "F Nth C A Lon - Bel"),
+                    (England, "A Lon - Bel"),
+                    (France, "F Bre S F Mao - Eng"),
+                    (France, "F Mao - Eng"),
+                ],
+            ),
+            (
+                &[
+                    (England, "F Nth"),
+                    (England, "A Lon"),
+                    (Germany, "F Eng"),
+                    (France, "F Bre"),
+                    (France, "F Mao"),
+                ],
+                &[
+                    (England, "F Nth C A Lon - Bel"),
+                    (England, "A Lon - Bel"),
+                    (Germany, "F Eng C A Lon - Bel"),
+                    (France, "F Bre S F Mao - Eng"),
+                    (France, "F Mao - Eng"),
+                ],
+            ),
+            (
+                &[
+                    (England, "A Lon"),
+                    (Germany, "F Eng"),
+                    (Russia, "F Nth"),
+                    (France, "F Bre"),
+                    (France, "F Mao"),
+                ],
+                &[
+                    (England, "A Lon - Bel"),
+                    (Germany, "F Eng C A Lon - Bel"),
+                    (Russia, "F Nth C A Lon - Bel"),
+                    (France, "F Bre S F Mao - Eng"),
+                    (France, "F Mao - Eng"),
+                ],
+            ),
+            (
+                &[
+                    (England, "F Eng"),
+                    (England, "A Lon"),
+                    (England, "F Iri"),
+                    (France, "F Nao"),
+                    (France, "F Mao"),
+                ],
+                &[
+                    (England, "F Eng C A Lon - Bel"),
+                    (England, "A Lon - Bel"),
+                    (England, "F Iri C A Lon - Bel"),
+                    (France, "F Nao S F Mao - Iri"),
+                    (France, "F Mao - Iri"),
+                ],
+            ),
+            (
+                &[
+                    (England, "A Lon"),
+                    (England, "F Nth"),
+                    (France, "F Eng"),
+                    (Germany, "F Hol"),
+                    (Germany, "F Den"),
+                ],
+                &[
+                    (England, "A Lon - Bel"),
+                    (England, "F Nth C A Lon - Bel"),
+                    (France, "F Eng C A Lon - Bel"),
+                    (Germany, "F Hol S F Den - Nth"),
+                    (Germany, "F Den - Nth"),
+                ],
+            ),
+        ];
+        for rules in both {
+            for (units, orders) in cases {
+                let result = run_with(units, orders, rules);
+                assert!(succeeded(&result, "Lon"));
+                assert_eq!(result.dislodged().count(), 1);
+            }
+        }
+
+        // 6.F.14: the simple convoy paradox. Under the Szykman rule the
+        // army's convoy is disrupted and the fleet dislodged; under the
+        // all-hold rule the fleet survives.
+        let units = [
+            (England, "F Lon"),
+            (England, "F Wal"),
+            (France, "A Bre"),
+            (France, "F Eng"),
+        ];
+        let orders = [
+            (England, "F Lon S F Wal - Eng"),
+            (England, "F Wal - Eng"),
+            (France, "A Bre - Lon"),
+            (France, "F Eng C A Bre - Lon"),
+        ];
+        let result = run_with(&units, &orders, szykman);
+        assert!(!succeeded(&result, "Bre"));
+        assert_eq!(dislodged_by(&result, "Eng"), Some("Wal"));
+        let result = run_with(&units, &orders, all_hold);
+        assert!(!succeeded(&result, "Bre"));
+        assert!(!succeeded(&result, "Wal"));
+        assert_eq!(dislodged_by(&result, "Eng"), None);
+
+        // 6.F.15: with an additional convoy, under the Szykman rule the
+        // Welsh fleet moves and lets the convoy from North Africa in
+        // behind it.
+        let units = [
+            (England, "F Lon"),
+            (England, "F Wal"),
+            (France, "A Bre"),
+            (France, "F Eng"),
+            (Italy, "F Iri"),
+            (Italy, "F Mao"),
+            (Italy, "A Naf"),
+        ];
+        let orders = [
+            (England, "F Lon S F Wal - Eng"),
+            (England, "F Wal - Eng"),
+            (France, "A Bre - Lon"),
+            (France, "F Eng C A Bre - Lon"),
+            (Italy, "F Iri C A Naf - Wal"),
+            (Italy, "F Mao C A Naf - Wal"),
+            (Italy, "A Naf - Wal"),
+        ];
+        let result = run_with(&units, &orders, szykman);
+        assert_eq!(dislodged_by(&result, "Eng"), Some("Wal"));
+        assert!(succeeded(&result, "Naf"));
+        let result = run_with(&units, &orders, all_hold);
+        assert!(!succeeded(&result, "Wal"));
+        assert!(!succeeded(&result, "Bre"));
+        assert!(!succeeded(&result, "Naf"));
+        assert_eq!(dislodged_by(&result, "Eng"), None);
+
+        // 6.F.16 (Pandin's paradox) and 6.F.17 (Pandin's extended
+        // paradox) come out the same under both rules.
+        let units = [
+            (England, "F Lon"),
+            (England, "F Wal"),
+            (France, "A Bre"),
+            (France, "F Eng"),
+            (France, "F Yor"),
+            (Germany, "F Nth"),
+            (Germany, "F Bel"),
+        ];
+        let orders = [
+            (England, "F Lon S F Wal - Eng"),
+            (England, "F Wal - Eng"),
+            (France, "A Bre - Lon"),
+            (France, "F Eng C A Bre - Lon"),
+            (Germany, "F Nth S F Bel - Eng"),
+            (Germany, "F Bel - Eng"),
+        ];
+        let mut extended = orders.to_vec();
+        extended.push((France, "F Yor S A Bre - Lon"));
+        for rules in both {
+            for orders in [&orders[..], &extended] {
+                let result = run_with(&units, orders, rules);
+                assert!(!succeeded(&result, "Wal"));
+                assert!(!succeeded(&result, "Bel"));
+                assert!(!succeeded(&result, "Bre"));
+                assert_eq!(dislodged_by(&result, "Eng"), None);
+                assert_eq!(dislodged_by(&result, "Lon"), None);
+            }
+        }
+
+        // 6.F.18: the betrayal paradox. Either way nothing moves.
+        for rules in both {
+            let result = run_with(
+                &[
+                    (England, "F Nth"),
+                    (England, "A Lon"),
+                    (England, "F Eng"),
+                    (France, "F Bel"),
+                    (Germany, "F Hel"),
+                    (Germany, "F Ska"),
+                ],
+                &[
+                    (England, "F Nth C A Lon - Bel"),
+                    (England, "A Lon - Bel"),
+                    (England, "F Eng S A Lon - Bel"),
+                    (France, "F Bel S F Nth"),
+                    (Germany, "F Hel S F Ska - Nth"),
+                    (Germany, "F Ska - Nth"),
+                ],
+                rules,
+            );
+            assert!(!succeeded(&result, "Lon"));
+            assert!(!succeeded(&result, "Ska"));
+            assert_eq!(result.dislodged().count(), 0);
+        }
+
+        // 6.F.19: the multi-route convoy disruption paradox. The army
+        // does not reach Naples, and the Tyrrhenian fleet stays.
+        for rules in both {
+            let result = run_with(
+                &[
+                    (France, "A Tun"),
+                    (France, "F Tys"),
+                    (France, "F Ion"),
+                    (Italy, "F Nap"),
+                    (Italy, "F Rom"),
+                ],
+                &[
+                    (France, "A Tun - Nap"),
+                    (France, "F Tys C A Tun - Nap"),
+                    (France, "F Ion C A Tun - Nap"),
+                    (Italy, "F Nap S F Rom - Tys"),
+                    (Italy, "F Rom - Tys"),
+                ],
+                rules,
+            );
+            assert!(!succeeded(&result, "Tun"));
+            assert_eq!(dislodged_by(&result, "Tys"), None);
+        }
+
+        // 6.F.20: the unwanted multi-route convoy paradox, which has one
+        // consistent resolution: the Ionian fleet is dislodged.
+        for rules in both {
+            let result = run_with(
+                &[
+                    (France, "A Tun"),
+                    (France, "F Tys"),
+                    (Italy, "F Nap"),
+                    (Italy, "F Ion"),
+                    (Turkey, "F Aeg"),
+                    (Turkey, "F Eas"),
+                ],
+                &[
+                    (France, "A Tun - Nap"),
+                    (France, "F Tys C A Tun - Nap"),
+                    (Italy, "F Nap S F Ion"),
+                    (Italy, "F Ion C A Tun - Nap"),
+                    (Turkey, "F Aeg S F Eas - Ion"),
+                    (Turkey, "F Eas - Ion"),
+                ],
+                rules,
+            );
+            assert!(!succeeded(&result, "Tun"));
+            assert_eq!(dislodged_by(&result, "Ion"), Some("Eas"));
+        }
+
+        // 6.F.21: Dad's army convoy. Either way the convoy from Liverpool
+        // fails, the Norwegian army dislodges the fleet in Clyde, and the
+        // Mid-Atlantic fleet the one in the North Atlantic.
+        for rules in both {
+            let result = run_with(
+                &[
+                    (Russia, "A Edi"),
+                    (Russia, "F Nwg"),
+                    (Russia, "A Nwy"),
+                    (France, "F Iri"),
+                    (France, "F Mao"),
+                    (England, "A Lvp"),
+                    (England, "F Nao"),
+                    (England, "F Cly"),
+                ],
+                &[
+                    (Russia, "A Edi S A Nwy - Cly"),
+                    (Russia, "F Nwg C A Nwy - Cly"),
+                    (Russia, "A Nwy - Cly"),
+                    (France, "F Iri S F Mao - Nao"),
+                    (France, "F Mao - Nao"),
+                    (England, "A Lvp - Cly via convoy"),
+                    (England, "F Nao C A Lvp - Cly"),
+                    (England, "F Cly S F Nao"),
+                ],
+                rules,
+            );
+            assert!(!succeeded(&result, "Lvp"));
+            assert_eq!(dislodged_by(&result, "Cly"), Some("Nwy"));
+            assert_eq!(dislodged_by(&result, "Nao"), Some("Mao"));
+        }
+
+        // 6.F.22: a second-order paradox, where each army's convoy turns
+        // on the other's. The Szykman rule disrupts both convoys, so both
+        // supports stand; under the all-hold rule nothing moves.
+        let units = [
+            (England, "F Edi"),
+            (England, "F Lon"),
+            (France, "A Bre"),
+            (France, "F Eng"),
+            (Germany, "F Bel"),
+            (Germany, "F Pic"),
+            (Russia, "A Nwy"),
+            (Russia, "F Nth"),
+        ];
+        let orders = [
+            (England, "F Edi - Nth"),
+            (England, "F Lon S F Edi - Nth"),
+            (France, "A Bre - Lon"),
+            (France, "F Eng C A Bre - Lon"),
+            (Germany, "F Bel S F Pic - Eng"),
+            (Germany, "F Pic - Eng"),
+            (Russia, "A Nwy - Bel"),
+            (Russia, "F Nth C A Nwy - Bel"),
+        ];
+        let result = run_with(&units, &orders, szykman);
+        assert!(!succeeded(&result, "Bre"));
+        assert!(!succeeded(&result, "Nwy"));
+        assert_eq!(dislodged_by(&result, "Nth"), Some("Edi"));
+        assert_eq!(dislodged_by(&result, "Eng"), Some("Pic"));
+        let result = run_with(&units, &orders, all_hold);
+        for province in ["Edi", "Bre", "Pic", "Nwy"] {
+            assert!(!succeeded(&result, province));
+        }
+        assert_eq!(dislodged_by(&result, "Nth"), None);
+        assert_eq!(dislodged_by(&result, "Eng"), None);
+
+        // 6.F.23: a second-order paradox with two exclusive convoys. No
+        // unit moves under either rule.
+        for rules in both {
+            let result = run_with(
+                &[
+                    (England, "F Edi"),
+                    (England, "F Yor"),
+                    (France, "A Bre"),
+                    (France, "F Eng"),
+                    (Germany, "F Bel"),
+                    (Germany, "F Lon"),
+                    (Italy, "F Mao"),
+                    (Italy, "F Iri"),
+                    (Russia, "A Nwy"),
+                    (Russia, "F Nth"),
+                ],
+                &[
+                    (England, "F Edi S F Yor - Nth"),
+                    (England, "F Yor - Nth"),
+                    (France, "A Bre - Lon"),
+                    (France, "F Eng C A Bre - Lon"),
+                    (Germany, "F Bel S F Eng"),
+                    (Germany, "F Lon S F Nth"),
+                    (Italy, "F Mao S F Iri - Eng"),
+                    (Italy, "F Iri - Eng"),
+                    (Russia, "A Nwy - Bel"),
+                    (Russia, "F Nth C A Nwy - Bel"),
+                ],
+                rules,
+            );
+            for province in ["Yor", "Bre", "Iri", "Nwy"] {
+                assert!(!succeeded(&result, province));
+            }
+            assert_eq!(result.dislodged().count(), 0);
+        }
+
+        // 6.F.24: a second-order paradox with no resolution. Under the
+        // Szykman rule both convoys are disrupted and the fleet from
+        // Edinburgh dislodges the one in the North Sea.
+        let units = [
+            (England, "F Edi"),
+            (England, "F Lon"),
+            (England, "F Iri"),
+            (England, "F Mao"),
+            (France, "A Bre"),
+            (France, "F Eng"),
+            (France, "F Bel"),
+            (Russia, "A Nwy"),
+            (Russia, "F Nth"),
+        ];
+        let orders = [
+            (England, "F Edi - Nth"),
+            (England, "F Lon S F Edi - Nth"),
+            (England, "F Iri - Eng"),
+            (England, "F Mao S F Iri - Eng"),
+            (France, "A Bre - Lon"),
+            (France, "F Eng C A Bre - Lon"),
+            (France, "F Bel S F Eng"),
+            (Russia, "A Nwy - Bel"),
+            (Russia, "F Nth C A Nwy - Bel"),
+        ];
+        let result = run_with(&units, &orders, szykman);
+        assert_eq!(dislodged_by(&result, "Nth"), Some("Edi"));
+        assert_eq!(dislodged_by(&result, "Eng"), None);
+        for province in ["Bre", "Nwy", "Iri"] {
+            assert!(!succeeded(&result, province));
+        }
+        let result = run_with(&units, &orders, all_hold);
+        for province in ["Edi", "Bre", "Iri", "Nwy"] {
+            assert!(!succeeded(&result, province));
+        }
+        assert_eq!(result.dislodged().count(), 0);
+    }
+
+    #[test]
+    fn support_to_unreachable_coast_is_allowed() {
+        // DATC 6.B.4.
+        let result = run(
+            &[(France, "F Gas"), (France, "F Mar"), (Italy, "F Wes")],
+            &[
+                (France, "F Gas - Spa (nc)"),
+                (France, "F Mar S F Gas - Spa (nc)"),
+                (Italy, "F Wes - Spa (sc)"),
+            ],
+        );
+        assert!(succeeded(&result, "Gas"));
+        assert!(!succeeded(&result, "Wes"));
+    }
+
+    #[test]
+    fn support_from_unreachable_coast_is_not_allowed() {
+        // DATC 6.B.5.
+        let result = run(
+            &[(France, "F Mar"), (France, "F Spa/nc"), (Italy, "F Lyo")],
+            &[
+                (France, "F Mar - Lyo"),
+                (France, "F Spa (nc) S F Mar - Lyo"),
+                (Italy, "F Lyo H"),
+            ],
+        );
+        assert_eq!(
+            result.get(province("Spa")).unwrap().status(),
+            OrderStatus::Illegal(IllegalOrder::Unreachable)
+        );
+        assert!(!succeeded(&result, "Mar"));
+        assert_eq!(dislodged_by(&result, "Lyo"), None);
+    }
+
+    #[test]
+    fn support_can_be_cut_from_the_other_coast() {
+        // DATC 6.B.6.
+        let result = run(
+            &[
+                (England, "F Iri"),
+                (England, "F Nao"),
+                (France, "F Spa/nc"),
+                (France, "F Mao"),
+                (Italy, "F Lyo"),
+            ],
+            &[
+                (England, "F Iri S F Nao - Mao"),
+                (England, "F Nao - Mao"),
+                (France, "F Spa (nc) S F Mao"),
+                (France, "F Mao H"),
+                (Italy, "F Lyo - Spa (sc)"),
+            ],
+        );
+        assert_eq!(dislodged_by(&result, "Mao"), Some("Nao"));
+    }
+
+    #[test]
+    fn support_naming_the_wrong_coast_still_supports() {
+        let result = run(
+            &[
+                (France, "F Spa/nc"),
+                (France, "F Mao"),
+                (Italy, "F Wes"),
+                (Italy, "F Lyo"),
+            ],
+            &[
+                (France, "F Spa (nc) H"),
+                (France, "F Mao S F Spa (sc)"),
+                (Italy, "F Wes - Spa (sc)"),
+                (Italy, "F Lyo S F Wes - Spa"),
+            ],
+        );
+        assert!(!succeeded(&result, "Wes"));
+        assert_eq!(dislodged_by(&result, "Spa"), None);
+    }
+
+    #[test]
+    fn illegal_orders_hold() {
+        let result = run(
+            &[(France, "A Par"), (France, "F Mao"), (France, "A Mar")],
+            &[
+                (France, "A Par - Mun"),
+                (France, "F Mao - Spa"),
+                (France, "F Mar - Spa"),
+                (France, "A Bre - Pic"),
+                (Germany, "A Mar - Pie"),
+            ],
+        );
+        let status = |abbreviation| result.get(province(abbreviation)).unwrap().status();
+        assert_eq!(
+            status("Par"),
+            OrderStatus::Illegal(IllegalOrder::Unreachable)
+        );
+        assert_eq!(
+            status("Mao"),
+            OrderStatus::Illegal(IllegalOrder::AmbiguousCoast)
+        );
+        assert_eq!(
+            status("Mar"),
+            OrderStatus::Illegal(IllegalOrder::WrongUnitType)
+        );
+        let reasons: Vec<_> = result.rejected().iter().map(|r| r.reason).collect();
+        assert_eq!(reasons, [IllegalOrder::NoUnit, IllegalOrder::ForeignUnit]);
+    }
+
+    #[test]
+    fn retreats() {
+        let before = state(&[
+            (France, "A Bur"),
+            (France, "A Bel"),
+            (Germany, "A Mun"),
+            (Germany, "A Ruh"),
+            (England, "A Hol"),
+            (England, "F Nth"),
+        ]);
+        let result = adjudicate(
+            orders(&[
+                (Germany, "A Mun - Bur"),
+                (Germany, "A Ruh S A Mun - Bur"),
+                (England, "A Hol - Bel"),
+                (England, "F Nth S A Hol - Bel"),
+            ]),
+            &before,
+        );
+        let position = before.apply(&result);
+        assert_eq!(position.phase().kind, PhaseKind::Retreats);
+        let places = |abbreviation| -> Vec<&str> {
+            let mut places: Vec<_> = position
+                .retreats(province(abbreviation))
+                .iter()
+                .map(|(place, _)| place.abbreviation())
+                .collect();
+            places.sort();
+            places
+        };
+        assert_eq!(places("Bur"), ["Gas", "Mar", "Par", "Pic"]);
+        assert_eq!(places("Bel"), ["Pic"]);
+
+        // Two units retreating to the same province are both disbanded.
+        let result = adjudicate(
+            orders(&[(France, "A Bur - Pic"), (France, "A Bel - Pic")]),
+            &position,
+        );
+        assert!(!succeeded(&result, "Bur"));
+        assert!(!succeeded(&result, "Bel"));
+
+        let result = adjudicate(
+            orders(&[
+                (France, "A Bur - Mun"),
+                (France, "A Bel - Pic"),
+                (Germany, "A Bur - Par"),
+            ]),
+            &position,
+        );
+        let bur = result.get(province("Bur")).unwrap();
+        assert_eq!(
+            bur.status(),
+            OrderStatus::Illegal(IllegalOrder::Unreachable)
+        );
+        assert!(succeeded(&result, "Bel"));
+        let reasons: Vec<_> = result.rejected().iter().map(|r| r.reason).collect();
+        assert_eq!(reasons, [IllegalOrder::ForeignUnit]);
+
+        let result = adjudicate(orders(&[(France, "A Bel S A Bur - Pic")]), &position);
+        let status = |abbreviation| result.get(province(abbreviation)).unwrap().status();
+        assert_eq!(
+            status("Bel"),
+            OrderStatus::Illegal(IllegalOrder::WrongPhase)
+        );
+        assert_eq!(status("Bur"), OrderStatus::Unordered);
+    }
+
+    #[test]
+    fn retreat_to_convoyed_attackers_origin() {
+        let before = state(&[
+            (France, "A Pic"),
+            (France, "F Eng"),
+            (France, "A Bur"),
+            (Germany, "A Bel"),
+        ]);
+        let result = adjudicate(
+            orders(&[
+                (France, "A Pic - Bel via convoy"),
+                (France, "F Eng C A Pic - Bel"),
+                (France, "A Bur S A Pic - Bel"),
+            ]),
+            &before,
+        );
+        assert_eq!(dislodged_by(&result, "Bel"), Some("Pic"));
+        let position = before.apply(&result);
+        assert!(position
+            .retreats(province("Bel"))
+            .contains(&(province("Pic"), None)));
+    }
+
+    #[test]
+    fn adjustments() {
+        let mut state = state(&[
+            (France, "A Spa"),
+            (Germany, "A Ber"),
+            (Germany, "F Kie"),
+            (Germany, "A Mun"),
+        ]);
+        state.set_phase("Winter 1901 Adjustments".parse().unwrap());
+        for abbreviation in ["Par", "Mar", "Bre", "Spa"] {
+            state.set_owner(province(abbreviation), France);
+        }
+        state.set_owner(province("Ber"), Germany);
+        state.set_owner(province("StP"), Russia);
+        let build = |unit, abbreviation, coast| Adjustment::Build {
+            unit,
+            province: province(abbreviation),
+            coast,
+        };
+        let adjustments = HashMap::from([
+            (
+                France,
+                vec![
+                    build(UnitType::Army, "Par", None),
+                    build(UnitType::Fleet, "Spa", None),
+                    build(UnitType::Fleet, "Mar", None),
+                    build(UnitType::Army, "Bre", None),
+                    build(UnitType::Army, "Bre", None),
+                ],
+            ),
+            (
+                Germany,
+                vec![
+                    Adjustment::Disband(province("Par")),
+                    Adjustment::Disband(province("Kie")),
+                ],
+            ),
+            (
+                Russia,
+                vec![
+                    build(UnitType::Fleet, "StP", None),
+                    build(UnitType::Fleet, "StP", Some(Coast::East)),
+                    build(UnitType::Fleet, "StP", Some(Coast::North)),
+                ],
+            ),
+        ]);
+        let result = adjudicate_adjustments(adjustments, &state, &Rules::default());
+        let built: Vec<_> = result
+            .builds()
+            .iter()
+            .map(|(province, unit)| (province.abbreviation(), *unit))
+            .collect();
+        assert_eq!(
+            built,
+            [
+                ("Par", Unit::army(France)),
+                ("Mar", Unit::fleet(France)),
+                ("Bre", Unit::army(France)),
+                ("StP", Unit::fleet_on(Russia, Coast::North)),
+            ]
+        );
+        let reasons: Vec<_> = result.rejected().iter().map(|r| r.reason).collect();
+        assert_eq!(
+            reasons,
+            [
+                AdjustmentError::NotHomeCenter,
+                AdjustmentError::TooMany,
+                AdjustmentError::ForeignUnit,
+                AdjustmentError::MissingCoast,
+                AdjustmentError::InvalidCoast,
+            ]
+        );
+        assert_eq!(result.disbands(), [(province("Kie"), Unit::fleet(Germany))]);
+        // Germany, with one center, disbanded one unit too few.
+        assert_eq!(result.removals().len(), 1);
+
+        let next = state.apply_adjustments(&result);
+        assert_eq!(next.phase(), "Spring 1902 Movement".parse().unwrap());
+        assert_eq!(next.adjustment(France), 0);
+        assert_eq!(next.adjustment(Germany), 0);
+    }
+}
@@ -0,0 +1,571 @@
+//! The resolution core: Kruijswijk's guess-and-check algorithm over the
+//! legal orders of every unit.
+//!
+//! Each unit has one decision, resolved on demand: whether a move
+//! succeeds, whether a support is given, or whether a convoying fleet
+//! survives. A decision that turns out to depend on itself is first
+//! guessed false and then true; if both guesses are consistent the
+//! answer is settled, and if they disagree the cycle is broken with the
+//! backup rule: circular movement succeeds, and convoy paradoxes are
+//! resolved by the [`ParadoxRule`] in force. Under the DPTG rule,
+//! convoyed armies are kept from cutting the supports that would make
+//! most paradoxes to begin with.
+//!
+//! Everything is indexed by province id, and the units moving into, and
+//! supporting, and convoying from each province are listed up front, so
+//! that resolving a phase allocates little beyond its arrays.
+
+use std::collections::VecDeque;
+
+use super::ParadoxRule;
+use crate::map::{Map, ProvinceKind};
+use crate::power::Power;
+
+/// A legal order, in terms of province ids.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Kind {
+    Hold,
+    Move { to: usize, convoy: bool },
+    Support { from: usize, to: Option<usize> },
+    Convoy { from: usize, to: usize },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Entry {
+    pub(super) power: Power,
+    pub(super) kind: Kind,
+}
+
+/// What became of a support.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Fate {
+    /// It was given, and counts.
+    Given,
+    /// The supported unit was not ordered as the support said.
+    Mismatched,
+    /// The backup rule held the supporter.
+    Held,
+    /// The support was cut by an attack from the province with this id.
+    Cut(usize),
+    /// The supporter was dislodged by the unit from this province.
+    Dislodged(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Unresolved,
+    Guessing(bool),
+    Resolved(bool),
+}
+
+pub(super) struct Resolver<'m> {
+    map: &'m Map,
+    units: Vec<Option<Entry>>,
+    /// The units ordered into each province.
+    attackers: Vec<Vec<usize>>,
+    /// The units ordered to support the unit in each province.
+    supporters: Vec<Vec<usize>>,
+    /// The fleets ordered to convoy the army in each province.
+    convoys: Vec<Vec<usize>>,
+    states: Vec<State>,
+    dependencies: Vec<usize>,
+    /// Units held by the backup rule: convoyed armies whose convoy was
+    /// disrupted by a paradox, and under the all-hold rule, every move and
+    /// support caught up in one.
+    held: Vec<bool>,
+    paradox: ParadoxRule,
+    /// The cycles the backup rule settled, and whether each was circular
+    /// movement rather than a convoy paradox.
+    cycles: Vec<(Vec<usize>, bool)>,
+}
+
+impl<'m> Resolver<'m> {
+    /// `units` is indexed by province id.
+    pub(super) fn new(map: &'m Map, units: Vec<Option<Entry>>, paradox: ParadoxRule) -> Self {
+        let states = vec![State::Unresolved; units.len()];
+        let held = vec![false; units.len()];
+        let mut attackers = vec![Vec::new(); units.len()];
+        let mut supporters = vec![Vec::new(); units.len()];
+        let mut convoys = vec![Vec::new(); units.len()];
+        for (province, entry) in units.iter().enumerate() {
+            match entry.map(|entry| entry.kind) {
+                Some(Kind::Move { to, .. }) => attackers[to].push(province),
+                Some(Kind::Support { from, .. }) => supporters[from].push(province),
+                Some(Kind::Convoy { from, .. }) => convoys[from].push(province),
+                Some(Kind::Hold) | None => {}
+            }
+        }
+        Resolver {
+            map,
+            units,
+            attackers,
+            supporters,
+            convoys,
+            states,
+            dependencies: Vec::new(),
+            held,
+            paradox,
+            cycles: Vec::new(),
+        }
+    }
+
+    /// Whether the order of the unit in `province` succeeds.
+    pub(super) fn resolve(&mut self, province: usize) -> bool {
+        match self.states[province] {
+            State::Resolved(result) => return result,
+            State::Guessing(guess) => {
+                if !self.dependencies.contains(&province) {
+                    self.dependencies.push(province);
+                }
+                return guess;
+            }
+            State::Unresolved => {}
+        }
+
+        let old_len = self.dependencies.len();
+        self.states[province] = State::Guessing(false);
+        let first = self.adjudicate(province);
+        if self.dependencies.len() == old_len {
+            // The answer did not depend on any guess.
+            if let State::Resolved(result) = self.states[province] {
+                return result;
+            }
+            self.states[province] = State::Resolved(first);
+            trace_event!(province = self.name(province), result = first, "resolved");
+            return first;
+        }
+        if self.dependencies[old_len] != province {
+            // It depends on some other decision's guess; leave it to that
+            // decision to settle the cycle.
+            self.dependencies.push(province);
+            self.states[province] = State::Guessing(first);
+            return first;
+        }
+
+        // It depends on its own guess. Try the other one.
+        self.forget_from(old_len);
+        self.states[province] = State::Guessing(true);
+        let second = self.adjudicate(province);
+        if first == second {
+            self.forget_from(old_len);
+            self.states[province] = State::Resolved(first);
+            trace_event!(
+                province = self.name(province),
+                result = first,
+                "resolved with either guess"
+            );
+            return first;
+        }
+
+        // Neither guess is consistent, or both are: the backup rule.
+        self.backup_rule(old_len);
+        self.resolve(province)
+    }
+
+    fn forget_from(&mut self, len: usize) {
+        for province in self.dependencies.drain(len..) {
+            self.states[province] = State::Unresolved;
+        }
+    }
+
+    fn backup_rule(&mut self, len: usize) {
+        let cycle: Vec<usize> = self.dependencies.drain(len..).collect();
+        let circular = cycle
+            .iter()
+            .all(|&p| matches!(self.kind(p), Some(Kind::Move { .. })));
+        trace_event!(
+            cycle = ?cycle.iter().map(|&p| self.name(p)).collect::<Vec<_>>(),
+            circular,
+            "backup rule"
+        );
+        self.cycles.push((cycle.clone(), circular));
+        if circular {
+            // Circular movement, including swaps by convoy: every unit
+            // moves.
+            for &province in &cycle {
+                self.states[province] = State::Resolved(true);
+            }
+            return;
+        }
+
+        // A convoy paradox. By the Szykman rule, every army convoyed
+        // through the cycle has its convoy disrupted; by the all-hold rule,
+        // every move and support in the cycle is held as well. The rest is
+        // adjudicated afresh.
+        let all_hold = self.paradox == ParadoxRule::AllHold;
+        let mut changed = false;
+        for &province in &cycle {
+            let army = match self.kind(province) {
+                Some(Kind::Convoy { from, to }) => match self.kind(from) {
+                    Some(Kind::Move {
+                        to: dest,
+                        convoy: true,
+                    }) if dest == to => Some(from),
+                    _ => None,
+                },
+                Some(Kind::Move { convoy: true, .. }) => Some(province),
+                _ => None,
+            };
+            let held = match self.kind(province) {
+                Some(Kind::Move { .. } | Kind::Support { .. }) if all_hold => Some(province),
+                _ => None,
+            };
+            for unit in army.into_iter().chain(held) {
+                changed |= !self.held[unit];
+                self.held[unit] = true;
+            }
+        }
+        for &province in &cycle {
+            self.states[province] = if changed {
+                State::Unresolved
+            } else {
+                State::Resolved(false)
+            };
+        }
+    }
+
+    /// The abbreviation of the province with id `province`, for traces.
+    #[cfg(feature = "trace-adjudication")]
+    fn name(&self, province: usize) -> &'m str {
+        self.map.province(province).abbreviation()
+    }
+
+    pub(super) fn kind(&self, province: usize) -> Option<Kind> {
+        self.units[province].map(|entry| entry.kind)
+    }
+
+    fn power(&self, province: usize) -> Option<Power> {
+        self.units[province].map(|entry| entry.power)
+    }
+
+    fn adjudicate(&mut self, province: usize) -> bool {
+        let result = match self.kind(province) {
+            Some(Kind::Move { to, .. }) => self.adjudicate_move(province, to),
+            Some(Kind::Support { to, .. }) => self.adjudicate_support(province, to),
+            Some(Kind::Convoy { .. }) => !self.dislodged(province),
+            Some(Kind::Hold) | None => true,
+        };
+        // The backup rule may have held the unit while its own order was
+        // being adjudicated.
+        result && !self.held[province]
+    }
+
+    fn adjudicate_move(&mut self, province: usize, to: usize) -> bool {
+        let attack = self.attack_strength(province);
+        let resisted = if self.head_to_head(province) {
+            self.defend_strength(to)
+        } else {
+            self.hold_strength(to)
+        };
+        trace_event!(
+            province = self.name(province),
+            to = self.name(to),
+            attack,
+            resisted,
+            "move strengths"
+        );
+        if attack <= resisted {
+            return false;
+        }
+        for i in 0..self.attackers[to].len() {
+            let other = self.attackers[to][i];
+            if other == province {
+                continue;
+            }
+            let prevent = self.prevent_strength(other);
+            trace_event!(
+                province = self.name(province),
+                from = self.name(other),
+                prevent,
+                "prevent strength"
+            );
+            if attack <= prevent {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn adjudicate_support(&mut self, province: usize, to: Option<usize>) -> bool {
+        if self.held[province] || !self.support_matches(province) {
+            return false;
+        }
+        if self.cut_by(province, to).is_some() {
+            return false;
+        }
+        !self.dislodged(province)
+    }
+
+    /// The first unit, if any, that cuts the support given by the unit in
+    /// `province` for a move to `to`, or a hold if `to` is `None`.
+    fn cut_by(&mut self, province: usize, to: Option<usize>) -> Option<usize> {
+        let power = self.power(province);
+        for i in 0..self.attackers[province].len() {
+            let attacker = self.attackers[province][i];
+            // A unit cannot cut support given against its own province,
+            // only dislodge the supporter.
+            if Some(attacker) == to || self.power(attacker) == power {
+                continue;
+            }
+            if self.paradox == ParadoxRule::Dptg && to.is_some_and(|to| self.convoys(to, attacker))
+            {
+                continue;
+            }
+            if self.path(attacker) {
+                trace_event!(
+                    province = self.name(province),
+                    by = self.name(attacker),
+                    "support cut"
+                );
+                return Some(attacker);
+            }
+        }
+        None
+    }
+
+    /// What became of the support ordered for the unit in `province`.
+    pub(super) fn fate(&mut self, province: usize) -> Fate {
+        let to = match self.kind(province) {
+            Some(Kind::Support { to, .. }) => to,
+            _ => return Fate::Mismatched,
+        };
+        if !self.support_matches(province) {
+            return Fate::Mismatched;
+        }
+        if self.held[province] {
+            return Fate::Held;
+        }
+        if let Some(attacker) = self.cut_by(province, to) {
+            return Fate::Cut(attacker);
+        }
+        match self.dislodged_by(province) {
+            Some(attacker) => Fate::Dislodged(attacker),
+            None => Fate::Given,
+        }
+    }
+
+    /// The units ordered into `province`.
+    pub(super) fn moving_to(&self, province: usize) -> &[usize] {
+        &self.attackers[province]
+    }
+
+    /// The cycles the backup rule settled, each with whether it was
+    /// circular movement rather than a convoy paradox.
+    pub(super) fn cycles(&self) -> &[(Vec<usize>, bool)] {
+        &self.cycles
+    }
+
+    /// Whether any move into `province` succeeds.
+    fn dislodged(&mut self, province: usize) -> bool {
+        (0..self.attackers[province].len()).any(|i| self.resolve(self.attackers[province][i]))
+    }
+
+    /// Whether the support by the unit in `province` matches the order
+    /// actually given to the supported unit.
+    fn support_matches(&self, province: usize) -> bool {
+        match self.kind(province) {
+            Some(Kind::Support { from, to: Some(to) }) => {
+                matches!(self.kind(from), Some(Kind::Move { to: dest, .. }) if dest == to)
+            }
+            Some(Kind::Support { from, to: None }) => {
+                matches!(self.kind(from), Some(kind) if !matches!(kind, Kind::Move { .. }))
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the unit in `fleet` is ordered to convoy the army in
+    /// `army` where it is going.
+    fn convoys(&self, fleet: usize, army: usize) -> bool {
+        match self.kind(army) {
+            Some(Kind::Move { to, convoy: true }) => {
+                self.kind(fleet) == Some(Kind::Convoy { from: army, to })
+            }
+            _ => false,
+        }
+    }
+
+    /// True if the move from `province` and the unit at its destination
+    /// are moving directly into each other.
+    pub(super) fn head_to_head(&self, province: usize) -> bool {
+        match self.kind(province) {
+            Some(Kind::Move { to, convoy: false }) => matches!(
+                self.kind(to),
+                Some(Kind::Move { to: back, convoy: false }) if back == province
+            ),
+            _ => false,
+        }
+    }
+
+    /// The number of successful supports for the unit in `province`,
+    /// ignoring those given by `excluding`.
+    fn supports(&mut self, province: usize, excluding: Option<Power>) -> usize {
+        let mut count = 0;
+        for i in 0..self.supporters[province].len() {
+            let supporter = self.supporters[province][i];
+            if self.support_matches(supporter)
+                && (excluding.is_none() || self.power(supporter) != excluding)
+                && self.resolve(supporter)
+            {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    pub(super) fn hold_strength(&mut self, province: usize) -> usize {
+        match self.kind(province) {
+            None => 0,
+            Some(Kind::Move { .. }) => {
+                if self.resolve(province) {
+                    0
+                } else {
+                    1
+                }
+            }
+            Some(_) => 1 + self.supports(province, None),
+        }
+    }
+
+    pub(super) fn attack_strength(&mut self, province: usize) -> usize {
+        let to = match self.kind(province) {
+            Some(Kind::Move { to, .. }) => to,
+            _ => return 0,
+        };
+        if !self.path(province) {
+            return 0;
+        }
+        let defender = match self.units[to] {
+            Some(defender) => defender,
+            None => return 1 + self.supports(province, None),
+        };
+        let leaving = matches!(defender.kind, Kind::Move { .. })
+            && !self.head_to_head(province)
+            && self.resolve(to);
+        if leaving {
+            1 + self.supports(province, None)
+        } else if Some(defender.power) == self.power(province) {
+            // A power cannot dislodge its own unit.
+            0
+        } else {
+            1 + self.supports(province, Some(defender.power))
+        }
+    }
+
+    pub(super) fn defend_strength(&mut self, province: usize) -> usize {
+        1 + self.supports(province, None)
+    }
+
+    pub(super) fn prevent_strength(&mut self, province: usize) -> usize {
+        let to = match self.kind(province) {
+            Some(Kind::Move { to, .. }) => to,
+            _ => return 0,
+        };
+        if !self.path(province) {
+            return 0;
+        }
+        // A unit that loses a head-to-head battle cannot prevent others
+        // from moving into the province it attacked.
+        if self.head_to_head(province) && self.resolve(to) {
+            return 0;
+        }
+        1 + self.supports(province, None)
+    }
+
+    /// Whether the unit in `province` can get to its destination: always
+    /// for direct moves, and for convoyed moves if a chain of surviving
+    /// fleets connects the two. Held moves get nowhere.
+    fn path(&mut self, province: usize) -> bool {
+        if self.held[province] {
+            return false;
+        }
+        let to = match self.kind(province) {
+            Some(Kind::Move { to, convoy: true }) => to,
+            Some(Kind::Move { .. }) => return true,
+            _ => return false,
+        };
+        let mut fleets = Vec::new();
+        for i in 0..self.convoys[province].len() {
+            let fleet = self.convoys[province][i];
+            if self.kind(fleet) == Some(Kind::Convoy { from: province, to }) && self.resolve(fleet)
+            {
+                fleets.push(fleet);
+            }
+        }
+        self.convoy_route(province, to, &fleets)
+    }
+
+    /// The fleets ordered to convoy the army in `from` to `to`.
+    pub(super) fn convoying(&self, from: usize, to: usize) -> Vec<usize> {
+        self.convoys[from]
+            .iter()
+            .copied()
+            .filter(|&p| self.kind(p) == Some(Kind::Convoy { from, to }))
+            .collect()
+    }
+
+    /// Whether `fleets` form a chain from `from` to `to`.
+    pub(super) fn convoy_route(&self, from: usize, to: usize, fleets: &[usize]) -> bool {
+        let map = self.map;
+        let adjacent =
+            |a: usize, b: usize| map.fleet_can_reach(map.province(a), None, map.province(b));
+        let mut seen = vec![false; fleets.len()];
+        let mut queue = VecDeque::new();
+        for (i, &fleet) in fleets.iter().enumerate() {
+            if map.province(fleet).kind() == ProvinceKind::Sea && adjacent(fleet, from) {
+                seen[i] = true;
+                queue.push_back(fleet);
+            }
+        }
+        while let Some(fleet) = queue.pop_front() {
+            if adjacent(fleet, to) {
+                return true;
+            }
+            for (i, &next) in fleets.iter().enumerate() {
+                if !seen[i] && adjacent(fleet, next) {
+                    seen[i] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        false
+    }
+
+    /// The province, if any, whose unit moved into `province`.
+    pub(super) fn dislodged_by(&mut self, province: usize) -> Option<usize> {
+        if let Some(Kind::Move { .. }) = self.kind(province) {
+            if self.resolve(province) {
+                return None;
+            }
+        }
+        self.units[province]?;
+        for i in 0..self.attackers[province].len() {
+            let attacker = self.attackers[province][i];
+            if self.resolve(attacker) {
+                trace_event!(
+                    province = self.name(province),
+                    by = self.name(attacker),
+                    "dislodged"
+                );
+                return Some(attacker);
+            }
+        }
+        None
+    }
+
+    /// Whether `province` was the site of a standoff: two or more units
+    /// tried to enter it and none did.
+    pub(super) fn standoff(&mut self, province: usize) -> bool {
+        let mut attempts = 0;
+        for i in 0..self.attackers[province].len() {
+            let attacker = self.attackers[province][i];
+            if self.resolve(attacker) {
+                return false;
+            }
+            if self.path(attacker) {
+                attempts += 1;
+            }
+        }
+        attempts >= 2
+    }
+}
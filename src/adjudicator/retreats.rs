@@ -0,0 +1,147 @@
+//! Adjudication of a retreat phase.
+
+use std::collections::HashMap;
+
+use super::{
+    legality, AdjudicationResult, IllegalOrder, OrderLegality, OrderStatus, RejectedOrder,
+    Resolution, Rules,
+};
+use crate::map::{Province, ProvinceSet};
+use crate::order::{Hold, Move, Order};
+use crate::power::Power;
+use crate::state::{GameState, Unit};
+
+/// Adjudicates the retreats of the units dislodged in the last movement
+/// phase.
+///
+/// A retreat is a move to one of the places the state says the unit may
+/// retreat to. Units ordered to do anything else, or nothing, are
+/// disbanded, as are units of powers in civil disorder and units that
+/// retreat to the same province as another.
+pub(super) fn adjudicate<'m>(
+    orders: HashMap<Power, Vec<Order<'m>>>,
+    state: &GameState<'m>,
+    rules: &Rules,
+) -> AdjudicationResult<'m> {
+    let mut submitted: HashMap<&'m Province, Order<'m>> = HashMap::new();
+    let mut rejected = Vec::new();
+    for &power in &Power::ALL {
+        if state.in_civil_disorder(power) {
+            continue;
+        }
+        for order in orders.get(&power).into_iter().flatten() {
+            match legality(state, power, order, rules) {
+                OrderLegality::Rejected(reason) => rejected.push(RejectedOrder {
+                    power,
+                    order: order.clone(),
+                    reason,
+                }),
+                _ => {
+                    submitted.insert(order.province(), order.clone());
+                }
+            }
+        }
+    }
+
+    let mut resolutions = Vec::new();
+    for (province, unit) in state.dislodged() {
+        let disband = Order::Hold(Hold {
+            unit: Some(unit.unit_type),
+            province,
+            coast: unit.coast,
+        });
+        let submitted = submitted.remove(province);
+        let (order, status) = match &submitted {
+            None => (disband, OrderStatus::Unordered),
+            Some(order) => match check(state, unit, order) {
+                Ok(Some(order)) => (order, OrderStatus::Ordered),
+                Ok(None) => (disband, OrderStatus::Ordered),
+                Err(reason) => (disband, OrderStatus::Illegal(reason)),
+            },
+        };
+        resolutions.push((
+            province,
+            Resolution {
+                power: unit.power,
+                unit: unit.unit_type,
+                order,
+                submitted,
+                status,
+                succeeded: false,
+                convoyed: false,
+                dislodged_by: None,
+            },
+        ));
+    }
+
+    // A retreat succeeds unless another unit retreats to the same place.
+    let mut destinations: HashMap<&'m Province, usize> = HashMap::new();
+    for (_, resolution) in &resolutions {
+        if let Some(to) = resolution.order.destination() {
+            *destinations.entry(to).or_default() += 1;
+        }
+    }
+    for (_, resolution) in &mut resolutions {
+        if let Some(to) = resolution.order.destination() {
+            resolution.succeeded = destinations[to] == 1;
+        }
+    }
+
+    AdjudicationResult {
+        resolutions,
+        rejected,
+        standoffs: ProvinceSet::new(state.map()),
+        rules: *rules,
+    }
+}
+
+/// Checks a retreat against the places the unit may go, filling in the
+/// unit type and the coast.
+/// Checks the order given to the dislodged `unit`, giving the retreat it
+/// makes, or `None` for an order to disband.
+pub(super) fn check<'m>(
+    state: &GameState<'m>,
+    unit: &Unit,
+    order: &Order<'m>,
+) -> Result<Option<Order<'m>>, IllegalOrder> {
+    if order.unit().is_some_and(|u| u != unit.unit_type) {
+        return Err(IllegalOrder::WrongUnitType);
+    }
+    match order {
+        Order::Hold(_) => Ok(None),
+        Order::Move(_) if !unit.is_mobile(state.map()) => Err(IllegalOrder::Immobile),
+        Order::Move(move_) => retreat(state, unit, move_).map(Some),
+        _ => Err(IllegalOrder::WrongPhase),
+    }
+}
+
+fn retreat<'m>(
+    state: &GameState<'m>,
+    unit: &Unit,
+    move_: &Move<'m>,
+) -> Result<Order<'m>, IllegalOrder> {
+    let province = move_.province;
+    let coasts: Vec<_> = state
+        .retreats(province)
+        .iter()
+        .filter(|(to, _)| *to == move_.destination)
+        .map(|&(_, coast)| coast)
+        .collect();
+    let coast = match (move_.coast, coasts.as_slice()) {
+        (_, []) => return Err(IllegalOrder::Unreachable),
+        (Some(coast), _) if move_.destination.is_bicoastal() => {
+            if !coasts.contains(&Some(coast)) {
+                return Err(IllegalOrder::Unreachable);
+            }
+            Some(coast)
+        }
+        (_, [coast]) => *coast,
+        (_, _) => return Err(IllegalOrder::AmbiguousCoast),
+    };
+    Ok(Order::Move(Move {
+        unit: Some(unit.unit_type),
+        coast,
+        via_convoy: false,
+        ..move_.clone()
+    }))
+}
@@ -0,0 +1,110 @@
+//! The rule choices on which communities differ, as catalogued in section
+//! 4 of the DATC, and the house rules supported besides.
+
+use crate::rng::Rng;
+
+/// Which rulebook decides when an army goes by convoy to a province it
+/// could also reach over land (DATC 4.A.3).
+///
+/// Armies moving to provinces they are not next to always go by convoy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ConvoyRules {
+    /// Armies never go by convoy to a neighbouring province.
+    Rules1971,
+    /// An army goes by convoy to a neighbouring province when it is
+    /// ordered to, or when a fleet of its own power is ordered to convoy
+    /// it there, taken as a sign of what its player meant.
+    Rules1982,
+    /// An army goes by convoy to a neighbouring province only when it is
+    /// ordered to (`via convoy`).
+    #[default]
+    Rules2000,
+}
+
+/// How a convoy paradox is settled (DATC 4.A.2): a convoyed army that
+/// cuts a support which, uncut, would dislodge one of its own convoying
+/// fleets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ParadoxRule {
+    /// Armies convoyed through a paradox have their convoys disrupted:
+    /// they neither move nor cut support, and their fleets hold.
+    #[default]
+    Szykman,
+    /// A convoyed army never cuts a support for an attack on one of the
+    /// fleets convoying it, whether or not that makes a paradox. Any
+    /// paradox left over is settled as by [`ParadoxRule::Szykman`].
+    Dptg,
+    /// Every unit caught up in a paradox holds: its moves fail, its
+    /// supports are not given, and its convoying fleets stay put.
+    AllHold,
+}
+
+/// How far a unit is from home, when a power in civil disorder has units
+/// removed (DATC 4.D.8).
+///
+/// Either way, the units farthest from their power's home centers go
+/// first; [`RemovalTies`] chooses among units as far as each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RemovalRules {
+    /// Distances are counted through any province, for armies and fleets
+    /// alike.
+    Rules1982,
+    /// Armies count through any province, as if they could be convoyed,
+    /// but fleets only through provinces a fleet could move to.
+    #[default]
+    Rules2000,
+}
+
+/// How a power in civil disorder chooses between units as far from home
+/// as each other, when it has units removed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RemovalTies {
+    /// Fleets go before armies, and then units in alphabetical order of
+    /// their provinces, as the rulebook has it.
+    #[default]
+    Rulebook,
+    /// A house rule: the units go in an order drawn from the
+    /// [`RngSource`] given to
+    /// [`adjudicate_adjustments_with`](super::adjudicate_adjustments_with).
+    Random,
+}
+
+/// The rule choices that [`adjudicate_with`](super::adjudicate_with)
+/// follows. The default follows the 2000 rulebook and the DATC's
+/// preferences.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rules {
+    pub convoys: ConvoyRules,
+    pub paradox: ParadoxRule,
+    pub removals: RemovalRules,
+    pub removal_ties: RemovalTies,
+}
+
+/// Where house rules that settle things by chance get their random
+/// numbers. A game that keeps the source, or its seed, can be adjudicated
+/// again with the same outcome.
+pub trait RngSource {
+    /// The next number, spread evenly over every `u64`.
+    fn next_u64(&mut self) -> u64;
+
+    /// A number from 0 up to but not including `n`, which must not be 0.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// A small generator that gives the same numbers for the same seed.
+#[derive(Clone, Debug)]
+pub struct SeededRng(Rng);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng(Rng::new(seed))
+    }
+}
+
+impl RngSource for SeededRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+}
@@ -0,0 +1,131 @@
+//! What one power's other orders would have changed, for analysis tools
+//! and bots weighing candidate moves against the orders they expect.
+
+use std::collections::HashMap;
+
+use super::{adjudicate_with, AdjudicationResult, Resolution, Rules};
+use crate::map::{Province, ProvinceSet};
+use crate::order::Order;
+use crate::power::Power;
+use crate::state::GameState;
+
+/// A phase adjudicated twice: once with the orders given, and once with
+/// one power's replaced. Made by [`GameState::what_if`].
+#[derive(Clone, Debug)]
+pub struct WhatIf<'m> {
+    power: Power,
+    baseline: AdjudicationResult<'m>,
+    alternative: AdjudicationResult<'m>,
+}
+
+impl<'m> WhatIf<'m> {
+    /// Adjudicates `orders`, and again with `power`'s replaced by
+    /// `alternative`.
+    pub(crate) fn new(
+        orders: &HashMap<Power, Vec<Order<'m>>>,
+        power: Power,
+        alternative: Vec<Order<'m>>,
+        state: &GameState<'m>,
+        rules: &Rules,
+    ) -> Self {
+        let mut replaced = orders.clone();
+        replaced.insert(power, alternative);
+        WhatIf {
+            power,
+            baseline: adjudicate_with(orders.clone(), state, rules),
+            alternative: adjudicate_with(replaced, state, rules),
+        }
+    }
+
+    /// The power whose orders were replaced.
+    pub fn power(&self) -> Power {
+        self.power
+    }
+
+    /// The result with the orders as given.
+    pub fn baseline(&self) -> &AdjudicationResult<'m> {
+        &self.baseline
+    }
+
+    /// The result with the power's orders replaced.
+    pub fn alternative(&self) -> &AdjudicationResult<'m> {
+        &self.alternative
+    }
+
+    /// Every unit whose resolution differs, with what happened to it in
+    /// the baseline and then in the alternative, in map order.
+    pub fn changes(
+        &self,
+    ) -> impl Iterator<Item = (&'m Province, &Resolution<'m>, &Resolution<'m>)> {
+        self.baseline
+            .resolutions()
+            .zip(self.alternative.resolutions())
+            .filter(|((_, before), (_, after))| before != after)
+            .map(|((province, before), (_, after))| (province, before, after))
+    }
+
+    /// Whether the power's other orders would have made no difference.
+    pub fn is_unchanged(&self) -> bool {
+        self.changes().next().is_none() && self.baseline.standoffs() == self.alternative.standoffs()
+    }
+
+    /// Provinces left empty by a standoff in the alternative only.
+    pub fn standoffs_gained(&self) -> ProvinceSet<'m> {
+        self.alternative
+            .standoffs()
+            .difference(&self.baseline.standoffs())
+    }
+
+    /// Provinces left empty by a standoff in the baseline only.
+    pub fn standoffs_lost(&self) -> ProvinceSet<'m> {
+        self.baseline
+            .standoffs()
+            .difference(&self.alternative.standoffs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::Map;
+    use crate::parser::parse_order;
+    use crate::power::Power;
+    use crate::state::{GameState, Unit};
+
+    use std::collections::HashMap;
+
+    #[test]
+    fn what_if_reports_the_difference() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let order = |text| parse_order(map, text).unwrap();
+        let mut state = GameState::new(map);
+        state.place_unit(province("Par"), Unit::army(Power::France));
+        state.place_unit(province("Mar"), Unit::army(Power::France));
+        state.place_unit(province("Mun"), Unit::army(Power::Germany));
+        let orders = HashMap::from([
+            (Power::France, vec![order("A Par - Bur"), order("A Mar H")]),
+            (Power::Germany, vec![order("A Mun - Bur")]),
+        ]);
+
+        let what_if = state.what_if(
+            &orders,
+            Power::France,
+            vec![order("A Par - Bur"), order("A Mar S A Par - Bur")],
+        );
+        let changes: Vec<_> = what_if
+            .changes()
+            .map(|(p, _, _)| p.abbreviation())
+            .collect();
+        assert_eq!(changes, ["Mar", "Par"]);
+        assert!(what_if
+            .alternative()
+            .get(province("Par"))
+            .unwrap()
+            .succeeded());
+        assert!(what_if.standoffs_lost().contains(province("Bur")));
+        assert!(what_if.standoffs_gained().is_empty());
+
+        let same = state.what_if(&orders, Power::France, orders[&Power::France].clone());
+        assert!(same.is_unchanged());
+    }
+}
@@ -0,0 +1,385 @@
+//! A plain-text file of a whole game: the starting position, then each
+//! phase's header followed by the orders given in it, each with the power
+//! that gave it, as in
+//!
+//! ```text
+//! # A game of Diplomacy
+//! Rules: convoys 2000, paradox DPTG, removals 1982, removal ties rulebook
+//! {"phase": "Spring 1901 Movement", "units": {...}, "centers": {...}}
+//!
+//! Spring 1901 Movement
+//! France: A Paris - Burgundy
+//! Germany: A Munich - Burgundy
+//!
+//! Winter 1901 Adjustments
+//! France: Build A Paris
+//!
+//! Spring 1902 Movement
+//! Draw: France, Germany
+//! ```
+//!
+//! The starting position is written as by [`GameState::to_json`], or as a
+//! scenario for [`GameState::from_scenario`], and may be left out for the
+//! standard one. Lines starting with `#` are comments. A `Rules:` line
+//! before the starting position gives the [`Rules`] the game was played
+//! under, and is left out for the default ones.
+//!
+//! Only the orders are kept, and reading a file plays them again under
+//! the game's rules, so the outcomes in a file follow from its orders and
+//! rules, as the adjudicator judges them when it is read. A game that
+//! ended by a vote records how in the phase it ended, as a `Draw:` line
+//! naming the powers that shared it or a `Solo:` line naming the power
+//! others conceded to.
+//!
+//! [`History::save`] and [`History::load`] write and read these files.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use crate::adjudicator::{Adjustment, ConvoyRules, ParadoxRule, RemovalRules, RemovalTies, Rules};
+use crate::history::History;
+use crate::map::Map;
+use crate::parser::parse_attributed_order;
+use crate::phase::{split_phases, PhaseKind};
+use crate::power::Power;
+use crate::state::GameState;
+use crate::vote::{Ballot, Outcome, Proposal};
+
+/// Something in a game file that could not be understood.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveError {
+    line: usize,
+    message: String,
+}
+
+impl ArchiveError {
+    /// The 1-based line number of the problem, or 0 for the starting
+    /// position as a whole.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ArchiveError {}
+
+/// Writes `history` as a game file.
+pub fn write(history: &History) -> String {
+    let mut out = String::from("# A game of Diplomacy\n");
+    if *history.rules() != Rules::default() {
+        writeln!(out, "Rules: {}", rules_text(history.rules())).unwrap();
+    }
+    out.push_str(&history.start().to_json());
+    out.push('\n');
+    for turn in history.turns() {
+        write!(out, "\n{}\n", turn.phase()).unwrap();
+        for &power in &Power::ALL {
+            for order in turn.orders().get(&power).into_iter().flatten() {
+                writeln!(out, "{}: {}", power, order).unwrap();
+            }
+            for adjustment in turn.adjustments().get(&power).into_iter().flatten() {
+                writeln!(out, "{}: {}", power, adjustment).unwrap();
+            }
+        }
+        match turn.outcome() {
+            Some(Outcome::Draw(powers)) => {
+                let powers: Vec<&str> = powers.iter().map(|power| power.name()).collect();
+                writeln!(out, "Draw: {}", powers.join(", ")).unwrap();
+            }
+            Some(Outcome::Solo(power)) => writeln!(out, "Solo: {}", power).unwrap(),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Reads a game file on `map`, playing each phase's orders in turn under
+/// the rules it gives, or the default rules if it gives none.
+pub fn read<'m>(map: &'m Map, text: &str) -> Result<History<'m>, ArchiveError> {
+    let line_of = |part: &str| {
+        text[..part.as_ptr() as usize - text.as_ptr() as usize]
+            .lines()
+            .count()
+            + 1
+    };
+    let mut parts = split_phases(text).into_iter().peekable();
+    let mut rules = Rules::default();
+    let start = match parts.peek() {
+        Some((None, start)) => {
+            let mut lines = Vec::new();
+            for line in start.lines() {
+                match line.trim().strip_prefix("Rules:") {
+                    Some(given) => {
+                        rules = parse_rules(given).map_err(|message| ArchiveError {
+                            line: line_of(line),
+                            message,
+                        })?;
+                    }
+                    None => lines.push(line),
+                }
+            }
+            parts.next();
+            uncommented(&lines.join("\n"))
+        }
+        _ => String::new(),
+    };
+    let start = match start.trim() {
+        "" => GameState::STANDARD,
+        start => start,
+    };
+    let start = GameState::from_scenario(map, start).map_err(|e| ArchiveError {
+        line: 0,
+        message: format!("starting position: {}", e),
+    })?;
+    let mut history = History::with_rules(start, rules);
+    for (phase, part) in parts {
+        let phase = phase.expect("only the first part has no phase");
+        let first = line_of(part);
+        let error = |line: usize, message: String| ArchiveError { line, message };
+        let expected = history.current().phase();
+        if phase != expected {
+            return Err(error(
+                first - 1,
+                format!("expected {}, not {}", expected, phase),
+            ));
+        }
+        let mut orders: HashMap<Power, Vec<_>> = HashMap::new();
+        let mut adjustments: HashMap<Power, Vec<_>> = HashMap::new();
+        let mut ballots = Vec::new();
+        for (i, line) in part.lines().enumerate() {
+            let line = line.trim();
+            let error = |message: String| error(first + i, message);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(powers) = line.strip_prefix("Draw:") {
+                let powers = powers
+                    .split(',')
+                    .map(|power| power.trim().parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| error(format!("{}", e)))?;
+                ballots.push(unanimous(Proposal::Draw(powers)));
+                continue;
+            }
+            if let Some(power) = line.strip_prefix("Solo:") {
+                let power = power.trim().parse().map_err(|e| error(format!("{}", e)))?;
+                ballots.push(unanimous(Proposal::Concede(power)));
+                continue;
+            }
+            let (power, given) = line
+                .split_once(':')
+                .ok_or_else(|| error("expected a power and its order".to_owned()))?;
+            let power: Power = power.trim().parse().map_err(|e| error(format!("{}", e)))?;
+            if phase.kind == PhaseKind::Adjustments {
+                let adjustment = Adjustment::parse(map, given)
+                    .ok_or_else(|| error(format!("not a build or disband: {:?}", given.trim())))?;
+                adjustments.entry(power).or_default().push(adjustment);
+            } else {
+                let (_, order) =
+                    parse_attributed_order(map, given).map_err(|e| error(e.to_string()))?;
+                orders.entry(power).or_default().push(order);
+            }
+        }
+        history
+            .play_turn(orders, adjustments, &ballots)
+            .map_err(|e| error(first - 1, e.to_string()))?;
+    }
+    Ok(history)
+}
+
+/// A ballot on `proposal` that every power voted for, standing for a vote
+/// that passed.
+fn unanimous(proposal: Proposal) -> Ballot {
+    let mut ballot = Ballot::new(proposal);
+    for &power in &Power::ALL {
+        ballot.vote(power, true);
+    }
+    ballot
+}
+
+/// The rule choices of `rules`, as a `Rules:` line gives them.
+fn rules_text(rules: &Rules) -> String {
+    let convoys = match rules.convoys {
+        ConvoyRules::Rules1971 => "1971",
+        ConvoyRules::Rules1982 => "1982",
+        ConvoyRules::Rules2000 => "2000",
+    };
+    let paradox = match rules.paradox {
+        ParadoxRule::Szykman => "Szykman",
+        ParadoxRule::Dptg => "DPTG",
+        ParadoxRule::AllHold => "all hold",
+    };
+    let removals = match rules.removals {
+        RemovalRules::Rules1982 => "1982",
+        RemovalRules::Rules2000 => "2000",
+    };
+    let removal_ties = match rules.removal_ties {
+        RemovalTies::Rulebook => "rulebook",
+        RemovalTies::Random => "random",
+    };
+    format!(
+        "convoys {}, paradox {}, removals {}, removal ties {}",
+        convoys, paradox, removals, removal_ties
+    )
+}
+
+/// Reads the rule choices of a `Rules:` line, each as written by
+/// [`rules_text`], ignoring case. Choices not given are the default.
+fn parse_rules(text: &str) -> Result<Rules, String> {
+    let mut rules = Rules::default();
+    for choice in text.split(',') {
+        let choice = choice.trim().to_lowercase();
+        let unknown = || format!("unknown rule {:?}", choice);
+        let (rule, value) = ["convoys", "paradox", "removals", "removal ties"]
+            .iter()
+            .find_map(|&rule| Some((rule, choice.strip_prefix(rule)?.strip_prefix(' ')?)))
+            .ok_or_else(unknown)?;
+        match (rule, value.trim()) {
+            ("convoys", "1971") => rules.convoys = ConvoyRules::Rules1971,
+            ("convoys", "1982") => rules.convoys = ConvoyRules::Rules1982,
+            ("convoys", "2000") => rules.convoys = ConvoyRules::Rules2000,
+            ("paradox", "szykman") => rules.paradox = ParadoxRule::Szykman,
+            ("paradox", "dptg") => rules.paradox = ParadoxRule::Dptg,
+            ("paradox", "all hold") => rules.paradox = ParadoxRule::AllHold,
+            ("removals", "1982") => rules.removals = RemovalRules::Rules1982,
+            ("removals", "2000") => rules.removals = RemovalRules::Rules2000,
+            ("removal ties", "rulebook") => rules.removal_ties = RemovalTies::Rulebook,
+            ("removal ties", "random") => rules.removal_ties = RemovalTies::Random,
+            _ => return Err(unknown()),
+        }
+    }
+    Ok(rules)
+}
+
+/// `text` without its comment lines.
+fn uncommented(text: &str) -> String {
+    text.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_order;
+
+    #[test]
+    fn write_and_read() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let mut history = History::new(GameState::standard());
+        history
+            .play(HashMap::from([
+                (Power::France, vec![order("A Par - Bur")]),
+                (
+                    Power::Germany,
+                    vec![order("A Mun - Bur"), order("A Ber - Par")],
+                ),
+            ]))
+            .unwrap();
+        history
+            .play(HashMap::from([(
+                Power::Germany,
+                vec![order("F Kie - Hol")],
+            )]))
+            .unwrap();
+        history
+            .play_adjustments(HashMap::from([(
+                Power::Germany,
+                vec![Adjustment::parse(map, "Build F Kiel").unwrap()],
+            )]))
+            .unwrap();
+        let mut draw = Ballot::new(Proposal::Draw(vec![Power::France, Power::Germany]));
+        for power in Power::ALL {
+            draw.vote(power, true);
+        }
+        history.play_with_ballots(HashMap::new(), &[draw]).unwrap();
+
+        let text = write(&history);
+        assert!(text.contains("\nWinter 1901 Adjustments\nGermany: Build F Kiel\n"));
+        assert!(text.ends_with("\nSpring 1902 Movement\nDraw: France, Germany\n"));
+        let read = read(map, &text).unwrap();
+        assert_eq!(read.turns().len(), 4);
+        assert_eq!(read.current(), history.current());
+        assert_eq!(read.outcome(), history.outcome());
+        assert_eq!(write(&read), text);
+
+        let path = std::env::temp_dir().join("dipboy-archive-test.txt");
+        history.save(&path).unwrap();
+        let loaded = History::load(map, &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.current(), history.current());
+    }
+
+    #[test]
+    fn standard_start_and_errors() {
+        let map = Map::standard();
+        let history = read(map, "# Just the one phase\nS1901M\nFrance: A Par - Bur\n").unwrap();
+        assert_eq!(history.start(), &GameState::standard());
+        assert_eq!(history.turns().len(), 1);
+
+        let error = read(map, "Spring 1901 Movement\nA Par - Bur\n").unwrap_err();
+        assert_eq!(error.line(), 2);
+        let error = read(map, "Fall 1901 Movement\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1: expected Spring 1901 Movement, not Fall 1901 Movement"
+        );
+        let error = read(map, "\nSpring 1901 Movement\n\nFrance: A Par - Atlantis\n").unwrap_err();
+        assert_eq!(error.line(), 4);
+        assert_eq!(
+            Adjustment::parse(map, "build f stp/nc"),
+            Adjustment::parse(map, "Build F St. Petersburg (nc)")
+        );
+    }
+
+    #[test]
+    fn rules_are_kept() {
+        use crate::phase::{Phase, Season};
+        use crate::state::Unit;
+
+        // Russia is in civil disorder with a unit too many, and the
+        // removal rules choose which goes.
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut start = GameState::new(map);
+        start.place_unit(province("Mos"), Unit::army(Power::Russia));
+        start.place_unit(province("Apu"), Unit::army(Power::Russia));
+        start.place_unit(province("Adr"), Unit::fleet(Power::Russia));
+        start.set_owner(province("Mos"), Power::Russia);
+        start.set_owner(province("Sev"), Power::Russia);
+        start.set_civil_disorder(Power::Russia, true);
+        start.set_phase(Phase::movement(Season::Fall, 1901));
+        let rules = Rules {
+            removals: RemovalRules::Rules1982,
+            paradox: ParadoxRule::AllHold,
+            ..Rules::default()
+        };
+        let mut history = History::with_rules(start.clone(), rules);
+        history.play(HashMap::new()).unwrap();
+        history.play(HashMap::new()).unwrap();
+        let mut default = History::new(start);
+        default.play(HashMap::new()).unwrap();
+        default.play(HashMap::new()).unwrap();
+        assert_ne!(history.current(), default.current());
+
+        let text = write(&history);
+        assert!(text.contains(
+            "\nRules: convoys 2000, paradox all hold, removals 1982, removal ties rulebook\n"
+        ));
+        let read = read(map, &text).unwrap();
+        assert_eq!(read.rules(), &rules);
+        assert_eq!(read.current(), history.current());
+        assert!(!write(&default).contains("Rules:"));
+
+        let error = super::read(map, "Rules: convoys 1990\nSpring 1901 Movement\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: unknown rule \"convoys 1990\"");
+    }
+}
@@ -0,0 +1,142 @@
+//! Players that choose their own orders.
+
+use std::collections::HashSet;
+
+use crate::map::Province;
+use crate::order::Order;
+use crate::power::Power;
+use crate::rng::Rng;
+use crate::state::{GameState, Unit};
+
+/// Something that can play a power.
+pub trait Strategist {
+    /// Orders for each of `power`'s units in the current phase.
+    fn orders<'m>(&self, state: &GameState<'m>, power: Power) -> Vec<Order<'m>>;
+}
+
+/// Holds or moves each unit at random.
+///
+/// Its orders are always legal, and are the same for the same seed and
+/// position.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RandomBot {
+    seed: u64,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> Self {
+        RandomBot { seed }
+    }
+}
+
+impl Strategist for RandomBot {
+    fn orders<'m>(&self, state: &GameState<'m>, power: Power) -> Vec<Order<'m>> {
+        let phase = state.phase();
+        let mut rng = Rng::new(
+            self.seed ^ (u64::from(phase.year) << 8) ^ ((phase.season as u64) << 4) ^ power as u64,
+        );
+        own_units(state, power)
+            .into_iter()
+            .map(|(province, _)| rng.choose(&holds_and_moves(state, province)).clone())
+            .collect()
+    }
+}
+
+/// Moves each unit into a neighbouring supply center that its power does
+/// not own, where it can, and holds the rest.
+///
+/// Units already on a center their power does not own stay to take it,
+/// and no two units are sent to the same place.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HoldAndGrab;
+
+impl Strategist for HoldAndGrab {
+    fn orders<'m>(&self, state: &GameState<'m>, power: Power) -> Vec<Order<'m>> {
+        let wanted = |province: &Province| {
+            province.is_supply_center() && state.owner(province) != Some(power)
+        };
+        let mut claimed = HashSet::new();
+        own_units(state, power)
+            .into_iter()
+            .map(|(province, unit)| {
+                if !wanted(province) {
+                    let grab = holds_and_moves(state, province).into_iter().find(|order| {
+                        order.destination().is_some_and(|to| {
+                            wanted(to)
+                                && state.unit_at(to).is_none_or(|unit| unit.power() != power)
+                                && claimed.insert(to.id())
+                        })
+                    });
+                    if let Some(order) = grab {
+                        return order;
+                    }
+                }
+                Order::hold(unit.unit_type(), province)
+            })
+            .collect()
+    }
+}
+
+/// `power`'s units, in map order.
+fn own_units<'m>(state: &GameState<'m>, power: Power) -> Vec<(&'m Province, Unit)> {
+    let mut units: Vec<_> = state
+        .units()
+        .filter(|(_, unit)| unit.power() == power)
+        .map(|(province, &unit)| (province, unit))
+        .collect();
+    units.sort_by_key(|(province, _)| province.id());
+    units
+}
+
+/// The orders to hold or to move without a convoy that the unit in
+/// `province` could be given.
+fn holds_and_moves<'m>(state: &GameState<'m>, province: &'m Province) -> Vec<Order<'m>> {
+    state
+        .legal_orders(province)
+        .into_iter()
+        .filter(|order| match order {
+            Order::Hold(_) => true,
+            Order::Move(move_) => !move_.via_convoy(),
+            _ => false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjudicator::{adjudicate, OrderStatus};
+    use std::collections::HashMap;
+
+    #[test]
+    fn random_orders_are_legal() {
+        let state = GameState::standard();
+        for seed in 0..20 {
+            let bot = RandomBot::new(seed);
+            let orders: HashMap<_, _> = Power::ALL
+                .iter()
+                .map(|&power| (power, bot.orders(&state, power)))
+                .collect();
+            assert_eq!(orders[&Power::Russia].len(), 4);
+            assert_eq!(bot.orders(&state, Power::Russia), orders[&Power::Russia]);
+            let result = adjudicate(orders, &state);
+            assert!(result.rejected().is_empty());
+            assert!(result
+                .resolutions()
+                .all(|(_, r)| r.status() == OrderStatus::Ordered));
+        }
+    }
+
+    #[test]
+    fn hold_and_grab_takes_neutral_centers() {
+        let state = GameState::standard();
+        let orders = HoldAndGrab.orders(&state, Power::France);
+        let targets: Vec<_> = orders
+            .iter()
+            .filter_map(|order| order.destination())
+            .map(Province::abbreviation)
+            .collect();
+        assert_eq!(targets, ["Spa"]);
+        assert_eq!(orders.len(), 3);
+    }
+}
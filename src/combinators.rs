@@ -0,0 +1,1692 @@
+//! A small library of backtracking parser combinators.
+//!
+//! A [`Parser`] does not commit to a single interpretation of its input.
+//! Instead it lazily enumerates every way it can match a prefix of the
+//! input, as `(item, remaining input)` pairs. Sequencing two parsers tries
+//! the second against every remainder left by the first, so ambiguity is
+//! resolved by whichever caller finally inspects the results (usually by
+//! asking for a parse that consumes the whole input).
+//!
+//! The combinators hold nothing but their child parsers and closures, so a
+//! parser (and the iterator it returns) is `Send` and `Sync` whenever its
+//! closures are. A [`boxed`] parser is too, though its iterator is not.
+//!
+//! Boxing also erases a parser's type, so parsers of different types can
+//! be kept together, as in the `Vec` tried in turn by [`choice`], and a
+//! grammar can refer to itself through the handle [`recursive`] gives it.
+//!
+//! Parsers given a name with [`Parser::named`] or [`context`] can be
+//! watched at work: [`parse_with_trace`] records every attempt each of them
+//! makes as a tree of [`Trace`]s, which shows where a grammar gave up on
+//! some input and what it was expecting there.
+//!
+//! Because every parse is enumerated, a sufficiently ambiguous input can
+//! take a very long time to exhaust. [`with_budget`] bounds the number of
+//! steps the parsers may take, so that such input fails instead. Where the
+//! same parser is tried at the same position many times over, [`memo`]
+//! remembers its parses so the work is only done once.
+
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+use core::fmt;
+
+use crate::prelude::*;
+
+/// A parser over string slices with lifetime `'a`.
+pub trait Parser<'a>: Clone {
+    /// The value produced by a successful parse.
+    type Item;
+
+    /// The iterator over all successful parses of a prefix of the input.
+    type Iter: Iterator<Item = (Self::Item, &'a str)>;
+
+    /// Enumerates every parse of a prefix of `input`.
+    fn parse(&self, input: &'a str) -> Self::Iter;
+
+    /// Transforms each parsed item with `f`.
+    fn map<F, B>(self, f: F) -> Map<Self, F>
+    where
+        F: Fn(Self::Item) -> B + Clone,
+    {
+        Map { parser: self, f }
+    }
+
+    /// Discards the parses for which `pred` returns `false`.
+    ///
+    /// The predicate sees the parsed item and the input remaining after it,
+    /// which makes this the natural place for lookahead checks such as word
+    /// boundaries.
+    fn filter<F>(self, pred: F) -> Filter<Self, F>
+    where
+        F: Fn(&Self::Item, &'a str) -> bool + Clone,
+    {
+        Filter { parser: self, pred }
+    }
+
+    /// Names the parser, so that [`parse_with_trace`] records its
+    /// attempts. Outside a trace, the name changes nothing.
+    fn named(self, name: &'static str) -> Named<Self> {
+        Named { parser: self, name }
+    }
+
+    /// Hides the type of the parser behind a pointer. See [`boxed`].
+    fn boxed<'p>(self) -> Boxed<'a, 'p, Self::Item>
+    where
+        Self: Send + Sync + 'p,
+        Self::Iter: 'p,
+    {
+        boxed(self)
+    }
+}
+
+/// Matches `tag` at the start of the input, ignoring ASCII case.
+pub fn tag(tag: &'static str) -> Tag {
+    Tag { tag }
+}
+
+/// See [`tag`].
+#[derive(Clone, Copy, Debug)]
+pub struct Tag {
+    tag: &'static str,
+}
+
+impl<'a> Parser<'a> for Tag {
+    type Item = &'a str;
+    type Iter = core::option::IntoIter<(&'a str, &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        let len = self.tag.len();
+        match input.get(..len) {
+            Some(head) if head.eq_ignore_ascii_case(self.tag) => Some((head, &input[len..])),
+            _ => None,
+        }
+        .into_iter()
+    }
+}
+
+/// Matches any of `tags` at the start of the input, ignoring ASCII case.
+///
+/// Every matching tag is yielded, in the order given.
+pub fn any_tag(tags: &'static [&'static str]) -> AnyTag {
+    AnyTag { tags }
+}
+
+/// See [`any_tag`].
+#[derive(Clone, Copy, Debug)]
+pub struct AnyTag {
+    tags: &'static [&'static str],
+}
+
+impl<'a> Parser<'a> for AnyTag {
+    type Item = &'a str;
+    type Iter = alloc::vec::IntoIter<(&'a str, &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        self.tags
+            .iter()
+            .flat_map(|&t| tag(t).parse(input))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+/// Matches a single character for which `pred` returns `true`.
+pub fn char_where<F>(pred: F) -> CharWhere<F>
+where
+    F: Fn(char) -> bool + Clone,
+{
+    CharWhere { pred }
+}
+
+/// See [`char_where`].
+#[derive(Clone, Copy, Debug)]
+pub struct CharWhere<F> {
+    pred: F,
+}
+
+impl<'a, F> Parser<'a> for CharWhere<F>
+where
+    F: Fn(char) -> bool + Clone,
+{
+    type Item = char;
+    type Iter = core::option::IntoIter<(char, &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        input
+            .chars()
+            .next()
+            .filter(|&c| (self.pred)(c))
+            .map(|c| (c, &input[c.len_utf8()..]))
+            .into_iter()
+    }
+}
+
+/// Matches a single character that appears in `chars`.
+pub fn one_of(chars: &'static str) -> OneOf {
+    OneOf { chars }
+}
+
+/// See [`one_of`].
+#[derive(Clone, Copy, Debug)]
+pub struct OneOf {
+    chars: &'static str,
+}
+
+impl<'a> Parser<'a> for OneOf {
+    type Item = char;
+    type Iter = core::option::IntoIter<(char, &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        char_where(|c| self.chars.contains(c)).parse(input)
+    }
+}
+
+/// Matches the longest run of one or more characters for which `pred`
+/// returns `true`.
+///
+/// Only the longest run is yielded, so `take_while1(char::is_alphabetic)`
+/// reads a whole word rather than every prefix of it.
+pub fn take_while1<F>(pred: F) -> TakeWhile1<F>
+where
+    F: Fn(char) -> bool + Clone,
+{
+    TakeWhile1 { pred }
+}
+
+/// See [`take_while1`].
+#[derive(Clone, Copy, Debug)]
+pub struct TakeWhile1<F> {
+    pred: F,
+}
+
+impl<'a, F> Parser<'a> for TakeWhile1<F>
+where
+    F: Fn(char) -> bool + Clone,
+{
+    type Item = &'a str;
+    type Iter = core::option::IntoIter<(&'a str, &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        let len = input.find(|c: char| !(self.pred)(c)).unwrap_or(input.len());
+        if len == 0 {
+            None
+        } else {
+            Some((&input[..len], &input[len..]))
+        }
+        .into_iter()
+    }
+}
+
+/// Matches one or more whitespace characters.
+pub fn whitespace() -> Whitespace {
+    Whitespace { required: true }
+}
+
+/// Matches zero or more whitespace characters.
+pub fn skip_whitespace() -> Whitespace {
+    Whitespace { required: false }
+}
+
+/// See [`whitespace`] and [`skip_whitespace`].
+#[derive(Clone, Copy, Debug)]
+pub struct Whitespace {
+    required: bool,
+}
+
+impl<'a> Parser<'a> for Whitespace {
+    type Item = ();
+    type Iter = core::option::IntoIter<((), &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        let rest = input.trim_start();
+        if self.required && rest.len() == input.len() {
+            None
+        } else {
+            Some(((), rest))
+        }
+        .into_iter()
+    }
+}
+
+/// Succeeds, consuming nothing, only at the end of the input.
+pub fn eof() -> Eof {
+    Eof
+}
+
+/// See [`eof`].
+#[derive(Clone, Copy, Debug)]
+pub struct Eof;
+
+impl<'a> Parser<'a> for Eof {
+    type Item = ();
+    type Iter = core::option::IntoIter<((), &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        if input.is_empty() {
+            Some(((), input))
+        } else {
+            None
+        }
+        .into_iter()
+    }
+}
+
+/// Runs `first`, then `second` on each remainder, yielding both items.
+pub fn chain<'a, P, Q>(first: P, second: Q) -> Chain<P, Q>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+    Q: Parser<'a>,
+{
+    Chain { first, second }
+}
+
+/// See [`chain`].
+#[derive(Clone, Copy, Debug)]
+pub struct Chain<P, Q> {
+    first: P,
+    second: Q,
+}
+
+impl<'a, P, Q> Parser<'a> for Chain<P, Q>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+    Q: Parser<'a>,
+{
+    type Item = (P::Item, Q::Item);
+    type Iter = ChainIter<'a, P, Q>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        ChainIter {
+            first: self.first.parse(input),
+            second: self.second.clone(),
+            current: None,
+        }
+    }
+}
+
+/// The iterator returned by [`Chain::parse`].
+pub struct ChainIter<'a, P: Parser<'a>, Q: Parser<'a>> {
+    first: P::Iter,
+    second: Q,
+    current: Option<(P::Item, Q::Iter)>,
+}
+
+impl<'a, P, Q> Iterator for ChainIter<'a, P, Q>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+    Q: Parser<'a>,
+{
+    type Item = ((P::Item, Q::Item), &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((a, iter)) = &mut self.current {
+                if let Some((b, rest)) = iter.next() {
+                    return Some(((a.clone(), b), rest));
+                }
+            }
+            if !budget::step() {
+                return None;
+            }
+            let (a, rest) = self.first.next()?;
+            self.current = Some((a, self.second.parse(rest)));
+        }
+    }
+}
+
+/// Runs `first`, then `second`, yielding only the item of `second`, as
+/// for a keyword followed by its value.
+pub fn preceded<'a, P, Q>(first: P, second: Q) -> Preceded<P, Q>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+    Q: Parser<'a>,
+{
+    Preceded {
+        inner: chain(first, second),
+    }
+}
+
+/// See [`preceded`].
+#[derive(Clone, Copy, Debug)]
+pub struct Preceded<P, Q> {
+    inner: Chain<P, Q>,
+}
+
+impl<'a, P, Q> Parser<'a> for Preceded<P, Q>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+    Q: Parser<'a>,
+{
+    type Item = Q::Item;
+    type Iter = MapIter<ChainIter<'a, P, Q>, fn((P::Item, Q::Item)) -> Q::Item>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        MapIter {
+            inner: self.inner.parse(input),
+            f: |(_, b)| b,
+        }
+    }
+}
+
+/// Runs `first`, then `second`, yielding only the item of `first`, as for
+/// a value followed by a terminator.
+pub fn terminated<'a, P, Q>(first: P, second: Q) -> Terminated<P, Q>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+    Q: Parser<'a>,
+{
+    Terminated {
+        inner: chain(first, second),
+    }
+}
+
+/// See [`terminated`].
+#[derive(Clone, Copy, Debug)]
+pub struct Terminated<P, Q> {
+    inner: Chain<P, Q>,
+}
+
+impl<'a, P, Q> Parser<'a> for Terminated<P, Q>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+    Q: Parser<'a>,
+{
+    type Item = P::Item;
+    type Iter = MapIter<ChainIter<'a, P, Q>, fn((P::Item, Q::Item)) -> P::Item>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        MapIter {
+            inner: self.inner.parse(input),
+            f: |(a, _)| a,
+        }
+    }
+}
+
+/// Runs `open`, `parser` and `close` in turn, yielding only the item of
+/// `parser`, as for a parenthesized value.
+pub fn delimited<'a, O, P, C>(open: O, parser: P, close: C) -> Delimited<O, P, C>
+where
+    O: Parser<'a>,
+    O::Item: Clone,
+    P: Parser<'a>,
+    P::Item: Clone,
+    C: Parser<'a>,
+{
+    Delimited {
+        inner: preceded(open, terminated(parser, close)),
+    }
+}
+
+/// See [`delimited`].
+#[derive(Clone, Copy, Debug)]
+pub struct Delimited<O, P, C> {
+    inner: Preceded<O, Terminated<P, C>>,
+}
+
+impl<'a, O, P, C> Parser<'a> for Delimited<O, P, C>
+where
+    O: Parser<'a>,
+    O::Item: Clone,
+    P: Parser<'a>,
+    P::Item: Clone,
+    C: Parser<'a>,
+{
+    type Item = P::Item;
+    type Iter = <Preceded<O, Terminated<P, C>> as Parser<'a>>::Iter;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        self.inner.parse(input)
+    }
+}
+
+/// Yields only the parses of `parser` that consume the whole input.
+pub fn complete<'a, P>(parser: P) -> Complete<P>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+{
+    Complete {
+        inner: terminated(parser, eof()),
+    }
+}
+
+/// See [`complete`].
+#[derive(Clone, Copy, Debug)]
+pub struct Complete<P> {
+    inner: Terminated<P, Eof>,
+}
+
+impl<'a, P> Parser<'a> for Complete<P>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+{
+    type Item = P::Item;
+    type Iter = <Terminated<P, Eof> as Parser<'a>>::Iter;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        self.inner.parse(input)
+    }
+}
+
+/// One of two alternatives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// Yields every parse of `left`, then every parse of `right`.
+pub fn either<'a, P, Q>(left: P, right: Q) -> Or<P, Q>
+where
+    P: Parser<'a>,
+    Q: Parser<'a>,
+{
+    Or { left, right }
+}
+
+/// See [`either`].
+#[derive(Clone, Copy, Debug)]
+pub struct Or<P, Q> {
+    left: P,
+    right: Q,
+}
+
+impl<'a, P, Q> Parser<'a> for Or<P, Q>
+where
+    P: Parser<'a>,
+    Q: Parser<'a>,
+{
+    type Item = Either<P::Item, Q::Item>;
+    type Iter = OrIter<'a, P, Q>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        OrIter {
+            left: self.left.parse(input),
+            right: self.right.clone(),
+            right_iter: None,
+            input,
+        }
+    }
+}
+
+/// The iterator returned by [`Or::parse`].
+pub struct OrIter<'a, P: Parser<'a>, Q: Parser<'a>> {
+    left: P::Iter,
+    right: Q,
+    right_iter: Option<Q::Iter>,
+    input: &'a str,
+}
+
+impl<'a, P, Q> Iterator for OrIter<'a, P, Q>
+where
+    P: Parser<'a>,
+    Q: Parser<'a>,
+{
+    type Item = (Either<P::Item, Q::Item>, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.right_iter.is_none() {
+            if let Some((a, rest)) = self.left.next() {
+                return Some((Either::Left(a), rest));
+            }
+            self.right_iter = Some(self.right.parse(self.input));
+        }
+        let (b, rest) = self.right_iter.as_mut()?.next()?;
+        Some((Either::Right(b), rest))
+    }
+}
+
+/// Yields every parse of each parser in `alternatives`, a tuple of up to
+/// eight parsers with the same item type, trying them in order.
+///
+/// Unlike nested [`either`]s, this needs no [`Either`] to be taken apart
+/// afterwards.
+pub fn alt<'a, T>(alternatives: T) -> Alt<T>
+where
+    T: Alternatives<'a>,
+{
+    Alt { alternatives }
+}
+
+/// A tuple of parsers that can be tried in turn by [`alt`].
+pub trait Alternatives<'a>: Clone {
+    /// The item produced by every one of the parsers.
+    type Item;
+
+    /// The iterator over the parses of all the parsers.
+    type Iter: Iterator<Item = (Self::Item, &'a str)>;
+
+    /// Enumerates the parses of each parser in turn.
+    fn parse(&self, input: &'a str) -> Self::Iter;
+}
+
+/// See [`alt`].
+#[derive(Clone, Copy, Debug)]
+pub struct Alt<T> {
+    alternatives: T,
+}
+
+impl<'a, T> Parser<'a> for Alt<T>
+where
+    T: Alternatives<'a>,
+{
+    type Item = T::Item;
+    type Iter = T::Iter;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        self.alternatives.parse(input)
+    }
+}
+
+/// The iterator returned by [`Alternatives::parse`] for two or more
+/// parsers: the first parser's parses, then those of the rest.
+pub struct AltIter<'a, P: Parser<'a>, R: Alternatives<'a>> {
+    first: P::Iter,
+    rest: R,
+    rest_iter: Option<R::Iter>,
+    input: &'a str,
+}
+
+impl<'a, P, R> Iterator for AltIter<'a, P, R>
+where
+    P: Parser<'a>,
+    R: Alternatives<'a, Item = P::Item>,
+{
+    type Item = (P::Item, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest_iter.is_none() {
+            if let Some(parse) = self.first.next() {
+                return Some(parse);
+            }
+            self.rest_iter = Some(self.rest.parse(self.input));
+        }
+        self.rest_iter.as_mut()?.next()
+    }
+}
+
+impl<'a, P> Alternatives<'a> for (P,)
+where
+    P: Parser<'a>,
+{
+    type Item = P::Item;
+    type Iter = P::Iter;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        self.0.parse(input)
+    }
+}
+
+/// Implements [`Alternatives`] for tuples of two or more parsers, each as
+/// its first parser followed by the tuple of the rest.
+macro_rules! alternatives {
+    ($first:ident $(, $rest:ident)+) => {
+        impl<'a, $first $(, $rest)+> Alternatives<'a> for ($first $(, $rest)+)
+        where
+            $first: Parser<'a>,
+            $($rest: Parser<'a, Item = $first::Item>,)+
+        {
+            type Item = $first::Item;
+            type Iter = AltIter<'a, $first, ($($rest,)+)>;
+
+            #[allow(non_snake_case)]
+            fn parse(&self, input: &'a str) -> Self::Iter {
+                let ($first $(, $rest)+) = self;
+                AltIter {
+                    first: $first.parse(input),
+                    rest: ($($rest.clone(),)+),
+                    rest_iter: None,
+                    input,
+                }
+            }
+        }
+
+        alternatives!($($rest),+);
+    };
+    ($only:ident) => {};
+}
+
+alternatives!(P1, P2, P3, P4, P5, P6, P7, P8);
+
+/// Yields every parse of `parser` wrapped in `Some`, then `None` without
+/// consuming any input.
+pub fn optional<'a, P>(parser: P) -> Optional<P>
+where
+    P: Parser<'a>,
+{
+    Optional { parser }
+}
+
+/// See [`optional`].
+#[derive(Clone, Copy, Debug)]
+pub struct Optional<P> {
+    parser: P,
+}
+
+impl<'a, P> Parser<'a> for Optional<P>
+where
+    P: Parser<'a>,
+{
+    type Item = Option<P::Item>;
+    type Iter = OptionalIter<'a, P>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        OptionalIter {
+            inner: Some(self.parser.parse(input)),
+            input,
+        }
+    }
+}
+
+/// The iterator returned by [`Optional::parse`].
+pub struct OptionalIter<'a, P: Parser<'a>> {
+    inner: Option<P::Iter>,
+    input: &'a str,
+}
+
+impl<'a, P> Iterator for OptionalIter<'a, P>
+where
+    P: Parser<'a>,
+{
+    type Item = (Option<P::Item>, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let inner = self.inner.as_mut()?;
+        match inner.next() {
+            Some((a, rest)) => Some((Some(a), rest)),
+            None => {
+                self.inner = None;
+                Some((None, self.input))
+            }
+        }
+    }
+}
+
+/// Yields every parse of `parser` without consuming any input.
+pub fn peek<'a, P>(parser: P) -> Peek<P>
+where
+    P: Parser<'a>,
+{
+    Peek { parser }
+}
+
+/// See [`peek`].
+#[derive(Clone, Copy, Debug)]
+pub struct Peek<P> {
+    parser: P,
+}
+
+impl<'a, P> Parser<'a> for Peek<P>
+where
+    P: Parser<'a>,
+{
+    type Item = P::Item;
+    type Iter = PeekIter<'a, P::Iter>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        PeekIter {
+            inner: self.parser.parse(input),
+            input,
+        }
+    }
+}
+
+/// The iterator returned by [`Peek::parse`].
+pub struct PeekIter<'a, I> {
+    inner: I,
+    input: &'a str,
+}
+
+impl<'a, I, A> Iterator for PeekIter<'a, I>
+where
+    I: Iterator<Item = (A, &'a str)>,
+{
+    type Item = (A, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (a, _) = self.inner.next()?;
+        Some((a, self.input))
+    }
+}
+
+/// Succeeds without consuming any input if, and only if, `parser` fails.
+pub fn not<'a, P>(parser: P) -> Not<P>
+where
+    P: Parser<'a>,
+{
+    Not { parser }
+}
+
+/// See [`not`].
+#[derive(Clone, Copy, Debug)]
+pub struct Not<P> {
+    parser: P,
+}
+
+impl<'a, P> Parser<'a> for Not<P>
+where
+    P: Parser<'a>,
+{
+    type Item = ();
+    type Iter = core::option::IntoIter<((), &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        match self.parser.parse(input).next() {
+            Some(_) => None,
+            None => Some(((), input)),
+        }
+        .into_iter()
+    }
+}
+
+/// See [`Parser::map`].
+#[derive(Clone, Copy, Debug)]
+pub struct Map<P, F> {
+    parser: P,
+    f: F,
+}
+
+impl<'a, P, F, B> Parser<'a> for Map<P, F>
+where
+    P: Parser<'a>,
+    F: Fn(P::Item) -> B + Clone,
+{
+    type Item = B;
+    type Iter = MapIter<P::Iter, F>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        MapIter {
+            inner: self.parser.parse(input),
+            f: self.f.clone(),
+        }
+    }
+}
+
+/// The iterator returned by [`Map::parse`].
+pub struct MapIter<I, F> {
+    inner: I,
+    f: F,
+}
+
+impl<'a, I, F, A, B> Iterator for MapIter<I, F>
+where
+    I: Iterator<Item = (A, &'a str)>,
+    F: Fn(A) -> B,
+{
+    type Item = (B, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (a, rest) = self.inner.next()?;
+        Some(((self.f)(a), rest))
+    }
+}
+
+/// See [`Parser::filter`].
+#[derive(Clone, Copy, Debug)]
+pub struct Filter<P, F> {
+    parser: P,
+    pred: F,
+}
+
+impl<'a, P, F> Parser<'a> for Filter<P, F>
+where
+    P: Parser<'a>,
+    F: Fn(&P::Item, &'a str) -> bool + Clone,
+{
+    type Item = P::Item;
+    type Iter = FilterIter<P::Iter, F>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        FilterIter {
+            inner: self.parser.parse(input),
+            pred: self.pred.clone(),
+        }
+    }
+}
+
+/// The iterator returned by [`Filter::parse`].
+pub struct FilterIter<I, F> {
+    inner: I,
+    pred: F,
+}
+
+impl<'a, I, F, A> Iterator for FilterIter<I, F>
+where
+    I: Iterator<Item = (A, &'a str)>,
+    F: Fn(&A, &'a str) -> bool,
+{
+    type Item = (A, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pred = &self.pred;
+        self.inner.find(|(a, rest)| pred(a, rest))
+    }
+}
+
+/// Labels `parser` as a part of the grammar, such as "province", like
+/// [`Parser::named`].
+///
+/// The label is what failures of the parser are reported as: when no parse
+/// is found, [`Trace::expected`] names the labelled parts that the input
+/// ran out of or did not match.
+pub fn context<'a, P>(label: &'static str, parser: P) -> Named<P>
+where
+    P: Parser<'a>,
+{
+    parser.named(label)
+}
+
+/// See [`Parser::named`] and [`context`].
+#[derive(Clone, Copy, Debug)]
+pub struct Named<P> {
+    parser: P,
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    name: &'static str,
+}
+
+impl<'a, P> Parser<'a> for Named<P>
+where
+    P: Parser<'a>,
+{
+    type Item = P::Item;
+    type Iter = NamedIter<'a, P>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        #[cfg(feature = "std")]
+        if trace::enter(self.name, input) {
+            // The attempt is run to the end while the trace is open, so that
+            // the attempts of the parsers inside it are its children.
+            let parses: Vec<_> = self.parser.parse(input).collect();
+            trace::exit(parses.len());
+            return NamedIter::Traced(parses.into_iter());
+        }
+        NamedIter::Lazy(self.parser.parse(input))
+    }
+}
+
+/// The iterator returned by [`Named::parse`].
+pub enum NamedIter<'a, P: Parser<'a>> {
+    Lazy(P::Iter),
+    Traced(alloc::vec::IntoIter<(P::Item, &'a str)>),
+}
+
+impl<'a, P> Iterator for NamedIter<'a, P>
+where
+    P: Parser<'a>,
+{
+    type Item = (P::Item, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            NamedIter::Lazy(inner) => inner.next(),
+            NamedIter::Traced(inner) => inner.next(),
+        }
+    }
+}
+
+/// Remembers the parses of `parser` at each position in the input, so that
+/// trying it again where it was tried before costs nothing.
+///
+/// Clones of the parser share what it remembers, which is what makes this
+/// worthwhile: a parser that follows an ambiguous one in a [`chain`] is
+/// cloned for every remainder, and tried again whenever two remainders
+/// are the same. Each position's parses are found in full the first time
+/// it is tried, rather than lazily.
+///
+/// The cache is bypassed while [`parse_with_trace`] is recording, so that
+/// every attempt shows in the trace. Without the `std` feature nothing is
+/// remembered.
+pub fn memo<'a, P>(parser: P) -> Memo<'a, P>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+{
+    Memo {
+        parser,
+        #[cfg(feature = "std")]
+        cache: Default::default(),
+        #[cfg(not(feature = "std"))]
+        cache: core::marker::PhantomData,
+    }
+}
+
+/// See [`memo`].
+pub struct Memo<'a, P: Parser<'a>> {
+    parser: P,
+    /// The parses found so far, by the address and length of the input
+    /// they were found in. Every input the parser sees outlives `'a`, so
+    /// no two different inputs can share a key.
+    #[cfg(feature = "std")]
+    cache: std::sync::Arc<std::sync::Mutex<MemoTable<'a, P::Item>>>,
+    #[cfg(not(feature = "std"))]
+    cache: core::marker::PhantomData<&'a ()>,
+}
+
+#[cfg(feature = "std")]
+type MemoTable<'a, T> = std::collections::HashMap<(usize, usize), Vec<(T, &'a str)>>;
+
+impl<'a, P> Clone for Memo<'a, P>
+where
+    P: Parser<'a>,
+{
+    // Without `std` the cache is a `PhantomData`, which is `Copy`.
+    #[cfg_attr(not(feature = "std"), allow(clippy::clone_on_copy))]
+    fn clone(&self) -> Self {
+        Memo {
+            parser: self.parser.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+impl<'a, P> fmt::Debug for Memo<'a, P>
+where
+    P: Parser<'a> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Memo")
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, P> Parser<'a> for Memo<'a, P>
+where
+    P: Parser<'a>,
+    P::Item: Clone,
+{
+    type Item = P::Item;
+    type Iter = alloc::vec::IntoIter<(P::Item, &'a str)>;
+
+    #[cfg(feature = "std")]
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        if trace::recording() {
+            return self.parser.parse(input).collect::<Vec<_>>().into_iter();
+        }
+        let key = (input.as_ptr() as usize, input.len());
+        let cached = self.cache.lock().unwrap().get(&key).cloned();
+        let parses = cached.unwrap_or_else(|| {
+            let parses: Vec<_> = self.parser.parse(input).collect();
+            self.cache.lock().unwrap().insert(key, parses.clone());
+            parses
+        });
+        parses.into_iter()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        self.parser.parse(input).collect::<Vec<_>>().into_iter()
+    }
+}
+
+/// Hides the type of `parser` behind a pointer, so that a grammar built
+/// from it need not spell it out.
+///
+/// Every combinator wraps the types of its children, and a large grammar
+/// nests them deeply enough that compiling it grows slow. Boxing its
+/// larger parts keeps the types small, at the cost of a dynamic call per
+/// attempt. `'p` is the lifetime of whatever the parser borrows, such as a
+/// map, and of the input.
+///
+/// The boxed parser is `Send` and `Sync`, but the iterators it returns are
+/// not, since the type of the iterator is not always known to be.
+pub fn boxed<'a, 'p, P>(parser: P) -> Boxed<'a, 'p, P::Item>
+where
+    P: Parser<'a> + Send + Sync + 'p,
+    P::Iter: 'p,
+{
+    Boxed {
+        parser: alloc::sync::Arc::new(parser),
+    }
+}
+
+/// See [`boxed`].
+pub struct Boxed<'a, 'p, T> {
+    parser: alloc::sync::Arc<dyn ErasedParser<'a, 'p, T> + Send + Sync + 'p>,
+}
+
+/// The iterator returned by [`Boxed::parse`].
+pub type BoxedIter<'a, 'p, T> = Box<dyn Iterator<Item = (T, &'a str)> + 'p>;
+
+/// A [`Parser`] that boxes its iterator, so that it can be made into a
+/// trait object.
+trait ErasedParser<'a, 'p, T> {
+    fn parse_boxed(&self, input: &'a str) -> BoxedIter<'a, 'p, T>;
+}
+
+impl<'a, 'p, P> ErasedParser<'a, 'p, P::Item> for P
+where
+    P: Parser<'a>,
+    P::Iter: 'p,
+{
+    fn parse_boxed(&self, input: &'a str) -> BoxedIter<'a, 'p, P::Item> {
+        Box::new(self.parse(input))
+    }
+}
+
+impl<'a, 'p, T> Clone for Boxed<'a, 'p, T> {
+    fn clone(&self) -> Self {
+        Boxed {
+            parser: self.parser.clone(),
+        }
+    }
+}
+
+impl<'a, 'p, T> fmt::Debug for Boxed<'a, 'p, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Boxed").finish_non_exhaustive()
+    }
+}
+
+impl<'a, 'p, T> Parser<'a> for Boxed<'a, 'p, T> {
+    type Item = T;
+    type Iter = BoxedIter<'a, 'p, T>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        self.parser.parse_boxed(input)
+    }
+}
+
+/// Builds a grammar that refers to itself, such as a parenthesized
+/// expression that may contain another.
+///
+/// `build` is given a handle that parses with the grammar it returns, and
+/// may use it anywhere within. The handle only holds on to the grammar
+/// weakly, so the two do not keep each other alive: once every clone of
+/// the returned parser is gone, a handle kept elsewhere parses nothing.
+///
+/// The grammar must consume some input before it uses the handle, or it
+/// tries itself at the same position forever; [`with_budget`] stops such a
+/// grammar, but does not make it finish.
+pub fn recursive<'a, 'p, T, F, P>(build: F) -> Boxed<'a, 'p, T>
+where
+    F: FnOnce(Boxed<'a, 'p, T>) -> P,
+    P: Parser<'a, Item = T> + Send + Sync + 'p,
+    P::Iter: 'p,
+    T: 'p,
+    'a: 'p,
+{
+    let parser = alloc::sync::Arc::new_cyclic(|grammar: &alloc::sync::Weak<P>| {
+        let grammar: alloc::sync::Weak<dyn ErasedParser<'a, 'p, T> + Send + Sync + 'p> =
+            grammar.clone();
+        build(Boxed {
+            parser: alloc::sync::Arc::new(Handle { grammar }),
+        })
+    });
+    Boxed { parser }
+}
+
+/// The handle given to the `build` function of [`recursive`].
+struct Handle<'a, 'p, T> {
+    grammar: alloc::sync::Weak<dyn ErasedParser<'a, 'p, T> + Send + Sync + 'p>,
+}
+
+impl<'a: 'p, 'p, T: 'p> ErasedParser<'a, 'p, T> for Handle<'a, 'p, T> {
+    fn parse_boxed(&self, input: &'a str) -> BoxedIter<'a, 'p, T> {
+        match self.grammar.upgrade() {
+            Some(grammar) => grammar.parse_boxed(input),
+            None => Box::new(core::iter::empty()),
+        }
+    }
+}
+
+/// Yields every parse of each of `parsers` in turn.
+///
+/// This is [`alt`] for a number of parsers only known at run time. The
+/// parsers must all be of one type, which [`boxed`] parsers are, whatever
+/// they were before boxing.
+pub fn choice<'a, P>(parsers: Vec<P>) -> Choice<P>
+where
+    P: Parser<'a>,
+{
+    Choice {
+        parsers: parsers.into(),
+    }
+}
+
+/// See [`choice`].
+#[derive(Clone, Debug)]
+pub struct Choice<P> {
+    parsers: alloc::sync::Arc<[P]>,
+}
+
+impl<'a, P> Parser<'a> for Choice<P>
+where
+    P: Parser<'a>,
+{
+    type Item = P::Item;
+    type Iter = ChoiceIter<'a, P>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        ChoiceIter {
+            parsers: self.parsers.clone(),
+            next: 0,
+            current: None,
+            input,
+        }
+    }
+}
+
+/// The iterator returned by [`Choice::parse`].
+pub struct ChoiceIter<'a, P: Parser<'a>> {
+    parsers: alloc::sync::Arc<[P]>,
+    next: usize,
+    current: Option<P::Iter>,
+    input: &'a str,
+}
+
+impl<'a, P> Iterator for ChoiceIter<'a, P>
+where
+    P: Parser<'a>,
+{
+    type Item = (P::Item, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(parse) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(parse);
+            }
+            let parser = self.parsers.get(self.next)?;
+            self.current = Some(parser.parse(self.input));
+            self.next += 1;
+        }
+    }
+}
+
+/// The error returned by [`with_budget`] when the parsers run out of
+/// steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BudgetExceeded;
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("parse step budget exceeded")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BudgetExceeded {}
+
+/// Runs `f`, letting the parsers it runs on this thread take at most
+/// `steps` steps between them, or any number if `steps` is `None`.
+///
+/// A step is one alternative tried by a sequence of parsers, such as one
+/// of the ways the first parser in a [`chain`] matched. Once the budget is
+/// spent, every sequence stops yielding parses, and the result of `f` is
+/// replaced with [`BudgetExceeded`].
+///
+/// Only the `std` feature enforces the budget; without it, `f` always runs
+/// to the end.
+pub fn with_budget<T>(steps: Option<usize>, f: impl FnOnce() -> T) -> Result<T, BudgetExceeded> {
+    let outer = budget::begin(steps);
+    let result = f();
+    if budget::end(outer) {
+        Err(BudgetExceeded)
+    } else {
+        Ok(result)
+    }
+}
+
+/// One attempt by a named parser, with the attempts made by the named
+/// parsers inside it.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace {
+    /// The name or label of the parser that made the attempt.
+    pub name: &'static str,
+    /// Where the attempt began, as a byte offset into the traced input.
+    pub position: usize,
+    /// How many ways the parser matched; none if it failed.
+    pub matches: usize,
+    /// The attempts made by the named parsers inside this one, in the
+    /// order they were made.
+    pub children: Vec<Trace>,
+}
+
+#[cfg(feature = "std")]
+impl Trace {
+    /// Whether the parser matched at least once.
+    pub fn succeeded(&self) -> bool {
+        self.matches > 0
+    }
+
+    /// Where the parse got furthest before failing, as a byte offset, and
+    /// the names of the parsers that failed there, in the order they were
+    /// tried.
+    ///
+    /// Only failures that count are kept: a parser that failed inside one
+    /// that succeeded, as an optional part does, is left out, and so is one
+    /// that failed where a named parser around it also failed, which names
+    /// the failure better.
+    pub fn expected(&self) -> Option<(usize, Vec<&'static str>)> {
+        let mut failures = Vec::new();
+        for child in &self.children {
+            child.failures(None, &mut failures);
+        }
+        let furthest = failures.iter().map(|attempt| attempt.position).max()?;
+        let mut names = Vec::new();
+        for attempt in failures {
+            if attempt.position == furthest && !names.contains(&attempt.name) {
+                names.push(attempt.name);
+            }
+        }
+        Some((furthest, names))
+    }
+
+    /// Gathers the failures that count in this attempt. `parent` is where
+    /// the failed attempt around it began, or `None` at the top of the
+    /// trace.
+    fn failures<'t>(&'t self, parent: Option<usize>, failures: &mut Vec<&'t Trace>) {
+        if self.succeeded() {
+            return;
+        }
+        if parent != Some(self.position) {
+            failures.push(self);
+        }
+        for child in &self.children {
+            child.failures(Some(self.position), failures);
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        write!(
+            f,
+            "{:indent$}{} at {}: ",
+            "",
+            self.name,
+            self.position,
+            indent = depth * 2
+        )?;
+        match self.matches {
+            0 => writeln!(f, "failed")?,
+            1 => writeln!(f, "1 match")?,
+            n => writeln!(f, "{} matches", n)?,
+        }
+        for child in &self.children {
+            child.write(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// One line per attempt, indented under the attempt that made it.
+#[cfg(feature = "std")]
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+/// Runs `parser` on `input` to the end, returning every parse along with
+/// a trace of the attempts made by the named parsers within it.
+///
+/// The root of the trace stands for `parser` itself, named "parse".
+#[cfg(feature = "std")]
+#[allow(clippy::type_complexity)]
+pub fn parse_with_trace<'a, P>(parser: &P, input: &'a str) -> (Vec<(P::Item, &'a str)>, Trace)
+where
+    P: Parser<'a>,
+{
+    let outer = trace::begin(input);
+    let parses: Vec<_> = parser.parse(input).collect();
+    let mut root = trace::end(outer);
+    root.matches = parses.len();
+    (parses, root)
+}
+
+/// The traces being recorded on this thread.
+#[cfg(feature = "std")]
+mod trace {
+    use super::{RefCell, Trace};
+    use crate::prelude::*;
+
+    pub(super) struct Recorder {
+        /// The address of the traced input, which positions count from.
+        base: usize,
+        /// The attempts still going on, outermost first.
+        open: Vec<Trace>,
+    }
+
+    std::thread_local! {
+        static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+    }
+
+    /// Starts a trace of `input`, returning any trace it interrupts.
+    pub(super) fn begin(input: &str) -> Option<Recorder> {
+        let root = Trace {
+            name: "parse",
+            position: 0,
+            matches: 0,
+            children: Vec::new(),
+        };
+        RECORDER.with(|recorder| {
+            recorder.replace(Some(Recorder {
+                base: input.as_ptr() as usize,
+                open: vec![root],
+            }))
+        })
+    }
+
+    /// Finishes the current trace, putting back the one it interrupted.
+    pub(super) fn end(outer: Option<Recorder>) -> Trace {
+        let recorder = RECORDER.with(|recorder| recorder.replace(outer));
+        recorder
+            .and_then(|mut recorder| recorder.open.pop())
+            .expect("a trace was begun")
+    }
+
+    /// Whether a trace is being recorded.
+    pub(super) fn recording() -> bool {
+        RECORDER.with(|recorder| recorder.borrow().is_some())
+    }
+
+    /// Opens an attempt by the parser `name` at `input`, if a trace is
+    /// being recorded.
+    pub(super) fn enter(name: &'static str, input: &str) -> bool {
+        RECORDER.with(|recorder| match &mut *recorder.borrow_mut() {
+            Some(recorder) => {
+                recorder.open.push(Trace {
+                    name,
+                    position: (input.as_ptr() as usize).saturating_sub(recorder.base),
+                    matches: 0,
+                    children: Vec::new(),
+                });
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// Closes the innermost attempt, which matched `matches` ways.
+    pub(super) fn exit(matches: usize) {
+        RECORDER.with(|recorder| {
+            if let Some(recorder) = &mut *recorder.borrow_mut() {
+                let mut attempt = recorder.open.pop().expect("an attempt is open");
+                attempt.matches = matches;
+                if let Some(parent) = recorder.open.last_mut() {
+                    parent.children.push(attempt);
+                }
+            }
+        })
+    }
+}
+
+/// The steps left to the parsers on this thread.
+#[cfg(feature = "std")]
+mod budget {
+    use core::cell::Cell;
+
+    #[derive(Clone, Copy)]
+    pub(super) struct Budget {
+        remaining: usize,
+        /// Whether a step was refused for want of budget.
+        exceeded: bool,
+    }
+
+    std::thread_local! {
+        static BUDGET: Cell<Option<Budget>> = const { Cell::new(None) };
+    }
+
+    /// Sets a budget of `steps`, or none, returning any budget it
+    /// interrupts.
+    pub(super) fn begin(steps: Option<usize>) -> Option<Budget> {
+        let budget = steps.map(|remaining| Budget {
+            remaining,
+            exceeded: false,
+        });
+        BUDGET.with(|current| current.replace(budget))
+    }
+
+    /// Removes the current budget, putting back the one it interrupted, and
+    /// returns whether it was exceeded.
+    pub(super) fn end(outer: Option<Budget>) -> bool {
+        let budget = BUDGET.with(|current| current.replace(outer));
+        budget.is_some_and(|budget| budget.exceeded)
+    }
+
+    /// Takes a step, returning `false` if the budget has run out.
+    pub(super) fn step() -> bool {
+        BUDGET.with(|current| match current.get() {
+            None => true,
+            Some(budget) if budget.remaining == 0 => {
+                current.set(Some(Budget {
+                    exceeded: true,
+                    ..budget
+                }));
+                false
+            }
+            Some(budget) => {
+                current.set(Some(Budget {
+                    remaining: budget.remaining - 1,
+                    ..budget
+                }));
+                true
+            }
+        })
+    }
+}
+
+/// Without `std`, there is nowhere to keep a budget, so none is enforced.
+#[cfg(not(feature = "std"))]
+mod budget {
+    pub(super) struct Budget;
+
+    pub(super) fn begin(_: Option<usize>) -> Option<Budget> {
+        None
+    }
+
+    pub(super) fn end(_: Option<Budget>) -> bool {
+        false
+    }
+
+    pub(super) fn step() -> bool {
+        true
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// Every parse of `input`, in order.
+    fn parses<'a, P: Parser<'a>>(parser: &P, input: &'a str) -> Vec<(P::Item, &'a str)> {
+        parser.parse(input).collect()
+    }
+
+    #[test]
+    fn lookahead() {
+        // Peeking yields what the parser would, but consumes nothing.
+        let ab = peek(any_tag(&["a", "ab"]));
+        assert_eq!(parses(&ab, "abc"), [("a", "abc"), ("ab", "abc")]);
+        assert!(parses(&ab, "c").is_empty());
+        assert_eq!(
+            parses(&chain(peek(tag("a")), tag("ab")), "abc"),
+            [(("a", "ab"), "c")]
+        );
+
+        // Not succeeds, consuming nothing, only where the parser fails.
+        let not_a = not(tag("a"));
+        assert_eq!(parses(&not_a, "bc"), [((), "bc")]);
+        assert_eq!(parses(&not_a, ""), [((), "")]);
+        assert!(parses(&not_a, "abc").is_empty());
+        assert!(parses(&not(any_tag(&["x", "a"])), "abc").is_empty());
+    }
+
+    #[test]
+    fn recursive_and_dynamic_grammars() {
+        // The depth of nested parentheses, as in `((()))`.
+        let depth = recursive(|nested: Boxed<usize>| {
+            either(delimited(tag("("), nested, tag(")")), tag("()")).map(|parse| match parse {
+                Either::Left(depth) => depth + 1,
+                Either::Right(_) => 1,
+            })
+        });
+        let parses = |input| complete(depth.clone()).parse(input).collect::<Vec<_>>();
+        assert_eq!(parses("()"), [(1, "")]);
+        assert_eq!(parses("((()))"), [(3, "")]);
+        assert!(parses("(()").is_empty());
+
+        // Parsers of different types, kept together once boxed.
+        let words = vec![
+            tag("hold").map(|_| 0).boxed(),
+            take_while1(|c: char| c.is_ascii_digit())
+                .map(str::len)
+                .boxed(),
+            any_tag(&["h", "ho"]).map(str::len).boxed(),
+        ];
+        let parses = choice(words).parse("hold").collect::<Vec<_>>();
+        assert_eq!(parses, [(0, ""), (1, "old"), (2, "ld")]);
+
+        // A handle outliving its grammar parses nothing.
+        let mut handle = None;
+        drop(recursive(|nested: Boxed<()>| {
+            handle = Some(nested.clone());
+            tag("x").map(|_| ())
+        }));
+        assert_eq!(handle.unwrap().parse("x").count(), 0);
+    }
+
+    #[test]
+    fn sequences_keep_one_side() {
+        let digits = || take_while1(|c: char| c.is_ascii_digit());
+
+        assert_eq!(
+            parses(&preceded(tag("#"), digits()), "#12 x"),
+            [("12", " x")]
+        );
+        assert!(parses(&preceded(tag("#"), digits()), "12").is_empty());
+        assert!(parses(&preceded(tag("#"), digits()), "#x").is_empty());
+
+        assert_eq!(
+            parses(&terminated(digits(), tag(";")), "12; x"),
+            [("12", " x")]
+        );
+        assert!(parses(&terminated(digits(), tag(";")), ";").is_empty());
+        assert!(parses(&terminated(digits(), tag(";")), "12 ;").is_empty());
+
+        let parenthesized = delimited(tag("("), digits(), tag(")"));
+        assert_eq!(parses(&parenthesized, "(12) x"), [("12", " x")]);
+        assert!(parses(&parenthesized, "12)").is_empty());
+        assert!(parses(&parenthesized, "()").is_empty());
+        assert!(parses(&parenthesized, "(12").is_empty());
+
+        // Each parse of the first part is tried with the rest.
+        let either_length = preceded(any_tag(&["a", "ab"]), tag("b!"));
+        assert_eq!(parses(&either_length, "ab!"), [("b!", "")]);
+    }
+
+    #[test]
+    fn alternatives_in_order() {
+        // Every alternative's parses are yielded, in the order given.
+        let word = alt((tag("ab"), tag("a"), any_tag(&["abc", "x"])));
+        assert_eq!(
+            parses(&word, "abc"),
+            [("ab", "c"), ("a", "bc"), ("abc", "")]
+        );
+        assert_eq!(parses(&word, "x"), [("x", "")]);
+        assert!(parses(&word, "b").is_empty());
+        assert_eq!(parses(&alt((tag("a"),)), "a"), [("a", "")]);
+
+        // A sequence backtracks into a later alternative when an earlier
+        // one leaves input it cannot finish.
+        let sentence = complete(chain(word, tag("c")));
+        assert_eq!(parses(&sentence, "abc"), [(("ab", "c"), "")]);
+        let sentence = complete(chain(alt((tag("a"), tag("ab"))), tag("c")));
+        assert_eq!(parses(&sentence, "abc"), [(("ab", "c"), "")]);
+    }
+
+    #[test]
+    fn trailing_input() {
+        assert_eq!(parses(&eof(), ""), [((), "")]);
+        assert!(parses(&eof(), " ").is_empty());
+
+        // Only the parses that use up the input are kept.
+        let word = complete(any_tag(&["a", "ab"]));
+        assert_eq!(parses(&word, "ab"), [("ab", "")]);
+        assert_eq!(parses(&word, "a"), [("a", "")]);
+        assert!(parses(&word, "abc").is_empty());
+        assert!(parses(&word, "ab ").is_empty());
+    }
+
+    #[test]
+    fn characters() {
+        let digit = char_where(|c| c.is_ascii_digit());
+        assert_eq!(parses(&digit, "12"), [('1', "2")]);
+        assert!(parses(&digit, "a1").is_empty());
+        assert!(parses(&digit, "").is_empty());
+        assert_eq!(parses(&char_where(char::is_alphabetic), "éa"), [('é', "a")]);
+
+        let arrow = one_of("->");
+        assert_eq!(parses(&arrow, "- Bur"), [('-', " Bur")]);
+        assert_eq!(parses(&arrow, ">"), [('>', "")]);
+        assert!(parses(&arrow, "Bur").is_empty());
+
+        // Only the longest run is yielded, and an empty one fails.
+        let word = take_while1(char::is_alphabetic);
+        assert_eq!(parses(&word, "Par - Bur"), [("Par", " - Bur")]);
+        assert_eq!(parses(&word, "Mün"), [("Mün", "")]);
+        assert!(parses(&word, " Par").is_empty());
+        assert!(parses(&word, "").is_empty());
+    }
+
+    #[test]
+    fn budgets() {
+        // A step for each way the first part matched, and one to find
+        // there are no more.
+        let sentence = chain(any_tag(&["a", "ab"]), tag("c"));
+        let run = || parses(&sentence, "abc");
+        assert_eq!(with_budget(Some(3), run), Ok(vec![(("ab", "c"), "")]));
+        assert_eq!(with_budget(Some(2), run), Err(BudgetExceeded));
+        assert_eq!(with_budget(Some(0), run), Err(BudgetExceeded));
+        assert_eq!(with_budget(None, run).map(|parses| parses.len()), Ok(1));
+
+        // An inner budget stands in for the outer one while it runs: its
+        // steps are not taken from the outer budget, and running out of
+        // it fails only the inner run.
+        let nested = with_budget(Some(3), || {
+            let inner = with_budget(Some(1), run);
+            let unbounded = with_budget(None, run);
+            (inner, unbounded, run())
+        });
+        assert_eq!(nested, Ok((Err(BudgetExceeded), Ok(run()), run())));
+    }
+
+    #[test]
+    fn memoized_parses() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = {
+            let calls = calls.clone();
+            any_tag(&["a", "ab"]).map(move |word| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                word
+            })
+        };
+        let memoized = memo(counted.clone());
+        let input = "ab ab";
+
+        // The same parses as the parser itself, found once per position.
+        assert_eq!(parses(&memoized, input), parses(&counted, input));
+        let calls_before = calls.load(Ordering::Relaxed);
+        assert_eq!(parses(&memoized, input), [("a", "b ab"), ("ab", " ab")]);
+        assert_eq!(parses(&memoized.clone(), input), parses(&memoized, input));
+        assert_eq!(calls.load(Ordering::Relaxed), calls_before);
+
+        // Other positions, and shorter slices from the same one, are parsed
+        // afresh, even where the text is the same.
+        assert_eq!(parses(&memoized, &input[3..]), [("a", "b"), ("ab", "")]);
+        assert_eq!(calls.load(Ordering::Relaxed), calls_before + 2);
+        assert_eq!(parses(&memoized, &input[..1]), [("a", "")]);
+        assert_eq!(calls.load(Ordering::Relaxed), calls_before + 3);
+    }
+}
@@ -0,0 +1,220 @@
+//! Suggestions for finishing a partly typed order, for tab completion and
+//! autocomplete.
+//!
+//! Suggestions are drawn from the legal orders for a power's units, so
+//! only continuations that can lead to a legal order are offered.
+//! Provinces are suggested by abbreviation, though typed input may also
+//! use single-word names.
+
+use crate::map::{Coast, Province};
+use crate::order::{Order, SupportedOrder, UnitType};
+use crate::power::Power;
+use crate::state::GameState;
+
+/// One way to continue an order.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Completion {
+    /// The word suggested: the next one, or the whole of the word being
+    /// typed.
+    pub word: String,
+    /// The input with the suggestion in place.
+    pub text: String,
+}
+
+/// Suggests how `input` could continue as an order for one of `power`'s
+/// units.
+///
+/// If `input` ends in the middle of a word, the suggestions complete that
+/// word; otherwise they are the words that could come next. Suggestions
+/// are in map order of the ordered unit, without repeats.
+pub fn complete(input: &str, state: &GameState, power: Power) -> Vec<Completion> {
+    let mut words: Vec<&str> = input.split_whitespace().collect();
+    let partial = match input.chars().last() {
+        Some(c) if !c.is_whitespace() => words.pop(),
+        _ => None,
+    };
+    let prefix = match partial {
+        Some(partial) => &input[..input.len() - partial.len()],
+        None => input,
+    };
+
+    let mut units: Vec<_> = state
+        .units()
+        .filter(|(_, unit)| unit.power() == power)
+        .map(|(province, _)| province)
+        .collect();
+    units.sort_by_key(|province| province.id());
+
+    let mut completions: Vec<Completion> = Vec::new();
+    for province in units {
+        for order in state.legal_orders(province) {
+            let tokens = tokens(&order);
+            for next in following(&tokens, &words) {
+                let word = tokens[next].text();
+                if partial.is_none_or(|partial| tokens[next].starts_with(partial)) {
+                    let text = format!("{}{}", prefix, word);
+                    if !completions.iter().any(|c| c.text == text) {
+                        completions.push(Completion { word, text });
+                    }
+                }
+            }
+        }
+    }
+    completions
+}
+
+/// A word of an order as it would be typed.
+#[derive(Clone, Copy, Debug)]
+enum Token<'m> {
+    /// A word that can be left out, like a unit type or "via convoy".
+    Optional(&'static str),
+    Word(&'static str),
+    Province(&'m Province),
+    Coast(Coast),
+}
+
+impl<'m> Token<'m> {
+    fn text(self) -> String {
+        match self {
+            Token::Optional(word) | Token::Word(word) => word.to_owned(),
+            Token::Province(province) => province.abbreviation().to_owned(),
+            Token::Coast(coast) => format!("({})", coast.abbreviation()),
+        }
+    }
+
+    fn is_optional(self) -> bool {
+        matches!(self, Token::Optional(_))
+    }
+
+    fn matches(self, word: &str) -> bool {
+        match self {
+            Token::Optional(text) | Token::Word(text) => text.eq_ignore_ascii_case(word),
+            Token::Province(province) => {
+                province.abbreviation().eq_ignore_ascii_case(word)
+                    || province.name().eq_ignore_ascii_case(word)
+                    || province
+                        .aliases()
+                        .any(|alias| alias.eq_ignore_ascii_case(word))
+            }
+            Token::Coast(coast) => word
+                .trim_start_matches(['(', '/'])
+                .trim_end_matches(')')
+                .eq_ignore_ascii_case(coast.abbreviation()),
+        }
+    }
+
+    fn starts_with(self, partial: &str) -> bool {
+        let partial = partial.to_lowercase();
+        let starts = |text: &str| text.to_lowercase().starts_with(&partial);
+        match self {
+            Token::Province(province) => starts(province.abbreviation()) || starts(province.name()),
+            Token::Coast(coast) => {
+                starts(&self.text()) || starts(coast.abbreviation()) || starts(coast.name())
+            }
+            _ => starts(&self.text()),
+        }
+    }
+}
+
+fn tokens<'m>(order: &Order<'m>) -> Vec<Token<'m>> {
+    let unit = |unit: Option<UnitType>, province| {
+        let mut tokens = Vec::new();
+        match unit {
+            Some(UnitType::Army) => tokens.push(Token::Optional("A")),
+            Some(UnitType::Fleet) => tokens.push(Token::Optional("F")),
+            None => {}
+        }
+        tokens.push(Token::Province(province));
+        tokens
+    };
+    let mut tokens = unit(order.unit(), order.province());
+    match order {
+        Order::Hold(_) => tokens.push(Token::Word("H")),
+        Order::Move(move_) => {
+            tokens.push(Token::Word("-"));
+            tokens.push(Token::Province(move_.destination()));
+            tokens.extend(move_.coast().map(Token::Coast));
+            if move_.via_convoy() {
+                tokens.push(Token::Optional("via"));
+                tokens.push(Token::Optional("convoy"));
+            }
+        }
+        Order::Support(support) => {
+            tokens.push(Token::Word("S"));
+            tokens.extend(unit(support.supported_unit(), support.from()));
+            if let SupportedOrder::Move(move_) = support.supported() {
+                tokens.push(Token::Word("-"));
+                tokens.push(Token::Province(move_.destination()));
+            }
+        }
+        Order::Convoy(convoy) => {
+            tokens.push(Token::Word("C"));
+            tokens.extend(unit(convoy.convoyed_unit(), convoy.from()));
+            tokens.push(Token::Word("-"));
+            tokens.push(Token::Province(convoy.to()));
+        }
+    }
+    tokens
+}
+
+/// The indexes of the tokens that could follow `words`, if they match the
+/// start of `tokens`.
+fn following(tokens: &[Token], words: &[&str]) -> Vec<usize> {
+    let mut positions = vec![0];
+    for word in words {
+        let mut next = Vec::new();
+        for position in skipping_optional(tokens, &positions) {
+            if tokens[position].matches(word) && !next.contains(&(position + 1)) {
+                next.push(position + 1);
+            }
+        }
+        positions = next;
+    }
+    skipping_optional(tokens, &positions)
+}
+
+/// `positions` and the positions after any optional tokens at them, that
+/// are still within `tokens`.
+fn skipping_optional(tokens: &[Token], positions: &[usize]) -> Vec<usize> {
+    let mut result = Vec::new();
+    for &start in positions {
+        let mut position = start;
+        while position < tokens.len() {
+            if !result.contains(&position) {
+                result.push(position);
+            }
+            if !tokens[position].is_optional() {
+                break;
+            }
+            position += 1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(input: &str) -> Vec<String> {
+        complete(input, &GameState::standard(), Power::France)
+            .into_iter()
+            .map(|completion| completion.word)
+            .collect()
+    }
+
+    #[test]
+    fn suggestions() {
+        assert_eq!(words(""), ["F", "Bre", "A", "Mar", "Par"]);
+        assert_eq!(words("A Par "), ["H", "-", "S"]);
+        assert!(words("A Par S ").contains(&"Bre".to_owned()));
+        assert!(words("A Par S ").contains(&"Mun".to_owned()));
+        assert_eq!(words("A Par - B"), ["Bre", "Bur"]);
+        assert_eq!(words("paris - bu"), ["Bur"]);
+        assert_eq!(words("A Par S F Bre - "), ["Gas", "Pic"]);
+        assert!(words("A Par - Mos").is_empty());
+
+        let completions = complete("Par - bu", &GameState::standard(), Power::France);
+        assert_eq!(completions[0].text, "Par - Bur");
+    }
+}
@@ -0,0 +1,364 @@
+//! House-rule orders, like "transform army into fleet", plugged in
+//! without changing the core grammar or adjudicator.
+//!
+//! An [`OrderSyntax`] reads the orders the core grammar does not know, as
+//! [`CustomOrder`]s, and an [`OrderRule`] carries them out. Register both
+//! with [`Extensions`], then parse and adjudicate through it: core orders
+//! go to the core parser and adjudicator as usual, and the unit given a
+//! custom order holds while the phase is adjudicated, and is then handed
+//! to the rule for its kind of order.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::adjudicator::{adjudicate_with, AdjudicationResult, Rules};
+use crate::map::{Map, Province};
+use crate::order::{Order, UnitType};
+use crate::parser::{normalize, parse_order_with, ParseError, ParserOptions};
+use crate::power::Power;
+use crate::state::GameState;
+
+/// An order of a kind the core grammar does not know.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CustomOrder<'m> {
+    /// What kind of order it is, like `"transform"`, which picks the
+    /// [`OrderRule`] that carries it out.
+    pub kind: String,
+    pub unit: Option<UnitType>,
+    /// Where the ordered unit is.
+    pub province: &'m Province,
+    /// Any other provinces the order names, in the order it names them.
+    pub provinces: Vec<&'m Province>,
+}
+
+/// `A Brest transform`, followed by any other provinces.
+impl<'m> fmt::Display for CustomOrder<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(unit) = self.unit {
+            write!(f, "{} ", unit)?;
+        }
+        write!(f, "{} {}", self.province, self.kind)?;
+        for province in &self.provinces {
+            write!(f, " {}", province)?;
+        }
+        Ok(())
+    }
+}
+
+/// An order the core grammar knows, or one read by an [`OrderSyntax`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExtendedOrder<'m> {
+    Core(Order<'m>),
+    Custom(CustomOrder<'m>),
+}
+
+impl<'m> ExtendedOrder<'m> {
+    /// Where the ordered unit is.
+    pub fn province(&self) -> &'m Province {
+        match self {
+            ExtendedOrder::Core(order) => order.province(),
+            ExtendedOrder::Custom(order) => order.province,
+        }
+    }
+}
+
+impl<'m> fmt::Display for ExtendedOrder<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExtendedOrder::Core(order) => order.fmt(f),
+            ExtendedOrder::Custom(order) => order.fmt(f),
+        }
+    }
+}
+
+/// Reads orders of some kind the core grammar does not know.
+pub trait OrderSyntax: Send + Sync {
+    /// Reads the whole of `input` as one of these orders, if it is one.
+    /// The input is [normalized](crate::parser::normalize) first if the
+    /// parser options say so.
+    fn parse<'m>(&self, map: &'m Map, input: &str) -> Option<CustomOrder<'m>>;
+}
+
+/// Carries out orders of one custom kind.
+pub trait OrderRule: Send + Sync {
+    /// The [`CustomOrder::kind`] of the orders this rule carries out.
+    fn kind(&self) -> &str;
+
+    /// Whether `power` may give `order` in `state`. By default, it may if
+    /// the unit in the order's province is its own.
+    fn is_legal(&self, state: &GameState, power: Power, order: &CustomOrder) -> bool {
+        state
+            .unit_at(order.province)
+            .is_some_and(|unit| unit.power() == power)
+    }
+
+    /// Carries out `order`, given by `power`, on `next`, the position
+    /// after the phase `result` describes. The ordered unit held during
+    /// the phase, and may have been dislodged.
+    fn apply<'m>(
+        &self,
+        power: Power,
+        order: &CustomOrder<'m>,
+        result: &AdjudicationResult<'m>,
+        next: &mut GameState<'m>,
+    );
+}
+
+/// The custom order syntaxes and rules of a house-rule variant.
+#[derive(Default)]
+pub struct Extensions {
+    syntaxes: Vec<Box<dyn OrderSyntax>>,
+    rules: Vec<Box<dyn OrderRule>>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Extensions::default()
+    }
+
+    /// Adds a syntax, tried after the core grammar and any syntax added
+    /// before it.
+    pub fn add_syntax(&mut self, syntax: impl OrderSyntax + 'static) {
+        self.syntaxes.push(Box::new(syntax));
+    }
+
+    /// Adds a rule, replacing any earlier rule for the same kind of order.
+    pub fn add_rule(&mut self, rule: impl OrderRule + 'static) {
+        self.rules.retain(|existing| existing.kind() != rule.kind());
+        self.rules.push(Box::new(rule));
+    }
+
+    /// Parses a single order with the core grammar or, failing that, the
+    /// syntaxes in the order they were added, with the default
+    /// [`ParserOptions`].
+    pub fn parse_order<'a, 'm>(
+        &self,
+        map: &'m Map,
+        input: &'a str,
+    ) -> Result<ExtendedOrder<'m>, ParseError<'a>> {
+        self.parse_order_with(map, input, &ParserOptions::default())
+    }
+
+    /// Parses a single order with the core grammar or, failing that, the
+    /// syntaxes in the order they were added. If none can read it, the
+    /// error is the core grammar's.
+    pub fn parse_order_with<'a, 'm>(
+        &self,
+        map: &'m Map,
+        input: &'a str,
+        options: &ParserOptions,
+    ) -> Result<ExtendedOrder<'m>, ParseError<'a>> {
+        let error = match parse_order_with(map, input, options) {
+            Ok(order) => return Ok(ExtendedOrder::Core(order)),
+            Err(error) => error,
+        };
+        let normalized;
+        let text = if options.normalize {
+            normalized = normalize(input);
+            &normalized
+        } else {
+            input.trim()
+        };
+        self.syntaxes
+            .iter()
+            .find_map(|syntax| syntax.parse(map, text))
+            .map(ExtendedOrder::Custom)
+            .ok_or(error)
+    }
+
+    /// Adjudicates the phase `state` is in, as
+    /// [`adjudicate_with`] does, with each unit given a custom order
+    /// holding. A custom order replaces any core order for the same unit.
+    pub fn adjudicate<'m>(
+        &self,
+        orders: HashMap<Power, Vec<ExtendedOrder<'m>>>,
+        state: &GameState<'m>,
+        rules: &Rules,
+    ) -> ExtendedResult<'m> {
+        let mut core: HashMap<Power, Vec<Order<'m>>> = HashMap::new();
+        let mut custom = Vec::new();
+        let mut powers: Vec<_> = orders.into_iter().collect();
+        powers.sort_by_key(|(power, _)| *power);
+        for (power, orders) in powers {
+            let customized: Vec<&Province> = orders
+                .iter()
+                .filter_map(|order| match order {
+                    ExtendedOrder::Custom(order) => Some(order.province),
+                    ExtendedOrder::Core(_) => None,
+                })
+                .collect();
+            let core = core.entry(power).or_default();
+            for order in orders {
+                match order {
+                    ExtendedOrder::Core(order) => {
+                        if !customized.contains(&order.province()) {
+                            core.push(order);
+                        }
+                    }
+                    ExtendedOrder::Custom(order) => {
+                        let legal = self
+                            .rule(&order.kind)
+                            .is_some_and(|rule| rule.is_legal(state, power, &order));
+                        custom.push((power, order, legal));
+                    }
+                }
+            }
+        }
+        ExtendedResult {
+            result: adjudicate_with(core, state, rules),
+            custom,
+        }
+    }
+
+    /// The position after the phase `result` describes: the core result
+    /// applied as [`GameState::apply`] does, and then each legal custom
+    /// order carried out by its rule, by power and then in the order given.
+    pub fn apply<'m>(&self, state: &GameState<'m>, result: &ExtendedResult<'m>) -> GameState<'m> {
+        let mut next = state.apply(&result.result);
+        for (power, order, legal) in &result.custom {
+            if let (true, Some(rule)) = (legal, self.rule(&order.kind)) {
+                rule.apply(*power, order, &result.result, &mut next);
+            }
+        }
+        next
+    }
+
+    fn rule(&self, kind: &str) -> Option<&dyn OrderRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.kind() == kind)
+            .map(|rule| rule.as_ref())
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Extensions")
+            .field("syntaxes", &self.syntaxes.len())
+            .field(
+                "rules",
+                &self
+                    .rules
+                    .iter()
+                    .map(|rule| rule.kind())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// The outcome of a phase with custom orders.
+#[derive(Clone, Debug)]
+pub struct ExtendedResult<'m> {
+    result: AdjudicationResult<'m>,
+    custom: Vec<(Power, CustomOrder<'m>, bool)>,
+}
+
+impl<'m> ExtendedResult<'m> {
+    /// The result of the core orders, with the custom-ordered units
+    /// holding.
+    pub fn result(&self) -> &AdjudicationResult<'m> {
+        &self.result
+    }
+
+    /// Every custom order, with the power that gave it and whether it is
+    /// to be carried out: whether there is a rule for it, and the rule
+    /// finds it legal.
+    pub fn custom_orders(&self) -> impl Iterator<Item = (Power, &CustomOrder<'m>, bool)> {
+        self.custom
+            .iter()
+            .map(|(power, order, legal)| (*power, order, *legal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combinators::Parser;
+    use crate::parser::ParseProvince;
+    use crate::state::Unit;
+
+    /// `A Bre transforms`, or `A Bre T`.
+    struct Transform;
+
+    impl OrderSyntax for Transform {
+        fn parse<'m>(&self, map: &'m Map, input: &str) -> Option<CustomOrder<'m>> {
+            let rest = input.strip_prefix("a ")?;
+            let (province, rest) = ParseProvince::new(map).parse(rest).next()?;
+            matches!(rest.trim(), "t" | "transforms").then(|| CustomOrder {
+                kind: "transform".to_owned(),
+                unit: Some(UnitType::Army),
+                province,
+                provinces: Vec::new(),
+            })
+        }
+    }
+
+    /// Turns an army on the coast into a fleet, unless it was dislodged.
+    impl OrderRule for Transform {
+        fn kind(&self) -> &str {
+            "transform"
+        }
+
+        fn apply<'m>(
+            &self,
+            power: Power,
+            order: &CustomOrder<'m>,
+            result: &AdjudicationResult<'m>,
+            next: &mut GameState<'m>,
+        ) {
+            let dislodged = result
+                .get(order.province)
+                .is_none_or(|resolution| resolution.is_dislodged());
+            if !dislodged && order.province.is_coastal() {
+                next.place_unit(order.province, Unit::fleet(power));
+            }
+        }
+    }
+
+    #[test]
+    fn custom_orders() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut extensions = Extensions::new();
+        extensions.add_syntax(Transform);
+        extensions.add_rule(Transform);
+        let order = |text| extensions.parse_order(map, text).unwrap();
+
+        let mut state = GameState::new(map);
+        state.place_unit(province("Bre"), Unit::army(Power::France));
+        state.place_unit(province("Mar"), Unit::army(Power::France));
+        state.place_unit(province("Par"), Unit::army(Power::France));
+        let transform = order("A Bre transforms");
+        assert_eq!(transform.to_string(), "A Brest transform");
+        let orders = HashMap::from([(
+            Power::France,
+            vec![
+                order("A Bre - Pic"),
+                transform,
+                order("A Mar T"),
+                order("A Par - Bur"),
+            ],
+        )]);
+        assert!(matches!(order("A Par - Bur"), ExtendedOrder::Core(_)));
+        assert!(extensions.parse_order(map, "A Par dances").is_err());
+
+        let result = extensions.adjudicate(orders, &state, &Rules::default());
+        assert!(!result.result().get(province("Bre")).unwrap().is_dislodged());
+        assert_eq!(result.custom_orders().count(), 2);
+        let next = extensions.apply(&state, &result);
+        assert_eq!(
+            next.unit_at(province("Bre")),
+            Some(&Unit::fleet(Power::France))
+        );
+        assert_eq!(
+            next.unit_at(province("Mar")),
+            Some(&Unit::fleet(Power::France))
+        );
+        assert_eq!(
+            next.unit_at(province("Bur")),
+            Some(&Unit::army(Power::France))
+        );
+        assert_eq!(next.unit_at(province("Pic")), None);
+    }
+}
@@ -0,0 +1,580 @@
+//! Exports for C and C++ that pass orders and units as plain structs.
+//!
+//! These are declared in `include/dipboy.h`. Provinces are numbered by
+//! their place in the standard map, as listed by
+//! [`dipboy_province_abbreviation`]; powers, unit types, coasts and order
+//! kinds are the `DIPBOY_` constants below. Functions that can fail
+//! return a negative number and leave a message for
+//! [`dipboy_last_error`].
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::sync::OnceLock;
+use std::{ptr, slice};
+
+use crate::adjudicator::{self, IllegalOrder, OrderStatus};
+use crate::map::{Coast, Map, Province};
+use crate::order::{Order, UnitType};
+use crate::parser;
+use crate::power::Power;
+use crate::state::{GameState, Unit};
+
+/// No province.
+pub const DIPBOY_NO_PROVINCE: u16 = 0xffff;
+/// No unit type, coast or reason.
+pub const DIPBOY_NONE: u8 = 0xff;
+
+pub const DIPBOY_HOLD: u8 = 0;
+pub const DIPBOY_MOVE: u8 = 1;
+pub const DIPBOY_SUPPORT: u8 = 2;
+pub const DIPBOY_CONVOY: u8 = 3;
+
+pub const DIPBOY_ARMY: u8 = 0;
+pub const DIPBOY_FLEET: u8 = 1;
+
+pub const DIPBOY_NORTH_COAST: u8 = 0;
+pub const DIPBOY_SOUTH_COAST: u8 = 1;
+pub const DIPBOY_EAST_COAST: u8 = 2;
+pub const DIPBOY_WEST_COAST: u8 = 3;
+
+pub const DIPBOY_AUSTRIA: u8 = 0;
+pub const DIPBOY_ENGLAND: u8 = 1;
+pub const DIPBOY_FRANCE: u8 = 2;
+pub const DIPBOY_GERMANY: u8 = 3;
+pub const DIPBOY_ITALY: u8 = 4;
+pub const DIPBOY_RUSSIA: u8 = 5;
+pub const DIPBOY_TURKEY: u8 = 6;
+
+pub const DIPBOY_ORDERED: u8 = 0;
+pub const DIPBOY_UNORDERED: u8 = 1;
+pub const DIPBOY_ILLEGAL: u8 = 2;
+
+/// An order. Fields that do not apply to its kind are `DIPBOY_NONE` or
+/// `DIPBOY_NO_PROVINCE`, and a unit type of `DIPBOY_NONE` means the order
+/// did not name one.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DipboyOrder {
+    pub kind: u8,
+    pub unit: u8,
+    pub province: u16,
+    /// Where a move goes.
+    pub destination: u16,
+    /// The coast a move asks for.
+    pub coast: u8,
+    /// Whether a move asks to go by convoy.
+    pub via_convoy: bool,
+    /// The unit type in the order a support or convoy is for.
+    pub target_unit: u8,
+    /// Where the unit a support or convoy is for is.
+    pub target_province: u16,
+    /// Where that unit is going, or `DIPBOY_NO_PROVINCE` for a support
+    /// to hold.
+    pub target_destination: u16,
+}
+
+/// A unit on the board.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DipboyUnit {
+    pub power: u8,
+    pub unit: u8,
+    /// For fleets on bicoastal provinces; otherwise `DIPBOY_NONE`.
+    pub coast: u8,
+    pub province: u16,
+}
+
+/// An order given by a power.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DipboyPowerOrder {
+    pub power: u8,
+    pub order: DipboyOrder,
+}
+
+/// What became of one unit's order.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DipboyResolution {
+    pub province: u16,
+    pub power: u8,
+    /// `DIPBOY_ORDERED`, `DIPBOY_UNORDERED` or `DIPBOY_ILLEGAL`. Units
+    /// that were not ordered, or were ordered illegally, hold.
+    pub status: u8,
+    /// Why an illegal order was illegal, numbered from 0 in the order of
+    /// `IllegalOrder`'s variants; otherwise `DIPBOY_NONE`.
+    pub reason: u8,
+    pub succeeded: bool,
+    /// The province the unit was dislodged from, or `DIPBOY_NO_PROVINCE`.
+    pub dislodged_by: u16,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// The message for the last call on this thread that failed. It is valid
+/// until the next call that fails.
+#[no_mangle]
+pub extern "C" fn dipboy_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ptr())
+}
+
+/// The number of provinces on the standard map.
+#[no_mangle]
+pub extern "C" fn dipboy_province_count() -> usize {
+    Map::standard().provinces().count()
+}
+
+/// The abbreviation of `province`, or null if there is no such province.
+/// The string lives as long as the library is loaded.
+#[no_mangle]
+pub extern "C" fn dipboy_province_abbreviation(province: u16) -> *const c_char {
+    static ABBREVIATIONS: OnceLock<Vec<CString>> = OnceLock::new();
+    ABBREVIATIONS
+        .get_or_init(|| {
+            Map::standard()
+                .provinces()
+                .map(|province| CString::new(province.abbreviation()).unwrap())
+                .collect()
+        })
+        .get(usize::from(province))
+        .map_or(ptr::null(), |abbreviation| abbreviation.as_ptr())
+}
+
+/// The province with the name or abbreviation `name`, or -1.
+///
+/// # Safety
+///
+/// `name` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dipboy_find_province(name: *const c_char) -> i32 {
+    let result = text(name).and_then(|name| {
+        let map = Map::standard();
+        map.find_by_abbreviation(name)
+            .or_else(|| map.find_by_name(name))
+            .ok_or_else(|| format!("unknown province \"{}\"", name))
+    });
+    fail_negative(result.map(|province| province.id() as i32))
+}
+
+/// Parses `text` as an order on the standard map into `order`. Returns 0,
+/// or -1 if it is not an order.
+///
+/// # Safety
+///
+/// `text` must be a NUL-terminated string, and `order` must point to a
+/// `DipboyOrder`.
+#[no_mangle]
+pub unsafe extern "C" fn dipboy_parse_into(text: *const c_char, order: *mut DipboyOrder) -> i32 {
+    let result = self::text(text).and_then(|text| {
+        parser::parse_order(Map::standard(), text).map_err(|error| error.to_string())
+    });
+    fail_negative(result.map(|parsed| {
+        *order = to_c(&parsed);
+        0
+    }))
+}
+
+/// Writes `order` as the parser would display it into `buffer`, which
+/// holds `len` bytes, cutting it short if it does not fit. Returns the
+/// length of the whole text, like `snprintf`, or -1 if the order is not
+/// valid.
+///
+/// # Safety
+///
+/// `order` must point to a `DipboyOrder`, and `buffer` to `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn dipboy_format_order(
+    order: *const DipboyOrder,
+    buffer: *mut c_char,
+    len: usize,
+) -> isize {
+    fail_negative(from_c(&*order).map(|order| {
+        let text = order.to_string();
+        if len > 0 {
+            let written = text.len().min(len - 1);
+            ptr::copy_nonoverlapping(text.as_ptr().cast(), buffer, written);
+            *buffer.add(written) = 0;
+        }
+        text.len() as isize
+    }))
+}
+
+/// Adjudicates a movement phase on the standard map.
+///
+/// Writes one resolution for each of the `unit_count` units into
+/// `resolutions`, which has room for `capacity`, and returns how many it
+/// wrote; or returns -1 if a unit or order is not valid, or there is not
+/// room. Orders for provinces without one of the power's units are
+/// ignored.
+///
+/// # Safety
+///
+/// `units`, `orders` and `resolutions` must point to as many of their
+/// structs as their counts say.
+#[no_mangle]
+pub unsafe extern "C" fn dipboy_adjudicate_into(
+    units: *const DipboyUnit,
+    unit_count: usize,
+    orders: *const DipboyPowerOrder,
+    order_count: usize,
+    resolutions: *mut DipboyResolution,
+    capacity: usize,
+) -> isize {
+    let result = adjudicate(
+        slice_from(units, unit_count),
+        slice_from(orders, order_count),
+    )
+    .and_then(|results| {
+        if results.len() > capacity {
+            return Err(format!(
+                "{} resolutions do not fit in {}",
+                results.len(),
+                capacity
+            ));
+        }
+        ptr::copy_nonoverlapping(results.as_ptr(), resolutions, results.len());
+        Ok(results.len() as isize)
+    });
+    fail_negative(result)
+}
+
+fn adjudicate(
+    units: &[DipboyUnit],
+    orders: &[DipboyPowerOrder],
+) -> Result<Vec<DipboyResolution>, String> {
+    let map = Map::standard();
+    let mut state = GameState::new(map);
+    for unit in units {
+        let province = province(unit.province)?;
+        let power = power(unit.power)?;
+        let placed = match (unit_type(unit.unit)?, coast(unit.coast)?) {
+            (Some(UnitType::Army), None) => Unit::army(power),
+            (Some(UnitType::Fleet), None) => Unit::fleet(power),
+            (Some(UnitType::Fleet), Some(coast)) => Unit::fleet_on(power, coast),
+            _ => return Err(format!("not a unit in {}", province)),
+        };
+        if state.place_unit(province, placed).is_some() {
+            return Err(format!("two units in {}", province));
+        }
+    }
+    let mut by_power: HashMap<Power, Vec<Order>> = HashMap::new();
+    for order in orders {
+        by_power
+            .entry(power(order.power)?)
+            .or_default()
+            .push(from_c(&order.order)?);
+    }
+
+    let result = adjudicator::adjudicate(by_power, &state);
+    let mut resolutions: Vec<_> = result
+        .resolutions()
+        .map(|(province, resolution)| {
+            let (status, reason) = match resolution.status() {
+                OrderStatus::Ordered => (DIPBOY_ORDERED, DIPBOY_NONE),
+                OrderStatus::Unordered => (DIPBOY_UNORDERED, DIPBOY_NONE),
+                OrderStatus::Illegal(reason) => (DIPBOY_ILLEGAL, reason_code(reason)),
+            };
+            DipboyResolution {
+                province: province.id() as u16,
+                power: resolution.power() as u8,
+                status,
+                reason,
+                succeeded: resolution.succeeded(),
+                dislodged_by: resolution
+                    .dislodged_by()
+                    .map_or(DIPBOY_NO_PROVINCE, |by| by.id() as u16),
+            }
+        })
+        .collect();
+    resolutions.sort_by_key(|resolution| resolution.province);
+    Ok(resolutions)
+}
+
+fn reason_code(reason: IllegalOrder) -> u8 {
+    match reason {
+        IllegalOrder::NoUnit => 0,
+        IllegalOrder::ForeignUnit => 1,
+        IllegalOrder::WrongUnitType => 2,
+        IllegalOrder::Unreachable => 3,
+        IllegalOrder::AmbiguousCoast => 4,
+        IllegalOrder::NotAtSea => 5,
+        IllegalOrder::NoConvoyRoute => 6,
+        IllegalOrder::WrongPhase => 7,
+        IllegalOrder::Immobile => 8,
+    }
+}
+
+fn to_c(order: &Order) -> DipboyOrder {
+    let mut c = DipboyOrder {
+        kind: DIPBOY_HOLD,
+        unit: unit_code(order.unit()),
+        province: order.province().id() as u16,
+        destination: DIPBOY_NO_PROVINCE,
+        coast: DIPBOY_NONE,
+        via_convoy: false,
+        target_unit: DIPBOY_NONE,
+        target_province: DIPBOY_NO_PROVINCE,
+        target_destination: DIPBOY_NO_PROVINCE,
+    };
+    match order {
+        Order::Hold(_) => {}
+        Order::Move(move_) => {
+            c.kind = DIPBOY_MOVE;
+            c.destination = move_.destination().id() as u16;
+            c.coast = move_.coast().map_or(DIPBOY_NONE, |coast| coast as u8);
+            c.via_convoy = move_.via_convoy();
+        }
+        Order::Support(_) | Order::Convoy(_) => {
+            c.kind = match order {
+                Order::Support(_) => DIPBOY_SUPPORT,
+                _ => DIPBOY_CONVOY,
+            };
+            let target = order.target().unwrap();
+            c.target_unit = unit_code(target.unit());
+            c.target_province = target.province().id() as u16;
+            c.target_destination = target
+                .destination()
+                .map_or(DIPBOY_NO_PROVINCE, |to| to.id() as u16);
+        }
+    }
+    c
+}
+
+fn from_c(c: &DipboyOrder) -> Result<Order<'static>, String> {
+    let unit = unit_type(c.unit)?;
+    let province = province(c.province)?;
+    let order = match c.kind {
+        DIPBOY_HOLD => Ok(Order::hold(unit, province)),
+        DIPBOY_MOVE => {
+            let destination = self::province(c.destination)?;
+            match coast(c.coast)? {
+                Some(coast) => Order::move_to_coast(unit, province, destination, coast),
+                None if c.via_convoy => Order::move_via_convoy(unit, province, destination),
+                None => Order::move_(unit, province, destination),
+            }
+        }
+        DIPBOY_SUPPORT | DIPBOY_CONVOY => {
+            let target_unit = unit_type(c.target_unit)?;
+            let from = self::province(c.target_province)?;
+            match (c.kind, c.target_destination) {
+                (DIPBOY_SUPPORT, DIPBOY_NO_PROVINCE) => {
+                    Order::support_hold(unit, province, target_unit, from)
+                }
+                (DIPBOY_SUPPORT, to) => {
+                    Order::support_move(unit, province, target_unit, from, self::province(to)?)
+                }
+                (_, to) => Order::convoy(unit, province, target_unit, from, self::province(to)?),
+            }
+        }
+        kind => return Err(format!("no order kind {}", kind)),
+    };
+    order.map_err(|error| error.to_string())
+}
+
+fn province(id: u16) -> Result<&'static Province, String> {
+    Map::standard()
+        .provinces()
+        .nth(usize::from(id))
+        .ok_or_else(|| format!("no province {}", id))
+}
+
+fn power(code: u8) -> Result<Power, String> {
+    Power::ALL
+        .get(usize::from(code))
+        .copied()
+        .ok_or_else(|| format!("no power {}", code))
+}
+
+fn unit_type(code: u8) -> Result<Option<UnitType>, String> {
+    match code {
+        DIPBOY_ARMY => Ok(Some(UnitType::Army)),
+        DIPBOY_FLEET => Ok(Some(UnitType::Fleet)),
+        DIPBOY_NONE => Ok(None),
+        code => Err(format!("no unit type {}", code)),
+    }
+}
+
+fn unit_code(unit: Option<UnitType>) -> u8 {
+    match unit {
+        Some(UnitType::Army) => DIPBOY_ARMY,
+        Some(UnitType::Fleet) => DIPBOY_FLEET,
+        None => DIPBOY_NONE,
+    }
+}
+
+fn coast(code: u8) -> Result<Option<Coast>, String> {
+    match code {
+        DIPBOY_NORTH_COAST => Ok(Some(Coast::North)),
+        DIPBOY_SOUTH_COAST => Ok(Some(Coast::South)),
+        DIPBOY_EAST_COAST => Ok(Some(Coast::East)),
+        DIPBOY_WEST_COAST => Ok(Some(Coast::West)),
+        DIPBOY_NONE => Ok(None),
+        code => Err(format!("no coast {}", code)),
+    }
+}
+
+unsafe fn text<'a>(text: *const c_char) -> Result<&'a str, String> {
+    if text.is_null() {
+        return Err("no text".to_owned());
+    }
+    CStr::from_ptr(text)
+        .to_str()
+        .map_err(|_| "text is not valid UTF-8".to_owned())
+}
+
+unsafe fn slice_from<'a, T>(items: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(items, len)
+    }
+}
+
+/// The value of `result`, or -1 after recording its error.
+fn fail_negative<T: From<i8>>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|message| {
+        LAST_ERROR.with(|error| {
+            *error.borrow_mut() = CString::new(message.replace('\0', "")).unwrap();
+        });
+        T::from(-1)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> DipboyOrder {
+        let text = CString::new(text).unwrap();
+        let mut order = to_c(&Order::hold(None, province(0).unwrap()));
+        assert_eq!(unsafe { dipboy_parse_into(text.as_ptr(), &mut order) }, 0);
+        order
+    }
+
+    fn format(order: &DipboyOrder) -> String {
+        let mut buffer = [0 as c_char; 64];
+        let len = unsafe { dipboy_format_order(order, buffer.as_mut_ptr(), buffer.len()) };
+        assert!(len >= 0);
+        unsafe { CStr::from_ptr(buffer.as_ptr()) }
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    fn find(name: &str) -> u16 {
+        let name = CString::new(name).unwrap();
+        unsafe { dipboy_find_province(name.as_ptr()) as u16 }
+    }
+
+    #[test]
+    fn orders_round_trip() {
+        for text in [
+            "A Par H",
+            "F Mao - Spa (nc)",
+            "A Lon - Bel via convoy",
+            "A Mun S A Bur",
+            "A Mun S Par - Bur",
+            "F Nth C A Lon - Nwy",
+        ] {
+            let order = parse(text);
+            let expected = parser::parse_order(Map::standard(), text).unwrap();
+            assert_eq!(format(&order), expected.to_string());
+        }
+
+        let support = parse("A Mun S A Par - Bur");
+        assert_eq!(support.kind, DIPBOY_SUPPORT);
+        assert_eq!(support.target_unit, DIPBOY_ARMY);
+        assert_eq!(support.target_destination, find("Burgundy"));
+
+        let text = CString::new("A Atlantis H").unwrap();
+        let mut order = support;
+        assert_eq!(unsafe { dipboy_parse_into(text.as_ptr(), &mut order) }, -1);
+        assert_eq!(order, support);
+        let error = unsafe { CStr::from_ptr(dipboy_last_error()) };
+        assert!(error.to_str().unwrap().contains("Atlantis"));
+    }
+
+    #[test]
+    fn adjudicate_bounce() {
+        let unit = |power, abbreviation| DipboyUnit {
+            power,
+            unit: DIPBOY_ARMY,
+            coast: DIPBOY_NONE,
+            province: find(abbreviation),
+        };
+        let units = [unit(DIPBOY_FRANCE, "Par"), unit(DIPBOY_GERMANY, "Mun")];
+        let orders = [
+            DipboyPowerOrder {
+                power: DIPBOY_FRANCE,
+                order: parse("A Par - Bur"),
+            },
+            DipboyPowerOrder {
+                power: DIPBOY_GERMANY,
+                order: parse("A Mun - Bur"),
+            },
+        ];
+        let mut resolutions = [DipboyResolution {
+            province: DIPBOY_NO_PROVINCE,
+            power: 0,
+            status: 0,
+            reason: DIPBOY_NONE,
+            succeeded: false,
+            dislodged_by: DIPBOY_NO_PROVINCE,
+        }; 2];
+        let count = unsafe {
+            dipboy_adjudicate_into(
+                units.as_ptr(),
+                units.len(),
+                orders.as_ptr(),
+                orders.len(),
+                resolutions.as_mut_ptr(),
+                resolutions.len(),
+            )
+        };
+        assert_eq!(count, 2);
+        assert!(resolutions
+            .iter()
+            .all(|r| r.status == DIPBOY_ORDERED && !r.succeeded));
+
+        let count = unsafe {
+            dipboy_adjudicate_into(
+                units.as_ptr(),
+                units.len(),
+                orders.as_ptr(),
+                orders.len(),
+                resolutions.as_mut_ptr(),
+                1,
+            )
+        };
+        assert_eq!(count, -1);
+    }
+
+    #[test]
+    fn header_declares_every_export() {
+        let header = include_str!("../../include/dipboy.h");
+        let sources = [include_str!("mod.rs"), include_str!("c.rs")];
+        for source in sources {
+            for line in source.lines() {
+                if let Some(rest) = line.split("extern \"C\" fn ").nth(1) {
+                    let name = &rest[..rest.find('(').unwrap()];
+                    assert!(header.contains(&format!("{}(", name)), "{}", name);
+                }
+                if let Some(rest) = line.strip_prefix("pub const DIPBOY_") {
+                    let (name, value) = rest.split_once(':').unwrap();
+                    let value = value.split('=').nth(1).unwrap().trim();
+                    let value = value.trim_end_matches(';');
+                    let value = match value.strip_prefix("0x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).unwrap(),
+                        None => value.parse().unwrap(),
+                    };
+                    let define = format!("#define DIPBOY_{} {}\n", name, value);
+                    assert!(header.contains(&define), "{}", define);
+                }
+            }
+        }
+    }
+}
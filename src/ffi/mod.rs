@@ -0,0 +1,421 @@
+//! Exports for front ends in other languages: web pages through
+//! WebAssembly, Python through PyO3, and C and C++ through
+//! `include/dipboy.h`.
+//!
+//! For C, the functions in [`c`] pass orders and units as plain structs.
+//! The rest of this module works in JSON: [`map`], [`parse_order`] and
+//! [`adjudicate`] take and return JSON text.
+//! They are exported with the C ABI; text crosses the boundary through two
+//! input slots and one output buffer owned by the library.
+//!
+//! For the web, the `wasm` feature adds [`wasm`], with `wasm-bindgen`
+//! exports returning JavaScript objects. Build the package with
+//!
+//! ```text
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/dipboy.wasm
+//! ```
+//!
+//! and call it like this:
+//!
+//! ```js
+//! import init, { parseOrder, adjudicate } from "./pkg/dipboy.js";
+//!
+//! await init();
+//! const { order } = parseOrder("A Par - Bur");
+//! const { resolutions, state } = adjudicate(stateJson, JSON.stringify(orders));
+//! ```
+//!
+//! Without `wasm-bindgen`, the C ABI exports can be called from a module
+//! built with
+//!
+//! ```text
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features ffi --crate-type cdylib
+//! ```
+//!
+//! like this:
+//!
+//! ```js
+//! const { memory, dipboy_input, dipboy_output, dipboy_parse_order, dipboy_adjudicate } =
+//!   instance.exports;
+//! const write = (slot, text) => {
+//!   const bytes = new TextEncoder().encode(text);
+//!   new Uint8Array(memory.buffer, dipboy_input(slot, bytes.length), bytes.length).set(bytes);
+//! };
+//! const read = (len) =>
+//!   JSON.parse(new TextDecoder().decode(new Uint8Array(memory.buffer, dipboy_output(), len)));
+//! const parseOrder = (text) => (write(0, text), read(dipboy_parse_order()));
+//! const adjudicate = (state, orders) =>
+//!   (write(0, state), write(1, orders), read(dipboy_adjudicate()));
+//! ```
+//!
+//! For Python, the `python` feature adds [`python`], the `dipboy`
+//! extension module. Build it with
+//!
+//! ```text
+//! cargo rustc --lib --release --features python --crate-type cdylib
+//! cp target/release/libdipboy.so dipboy.so
+//! ```
+//!
+//! and use it like this:
+//!
+//! ```python
+//! import dipboy
+//!
+//! result = dipboy.adjudicate(dipboy.standard_state(), {"France": ["A Par - Bur"]})
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+pub mod c;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use crate::adjudicator::{self, OrderStatus};
+use crate::json::{self, Value};
+use crate::map::{Map, ProvinceKind};
+use crate::order::Order;
+use crate::parser;
+use crate::power::Power;
+use crate::state::GameState;
+
+/// The standard map as `{"provinces": [...]}`.
+///
+/// Each province has its `abbreviation`, `name`, `kind` ("land", "coast"
+/// or "sea"), `supplyCenter`, `home` power or null, and `coasts`.
+pub fn map() -> String {
+    let provinces = Map::standard()
+        .provinces()
+        .map(|province| {
+            let kind = match province.kind() {
+                ProvinceKind::Land => "land",
+                ProvinceKind::Coast => "coast",
+                ProvinceKind::Sea => "sea",
+            };
+            Value::Object(vec![
+                ("abbreviation".to_owned(), string(province.abbreviation())),
+                ("name".to_owned(), string(province.name())),
+                ("kind".to_owned(), string(kind)),
+                (
+                    "supplyCenter".to_owned(),
+                    Value::Bool(province.is_supply_center()),
+                ),
+                (
+                    "home".to_owned(),
+                    province
+                        .home_power()
+                        .map_or(Value::Null, |power| string(power.name())),
+                ),
+                (
+                    "coasts".to_owned(),
+                    Value::Array(
+                        province
+                            .coasts()
+                            .iter()
+                            .map(|coast| string(coast.abbreviation()))
+                            .collect(),
+                    ),
+                ),
+            ])
+        })
+        .collect();
+    object(vec![("provinces", Value::Array(provinces))])
+}
+
+/// The standard starting position, as written by [`GameState::to_json`].
+pub fn standard_state() -> String {
+    GameState::standard().to_json()
+}
+
+/// Parses an order on the standard map, returning `{"order": ...}` or
+/// `{"error": "..."}`.
+///
+/// An order is written as an object with its `kind` ("hold", "move",
+/// "support" or "convoy"), `unit` ("A", "F" or null), `province`, and its
+/// `text` as the parser would display it. Moves add `destination`, `coast`
+/// and `viaConvoy`; supports and convoys add the `target` order.
+pub fn parse_order(text: &str) -> String {
+    match parser::parse_order(Map::standard(), text) {
+        Ok(order) => object(vec![("order", order_value(&order))]),
+        Err(error) => self::error(error),
+    }
+}
+
+/// Adjudicates orders against a position on the standard map.
+///
+/// `state` is a position as written by [`GameState::to_json`], and
+/// `orders` maps power names to lists of orders, like
+/// `{"France": ["A Par - Bur"]}`. Returns `{"error": "..."}`, or
+/// `{"resolutions": [...], "rejected": [...], "state": ...}` with the
+/// position after the phase.
+pub fn adjudicate(state: &str, orders: &str) -> String {
+    match try_adjudicate(state, orders) {
+        Ok(result) => result,
+        Err(message) => error(message),
+    }
+}
+
+fn try_adjudicate(state: &str, orders: &str) -> Result<String, String> {
+    let map = Map::standard();
+    let state = GameState::from_json(map, state).map_err(|e| format!("state: {}", e))?;
+    let orders = json::parse(orders).map_err(|e| format!("orders: {}", e))?;
+    let mut by_power: HashMap<Power, Vec<Order>> = HashMap::new();
+    for (power, list) in orders
+        .as_object()
+        .ok_or("orders: expected an object of powers")?
+    {
+        let power: Power = power.parse().map_err(|e| format!("orders: {}", e))?;
+        let list = list
+            .as_array()
+            .ok_or_else(|| format!("orders: expected a list of orders for {}", power))?;
+        for order in list {
+            let text = order
+                .as_str()
+                .ok_or_else(|| format!("orders: expected text for {}'s orders", power))?;
+            let order = parser::parse_order(map, text).map_err(|e| format!("{}: {}", power, e))?;
+            by_power.entry(power).or_default().push(order);
+        }
+    }
+
+    let result = adjudicator::adjudicate(by_power, &state);
+    let mut resolutions: Vec<_> = result.resolutions().collect();
+    resolutions.sort_by_key(|(province, r)| (r.power(), province.id()));
+    let resolutions = resolutions
+        .into_iter()
+        .map(|(_, resolution)| {
+            let status = match resolution.status() {
+                OrderStatus::Ordered => "ordered".to_owned(),
+                OrderStatus::Unordered => "unordered".to_owned(),
+                OrderStatus::Illegal(reason) => format!("illegal: {}", reason),
+            };
+            Value::Object(vec![
+                ("power".to_owned(), string(resolution.power().name())),
+                ("order".to_owned(), order_value(resolution.order())),
+                ("status".to_owned(), Value::String(status)),
+                ("succeeded".to_owned(), Value::Bool(resolution.succeeded())),
+                (
+                    "dislodgedBy".to_owned(),
+                    resolution
+                        .dislodged_by()
+                        .map_or(Value::Null, |by| string(by.abbreviation())),
+                ),
+            ])
+        })
+        .collect();
+    let rejected = result
+        .rejected()
+        .iter()
+        .map(|rejected| {
+            Value::Object(vec![
+                ("power".to_owned(), string(rejected.power.name())),
+                ("order".to_owned(), order_value(&rejected.order)),
+                (
+                    "reason".to_owned(),
+                    Value::String(rejected.reason.to_string()),
+                ),
+            ])
+        })
+        .collect();
+    Ok(object(vec![
+        ("resolutions", Value::Array(resolutions)),
+        ("rejected", Value::Array(rejected)),
+        ("state", state.apply(&result).to_value()),
+    ]))
+}
+
+fn order_value(order: &Order) -> Value {
+    let kind = match order {
+        Order::Hold(_) => "hold",
+        Order::Move(_) => "move",
+        Order::Support(_) => "support",
+        Order::Convoy(_) => "convoy",
+    };
+    let mut members = vec![
+        ("kind".to_owned(), string(kind)),
+        (
+            "unit".to_owned(),
+            order
+                .unit()
+                .map_or(Value::Null, |unit| Value::String(unit.to_string())),
+        ),
+        (
+            "province".to_owned(),
+            string(order.province().abbreviation()),
+        ),
+    ];
+    if let Order::Move(move_) = order {
+        members.push((
+            "destination".to_owned(),
+            string(move_.destination().abbreviation()),
+        ));
+        members.push((
+            "coast".to_owned(),
+            move_
+                .coast()
+                .map_or(Value::Null, |coast| string(coast.abbreviation())),
+        ));
+        members.push(("viaConvoy".to_owned(), Value::Bool(move_.via_convoy())));
+    }
+    if let Some(target) = order.target() {
+        members.push(("target".to_owned(), order_value(&target)));
+    }
+    members.push(("text".to_owned(), Value::String(order.to_string())));
+    Value::Object(members)
+}
+
+fn string(text: &str) -> Value {
+    Value::String(text.to_owned())
+}
+
+fn object(members: Vec<(&str, Value)>) -> String {
+    Value::Object(
+        members
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value))
+            .collect(),
+    )
+    .to_string()
+}
+
+fn error(message: impl ToString) -> String {
+    object(vec![("error", Value::String(message.to_string()))])
+}
+
+thread_local! {
+    static INPUTS: RefCell<[Vec<u8>; 2]> = const { RefCell::new([Vec::new(), Vec::new()]) };
+    static OUTPUT: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Makes room for `len` bytes of UTF-8 in input `slot`, 0 or 1, and
+/// returns where to write them.
+#[no_mangle]
+pub extern "C" fn dipboy_input(slot: usize, len: usize) -> *mut u8 {
+    INPUTS.with(|inputs| {
+        let input = &mut inputs.borrow_mut()[slot.min(1)];
+        input.clear();
+        input.resize(len, 0);
+        input.as_mut_ptr()
+    })
+}
+
+/// Where the result of the last call is, valid until the next call.
+#[no_mangle]
+pub extern "C" fn dipboy_output() -> *const u8 {
+    OUTPUT.with(|output| output.borrow().as_ptr())
+}
+
+/// [`map`]. Returns the length of the output.
+#[no_mangle]
+pub extern "C" fn dipboy_map() -> usize {
+    set_output(map())
+}
+
+/// [`standard_state`]. Returns the length of the output.
+#[no_mangle]
+pub extern "C" fn dipboy_standard_state() -> usize {
+    set_output(standard_state())
+}
+
+/// [`parse_order`] on input slot 0. Returns the length of the output.
+#[no_mangle]
+pub extern "C" fn dipboy_parse_order() -> usize {
+    with_inputs(|[text, _]| parse_order(text))
+}
+
+/// [`adjudicate`] on the state in input slot 0 and the orders in slot 1.
+/// Returns the length of the output.
+#[no_mangle]
+pub extern "C" fn dipboy_adjudicate() -> usize {
+    with_inputs(|[state, orders]| adjudicate(state, orders))
+}
+
+fn with_inputs(f: impl FnOnce([&str; 2]) -> String) -> usize {
+    let result = INPUTS.with(|inputs| {
+        let inputs = inputs.borrow();
+        match (
+            std::str::from_utf8(&inputs[0]),
+            std::str::from_utf8(&inputs[1]),
+        ) {
+            (Ok(first), Ok(second)) => f([first, second]),
+            _ => error("input is not valid UTF-8"),
+        }
+    });
+    set_output(result)
+}
+
+fn set_output(result: String) -> usize {
+    OUTPUT.with(|output| {
+        let len = result.len();
+        *output.borrow_mut() = result;
+        len
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(f: extern "C" fn() -> usize, inputs: &[&str]) -> Value {
+        for (slot, input) in inputs.iter().enumerate() {
+            let ptr = dipboy_input(slot, input.len());
+            unsafe { std::ptr::copy_nonoverlapping(input.as_ptr(), ptr, input.len()) };
+        }
+        let len = f();
+        let output = unsafe { std::slice::from_raw_parts(dipboy_output(), len) };
+        json::parse(std::str::from_utf8(output).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn exports() {
+        let order = call(dipboy_parse_order, &["F Mao - Spa (nc)"]);
+        let order = order.get("order").unwrap();
+        assert_eq!(order.get("kind").and_then(Value::as_str), Some("move"));
+        assert_eq!(order.get("coast").and_then(Value::as_str), Some("nc"));
+        assert!(call(dipboy_parse_order, &["A Atlantis H"])
+            .get("error")
+            .is_some());
+
+        let state = GameState::standard().to_json();
+        let result = call(
+            dipboy_adjudicate,
+            &[
+                &state,
+                r#"{"France": ["A Par - Bur"], "Germany": ["A Mun - Bur"]}"#,
+            ],
+        );
+        let resolutions = result.get("resolutions").and_then(Value::as_array).unwrap();
+        assert_eq!(resolutions.len(), 22);
+        assert!(resolutions
+            .iter()
+            .filter(|r| r
+                .get("order")
+                .and_then(|o| o.get("kind"))
+                .and_then(Value::as_str)
+                == Some("move"))
+            .all(|r| r.get("succeeded") == Some(&Value::Bool(false))));
+        assert_eq!(
+            result
+                .get("state")
+                .and_then(|state| state.get("phase"))
+                .and_then(Value::as_str),
+            Some("Fall 1901 Movement")
+        );
+        assert!(adjudicate(&state, "[]").contains("error"));
+
+        let map = call(dipboy_map, &[]);
+        let provinces = map.get("provinces").and_then(Value::as_array).unwrap();
+        assert_eq!(provinces.len(), 75);
+        let stp = provinces
+            .iter()
+            .find(|p| p.get("abbreviation").and_then(Value::as_str) == Some("StP"))
+            .unwrap();
+        assert_eq!(stp.get("home").and_then(Value::as_str), Some("Russia"));
+        assert_eq!(
+            stp.get("coasts").and_then(Value::as_array).unwrap().len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! PyO3 bindings: the `dipboy` Python module.
+//!
+//! Positions are dicts in the form written by
+//! [`GameState::to_json`](crate::state::GameState::to_json), and orders
+//! are dicts of power names to lists of order text, as in
+//! `{"France": ["A Par - Bur"]}`. Each function returns what its
+//! counterpart in the parent module writes as JSON, as Python objects,
+//! and raises `dipboy.DipboyError` where that would be `{"error": ...}`.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+create_exception!(
+    dipboy,
+    DipboyError,
+    PyException,
+    "An order, position or set of orders that dipboy could not read."
+);
+
+/// The standard map's provinces.
+#[pyfunction]
+fn map(py: Python) -> PyResult<Bound<PyAny>> {
+    member(loads(py, &super::map())?, "provinces")
+}
+
+/// The starting position of a standard game.
+#[pyfunction]
+fn standard_state(py: Python) -> PyResult<Bound<PyAny>> {
+    loads(py, &super::standard_state())
+}
+
+/// Parses an order on the standard map.
+#[pyfunction]
+fn parse_order<'py>(py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyAny>> {
+    member(loads(py, &super::parse_order(text))?, "order")
+}
+
+/// Adjudicates `orders` against `state`, returning a dict of the order
+/// `resolutions`, the `rejected` orders, and the `state` after the phase.
+#[pyfunction]
+fn adjudicate<'py>(
+    py: Python<'py>,
+    state: &Bound<'py, PyAny>,
+    orders: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let result = super::adjudicate(&dumps(state)?, &dumps(orders)?);
+    loads(py, &result)
+}
+
+#[pymodule]
+fn dipboy(module: &Bound<PyModule>) -> PyResult<()> {
+    module.add("DipboyError", module.py().get_type::<DipboyError>())?;
+    module.add_function(wrap_pyfunction!(map, module)?)?;
+    module.add_function(wrap_pyfunction!(standard_state, module)?)?;
+    module.add_function(wrap_pyfunction!(parse_order, module)?)?;
+    module.add_function(wrap_pyfunction!(adjudicate, module)?)?;
+    Ok(())
+}
+
+/// Reads JSON written by the parent module, raising its error if it is
+/// one.
+fn loads<'py>(py: Python<'py>, json: &str) -> PyResult<Bound<'py, PyAny>> {
+    let value = py.import("json")?.call_method1("loads", (json,))?;
+    if let Some(error) = value.downcast::<PyDict>()?.get_item("error")? {
+        return Err(DipboyError::new_err(error.extract::<String>()?));
+    }
+    Ok(value)
+}
+
+fn dumps(value: &Bound<PyAny>) -> PyResult<String> {
+    value
+        .py()
+        .import("json")?
+        .call_method1("dumps", (value,))?
+        .extract()
+}
+
+fn member<'py>(value: Bound<'py, PyAny>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+    value.get_item(key)
+}
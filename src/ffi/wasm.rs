@@ -0,0 +1,24 @@
+//! `wasm-bindgen` exports, for web front ends.
+//!
+//! Each takes and returns the same JSON as its counterpart in the parent
+//! module, but returns it parsed into a JavaScript object.
+
+use js_sys::JSON;
+use wasm_bindgen::prelude::*;
+
+/// [`parse_order`](super::parse_order): `{order: ...}` or `{error: "..."}`.
+#[wasm_bindgen(js_name = parseOrder)]
+pub fn parse_order(text: &str) -> JsValue {
+    to_js(super::parse_order(text))
+}
+
+/// [`adjudicate`](super::adjudicate): `{resolutions, rejected, state}` or
+/// `{error: "..."}`.
+#[wasm_bindgen]
+pub fn adjudicate(state_json: &str, orders_json: &str) -> JsValue {
+    to_js(super::adjudicate(state_json, orders_json))
+}
+
+fn to_js(json: String) -> JsValue {
+    JSON::parse(&json).expect("the exports write valid JSON")
+}
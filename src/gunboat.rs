@@ -0,0 +1,104 @@
+//! Orders and results for blind games, where the GM publishes what was
+//! ordered without saying which power ordered it.
+//!
+//! Orders are shuffled before they are shown, so that the order in which
+//! the powers or their units happen to be listed gives nothing away. The
+//! shuffle is fixed by a seed, so the same seed, like a hash of the game's
+//! name and the phase, always gives the same presentation.
+
+use crate::adjudicator::{AdjudicationResult, OrderStatus};
+use crate::judge::notes;
+use crate::order::Order;
+use crate::rng::Rng;
+use crate::submission::OrderSet;
+
+/// The orders in `orders`, without the powers that gave them, in
+/// [canonical](Order::canonicalize) form and shuffled by `seed`.
+pub fn anonymize<'m>(orders: &OrderSet<'m>, seed: u64) -> Vec<Order<'m>> {
+    let mut anonymous: Vec<_> = orders
+        .iter()
+        .map(|(_, order)| order.canonicalize(orders.map()).into_order())
+        .collect();
+    anonymous.sort_by_key(|order| order.province().id());
+    shuffle(&mut anonymous, seed);
+    anonymous
+}
+
+/// Puts `items` in an order fixed by `seed`, each possible order being as
+/// likely as any other for a seed chosen at random.
+pub fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut rng = Rng::new(seed);
+    for i in (1..items.len()).rev() {
+        items.swap(i, rng.below(i + 1));
+    }
+}
+
+/// The results of a phase for everyone to see: each order and its outcome,
+/// as in `A Paris - Burgundy.  (*bounce*)`, one to a line, without powers,
+/// and shuffled by `seed`.
+pub fn public_results(result: &AdjudicationResult, seed: u64) -> String {
+    let mut resolutions: Vec<_> = result.resolutions().collect();
+    resolutions.sort_by_key(|(province, _)| province.id());
+    let mut lines: Vec<String> = resolutions
+        .into_iter()
+        .filter(|(_, resolution)| resolution.status() != OrderStatus::Unordered)
+        .map(|(_, resolution)| {
+            let order = match resolution.status() {
+                OrderStatus::Illegal(_) => resolution.submitted().unwrap_or(resolution.order()),
+                _ => resolution.order(),
+            };
+            let notes = notes(resolution, result);
+            if notes.is_empty() {
+                format!("{}.", order)
+            } else {
+                format!("{}.  (*{}*)", order, notes.join(", "))
+            }
+        })
+        .collect();
+    shuffle(&mut lines, seed);
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjudicator::adjudicate;
+    use crate::map::Map;
+    use crate::parser::parse_order;
+    use crate::power::Power;
+    use crate::state::GameState;
+
+    #[test]
+    fn blind_results() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let mut orders = OrderSet::new(map);
+        orders.submit(Power::France, order("A Par - Bur"));
+        orders.submit(Power::France, order("Brest - Mid-Atlantic"));
+        orders.submit(Power::Germany, order("A Mun - Bur"));
+        orders.submit(Power::Germany, order("F Kie - Den"));
+
+        let anonymous = anonymize(&orders, 7);
+        assert_eq!(anonymous, anonymize(&orders, 7));
+        assert_eq!(anonymous.len(), 4);
+        assert!(anonymous.contains(&order("F Bre - Mao")));
+        let shuffles: Vec<_> = (0..20).map(|seed| anonymize(&orders, seed)).collect();
+        assert!(shuffles.iter().any(|shuffle| *shuffle != anonymous));
+
+        let state = GameState::standard();
+        let result = adjudicate(orders.to_orders(), &state);
+        let text = public_results(&result, 7);
+        assert_eq!(text, public_results(&result, 7));
+        let mut lines: Vec<_> = text.lines().collect();
+        lines.sort_unstable();
+        assert_eq!(
+            lines,
+            [
+                "A Munich - Burgundy.  (*bounce*)",
+                "A Paris - Burgundy.  (*bounce*)",
+                "F Brest - Mid-Atlantic Ocean.",
+                "F Kiel - Denmark.",
+            ]
+        );
+    }
+}
@@ -0,0 +1,845 @@
+//! A record of a game, phase by phase.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::path::Path;
+use std::{fs, io};
+
+use crate::adjudicator::{
+    adjudicate_adjustments, adjudicate_with, AdjudicationResult, Adjustment, AdjustmentResult,
+    OrderStatus, Resolution, Rules,
+};
+use crate::archive;
+use crate::json::Value;
+use crate::map::{Map, Province};
+use crate::order::Order;
+use crate::phase::{Phase, PhaseKind};
+use crate::power::Power;
+use crate::state::{GameState, Unit};
+use crate::submission::{Action, Envelope, OrderSet};
+use crate::vote::{Ballot, Outcome};
+
+/// One adjudicated phase: the orders given, what came of them, the
+/// position that resulted, and whether that ended the game.
+#[derive(Clone, Debug)]
+pub struct Turn<'m> {
+    phase: Phase,
+    orders: HashMap<Power, Vec<Order<'m>>>,
+    result: AdjudicationResult<'m>,
+    adjustments: HashMap<Power, Vec<Adjustment<'m>>>,
+    adjustment_result: Option<AdjustmentResult<'m>>,
+    position: GameState<'m>,
+    outcome: Option<Outcome>,
+    trail: Vec<(Power, Envelope<Action<'m>>)>,
+}
+
+impl<'m> Turn<'m> {
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// The orders as they were submitted.
+    pub fn orders(&self) -> &HashMap<Power, Vec<Order<'m>>> {
+        &self.orders
+    }
+
+    pub fn result(&self) -> &AdjudicationResult<'m> {
+        &self.result
+    }
+
+    /// The builds and disbands as they were submitted, in an adjustment
+    /// phase.
+    pub fn adjustments(&self) -> &HashMap<Power, Vec<Adjustment<'m>>> {
+        &self.adjustments
+    }
+
+    /// What came of the builds and disbands, for an adjustment phase.
+    pub fn adjustment_result(&self) -> Option<&AdjustmentResult<'m>> {
+        self.adjustment_result.as_ref()
+    }
+
+    /// The position after the phase.
+    pub fn position(&self) -> &GameState<'m> {
+        &self.position
+    }
+
+    /// How the game ended with this phase, if it did.
+    pub fn outcome(&self) -> Option<&Outcome> {
+        self.outcome.as_ref()
+    }
+
+    /// Every order submitted or withdrawn in an envelope during the
+    /// phase, including those since replaced, if it was played with
+    /// [`History::play_order_set`]. See [`OrderSet::trail`].
+    ///
+    /// The trail is kept only in memory: game files record just the
+    /// orders that stood.
+    pub fn trail(&self) -> &[(Power, Envelope<Action<'m>>)] {
+        &self.trail
+    }
+}
+
+/// The differences between two positions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PositionDiff<'m> {
+    /// Units in the first position that are not in the second.
+    pub removed: Vec<(&'m Province, Unit)>,
+    /// Units in the second position that are not in the first.
+    pub added: Vec<(&'m Province, Unit)>,
+    /// Supply centers that changed hands, with their old and new owners.
+    pub captured: Vec<(&'m Province, Option<Power>, Option<Power>)>,
+}
+
+impl<'m> PositionDiff<'m> {
+    /// Compares two positions on the same map.
+    pub fn between(from: &GameState<'m>, to: &GameState<'m>) -> Self {
+        let mut diff = PositionDiff::default();
+        for province in from.map().provinces() {
+            let (before, after) = (from.unit_at(province), to.unit_at(province));
+            if before != after {
+                diff.removed.extend(before.map(|&unit| (province, unit)));
+                diff.added.extend(after.map(|&unit| (province, unit)));
+            }
+            let (before, after) = (from.owner(province), to.owner(province));
+            if before != after {
+                diff.captured.push((province, before, after));
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.captured.is_empty()
+    }
+}
+
+/// The error returned when a phase is played after the game has ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameOver {
+    outcome: Outcome,
+}
+
+impl GameOver {
+    /// How the game ended.
+    pub fn outcome(&self) -> &Outcome {
+        &self.outcome
+    }
+}
+
+impl fmt::Display for GameOver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("the game is over")
+    }
+}
+
+impl Error for GameOver {}
+
+/// Every phase of a game, from its starting position.
+///
+/// Position 0 is the start of the game, and position `n` is the position
+/// after the `n`th turn. Every phase is adjudicated under the same
+/// [`Rules`].
+#[derive(Clone, Debug)]
+pub struct History<'m> {
+    start: GameState<'m>,
+    rules: Rules,
+    turns: Vec<Turn<'m>>,
+    /// The index of the turn played in each phase.
+    phases: HashMap<Phase, usize>,
+}
+
+impl<'m> History<'m> {
+    /// A game played under the default rules.
+    pub fn new(start: GameState<'m>) -> Self {
+        History::with_rules(start, Rules::default())
+    }
+
+    pub fn with_rules(start: GameState<'m>, rules: Rules) -> Self {
+        History {
+            start,
+            rules,
+            turns: Vec::new(),
+            phases: HashMap::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Adjudicates `orders` against the current position and records the
+    /// result, unless the game is over.
+    ///
+    /// In an adjustment phase, the orders are all rejected, and no units
+    /// are built; powers with too many units have them removed.
+    pub fn play(&mut self, orders: HashMap<Power, Vec<Order<'m>>>) -> Result<&Turn<'m>, GameOver> {
+        self.play_with_ballots(orders, &[])
+    }
+
+    /// Like [`play`](Self::play), then counts the votes cast during the
+    /// phase.
+    ///
+    /// The turn ends the game if it leaves a power with a majority of the
+    /// supply centers, or else if one of `ballots` passes in the position
+    /// it leaves; the first that passes counts.
+    pub fn play_with_ballots(
+        &mut self,
+        orders: HashMap<Power, Vec<Order<'m>>>,
+        ballots: &[Ballot],
+    ) -> Result<&Turn<'m>, GameOver> {
+        self.play_turn(orders, HashMap::new(), ballots)
+    }
+
+    /// Like [`play`](Self::play), with the orders that stand in `orders`,
+    /// keeping its [audit trail](OrderSet::trail) in the turn.
+    pub fn play_order_set(&mut self, orders: OrderSet<'m>) -> Result<&Turn<'m>, GameOver> {
+        self.play_turn(orders.to_orders(), HashMap::new(), &[])?;
+        let turn = self.turns.last_mut().expect("a turn was just played");
+        turn.trail = orders.trail().to_vec();
+        Ok(turn)
+    }
+
+    /// Adjudicates the builds and disbands of an adjustment phase and
+    /// records the result, unless the game is over.
+    ///
+    /// Outside an adjustment phase, this is [`play`](Self::play) without
+    /// orders, and every adjustment is ignored.
+    pub fn play_adjustments(
+        &mut self,
+        adjustments: HashMap<Power, Vec<Adjustment<'m>>>,
+    ) -> Result<&Turn<'m>, GameOver> {
+        self.play_turn(HashMap::new(), adjustments, &[])
+    }
+
+    pub(crate) fn play_turn(
+        &mut self,
+        orders: HashMap<Power, Vec<Order<'m>>>,
+        adjustments: HashMap<Power, Vec<Adjustment<'m>>>,
+        ballots: &[Ballot],
+    ) -> Result<&Turn<'m>, GameOver> {
+        if let Some(outcome) = self.outcome() {
+            return Err(GameOver {
+                outcome: outcome.clone(),
+            });
+        }
+        let current = self.current();
+        let phase = current.phase();
+        let (result, adjustment_result, position) =
+            play_phase(current, &orders, &adjustments, &self.rules);
+        let outcome = match position.solo_winner() {
+            Some(power) => Some(Outcome::Solo(power)),
+            None => ballots.iter().find_map(|ballot| ballot.outcome(&position)),
+        };
+        self.phases.insert(phase, self.turns.len());
+        self.turns.push(Turn {
+            phase,
+            orders,
+            result,
+            adjustments,
+            adjustment_result,
+            position,
+            outcome,
+            trail: Vec::new(),
+        });
+        Ok(self.turns.last().expect("a turn was just pushed"))
+    }
+
+    /// How the game ended, if a turn has ended it.
+    pub fn outcome(&self) -> Option<&Outcome> {
+        self.turns.iter().find_map(Turn::outcome)
+    }
+
+    pub fn start(&self) -> &GameState<'m> {
+        &self.start
+    }
+
+    /// The latest position.
+    pub fn current(&self) -> &GameState<'m> {
+        self.position(self.turns.len())
+            .expect("the latest position exists")
+    }
+
+    pub fn turns(&self) -> &[Turn<'m>] {
+        &self.turns
+    }
+
+    /// The position after `n` turns.
+    pub fn position(&self, n: usize) -> Option<&GameState<'m>> {
+        match n {
+            0 => Some(&self.start),
+            n => self.turns.get(n - 1).map(Turn::position),
+        }
+    }
+
+    /// Undoes every turn after the `n`th.
+    pub fn truncate(&mut self, n: usize) {
+        self.turns.truncate(n);
+        self.phases.retain(|_, &mut turn| turn < n);
+    }
+
+    /// The position `phase` was played from, or the current position if
+    /// it is the phase to be played next, without replaying the game.
+    pub fn state_at(&self, phase: Phase) -> Option<&GameState<'m>> {
+        match self.phases.get(&phase) {
+            Some(&turn) => self.position(turn),
+            None => Some(self.current()).filter(|current| current.phase() == phase),
+        }
+    }
+
+    /// The orders given in `phase`, if it has been played.
+    pub fn orders_at(&self, phase: Phase) -> Option<&HashMap<Power, Vec<Order<'m>>>> {
+        self.turn_at(phase).map(Turn::orders)
+    }
+
+    /// The turn played in `phase`, if it has been.
+    pub fn turn_at(&self, phase: Phase) -> Option<&Turn<'m>> {
+        self.phases.get(&phase).map(|&turn| &self.turns[turn])
+    }
+
+    /// Plays the game again from the start up to the `n`th turn,
+    /// re-adjudicating each turn's orders and adjustments under the
+    /// game's rules, and returns the position reached.
+    ///
+    /// This matches [`position`](Self::position) unless the adjudicator
+    /// has changed since the game was recorded.
+    pub fn replay(&self, n: usize) -> Option<GameState<'m>> {
+        if n > self.turns.len() {
+            return None;
+        }
+        let mut state = self.start.clone();
+        for turn in &self.turns[..n] {
+            let (_, _, position) = play_phase(&state, &turn.orders, &turn.adjustments, &self.rules);
+            state = position;
+        }
+        Some(state)
+    }
+
+    /// The differences between the positions after `from` and `to` turns.
+    pub fn diff(&self, from: usize, to: usize) -> Option<PositionDiff<'m>> {
+        Some(PositionDiff::between(
+            self.position(from)?,
+            self.position(to)?,
+        ))
+    }
+
+    /// The supply centers each power held at the start and at the end of
+    /// each year played, for graphing the game.
+    ///
+    /// A year's counts are those after its last turn played, which for a
+    /// year played out is its Fall, when centers change hands.
+    pub fn center_timeline(&self) -> CenterTimeline {
+        let counts = |position: &GameState| {
+            let mut counts = [0; 7];
+            for (count, &power) in counts.iter_mut().zip(&Power::ALL) {
+                *count = position.center_count(power);
+            }
+            counts
+        };
+        let mut timeline = CenterTimeline {
+            start: counts(&self.start),
+            years: Vec::new(),
+        };
+        for turn in &self.turns {
+            let year = turn.phase.year;
+            let counts = counts(&turn.position);
+            match timeline.years.last_mut() {
+                Some((last, last_counts)) if *last == year => *last_counts = counts,
+                _ => timeline.years.push((year, counts)),
+            }
+        }
+        timeline
+    }
+
+    /// Saves the game's orders, phase by phase, to a game file at `path`,
+    /// in the format described in [`archive`], with the rules it is played
+    /// under.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, archive::write(self))
+    }
+
+    /// Loads a game saved with [`save`](Self::save), playing it again on
+    /// `map` under the rules it was saved with. A file that is not a valid game
+    /// fails with [`io::ErrorKind::InvalidData`], wrapping the
+    /// [`ArchiveError`](archive::ArchiveError).
+    pub fn load(map: &'m Map, path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        archive::read(map, &text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Writes out the whole game: each phase's header, then each power's
+    /// orders with their outcomes, and a last line for the phase that
+    /// ended the game.
+    pub fn export(&self) -> String {
+        let mut out = String::new();
+        for turn in &self.turns {
+            writeln!(out, "{}", turn.phase).unwrap();
+            let mut resolutions: Vec<_> = turn.result.resolutions().collect();
+            resolutions.sort_by_key(|(province, r)| (r.power(), province.id()));
+            for (_, resolution) in resolutions {
+                writeln!(
+                    out,
+                    "{}: {}{}",
+                    resolution.power(),
+                    resolution.submitted().unwrap_or_else(|| resolution.order()),
+                    outcome(resolution),
+                )
+                .unwrap();
+            }
+            for rejected in turn.result.rejected() {
+                writeln!(
+                    out,
+                    "{}: {} (rejected: {})",
+                    rejected.power, rejected.order, rejected.reason
+                )
+                .unwrap();
+            }
+            if let Some(result) = &turn.adjustment_result {
+                let built = result.builds().iter().map(|&(province, unit)| {
+                    let build = Adjustment::Build {
+                        unit: unit.unit_type(),
+                        province,
+                        coast: unit.coast(),
+                    };
+                    (unit.power(), build, "")
+                });
+                let disbanded = |note| {
+                    move |&(province, unit): &(&'m Province, Unit)| {
+                        (unit.power(), Adjustment::Disband(province), note)
+                    }
+                };
+                let mut made: Vec<_> = built
+                    .chain(result.disbands().iter().map(disbanded("")))
+                    .chain(result.removals().iter().map(disbanded(" (removed)")))
+                    .collect();
+                made.sort_by_key(|&(power, _, _)| power);
+                for (power, adjustment, note) in made {
+                    writeln!(out, "{}: {}{}", power, adjustment, note).unwrap();
+                }
+                for rejected in result.rejected() {
+                    writeln!(
+                        out,
+                        "{}: {} (rejected: {})",
+                        rejected.power, rejected.adjustment, rejected.reason
+                    )
+                    .unwrap();
+                }
+            }
+            match &turn.outcome {
+                Some(Outcome::Solo(power)) => writeln!(out, "{} wins", power).unwrap(),
+                Some(Outcome::Draw(powers)) => {
+                    let powers: Vec<&str> = powers.iter().map(|power| power.name()).collect();
+                    writeln!(out, "Draw between {}", powers.join(", ")).unwrap()
+                }
+                None => {}
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// How many supply centers each power held, at the start of a game and
+/// at the end of each year of it, from [`History::center_timeline`]. The
+/// counts are in [`Power::ALL`] order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CenterTimeline {
+    pub start: [usize; 7],
+    pub years: Vec<(u16, [usize; 7])>,
+}
+
+impl CenterTimeline {
+    /// Writes the timeline as CSV, with a header row naming the powers
+    /// and a row for the start followed by one for each year:
+    ///
+    /// ```text
+    /// year,Austria,England,France,Germany,Italy,Russia,Turkey
+    /// start,3,3,3,3,3,4,3
+    /// 1901,4,5,5,5,4,6,4
+    /// ```
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("year");
+        for power in &Power::ALL {
+            write!(out, ",{}", power).unwrap();
+        }
+        out.push('\n');
+        let rows = std::iter::once(("start".to_owned(), &self.start)).chain(
+            self.years
+                .iter()
+                .map(|(year, counts)| (year.to_string(), counts)),
+        );
+        for (label, counts) in rows {
+            out.push_str(&label);
+            for count in counts {
+                write!(out, ",{}", count).unwrap();
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Writes the timeline as JSON, mapping `"start"` and each year to the
+    /// powers' counts, as in `{"start": {"Austria": 3, ...}, "1901":
+    /// {"Austria": 4, ...}}`.
+    pub fn to_json(&self) -> String {
+        let counts = |counts: &[usize; 7]| {
+            let counts = Power::ALL
+                .iter()
+                .zip(counts)
+                .map(|(power, &count)| (power.name().to_owned(), Value::Number(count as f64)))
+                .collect();
+            Value::Object(counts)
+        };
+        let members = std::iter::once(("start".to_owned(), counts(&self.start)))
+            .chain(
+                self.years
+                    .iter()
+                    .map(|(year, years)| (year.to_string(), counts(years))),
+            )
+            .collect();
+        Value::Object(members).pretty()
+    }
+}
+
+/// Adjudicates a phase from `state` and applies the result: the orders in
+/// a movement or retreat phase, and the adjustments in an adjustment
+/// phase.
+#[allow(clippy::type_complexity)]
+fn play_phase<'m>(
+    state: &GameState<'m>,
+    orders: &HashMap<Power, Vec<Order<'m>>>,
+    adjustments: &HashMap<Power, Vec<Adjustment<'m>>>,
+    rules: &Rules,
+) -> (
+    AdjudicationResult<'m>,
+    Option<AdjustmentResult<'m>>,
+    GameState<'m>,
+) {
+    let result = adjudicate_with(orders.clone(), state, rules);
+    if state.phase().kind == PhaseKind::Adjustments {
+        let adjustments = adjudicate_adjustments(adjustments.clone(), state, rules);
+        let position = state.apply_adjustments(&adjustments);
+        (result, Some(adjustments), position)
+    } else {
+        let position = state.apply(&result);
+        (result, None, position)
+    }
+}
+
+/// A parenthetical note on how an order fared, for [`History::export`].
+fn outcome(resolution: &Resolution) -> String {
+    let mut notes = Vec::new();
+    match resolution.status() {
+        OrderStatus::Ordered => {}
+        OrderStatus::Unordered => notes.push("no order".to_owned()),
+        OrderStatus::Illegal(reason) => notes.push(format!("illegal: {}", reason)),
+    }
+    match resolution.order() {
+        Order::Move(_) if !resolution.succeeded() => notes.push("bounced".to_owned()),
+        Order::Support(_) if !resolution.succeeded() => notes.push("failed".to_owned()),
+        _ => {}
+    }
+    if let Some(attacker) = resolution.dislodged_by() {
+        notes.push(format!("dislodged by the unit from {}", attacker));
+    }
+    if notes.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", notes.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Map;
+    use crate::parser::parse_order;
+    use crate::phase::{PhaseKind, Season};
+
+    fn province(abbreviation: &str) -> &'static Province {
+        Map::standard().find_by_abbreviation(abbreviation).unwrap()
+    }
+
+    fn orders(power: Power, orders: &[&str]) -> HashMap<Power, Vec<Order<'static>>> {
+        let orders = orders
+            .iter()
+            .map(|order| parse_order(Map::standard(), order).unwrap())
+            .collect();
+        HashMap::from([(power, orders)])
+    }
+
+    #[test]
+    fn keeps_audit_trail() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let mut orders = OrderSet::new(map);
+        let sent = std::time::SystemTime::UNIX_EPOCH;
+        for text in ["A Par - Pic", "A Par - Bur"] {
+            orders.submit_enveloped(Power::France, Envelope::new(order(text), "alice", sent));
+        }
+        let mut history = History::new(GameState::standard());
+        let turn = history.play_order_set(orders).unwrap();
+        assert_eq!(turn.orders()[&Power::France], [order("A Par - Bur")]);
+        let trail: Vec<_> = turn
+            .trail()
+            .iter()
+            .map(|(_, envelope)| (envelope.contents().to_string(), envelope.revision()))
+            .collect();
+        assert_eq!(
+            trail,
+            [
+                ("A Paris - Picardy".to_owned(), 1),
+                ("A Paris - Burgundy".to_owned(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn play_replay_and_diff() {
+        let mut start = GameState::new(Map::standard());
+        start.place_unit(province("Par"), Unit::army(Power::France));
+        start.set_owner(province("Par"), Power::France);
+        let mut history = History::new(start);
+
+        history
+            .play(orders(Power::France, &["A Par - Bur"]))
+            .unwrap();
+        history
+            .play(orders(Power::France, &["A Bur - Bel"]))
+            .unwrap();
+        // France has a center to spare and an empty home center to build in.
+        assert_eq!(
+            history.current().phase(),
+            "Winter 1901 Adjustments".parse().unwrap()
+        );
+        history.play(HashMap::new()).unwrap();
+        assert_eq!(
+            history.current().phase(),
+            Phase::movement(Season::Spring, 1902)
+        );
+        assert_eq!(
+            history.current().owner(province("Bel")),
+            Some(Power::France)
+        );
+        assert_eq!(history.replay(3).as_ref(), history.position(3));
+
+        let diff = history.diff(0, 3).unwrap();
+        assert_eq!(diff.removed, [(province("Par"), Unit::army(Power::France))]);
+        assert_eq!(diff.added, [(province("Bel"), Unit::army(Power::France))]);
+        assert_eq!(
+            diff.captured,
+            [(province("Bel"), None, Some(Power::France))]
+        );
+
+        assert_eq!(
+            history.export(),
+            "Spring 1901 Movement\nFrance: A Paris - Burgundy\n\n\
+             Fall 1901 Movement\nFrance: A Burgundy - Belgium\n\n\
+             Winter 1901 Adjustments\n\n"
+        );
+
+        let fall = Phase::movement(Season::Fall, 1901);
+        assert_eq!(history.state_at(fall), history.position(1));
+        assert_eq!(
+            history.orders_at(fall),
+            Some(&orders(Power::France, &["A Bur - Bel"]))
+        );
+        assert_eq!(
+            history.state_at(Phase::movement(Season::Spring, 1902)),
+            Some(history.current())
+        );
+        assert_eq!(history.state_at(Phase::movement(Season::Fall, 1902)), None);
+        assert_eq!(
+            history.orders_at(Phase::movement(Season::Spring, 1902)),
+            None
+        );
+
+        history.truncate(1);
+        assert_eq!(history.state_at(fall), Some(history.current()));
+        assert_eq!(history.orders_at(fall), None);
+        assert_eq!(
+            history.current().unit_at(province("Bur")),
+            Some(&Unit::army(Power::France))
+        );
+    }
+
+    #[test]
+    fn export_notes_dislodgements() {
+        let mut start = GameState::new(Map::standard());
+        start.place_unit(province("Bur"), Unit::army(Power::France));
+        start.place_unit(province("Mun"), Unit::army(Power::Germany));
+        start.place_unit(province("Ruh"), Unit::army(Power::Germany));
+        let mut history = History::new(start);
+        let mut given = orders(Power::France, &["A Bur H"]);
+        given.extend(orders(
+            Power::Germany,
+            &["A Mun - Bur", "A Ruh S A Mun - Bur"],
+        ));
+        history.play(given).unwrap();
+        assert_eq!(
+            history.export(),
+            "Spring 1901 Movement\n\
+             France: A Burgundy H (dislodged by the unit from Munich)\n\
+             Germany: A Munich - Burgundy\n\
+             Germany: A Ruhr S A Munich - Burgundy\n\n"
+        );
+    }
+
+    #[test]
+    fn games_end_by_solo_or_vote() {
+        use crate::vote::Proposal;
+
+        let map = Map::standard();
+        let mut start = GameState::new(map);
+        start.place_unit(province("Par"), Unit::army(Power::France));
+        start.place_unit(province("Ber"), Unit::army(Power::Germany));
+        let mut history = History::new(start.clone());
+        let mut dias = Ballot::new(Proposal::Dias);
+        dias.vote(Power::France, true);
+        history
+            .play_with_ballots(HashMap::new(), &[dias.clone()])
+            .unwrap();
+        assert_eq!(history.outcome(), None);
+        dias.vote(Power::Germany, true);
+        history.play_with_ballots(HashMap::new(), &[dias]).unwrap();
+        assert_eq!(
+            history.outcome(),
+            Some(&Outcome::Draw(vec![Power::France, Power::Germany]))
+        );
+        assert!(history
+            .export()
+            .ends_with("Draw between France, Germany\n\n"));
+
+        // France takes Belgium for its eighteenth center.
+        let centers: Vec<_> = map
+            .provinces()
+            .filter(|p| p.is_supply_center() && !["Bel", "Ber"].contains(&p.abbreviation()))
+            .take(17)
+            .collect();
+        for center in centers {
+            start.set_owner(center, Power::France);
+        }
+        start.set_phase(Phase::movement(Season::Fall, 1901));
+        let mut history = History::new(start);
+        let turn = history
+            .play(orders(Power::France, &["A Par - Bur"]))
+            .unwrap();
+        assert_eq!(turn.outcome(), None);
+        assert_eq!(history.current().phase().kind, PhaseKind::Adjustments);
+        history.play(HashMap::new()).unwrap();
+        history.play(HashMap::new()).unwrap();
+        history
+            .play(orders(Power::France, &["A Bur - Bel"]))
+            .unwrap();
+        assert_eq!(history.outcome(), Some(&Outcome::Solo(Power::France)));
+        let over = history.play(HashMap::new()).unwrap_err();
+        assert_eq!(over.outcome(), &Outcome::Solo(Power::France));
+        assert_eq!(history.turns().len(), 4);
+    }
+
+    #[test]
+    fn retreats_and_adjustments() {
+        use crate::adjudicator::{Adjustment, RemovalRules};
+        use crate::order::UnitType;
+
+        let mut start = GameState::new(Map::standard());
+        start.set_phase(Phase::movement(Season::Fall, 1901));
+        start.place_unit(province("Ruh"), Unit::army(Power::Germany));
+        start.place_unit(province("Bur"), Unit::army(Power::Germany));
+        start.place_unit(province("Bel"), Unit::army(Power::France));
+        start.set_owner(province("Bel"), Power::France);
+        start.set_owner(province("Par"), Power::France);
+        start.set_owner(province("Mun"), Power::Germany);
+        let mut history = History::new(start);
+
+        history
+            .play(orders(
+                Power::Germany,
+                &["A Ruh - Bel", "A Bur S A Ruh - Bel"],
+            ))
+            .unwrap();
+        assert_eq!(history.current().phase().kind, PhaseKind::Retreats);
+        // The French army is disbanded, and France may build in Paris.
+        history.play(HashMap::new()).unwrap();
+        assert_eq!(history.current().phase().kind, PhaseKind::Adjustments);
+        let build = |abbreviation| Adjustment::Build {
+            unit: UnitType::Army,
+            province: province(abbreviation),
+            coast: None,
+        };
+        let turn = history
+            .play_adjustments(HashMap::from([(
+                Power::France,
+                vec![build("Par"), build("Mar")],
+            )]))
+            .unwrap();
+        assert_eq!(turn.adjustment_result().unwrap().builds().len(), 1);
+        assert_eq!(
+            history.current().phase(),
+            Phase::movement(Season::Spring, 1902)
+        );
+        assert_eq!(history.replay(3).as_ref(), history.position(3));
+        assert!(history.export().ends_with(
+            "Winter 1901 Adjustments\nFrance: Build A Paris\n\
+             France: Build A Marseilles (rejected: the power has no more adjustments to make)\n\n"
+        ));
+
+        // A power in civil disorder loses the unit its rules say is
+        // farthest from home, and a replay follows the same rules.
+        let mut start = GameState::new(Map::standard());
+        start.set_phase(Phase::movement(Season::Fall, 1901));
+        start.place_unit(province("Mos"), Unit::army(Power::Russia));
+        start.place_unit(province("Apu"), Unit::army(Power::Russia));
+        start.place_unit(province("Adr"), Unit::fleet(Power::Russia));
+        start.set_owner(province("Mos"), Power::Russia);
+        start.set_owner(province("Sev"), Power::Russia);
+        start.set_civil_disorder(Power::Russia, true);
+        let rules = Rules {
+            removals: RemovalRules::Rules1982,
+            ..Rules::default()
+        };
+        let mut history = History::with_rules(start, rules);
+        history.play(HashMap::new()).unwrap();
+        history.play(HashMap::new()).unwrap();
+        assert_eq!(history.current().unit_at(province("Apu")), None);
+        assert!(history.current().unit_at(province("Adr")).is_some());
+        assert_eq!(history.replay(2).as_ref(), history.position(2));
+    }
+
+    #[test]
+    fn center_timeline() {
+        let mut start = GameState::new(Map::standard());
+        start.place_unit(province("Par"), Unit::army(Power::France));
+        start.set_owner(province("Par"), Power::France);
+        let mut history = History::new(start);
+        history
+            .play(orders(Power::France, &["A Par - Bur"]))
+            .unwrap();
+        let timeline = history.center_timeline();
+        assert_eq!(timeline.years, [(1901, [0, 0, 1, 0, 0, 0, 0])]);
+
+        history
+            .play(orders(Power::France, &["A Bur - Bel"]))
+            .unwrap();
+        history.play(HashMap::new()).unwrap();
+        history.play(HashMap::new()).unwrap();
+        let timeline = history.center_timeline();
+        assert_eq!(timeline.start, [0, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(
+            timeline.years,
+            [(1901, [0, 0, 2, 0, 0, 0, 0]), (1902, [0, 0, 2, 0, 0, 0, 0])]
+        );
+        assert_eq!(
+            timeline.to_csv(),
+            "year,Austria,England,France,Germany,Italy,Russia,Turkey\n\
+             start,0,0,1,0,0,0,0\n\
+             1901,0,0,2,0,0,0,0\n\
+             1902,0,0,2,0,0,0,0\n"
+        );
+        let json = crate::json::parse(&timeline.to_json()).unwrap();
+        assert_eq!(
+            json.get("1901").and_then(|year| year.get("France")),
+            Some(&Value::Number(2.0))
+        );
+    }
+}
@@ -0,0 +1,636 @@
+//! The results format of the classic email judges (nJudge and USDP), as
+//! in
+//!
+//! ```text
+//! Movement results for Spring of 1901.
+//!
+//! France: Army  Paris -> Burgundy.  (*bounce*)
+//! Germany: Army  Munich -> Burgundy.  (*bounce*)
+//! ```
+//!
+//! Retreat and adjustment results are written and read the same way,
+//! as in `France: Fleet Brest -> Gascony.` and `France: Builds an army in
+//! Paris.`
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use crate::adjudicator::{
+    AdjudicationResult, Adjustment, AdjustmentResult, OrderStatus, Resolution,
+};
+use crate::history::{GameOver, History, Turn};
+use crate::map::{Coast, Map, Province};
+use crate::order::{Order, SupportedOrder, UnitType};
+use crate::parser::{parse_adjustment, parse_order};
+use crate::phase::{Phase, PhaseKind, Season};
+use crate::power::Power;
+use crate::state::GameState;
+
+/// Something in a judge results file that could not be understood.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JudgeError {
+    line: usize,
+    message: String,
+}
+
+impl JudgeError {
+    /// The 1-based line number of the problem.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for JudgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for JudgeError {}
+
+/// Writes the results of one phase.
+pub fn write_results(turn: &Turn) -> String {
+    let result = turn.result();
+    let phase = turn.phase();
+    let mut out = format!(
+        "{} results for {} of {}.\n\n",
+        kind_name(phase.kind),
+        phase.season,
+        phase.year
+    );
+    if let Some(adjustments) = turn.adjustment_result() {
+        write_adjustments(&mut out, adjustments);
+        return out;
+    }
+
+    let mut resolutions: Vec<_> = result.resolutions().collect();
+    resolutions.sort_by_key(|(province, r)| (r.power(), province.id()));
+    for (_, resolution) in &resolutions {
+        let order = match resolution.status() {
+            OrderStatus::Illegal(_) => resolution.submitted().unwrap_or(resolution.order()),
+            _ => resolution.order(),
+        };
+        if phase.kind == PhaseKind::Retreats && !order.is_move() {
+            write!(
+                out,
+                "{}: {:<5} {} DISBAND.",
+                resolution.power(),
+                unit_name(resolution.unit()),
+                order.province()
+            )
+            .unwrap();
+        } else {
+            write!(
+                out,
+                "{}: {}.",
+                resolution.power(),
+                JudgeOrder {
+                    order,
+                    unit: resolution.unit(),
+                    result,
+                }
+            )
+            .unwrap();
+        }
+        let notes = notes(resolution, result);
+        if !notes.is_empty() {
+            write!(out, "  (*{}*)", notes.join(", ")).unwrap();
+        }
+        out.push('\n');
+    }
+
+    let position = turn.position();
+    let mut dislodged: Vec<_> = position.dislodged().collect();
+    dislodged.sort_by_key(|(province, _)| province.id());
+    if !dislodged.is_empty() {
+        out.push_str("\nThe following units were dislodged:\n\n");
+        for (province, unit) in dislodged {
+            write!(
+                out,
+                "The {} {} in {}",
+                unit.power.adjective(),
+                unit_name(unit.unit_type),
+                province
+            )
+            .unwrap();
+            let retreats: Vec<String> = position
+                .retreats(province)
+                .iter()
+                .map(|&(province, coast)| location(province, coast))
+                .collect();
+            match retreats.split_last() {
+                None => out.push_str(" with no valid retreats was destroyed.\n"),
+                Some((last, [])) => writeln!(out, " can retreat to {}.", last).unwrap(),
+                Some((last, rest)) => {
+                    writeln!(out, " can retreat to {} or {}.", rest.join(", "), last).unwrap()
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Writes the builds, disbands and removals of an adjustment phase.
+fn write_adjustments(out: &mut String, result: &AdjustmentResult) {
+    let mut lines = Vec::new();
+    for (verb, units) in [
+        ("Builds", result.builds()),
+        ("Removes", result.disbands()),
+        ("Defaults, removing", result.removals()),
+    ] {
+        for (province, unit) in units {
+            lines.push((unit.power, province.id(), verb, *province, unit));
+        }
+    }
+    lines.sort_by_key(|&(power, id, ..)| (power, id));
+    for (power, _, verb, province, unit) in lines {
+        let (article, unit_type) = match (verb, unit.unit_type) {
+            ("Builds", UnitType::Army) => ("an", "army"),
+            ("Builds", UnitType::Fleet) => ("a", "fleet"),
+            (_, UnitType::Army) => ("the", "army"),
+            (_, UnitType::Fleet) => ("the", "fleet"),
+        };
+        writeln!(
+            out,
+            "{}: {} {} {} in {}.",
+            power,
+            verb,
+            article,
+            unit_type,
+            location(province, unit.coast)
+        )
+        .unwrap();
+    }
+}
+
+/// Writes the results of every phase of a game, one after another,
+/// leaving out adjustment phases in which nothing was built or removed.
+pub fn export(history: &History) -> String {
+    history
+        .turns()
+        .iter()
+        .filter(|turn| {
+            turn.adjustment_result().is_none_or(|result| {
+                !(result.builds().is_empty()
+                    && result.disbands().is_empty()
+                    && result.removals().is_empty())
+            })
+        })
+        .map(write_results)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Reads the orders of each movement and retreat phase in a judge
+/// results file.
+///
+/// Outcome annotations like `(*bounce*)` and the lists of dislodged units
+/// are ignored, since they follow from the orders, as are retreat results
+/// that disband a unit, since a unit not retreated is disbanded.
+#[allow(clippy::type_complexity)]
+pub fn parse_results<'m>(
+    map: &'m Map,
+    text: &str,
+) -> Result<Vec<(Phase, HashMap<Power, Vec<Order<'m>>>)>, JudgeError> {
+    Ok(read_results(map, text)?
+        .into_iter()
+        .filter_map(|(phase, _, results)| match results {
+            PhaseResults::Orders(orders) => Some((phase, orders)),
+            PhaseResults::Adjustments(_) => None,
+        })
+        .collect())
+}
+
+/// Plays the phases of a judge results file from `start`.
+///
+/// Each phase's orders and adjustments are adjudicated afresh, so the
+/// file's own outcome annotations are not needed. The phases must follow
+/// on from `start`. A retreat or adjustment phase the file has no results
+/// for is played without orders, so dislodged units are disbanded and no
+/// units are built. There must be no results after the phase that ends
+/// the game.
+///
+/// An order or adjustment the adjudicator rejects, such as one for a unit
+/// that is not there, fails the import, since the game played would no
+/// longer be the one the file records.
+pub fn import<'m>(start: GameState<'m>, text: &str) -> Result<History<'m>, JudgeError> {
+    let map = start.map();
+    let mut history = History::new(start);
+    for (phase, line, results) in read_results(map, text)? {
+        let error = |message: String| JudgeError { line, message };
+        let game_over = |e: GameOver| error(e.to_string());
+        while history.current().phase() != phase
+            && history.current().phase().kind != PhaseKind::Movement
+        {
+            history.play(HashMap::new()).map_err(game_over)?;
+        }
+        let expected = history.current().phase();
+        if phase != expected {
+            return Err(error(format!(
+                "expected results for {}, not {}",
+                expected, phase
+            )));
+        }
+        let turn = match results {
+            PhaseResults::Orders(orders) => history.play(orders),
+            PhaseResults::Adjustments(adjustments) => history.play_adjustments(adjustments),
+        }
+        .map_err(game_over)?;
+        if let Some(rejected) = turn.result().rejected().first() {
+            return Err(error(format!(
+                "{}'s order {} was rejected: {}",
+                rejected.power, rejected.order, rejected.reason
+            )));
+        }
+        if let Some(rejected) = turn.adjustment_result().and_then(|r| r.rejected().first()) {
+            return Err(error(format!(
+                "{}'s adjustment {} was rejected: {}",
+                rejected.power, rejected.adjustment, rejected.reason
+            )));
+        }
+    }
+    Ok(history)
+}
+
+/// The orders or adjustments of one phase in a judge results file.
+enum PhaseResults<'m> {
+    Orders(HashMap<Power, Vec<Order<'m>>>),
+    Adjustments(HashMap<Power, Vec<Adjustment<'m>>>),
+}
+
+/// Reads every phase in a judge results file, with the line number of its
+/// header.
+fn read_results<'m>(
+    map: &'m Map,
+    text: &str,
+) -> Result<Vec<(Phase, usize, PhaseResults<'m>)>, JudgeError> {
+    let mut phases = Vec::new();
+    let mut in_results = false;
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: String| JudgeError {
+            line: i + 1,
+            message,
+        };
+        let line = line.trim();
+        if let Some(header) = results_header(line) {
+            in_results = match header {
+                Some(phase) => {
+                    let results = match phase.kind {
+                        PhaseKind::Adjustments => PhaseResults::Adjustments(HashMap::new()),
+                        _ => PhaseResults::Orders(HashMap::new()),
+                    };
+                    phases.push((phase, i + 1, results));
+                    true
+                }
+                None => false,
+            };
+            continue;
+        }
+        if !in_results {
+            continue;
+        }
+        let (power, text) = match line.split_once(':') {
+            Some((power, text)) => match power.trim().parse::<Power>() {
+                Ok(power) => (power, strip_annotations(text)),
+                Err(_) => continue,
+            },
+            None => continue,
+        };
+        let (_, _, results) = phases.last_mut().expect("a phase has begun");
+        match results {
+            PhaseResults::Orders(orders) => {
+                if text.ends_with(" DISBAND") {
+                    continue;
+                }
+                let order = strip_origin_coast(text);
+                let order = parse_order(map, &order).map_err(|e| error(e.to_string()))?;
+                orders.entry(power).or_insert_with(Vec::new).push(order);
+            }
+            PhaseResults::Adjustments(adjustments) => {
+                let adjustment = match adjustment_text(text) {
+                    Some(adjustment) => adjustment,
+                    None => continue,
+                };
+                let adjustment =
+                    parse_adjustment(map, &adjustment).map_err(|e| error(e.to_string()))?;
+                adjustments
+                    .entry(power)
+                    .or_insert_with(Vec::new)
+                    .push(adjustment);
+            }
+        }
+    }
+    Ok(phases)
+}
+
+/// Reads a line like "Movement results for Spring of 1901", giving the
+/// phase for movement, retreat and adjustment results and `None` for
+/// other results.
+fn results_header(line: &str) -> Option<Option<Phase>> {
+    let (kind, when) = line.split_once(" results for ")?;
+    let when = when
+        .split("  ")
+        .next()
+        .unwrap_or(when)
+        .trim_end_matches('.');
+    let (season, year) = when.split_once(" of ")?;
+    let year = year.trim().parse().ok()?;
+    let season = match season.trim() {
+        "Spring" => Season::Spring,
+        "Fall" | "Autumn" => Season::Fall,
+        "Winter" => Season::Winter,
+        _ => return None,
+    };
+    let kind = match kind.trim() {
+        "Movement" => PhaseKind::Movement,
+        "Retreat" => PhaseKind::Retreats,
+        "Adjustment" => PhaseKind::Adjustments,
+        _ => return Some(None),
+    };
+    Some(Some(Phase::new(season, year, kind)))
+}
+
+/// The word the judges head a phase's results with.
+fn kind_name(kind: PhaseKind) -> &'static str {
+    match kind {
+        PhaseKind::Movement => "Movement",
+        PhaseKind::Retreats => "Retreat",
+        PhaseKind::Adjustments => "Adjustment",
+    }
+}
+
+/// Rewrites an adjustment as the judges write it, like "Builds an army in
+/// Paris" or "Removes the fleet in Kiel", the way the crate's grammar
+/// reads it. Lines that make no adjustment, like "Build waived", give
+/// `None`, as do the removals a power made by default, which the
+/// adjudicator makes again.
+fn adjustment_text(text: &str) -> Option<String> {
+    if let Some(build) = text.strip_prefix("Builds ") {
+        let (unit, place) = build.split_once(" in ")?;
+        let unit = match unit {
+            "an army" => "A",
+            "a fleet" => "F",
+            _ => return None,
+        };
+        return Some(format!("Build {} {}", unit, place));
+    }
+    let place = text.strip_prefix("Removes ")?;
+    let place = ["the army in ", "the fleet in "]
+        .iter()
+        .find_map(|unit| place.strip_prefix(unit))
+        .unwrap_or(place);
+    Some(format!("Disband {}", place))
+}
+
+/// Removes outcome annotations like "(*bounce*)" and the closing period.
+fn strip_annotations(order: &str) -> &str {
+    let order = order.split("(*").next().unwrap_or(order).trim();
+    order.strip_suffix('.').unwrap_or(order).trim()
+}
+
+/// Removes the coast of the ordered unit, as in "Fleet Spain (north
+/// coast) -> Gulf of Lyon", which orders do not record.
+fn strip_origin_coast(order: &str) -> String {
+    let verb = [" -> ", " SUPPORT ", " CONVOY ", " HOLD"]
+        .iter()
+        .filter_map(|verb| order.find(verb))
+        .min()
+        .unwrap_or(order.len());
+    let (unit, rest) = order.split_at(verb);
+    let unit = match unit.trim_end().strip_suffix(')') {
+        Some(unit) => unit.rsplit_once('(').map_or(unit, |(unit, _)| unit),
+        None => unit,
+    };
+    format!("{}{}", unit.trim_end(), rest)
+}
+
+/// The judges' notes on how an order fared, like "bounce" or "cut".
+pub(crate) fn notes(resolution: &Resolution, result: &AdjudicationResult) -> Vec<&'static str> {
+    let mut notes = Vec::new();
+    match (resolution.status(), resolution.order()) {
+        (OrderStatus::Illegal(_), _) => notes.push("void"),
+        (_, Order::Move(_)) if !resolution.succeeded() => notes.push("bounce"),
+        (_, Order::Support(support)) if !resolution.succeeded() => {
+            let supported = result.get(support.from()).map(Resolution::order);
+            let matched = match (support.to(), supported) {
+                (None, Some(order)) => !order.is_move(),
+                (Some(to), Some(order)) => order.destination() == Some(to),
+                (_, None) => false,
+            };
+            notes.push(if matched { "cut" } else { "void" });
+        }
+        _ => {}
+    }
+    if resolution.is_dislodged() {
+        notes.push("dislodged");
+    }
+    notes
+}
+
+fn unit_name(unit: UnitType) -> &'static str {
+    match unit {
+        UnitType::Army => "Army",
+        UnitType::Fleet => "Fleet",
+    }
+}
+
+pub(crate) fn location(province: &Province, coast: Option<Coast>) -> String {
+    match coast {
+        Some(coast) => format!("{} ({})", province, coast.name()),
+        None => province.to_string(),
+    }
+}
+
+/// An order written the way the judges write them.
+struct JudgeOrder<'r, 'm> {
+    order: &'r Order<'m>,
+    /// The type of the ordered unit, which the order may not name.
+    unit: UnitType,
+    /// For the types of supported and convoyed units.
+    result: &'r AdjudicationResult<'m>,
+}
+
+impl<'r, 'm> JudgeOrder<'r, 'm> {
+    fn unit_at(&self, unit: Option<UnitType>, province: &Province) -> &'static str {
+        let unit = unit
+            .or_else(|| self.result.get(province).map(Resolution::unit))
+            .unwrap_or(UnitType::Army);
+        unit_name(unit)
+    }
+}
+
+impl<'r, 'm> fmt::Display for JudgeOrder<'r, 'm> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:<5} {}", unit_name(self.unit), self.order.province())?;
+        match self.order {
+            Order::Hold(_) => f.write_str(" HOLD"),
+            Order::Move(move_) => write!(f, " -> {}", location(move_.destination(), move_.coast())),
+            Order::Support(support) => {
+                let supported = support.supported();
+                write!(
+                    f,
+                    " SUPPORT {} {}",
+                    self.unit_at(supported.unit(), supported.province()),
+                    supported.province()
+                )?;
+                match supported {
+                    SupportedOrder::Hold(_) => Ok(()),
+                    SupportedOrder::Move(move_) => {
+                        write!(f, " -> {}", location(move_.destination(), move_.coast()))
+                    }
+                }
+            }
+            Order::Convoy(convoy) => write!(f, " CONVOY Army {} -> {}", convoy.from(), convoy.to()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Unit;
+
+    const RESULTS: &str = "\
+Movement results for Spring of 1901.  (example)
+
+England: Fleet London -> English Channel.
+England: Fleet Irish Sea -> Mid-Atlantic Ocean.
+France: Army  Paris -> Burgundy.  (*bounce*)
+France: Fleet Brest SUPPORT Fleet Gascony.  (*void*)
+Germany: Army  Munich -> Burgundy.  (*bounce*)
+
+Movement results for Fall of 1901.
+
+England: Fleet English Channel -> Brest.
+England: Fleet Mid-Atlantic Ocean SUPPORT Fleet English Channel -> Brest.
+France: Army  Paris -> Burgundy.
+France: Fleet Brest HOLD.  (*dislodged*)
+Germany: Army  Munich -> Picardy.  (*void*)
+
+The following units were dislodged:
+
+The French Fleet in Brest can retreat to Gascony or Picardy.
+";
+
+    fn start() -> GameState<'static> {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut state = GameState::new(map);
+        state.place_unit(province("Lon"), Unit::fleet(Power::England));
+        state.place_unit(province("Iri"), Unit::fleet(Power::England));
+        state.place_unit(province("Par"), Unit::army(Power::France));
+        state.place_unit(province("Bre"), Unit::fleet(Power::France));
+        state.place_unit(province("Mun"), Unit::army(Power::Germany));
+        state
+    }
+
+    #[test]
+    fn import_and_export() {
+        let history = import(start(), RESULTS).unwrap();
+        assert_eq!(history.turns().len(), 2);
+        assert_eq!(
+            export(&history),
+            "\
+Movement results for Spring of 1901.
+
+England: Fleet Irish Sea -> Mid-Atlantic Ocean.
+England: Fleet London -> English Channel.
+France: Fleet Brest SUPPORT Fleet Gascony.  (*void*)
+France: Army  Paris -> Burgundy.  (*bounce*)
+Germany: Army  Munich -> Burgundy.  (*bounce*)
+
+Movement results for Fall of 1901.
+
+England: Fleet English Channel -> Brest.
+England: Fleet Mid-Atlantic Ocean SUPPORT Fleet English Channel -> Brest.
+France: Fleet Brest HOLD.  (*dislodged*)
+France: Army  Paris -> Burgundy.
+Germany: Army  Munich -> Picardy.  (*void*)
+
+The following units were dislodged:
+
+The French Fleet in Brest can retreat to Gascony or Picardy.
+"
+        );
+    }
+
+    #[test]
+    fn phases_must_follow_on() {
+        let text = RESULTS.replace("Fall of 1901", "Spring of 1902");
+        let error = import(start(), &text).unwrap_err();
+        assert_eq!(error.line(), 9);
+    }
+
+    const TWO_YEARS: &str = "\
+Movement results for Spring of 1901.
+
+France: Army  Paris -> Burgundy.
+France: Army  Marseilles -> Spain.
+France: Fleet Brest -> Mid-Atlantic Ocean.
+Germany: Army  Munich -> Ruhr.
+Germany: Army  Berlin -> Munich.
+
+Movement results for Fall of 1901.
+
+France: Army  Burgundy HOLD.  (*dislodged*)
+France: Fleet Mid-Atlantic Ocean -> Portugal.
+Germany: Army  Ruhr -> Burgundy.
+Germany: Army  Munich SUPPORT Army Ruhr -> Burgundy.
+
+Retreat results for Fall of 1901.
+
+France: Army  Burgundy -> Picardy.
+
+Adjustment results for Winter of 1901.
+
+France: Builds an army in Paris.
+France: Builds a fleet in Brest.
+
+Movement results for Spring of 1902.
+
+France: Army  Paris -> Gascony.
+France: Fleet Brest -> English Channel.
+";
+
+    #[test]
+    fn retreats_and_adjustments() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let history = import(GameState::standard(), TWO_YEARS).unwrap();
+        assert_eq!(history.turns().len(), 5);
+        let current = history.current();
+        assert_eq!(
+            current.phase(),
+            Phase::new(Season::Fall, 1902, PhaseKind::Movement)
+        );
+        for (abbreviation, unit) in [
+            ("Pic", Unit::army(Power::France)),
+            ("Gas", Unit::army(Power::France)),
+            ("Eng", Unit::fleet(Power::France)),
+            ("Bur", Unit::army(Power::Germany)),
+        ] {
+            assert_eq!(current.unit_at(province(abbreviation)), Some(&unit));
+        }
+        let exported = export(&history);
+        assert!(exported
+            .contains("Retreat results for Fall of 1901.\n\nFrance: Army  Burgundy -> Picardy.\n"));
+        assert!(exported.contains(
+            "Adjustment results for Winter of 1901.\n\n\
+             France: Builds a fleet in Brest.\nFrance: Builds an army in Paris.\n"
+        ));
+        assert_eq!(
+            export(&import(GameState::standard(), &exported).unwrap()),
+            exported
+        );
+
+        // Without the builds, the Spring 1902 orders are for units that
+        // are not there.
+        let text = TWO_YEARS.replace("France: Builds", "France: Build waived.");
+        let error = import(GameState::standard(), &text).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 25: France's order A Paris - Gascony was rejected: there is no unit there"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! A Diplomacy engine: maps, powers, orders, an order parser, an
+//! adjudicator, and game histories.
+//!
+//! Without the default `std` feature, only the maps, powers, phases,
+//! orders and the order parser are available, built on `core` and `alloc`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+// First, so that its macros are in scope in every other module.
+#[macro_use]
+mod trace;
+
+#[cfg(feature = "std")]
+pub mod adjudicator;
+#[cfg(feature = "std")]
+pub mod archive;
+#[cfg(feature = "std")]
+pub mod bot;
+pub mod combinators;
+#[cfg(feature = "std")]
+pub mod complete;
+#[cfg(feature = "std")]
+pub mod extension;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod gunboat;
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(feature = "std")]
+mod json;
+#[cfg(feature = "std")]
+pub mod judge;
+pub mod map;
+pub mod order;
+pub mod parser;
+#[cfg(feature = "std")]
+pub mod perft;
+pub mod phase;
+pub mod power;
+#[cfg(feature = "std")]
+pub mod press;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(feature = "render")]
+pub mod render;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+mod rng;
+#[cfg(feature = "std")]
+pub mod schedule;
+#[cfg(feature = "std")]
+pub mod spreadsheet;
+#[cfg(feature = "std")]
+pub mod state;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod submission;
+#[cfg(feature = "std")]
+pub mod terminal;
+#[cfg(feature = "std")]
+pub mod vote;
+#[cfg(feature = "std")]
+pub mod webdip;
+
+/// What the standard prelude would provide, for modules that also build
+/// without it.
+mod prelude {
+    pub(crate) use alloc::borrow::ToOwned;
+    pub(crate) use alloc::boxed::Box;
+    pub(crate) use alloc::string::String;
+    pub(crate) use alloc::vec::Vec;
+    pub(crate) use alloc::{format, vec};
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::combinators::{self, Parser};
+    use crate::map::{self, Map};
+    use crate::{adjudicator, order, parser, power, state, submission};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    fn assert_parser_send_sync<'a, P>(_: P)
+    where
+        P: Parser<'a> + Send + Sync,
+        P::Iter: Send + Sync,
+    {
+    }
+
+    #[test]
+    fn public_types_are_send_and_sync() {
+        assert_send_sync::<Map>();
+        assert_send_sync::<map::Province>();
+        assert_send_sync::<map::ProvinceSet>();
+        assert_send_sync::<map::MapError>();
+        assert_send_sync::<power::Power>();
+        assert_send_sync::<order::Order<'static>>();
+        assert_send_sync::<order::OrderError>();
+        assert_send_sync::<parser::ParseError<'static>>();
+        assert_send_sync::<parser::OwnedParseError>();
+        assert_send_sync::<parser::ParseProvince<'static>>();
+        assert_send_sync::<parser::Aliases>();
+        assert_send_sync::<combinators::Boxed<'static, 'static, order::Order<'static>>>();
+        assert_send_sync::<combinators::Choice<combinators::Boxed<'static, 'static, ()>>>();
+        assert_send_sync::<state::GameState<'static>>();
+        assert_send_sync::<submission::OrderBox<'static>>();
+        assert_send_sync::<adjudicator::AdjudicationResult<'static>>();
+    }
+
+    #[test]
+    fn combinators_are_send_and_sync() {
+        use combinators::{
+            alt, any_tag, chain, char_where, choice, complete, delimited, either, eof, memo, not,
+            one_of, optional, peek, preceded, skip_whitespace, tag, take_while1, terminated,
+            whitespace,
+        };
+
+        assert_parser_send_sync(tag("a"));
+        assert_parser_send_sync(chain(
+            optional(either(any_tag(&["a", "b"]), whitespace())),
+            peek(not(skip_whitespace())),
+        ));
+        assert_parser_send_sync(
+            tag("a")
+                .map(str::len)
+                .filter(|_, rest| rest.is_empty())
+                .named("a"),
+        );
+        assert_parser_send_sync(chain(
+            take_while1(char::is_alphabetic),
+            either(one_of("-"), char_where(char::is_numeric)),
+        ));
+        assert_parser_send_sync(complete(chain(tag("a"), eof())));
+        assert_parser_send_sync(alt((tag("a"), tag("b"), any_tag(&["c"]))));
+        assert_parser_send_sync(delimited(
+            tag("("),
+            preceded(whitespace(), terminated(tag("a"), whitespace())),
+            tag(")"),
+        ));
+        assert_parser_send_sync(parser::ParseProvince::new(Map::standard()));
+        assert_parser_send_sync(chain(
+            memo(tag("a")),
+            memo(parser::ParseProvince::new(Map::standard())),
+        ));
+        assert_parser_send_sync(choice(vec![tag("a"), tag("b")]));
+    }
+}
@@ -1,3 +1,112 @@
+//! The `dipboy` command: parse orders, adjudicate positions, and start new
+//! games from the command line.
+
+use std::collections::HashMap;
+use std::fs;
+#[cfg(feature = "colored")]
+use std::io::IsTerminal;
+use std::process;
+
+use dipboy::history::History;
+use dipboy::judge;
+use dipboy::map::Map;
+use dipboy::order::Order;
+use dipboy::parser::parse_order;
+use dipboy::power::Power;
+use dipboy::state::GameState;
+use dipboy::terminal;
+
+const USAGE: &str = "\
+usage:
+    dipboy parse ORDER...
+        Print each order in full, or why it could not be read.
+    dipboy adjudicate STATE.json ORDERS.txt
+        Adjudicate the orders, one per line as \"Power: order\", against the
+        state, and print the results.
+    dipboy show STATE.json
+        Print the units and supply centers of the state, power by power
+        and region by region.
+    dipboy new-game
+        Print the standard starting position as JSON.";
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("parse") if args.len() > 1 => parse(&args[1..]),
+        Some("adjudicate") if args.len() == 3 => adjudicate(&args[1], &args[2]),
+        Some("show") if args.len() == 2 => show(&args[1]),
+        Some("new-game") if args.len() == 1 => {
+            println!("{}", GameState::standard().to_json());
+            Ok(())
+        }
+        _ => Err(USAGE.to_owned()),
+    };
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn parse(orders: &[String]) -> Result<(), String> {
+    let mut failed = false;
+    for order in orders {
+        match parse_order(Map::standard(), order) {
+            Ok(order) => println!("{}", order),
+            Err(e) => {
+                println!("error: {}", e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        Err("some orders could not be read".to_owned())
+    } else {
+        Ok(())
+    }
+}
+
+fn adjudicate(state_path: &str, orders_path: &str) -> Result<(), String> {
+    let read = |path: &str| fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e));
+    let state = GameState::from_json(Map::standard(), &read(state_path)?)
+        .map_err(|e| format!("{}: {}", state_path, e))?;
+    let text = read(orders_path)?;
+    let orders = read_orders(&text).map_err(|e| format!("{}: {}", orders_path, e))?;
+
+    let mut history = History::new(state);
+    let turn = history.play(orders).map_err(|e| e.to_string())?;
+    print!("{}", judge::write_results(turn));
+    Ok(())
+}
+
+fn show(state_path: &str) -> Result<(), String> {
+    let text = fs::read_to_string(state_path).map_err(|e| format!("{}: {}", state_path, e))?;
+    let state = GameState::from_json(Map::standard(), &text)
+        .map_err(|e| format!("{}: {}", state_path, e))?;
+    #[cfg(feature = "colored")]
+    if std::io::stdout().is_terminal() {
+        print!("{}", terminal::coloured_summary(&state));
+        return Ok(());
+    }
+    print!("{}", terminal::summary(&state));
+    Ok(())
+}
+
+/// Reads orders written one per line as "Power: order", skipping blank
+/// lines and lines starting with '#'.
+fn read_orders(text: &str) -> Result<HashMap<Power, Vec<Order<'static>>>, String> {
+    let mut orders: HashMap<Power, Vec<Order>> = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| format!("line {}: {}", i + 1, message);
+        let (power, order) = line
+            .split_once(':')
+            .ok_or_else(|| error("expected \"Power: order\"".to_owned()))?;
+        let power: Power = power.trim().parse().map_err(|e| error(format!("{}", e)))?;
+        let order = parse_order(Map::standard(), order).map_err(|e| error(format!("{}", e)))?;
+        orders.entry(power).or_default().push(order);
+    }
+    Ok(orders)
 }
@@ -0,0 +1,50 @@
+//! Approximate matching of misspelled province names.
+
+use super::unicode::{fold, is_word_char};
+use crate::prelude::*;
+
+/// The similarity of `a` and `b`, from 0 (nothing in common) to 1
+/// (identical, ignoring case and accents), based on their edit distance.
+pub(crate) fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().flat_map(fold).collect();
+    let b: Vec<char> = b.chars().flat_map(fold).collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        core::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// The byte lengths of the first one, two and three words of `input`.
+pub(crate) fn word_spans(input: &str) -> Vec<usize> {
+    let mut spans = Vec::new();
+    let mut in_word = false;
+    for (i, c) in input.char_indices() {
+        let word_char = is_word_char(c) || c == '.' || c == '\'';
+        if in_word && !word_char {
+            spans.push(i);
+            if spans.len() == 3 || c != ' ' {
+                return spans;
+            }
+        }
+        in_word = word_char;
+    }
+    if in_word {
+        spans.push(input.len());
+    }
+    spans
+}
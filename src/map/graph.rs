@@ -0,0 +1,236 @@
+//! The map's adjacencies as a graph, for drawing with standard graph tools.
+
+use core::fmt::Write;
+
+use super::{Coast, Map, ProvinceKind};
+use crate::order::UnitType;
+use crate::prelude::*;
+
+/// One move a unit can make: the unit type, and the province and coast
+/// it moves from and to, by province id.
+type Edge = (UnitType, usize, Option<Coast>, usize, Option<Coast>);
+
+impl Map {
+    /// The adjacencies armies or fleets can move along, or both if `units`
+    /// is `None`, as a Graphviz DOT graph.
+    ///
+    /// Each province is a node named by its abbreviation: land provinces
+    /// are boxes, coastal ones ellipses and seas blue, with supply centers
+    /// drawn double. A pair of moves between two places is one edge, solid
+    /// for armies and dashed for fleets, labelled with any coasts at its
+    /// ends; a move with no move back is an arrow, which on most maps is
+    /// a mistake in the map's data.
+    pub fn to_dot(&self, units: Option<UnitType>) -> String {
+        let mut dot = String::from("graph map {\n");
+        for province in self.provinces.iter() {
+            let shape = match province.kind {
+                ProvinceKind::Land => "box",
+                ProvinceKind::Coast | ProvinceKind::Sea => "ellipse",
+            };
+            let _ = write!(
+                dot,
+                "  \"{}\" [label=\"{}\", shape={}",
+                dot_escape(&province.abbreviation),
+                dot_escape(&province.name),
+                shape
+            );
+            if province.kind == ProvinceKind::Sea {
+                dot.push_str(", color=blue");
+            }
+            if province.sc {
+                dot.push_str(", peripheries=2");
+            }
+            dot.push_str("];\n");
+        }
+        let edges = self.edges(units);
+        for &edge in &edges {
+            let (unit, from, from_coast, to, to_coast) = edge;
+            let reverse = (unit, to, to_coast, from, from_coast);
+            let two_way = edges.contains(&reverse);
+            if two_way && (to, to_coast) < (from, from_coast) {
+                continue;
+            }
+            let _ = write!(
+                dot,
+                "  \"{}\" -- \"{}\" [style={}",
+                dot_escape(&self.provinces[from].abbreviation),
+                dot_escape(&self.provinces[to].abbreviation),
+                match unit {
+                    UnitType::Army => "solid",
+                    UnitType::Fleet => "dashed",
+                }
+            );
+            if let Some(coast) = from_coast {
+                let _ = write!(dot, ", taillabel=\"{}\"", coast);
+            }
+            if let Some(coast) = to_coast {
+                let _ = write!(dot, ", headlabel=\"{}\"", coast);
+            }
+            if !two_way {
+                dot.push_str(", dir=forward");
+            }
+            dot.push_str("];\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The adjacencies armies or fleets can move along, or both if `units`
+    /// is `None`, as a GraphML document.
+    ///
+    /// Nodes have the province's name, kind and whether it is a supply
+    /// center; every move is its own directed edge, with the unit type and
+    /// any coasts it leaves and arrives on.
+    pub fn to_graphml(&self, units: Option<UnitType>) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+             \x20 <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"sc\" for=\"node\" attr.name=\"supply center\" attr.type=\"boolean\"/>\n\
+             \x20 <key id=\"unit\" for=\"edge\" attr.name=\"unit\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"from_coast\" for=\"edge\" attr.name=\"from coast\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"to_coast\" for=\"edge\" attr.name=\"to coast\" attr.type=\"string\"/>\n\
+             \x20 <graph id=\"map\" edgedefault=\"directed\">\n",
+        );
+        for province in self.provinces.iter() {
+            let kind = match province.kind {
+                ProvinceKind::Land => "land",
+                ProvinceKind::Coast => "coast",
+                ProvinceKind::Sea => "sea",
+            };
+            let _ = write!(
+                xml,
+                "    <node id=\"{}\">\n\
+                 \x20     <data key=\"name\">{}</data>\n\
+                 \x20     <data key=\"kind\">{}</data>\n\
+                 \x20     <data key=\"sc\">{}</data>\n\
+                 \x20   </node>\n",
+                xml_escape(&province.abbreviation),
+                xml_escape(&province.name),
+                kind,
+                province.sc
+            );
+        }
+        for (unit, from, from_coast, to, to_coast) in self.edges(units) {
+            let _ = write!(
+                xml,
+                "    <edge source=\"{}\" target=\"{}\">\n\
+                 \x20     <data key=\"unit\">{}</data>\n",
+                xml_escape(&self.provinces[from].abbreviation),
+                xml_escape(&self.provinces[to].abbreviation),
+                match unit {
+                    UnitType::Army => "army",
+                    UnitType::Fleet => "fleet",
+                }
+            );
+            if let Some(coast) = from_coast {
+                let _ = writeln!(xml, "      <data key=\"from_coast\">{}</data>", coast);
+            }
+            if let Some(coast) = to_coast {
+                let _ = writeln!(xml, "      <data key=\"to_coast\">{}</data>", coast);
+            }
+            xml.push_str("    </edge>\n");
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+
+    /// Every move of `units`, or of both unit types, in the order the map
+    /// defines them.
+    fn edges(&self, units: Option<UnitType>) -> Vec<Edge> {
+        let mut edges = Vec::new();
+        if units != Some(UnitType::Fleet) {
+            for (from, neighbours) in self.army.iter().enumerate() {
+                edges.extend(
+                    neighbours
+                        .iter()
+                        .map(|&to| (UnitType::Army, from, None, to, None)),
+                );
+            }
+        }
+        if units != Some(UnitType::Army) {
+            for (from, neighbours) in self.fleet.iter().enumerate() {
+                edges.extend(neighbours.iter().map(|edge| {
+                    (
+                        UnitType::Fleet,
+                        from,
+                        edge.from_coast,
+                        edge.to,
+                        edge.to_coast,
+                    )
+                }));
+            }
+        }
+        edges
+    }
+}
+
+/// `text` as the inside of a quoted DOT string.
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// `text` with the characters special in XML escaped.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("&quot;"),
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_and_graphml() {
+        let map = Map::parse(
+            "[provinces]\n\
+             Spa | Spain    | coast | neutral | nc sc |\n\
+             Por | Portugal | coast | neutral |       |\n\
+             Mao | Mid-Atlantic Ocean | sea |  |     |\n\
+             Gas | Gascony  | coast |         |       |\n\
+             [adjacencies]\n\
+             army Spa: Por Gas\n\
+             army Por: Spa\n\
+             army Gas: Spa\n\
+             fleet Spa/nc: Por Mao\n\
+             fleet Por: Spa/nc Mao\n\
+             fleet Mao: Spa/nc Por Gas\n",
+        )
+        .unwrap();
+
+        let armies = map.to_dot(Some(UnitType::Army));
+        assert!(armies.starts_with("graph map {\n"));
+        assert!(armies.contains("  \"Spa\" [label=\"Spain\", shape=ellipse, peripheries=2];\n"));
+        assert!(armies.contains("  \"Spa\" -- \"Por\" [style=solid];\n"));
+        assert!(!armies.contains("\"Por\" -- \"Spa\""));
+        assert!(!armies.contains("dashed"));
+
+        let both = map.to_dot(None);
+        assert!(
+            both.contains("  \"Mao\" [label=\"Mid-Atlantic Ocean\", shape=ellipse, color=blue];\n")
+        );
+        assert!(both.contains("  \"Spa\" -- \"Por\" [style=dashed, taillabel=\"nc\"];\n"));
+        // Gascony has no fleet move back to the Mid-Atlantic.
+        assert!(both.contains("  \"Mao\" -- \"Gas\" [style=dashed, dir=forward];\n"));
+
+        let graphml = map.to_graphml(Some(UnitType::Fleet));
+        assert_eq!(graphml.matches("<node ").count(), 4);
+        assert_eq!(graphml.matches("<edge ").count(), 7);
+        assert!(graphml.contains(
+            "    <edge source=\"Por\" target=\"Spa\">\n\
+             \x20     <data key=\"unit\">fleet</data>\n\
+             \x20     <data key=\"to_coast\">nc</data>\n\
+             \x20   </edge>\n"
+        ));
+    }
+}
@@ -0,0 +1,1051 @@
+//! Provinces and the maps they make up.
+
+use alloc::borrow::Cow;
+use alloc::collections::VecDeque;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+mod fuzzy;
+mod graph;
+mod set;
+pub(crate) mod trie;
+pub(crate) mod unicode;
+mod validate;
+
+use crate::order::UnitType;
+use crate::power::Power;
+use crate::prelude::*;
+pub use set::ProvinceSet;
+use trie::Trie;
+pub use validate::MapDiagnostic;
+
+/// Text a map owns if it was parsed at run time, or borrows from the
+/// standard map's tables, which `build.rs` compiles in.
+type Text = Cow<'static, str>;
+
+/// A list a map owns or borrows, as with [`Text`].
+type Table<T> = Cow<'static, [T]>;
+
+/// The terrain of a province, which determines the units that may enter it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProvinceKind {
+    /// An inland province; armies only.
+    Land,
+    /// A land province bordering the sea; armies and fleets.
+    Coast,
+    /// A body of water; fleets only.
+    Sea,
+}
+
+impl FromStr for ProvinceKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "land" => Ok(ProvinceKind::Land),
+            "coast" => Ok(ProvinceKind::Coast),
+            "sea" => Ok(ProvinceKind::Sea),
+            _ => Err(()),
+        }
+    }
+}
+
+/// One of the separately-navigable coasts of a province like Spain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Coast {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Coast {
+    /// The usual abbreviation, e.g. "nc".
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Coast::North => "nc",
+            Coast::South => "sc",
+            Coast::East => "ec",
+            Coast::West => "wc",
+        }
+    }
+
+    /// The name in full, e.g. "north coast".
+    pub fn name(self) -> &'static str {
+        match self {
+            Coast::North => "north coast",
+            Coast::South => "south coast",
+            Coast::East => "east coast",
+            Coast::West => "west coast",
+        }
+    }
+}
+
+impl fmt::Display for Coast {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.abbreviation())
+    }
+}
+
+impl FromStr for Coast {
+    type Err = ();
+
+    /// Accepts the abbreviations "nc", "sc", "ec" and "wc", ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Coast::North, Coast::South, Coast::East, Coast::West]
+            .iter()
+            .copied()
+            .find(|coast| coast.abbreviation().eq_ignore_ascii_case(s))
+            .ok_or(())
+    }
+}
+
+/// A single space on the board.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Province {
+    id: usize,
+    name: Text,
+    abbreviation: Text,
+    aliases: Table<Text>,
+    kind: ProvinceKind,
+    sc: bool,
+    home: Option<Power>,
+    coasts: Table<Coast>,
+    regions: Table<Text>,
+}
+
+impl Province {
+    /// The province's index among its map's provinces.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+
+    /// The full name, e.g. "St. Petersburg".
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The standard abbreviation, e.g. "StP".
+    pub fn abbreviation(&self) -> &str {
+        &self.abbreviation
+    }
+
+    pub fn kind(&self) -> ProvinceKind {
+        self.kind
+    }
+
+    /// Other names the province is known by, e.g. "St Petersburg".
+    pub fn aliases(&self) -> impl Iterator<Item = &str> {
+        self.aliases.iter().map(|alias| &**alias)
+    }
+
+    pub fn is_supply_center(&self) -> bool {
+        self.sc
+    }
+
+    /// The power for which this is a home supply center, if any.
+    pub fn home_power(&self) -> Option<Power> {
+        self.home
+    }
+
+    /// The separately-navigable coasts of the province; empty unless there
+    /// are at least two.
+    pub fn coasts(&self) -> &[Coast] {
+        &self.coasts
+    }
+
+    pub fn is_bicoastal(&self) -> bool {
+        !self.coasts.is_empty()
+    }
+
+    /// True for land provinces bordering the sea.
+    pub fn is_coastal(&self) -> bool {
+        self.kind == ProvinceKind::Coast
+    }
+
+    /// The regions the map puts the province in, e.g. "Balkans".
+    pub fn regions(&self) -> impl Iterator<Item = &str> {
+        self.regions.iter().map(|region| &**region)
+    }
+}
+
+impl fmt::Display for Province {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// A kind of unit a variant map adds to armies and fleets, like the
+/// garrisons of Machiavelli.
+///
+/// A unit of the kind moves, supports and convoys as the army or fleet it
+/// [moves as](UnitKind::moves_as) does, and orders name it as one, but it
+/// may be [fixed](UnitKind::is_mobile) in its province.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnitKind {
+    id: u8,
+    abbreviation: Text,
+    name: Text,
+    moves_as: UnitType,
+    mobile: bool,
+}
+
+impl UnitKind {
+    /// The kind's index among its map's unit kinds.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// The letter or short name units of the kind are written with, e.g.
+    /// "G".
+    pub fn abbreviation(&self) -> &str {
+        &self.abbreviation
+    }
+
+    /// The name in full, e.g. "Garrison".
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether units of the kind go where armies or fleets go.
+    pub fn moves_as(&self) -> UnitType {
+        self.moves_as
+    }
+
+    /// False for units that can hold and support but never move, and are
+    /// disbanded if dislodged.
+    pub fn is_mobile(&self) -> bool {
+        self.mobile
+    }
+}
+
+impl fmt::Display for UnitKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// A fleet move from one coast of a province to (a coast of) another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct FleetEdge {
+    from_coast: Option<Coast>,
+    to: usize,
+    to_coast: Option<Coast>,
+}
+
+/// A board: its provinces, how they connect, and the index used to
+/// recognize their names.
+#[derive(Clone, Debug)]
+pub struct Map {
+    provinces: Table<Province>,
+    /// Army moves out of each province, by province id.
+    army: Table<Table<usize>>,
+    /// Fleet moves out of each province, by province id.
+    fleet: Table<Table<FleetEdge>>,
+    /// Where each province is drawn, by province id.
+    positions: Table<Option<(u16, u16)>>,
+    /// The names of the regions, in the order the map defines them.
+    regions: Table<Text>,
+    /// The kinds of unit the map adds to armies and fleets.
+    unit_kinds: Table<UnitKind>,
+    matcher: Trie,
+}
+
+impl Map {
+    /// The definition of the standard map, for [`Map::parse`].
+    pub const STANDARD: &'static str = include_str!("standard.map");
+
+    /// German names for the standard map's provinces, for
+    /// [`Map::with_names`].
+    pub const STANDARD_GERMAN_NAMES: &'static str = include_str!("standard.de.names");
+
+    /// French names for the standard map's provinces, for
+    /// [`Map::with_names`].
+    pub const STANDARD_FRENCH_NAMES: &'static str = include_str!("standard.fr.names");
+
+    /// The most kinds of unit a map can add to armies and fleets.
+    pub const MAX_UNIT_KINDS: usize = 14;
+
+    /// The standard Diplomacy map.
+    ///
+    /// `build.rs` reads [`Map::STANDARD`] when the crate is compiled and
+    /// writes out its tables, so this costs nothing at run time and is the
+    /// same map [`Map::parse`] would make of it.
+    pub fn standard() -> &'static Map {
+        static STANDARD: Map = include!(concat!(env!("OUT_DIR"), "/standard_map.rs"));
+        &STANDARD
+    }
+
+    /// The map, with more names for its provinces, as a community writing
+    /// in another language knows them. `text` gives a province's
+    /// abbreviation, a bar, and its names separated by commas, a line at a
+    /// time:
+    ///
+    /// ```text
+    /// Mun | München
+    /// Nwg | Europäisches Nordmeer, Nordmeer
+    /// ```
+    ///
+    /// The names are recognized wherever the province's own are, ignoring
+    /// case and accents, though not as misspellings. The provinces are
+    /// otherwise unchanged, so orders and positions on the two maps
+    /// compare equal. Fails if a name already names another province.
+    pub fn with_names(&self, text: &str) -> Result<Map, MapError> {
+        let mut map = self.clone();
+        for (i, line) in text.lines().enumerate() {
+            let error = |message: String| MapError {
+                line: i + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (province, names) = line
+                .split_once('|')
+                .ok_or_else(|| error("expected a province, a bar, and names".to_owned()))?;
+            let (province, coast) =
+                parse_location(&map.provinces, province.trim()).map_err(error)?;
+            if coast.is_some() {
+                return Err(error("names are given for whole provinces".to_owned()));
+            }
+            for name in names.split(',').map(str::trim) {
+                if name.is_empty() {
+                    return Err(error("a name cannot be empty".to_owned()));
+                }
+                match map.matcher.get(name) {
+                    Some(named) if named == province => {}
+                    Some(named) => {
+                        return Err(error(format!(
+                            "{:?} already names {}",
+                            name, map.provinces[named].name
+                        )))
+                    }
+                    None => map.matcher.insert(name, province),
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    /// Reads a map definition.
+    ///
+    /// See `standard.map` for the format.
+    pub fn parse(text: &str) -> Result<Map, MapError> {
+        #[derive(PartialEq)]
+        enum Section {
+            None,
+            Provinces,
+            Adjacencies,
+            Positions,
+            Regions,
+            Units,
+        }
+
+        let mut provinces: Vec<Province> = Vec::new();
+        let mut adjacencies = Vec::new();
+        let mut positions = Vec::new();
+        let mut region_lines = Vec::new();
+        let mut unit_kinds: Vec<UnitKind> = Vec::new();
+        let mut section = Section::None;
+        for (i, line) in text.lines().enumerate() {
+            let error = |message: String| MapError {
+                line: i + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = match &line[1..line.len() - 1] {
+                    "provinces" => Section::Provinces,
+                    "adjacencies" => Section::Adjacencies,
+                    "positions" => Section::Positions,
+                    "regions" => Section::Regions,
+                    "units" => Section::Units,
+                    other => return Err(error(format!("unknown section {:?}", other))),
+                };
+                continue;
+            }
+            match section {
+                Section::None => return Err(error("expected a section header".to_owned())),
+                Section::Provinces => {
+                    let mut province = parse_province(line).map_err(error)?;
+                    if provinces
+                        .iter()
+                        .any(|p| p.abbreviation.eq_ignore_ascii_case(&province.abbreviation))
+                    {
+                        return Err(error(format!(
+                            "duplicate province {:?}",
+                            province.abbreviation
+                        )));
+                    }
+                    if provinces.len() == ProvinceSet::CAPACITY {
+                        return Err(error(format!(
+                            "a map has at most {} provinces",
+                            ProvinceSet::CAPACITY
+                        )));
+                    }
+                    province.id = provinces.len();
+                    provinces.push(province);
+                }
+                Section::Adjacencies => adjacencies.push((i + 1, line)),
+                Section::Positions => positions.push((i + 1, line)),
+                Section::Regions => region_lines.push((i + 1, line)),
+                Section::Units => {
+                    let mut kind = parse_unit_kind(line).map_err(error)?;
+                    let taken = ["A", "F"].contains(&kind.abbreviation.to_uppercase().as_str())
+                        || unit_kinds
+                            .iter()
+                            .any(|k| k.abbreviation.eq_ignore_ascii_case(&kind.abbreviation));
+                    if taken {
+                        return Err(error(format!(
+                            "duplicate unit kind {:?}",
+                            kind.abbreviation
+                        )));
+                    }
+                    if unit_kinds.len() == Map::MAX_UNIT_KINDS {
+                        return Err(error(format!(
+                            "a map has at most {} unit kinds",
+                            Map::MAX_UNIT_KINDS
+                        )));
+                    }
+                    kind.id = unit_kinds.len() as u8;
+                    unit_kinds.push(kind);
+                }
+            }
+        }
+
+        let mut army = vec![Vec::new(); provinces.len()];
+        let mut fleet = vec![Vec::new(); provinces.len()];
+        for (line, text) in adjacencies {
+            let error = |message: String| MapError { line, message };
+            let (head, neighbours) = text.split_once(':').ok_or_else(|| {
+                error("expected \"army\" or \"fleet\", a province and a colon".to_owned())
+            })?;
+            let mut head = head.split_whitespace();
+            let kind = head.next().unwrap_or("");
+            let (from, from_coast) =
+                parse_location(&provinces, head.next().unwrap_or("")).map_err(error)?;
+            for neighbour in neighbours.split_whitespace() {
+                let (to, to_coast) = parse_location(&provinces, neighbour).map_err(error)?;
+                match kind {
+                    "army" if from_coast.is_some() || to_coast.is_some() => {
+                        return Err(error("armies do not move between coasts".to_owned()))
+                    }
+                    "army" => army[from].push(to),
+                    "fleet" => {
+                        for (province, coast) in &[(from, from_coast), (to, to_coast)] {
+                            if provinces[*province].is_bicoastal() && coast.is_none() {
+                                return Err(error(format!(
+                                    "fleets in {} must be on a coast",
+                                    provinces[*province].abbreviation
+                                )));
+                            }
+                        }
+                        fleet[from].push(FleetEdge {
+                            from_coast,
+                            to,
+                            to_coast,
+                        });
+                    }
+                    other => return Err(error(format!("unknown unit kind {:?}", other))),
+                }
+            }
+        }
+
+        let mut coordinates = vec![None; provinces.len()];
+        for (line, text) in positions {
+            let error = |message: String| MapError { line, message };
+            let (province, xy) = text
+                .split_once(':')
+                .ok_or_else(|| error("expected a province, a colon, and x and y".to_owned()))?;
+            let (province, coast) = parse_location(&provinces, province.trim()).map_err(error)?;
+            if coast.is_some() {
+                return Err(error("positions are given for whole provinces".to_owned()));
+            }
+            let mut xy = xy.split_whitespace().map(str::parse);
+            coordinates[province] = match (xy.next(), xy.next(), xy.next()) {
+                (Some(Ok(x)), Some(Ok(y)), None) => Some((x, y)),
+                _ => return Err(error("expected x and y".to_owned())),
+            };
+        }
+
+        let mut regions: Vec<String> = Vec::new();
+        for (line, text) in region_lines {
+            let error = |message: String| MapError { line, message };
+            let (region, members) = text
+                .split_once(':')
+                .ok_or_else(|| error("expected a region, a colon, and provinces".to_owned()))?;
+            let region = region.trim();
+            if region.is_empty() {
+                return Err(error("expected a region name".to_owned()));
+            }
+            if regions.iter().any(|r| r.eq_ignore_ascii_case(region)) {
+                return Err(error(format!("duplicate region {:?}", region)));
+            }
+            for member in members.split_whitespace() {
+                let (province, coast) = parse_location(&provinces, member).map_err(error)?;
+                if coast.is_some() {
+                    return Err(error("regions are made of whole provinces".to_owned()));
+                }
+                provinces[province]
+                    .regions
+                    .to_mut()
+                    .push(region.to_owned().into());
+            }
+            regions.push(region.to_owned());
+        }
+        Ok(Map::new(
+            provinces,
+            army,
+            fleet,
+            coordinates,
+            regions,
+            unit_kinds,
+        ))
+    }
+
+    fn new(
+        provinces: Vec<Province>,
+        army: Vec<Vec<usize>>,
+        fleet: Vec<Vec<FleetEdge>>,
+        positions: Vec<Option<(u16, u16)>>,
+        regions: Vec<String>,
+        unit_kinds: Vec<UnitKind>,
+    ) -> Self {
+        let mut matcher = Trie::new();
+        for (index, province) in provinces.iter().enumerate() {
+            matcher.insert(&province.name, index);
+            matcher.insert(&province.abbreviation, index);
+            for alias in province.aliases.iter() {
+                matcher.insert(alias, index);
+            }
+        }
+        Map {
+            provinces: provinces.into(),
+            army: army.into_iter().map(Cow::Owned).collect::<Vec<_>>().into(),
+            fleet: fleet.into_iter().map(Cow::Owned).collect::<Vec<_>>().into(),
+            positions: positions.into(),
+            regions: regions
+                .into_iter()
+                .map(Cow::Owned)
+                .collect::<Vec<_>>()
+                .into(),
+            unit_kinds: unit_kinds.into(),
+            matcher,
+        }
+    }
+
+    /// True if an army can move directly from `from` to `to`.
+    pub fn army_can_move(&self, from: &Province, to: &Province) -> bool {
+        self.army[from.id].contains(&to.id)
+    }
+
+    /// The provinces an army can move to directly from `from`.
+    pub fn army_neighbours<'m>(&'m self, from: &Province) -> impl Iterator<Item = &'m Province> {
+        self.army[from.id]
+            .iter()
+            .map(move |&id| &self.provinces[id])
+    }
+
+    /// True if a fleet can move directly from `from` (on `from_coast`, for
+    /// bicoastal provinces) to `to` (on `to_coast`, likewise).
+    pub fn fleet_can_move(
+        &self,
+        from: &Province,
+        from_coast: Option<Coast>,
+        to: &Province,
+        to_coast: Option<Coast>,
+    ) -> bool {
+        self.fleet_neighbours(from, from_coast)
+            .any(|(p, coast)| p.id == to.id && coast == to_coast)
+    }
+
+    /// True if a fleet at `from` can move to any coast of `to`; that is,
+    /// whether it could support a move there.
+    pub fn fleet_can_reach(
+        &self,
+        from: &Province,
+        from_coast: Option<Coast>,
+        to: &Province,
+    ) -> bool {
+        self.fleet_neighbours(from, from_coast)
+            .any(|(p, _)| p.id == to.id)
+    }
+
+    /// The places a fleet can move to directly from `from` (on
+    /// `from_coast`, for bicoastal provinces), with the coast it would
+    /// arrive on.
+    pub fn fleet_neighbours<'m>(
+        &'m self,
+        from: &Province,
+        from_coast: Option<Coast>,
+    ) -> impl Iterator<Item = (&'m Province, Option<Coast>)> {
+        self.fleet[from.id]
+            .iter()
+            .filter(move |edge| edge.from_coast == from_coast)
+            .map(move |edge| (&self.provinces[edge.to], edge.to_coast))
+    }
+
+    /// The fewest moves a unit of type `unit` would take to get from `from`
+    /// to `to`, not counting convoys, or `None` if it cannot get there.
+    pub fn distance(&self, from: &Province, to: &Province, unit: UnitType) -> Option<usize> {
+        self.shortest_path(from, to, unit)
+            .map(|path| path.len() - 1)
+    }
+
+    /// The provinces, `from` and `to` among them, that a unit of type
+    /// `unit` would pass through on one of the shortest routes between the
+    /// two, not counting convoys.
+    ///
+    /// A fleet keeps to the coast it is on, but one in a bicoastal
+    /// province may start from either.
+    pub fn shortest_path(
+        &self,
+        from: &Province,
+        to: &Province,
+        unit: UnitType,
+    ) -> Option<Vec<&Province>> {
+        let coasts: Vec<_> = match unit {
+            UnitType::Fleet if from.is_bicoastal() => {
+                from.coasts.iter().copied().map(Some).collect()
+            }
+            _ => vec![None],
+        };
+        self.search(from, &coasts, Some(unit), |province| province.id == to.id)
+    }
+
+    /// A shortest route from `from`, on any of `coasts`, to the nearest
+    /// province for which `goal` is true, by the moves of `unit`, or across
+    /// any border at all if `unit` is `None`.
+    pub(crate) fn search(
+        &self,
+        from: &Province,
+        coasts: &[Option<Coast>],
+        unit: Option<UnitType>,
+        goal: impl Fn(&Province) -> bool,
+    ) -> Option<Vec<&Province>> {
+        // Places are numbered by province and coast, with room for each
+        // province's coasts.
+        let index = |id: usize, coast: Option<Coast>| id * 5 + coast.map_or(0, |c| c as usize + 1);
+        let mut came_from: Vec<Option<usize>> = vec![None; self.provinces.len() * 5];
+        let mut queue = VecDeque::new();
+        for &coast in coasts {
+            let start = index(from.id, coast);
+            came_from[start] = Some(start);
+            queue.push_back((from.id, coast));
+        }
+        while let Some((id, coast)) = queue.pop_front() {
+            let here = index(id, coast);
+            if goal(&self.provinces[id]) {
+                let mut path = vec![&self.provinces[id]];
+                let mut at = here;
+                while let Some(previous) = came_from[at].filter(|&previous| previous != at) {
+                    path.push(&self.provinces[previous / 5]);
+                    at = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let army = || self.army[id].iter().map(|&to| (to, None));
+            let fleet = self.fleet[id].iter();
+            let next: Vec<(usize, Option<Coast>)> = match unit {
+                Some(UnitType::Army) => army().collect(),
+                Some(UnitType::Fleet) => fleet
+                    .filter(|edge| edge.from_coast == coast)
+                    .map(|edge| (edge.to, edge.to_coast))
+                    .collect(),
+                None => army().chain(fleet.map(|edge| (edge.to, None))).collect(),
+            };
+            for (to, to_coast) in next {
+                let there = index(to, to_coast);
+                if came_from[there].is_none() {
+                    came_from[there] = Some(here);
+                    queue.push_back((to, to_coast));
+                }
+            }
+        }
+        None
+    }
+
+    /// Where `province` is drawn on the map, as x and y, if the map says.
+    pub fn position(&self, province: &Province) -> Option<(u16, u16)> {
+        self.positions[province.id]
+    }
+
+    /// The province with the given id.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn province(&self, id: usize) -> &Province {
+        &self.provinces[id]
+    }
+
+    /// Every province on the map.
+    pub fn provinces(&self) -> impl Iterator<Item = &Province> {
+        self.provinces.iter()
+    }
+
+    /// The names of the map's regions, the groups of provinces it defines
+    /// for strategy and statistics, like "Scandinavia".
+    pub fn regions(&self) -> impl Iterator<Item = &str> {
+        self.regions.iter().map(|region| &**region)
+    }
+
+    /// The kinds of unit the map adds to armies and fleets, in the order it
+    /// defines them; none on the standard map.
+    pub fn unit_kinds(&self) -> impl Iterator<Item = &UnitKind> {
+        self.unit_kinds.iter()
+    }
+
+    /// The unit kind written `abbreviation`, ignoring case.
+    pub fn unit_kind(&self, abbreviation: &str) -> Option<&UnitKind> {
+        self.unit_kinds
+            .iter()
+            .find(|kind| kind.abbreviation.eq_ignore_ascii_case(abbreviation))
+    }
+
+    /// The unit kind with the given id.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn unit_kind_by_id(&self, id: u8) -> &UnitKind {
+        &self.unit_kinds[usize::from(id)]
+    }
+
+    /// The provinces in the region named `region`, ignoring case.
+    pub fn provinces_in_region<'m>(
+        &'m self,
+        region: &'m str,
+    ) -> impl Iterator<Item = &'m Province> {
+        self.provinces
+            .iter()
+            .filter(move |p| p.regions().any(|r| r.eq_ignore_ascii_case(region)))
+    }
+
+    /// Looks up a province by its full name, ignoring case.
+    pub fn find_by_name(&self, name: &str) -> Option<&Province> {
+        self.provinces
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Looks up a province by its standard abbreviation, ignoring case.
+    pub fn find_by_abbreviation(&self, abbreviation: &str) -> Option<&Province> {
+        self.provinces
+            .iter()
+            .find(|p| p.abbreviation.eq_ignore_ascii_case(abbreviation))
+    }
+
+    /// Returns the provinces whose names or aliases approximately match the
+    /// first one, two or three words of `input`, with a similarity of at
+    /// least `threshold` (between 0 and 1).
+    ///
+    /// Results are best match first, as `(province, byte length of the
+    /// matched words, similarity)`. Abbreviations are too short to match
+    /// approximately, and are ignored.
+    pub(crate) fn fuzzy_match_provinces(
+        &self,
+        input: &str,
+        threshold: f64,
+    ) -> Vec<(&Province, usize, f64)> {
+        let mut found = Vec::new();
+        for len in fuzzy::word_spans(input) {
+            let words = &input[..len];
+            if words.chars().count() < 3 {
+                continue;
+            }
+            for province in self.provinces.iter() {
+                let score = core::iter::once(&*province.name)
+                    .chain(province.aliases())
+                    .map(|name| fuzzy::similarity(words, name))
+                    .fold(0.0, f64::max);
+                if score >= threshold {
+                    found.push((province, len, score));
+                }
+            }
+        }
+        found.sort_by(|a, b| b.2.total_cmp(&a.2));
+        found
+    }
+
+    /// Looks up a province by its name, abbreviation or an alias, ignoring
+    /// case and accents.
+    pub(crate) fn province_named(&self, name: &str) -> Option<&Province> {
+        self.matcher.get(name).map(|index| &self.provinces[index])
+    }
+
+    /// Whether `text` is the start of some province's name, abbreviation or
+    /// alias, ignoring case and accents.
+    pub(crate) fn is_name_prefix(&self, text: &str) -> bool {
+        self.matcher.has_prefix(text)
+    }
+}
+
+/// Parses a province abbreviation with an optional coast, as in "Spa/nc",
+/// into a province id and coast.
+fn parse_location(provinces: &[Province], text: &str) -> Result<(usize, Option<Coast>), String> {
+    let (abbreviation, coast) = match text.split_once('/') {
+        Some((abbreviation, coast)) => (abbreviation, Some(coast)),
+        None => (text, None),
+    };
+    let province = provinces
+        .iter()
+        .find(|p| p.abbreviation.eq_ignore_ascii_case(abbreviation))
+        .ok_or_else(|| format!("unknown province {:?}", abbreviation))?;
+    let coast = match coast {
+        None => None,
+        Some(coast) => {
+            let coast = coast
+                .parse()
+                .map_err(|()| format!("unknown coast {:?}", coast))?;
+            if !province.coasts.contains(&coast) {
+                return Err(format!("{} has no {} coast", province.abbreviation, coast));
+            }
+            Some(coast)
+        }
+    };
+    Ok((province.id, coast))
+}
+
+/// Parses one line of the `[provinces]` section.
+fn parse_province(line: &str) -> Result<Province, String> {
+    let mut columns = line.split('|').map(str::trim);
+    let mut column = || columns.next().unwrap_or("");
+    let abbreviation = column();
+    let name = column();
+    let kind = column();
+    let sc = column();
+    let coasts = column();
+    let aliases = column();
+    if abbreviation.is_empty() || name.is_empty() {
+        return Err("expected an abbreviation and a name".to_owned());
+    }
+    let kind = kind
+        .parse()
+        .map_err(|()| format!("unknown province kind {:?}", kind))?;
+    let home = match sc {
+        "" | "neutral" => None,
+        power => Some(power.parse().map_err(|e| format!("{}", e))?),
+    };
+    let coasts = coasts
+        .split_whitespace()
+        .map(|coast| {
+            coast
+                .parse()
+                .map_err(|()| format!("unknown coast {:?}", coast))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Province {
+        id: 0,
+        name: name.to_owned().into(),
+        abbreviation: abbreviation.to_owned().into(),
+        aliases: aliases
+            .split(',')
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+            .map(|alias| alias.to_owned().into())
+            .collect::<Vec<_>>()
+            .into(),
+        kind,
+        sc: !sc.is_empty(),
+        home,
+        coasts: coasts.into(),
+        regions: Table::default(),
+    })
+}
+
+/// Parses one line of the `[units]` section.
+fn parse_unit_kind(line: &str) -> Result<UnitKind, String> {
+    let mut columns = line.split('|').map(str::trim);
+    let mut column = || columns.next().unwrap_or("");
+    let abbreviation = column();
+    let name = column();
+    let moves_as = column();
+    let fixed = column();
+    if abbreviation.is_empty() || name.is_empty() {
+        return Err("expected an abbreviation and a name".to_owned());
+    }
+    if abbreviation.contains(|c: char| c.is_whitespace() || c == '/') {
+        return Err(format!("{:?} is not one word", abbreviation));
+    }
+    let moves_as = match moves_as {
+        "army" => UnitType::Army,
+        "fleet" => UnitType::Fleet,
+        other => return Err(format!("expected \"army\" or \"fleet\", not {:?}", other)),
+    };
+    let mobile = match fixed {
+        "" => true,
+        "fixed" => false,
+        other => return Err(format!("expected \"fixed\" or nothing, not {:?}", other)),
+    };
+    Ok(UnitKind {
+        id: 0,
+        abbreviation: abbreviation.to_owned().into(),
+        name: name.to_owned().into(),
+        moves_as,
+        mobile,
+    })
+}
+
+/// The error returned when a map definition is malformed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MapError {
+    line: usize,
+    message: String,
+}
+
+impl MapError {
+    /// The 1-based line number of the problem.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for MapError {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_map_counts() {
+        let map = Map::standard();
+        let count = |f: &dyn Fn(&Province) -> bool| map.provinces().filter(|p| f(p)).count();
+        assert_eq!(count(&|_| true), 75);
+        assert_eq!(count(&|p| p.kind() == ProvinceKind::Land), 14);
+        assert_eq!(count(&|p| p.kind() == ProvinceKind::Coast), 42);
+        assert_eq!(count(&|p| p.kind() == ProvinceKind::Sea), 19);
+        assert_eq!(count(&|p| p.is_supply_center()), 34);
+        assert_eq!(count(&|p| map.position(p).is_some()), 75);
+        assert_eq!(count(&|p| p.home_power().is_some()), 22);
+        for &power in &Power::ALL {
+            let expected = if power == Power::Russia { 4 } else { 3 };
+            assert_eq!(
+                count(&|p| p.home_power() == Some(power)),
+                expected,
+                "{}",
+                power
+            );
+        }
+        assert!(map
+            .provinces()
+            .filter(|p| p.home_power().is_some())
+            .all(Province::is_supply_center));
+    }
+
+    #[test]
+    fn standard_map_is_compiled_in() {
+        let parsed = Map::parse(Map::STANDARD).unwrap();
+        assert_eq!(format!("{:?}", Map::standard()), format!("{:?}", parsed));
+        assert!(matches!(Map::standard().provinces, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn standard_map_coasts() {
+        let map = Map::standard();
+        let bicoastal: Vec<_> = map
+            .provinces()
+            .filter(|p| p.is_bicoastal())
+            .map(Province::abbreviation)
+            .collect();
+        assert_eq!(bicoastal, ["Bul", "Spa", "StP"]);
+        assert!(map
+            .provinces()
+            .filter(|p| p.is_bicoastal())
+            .all(Province::is_coastal));
+        for name in &["Brest", "London", "St. Petersburg", "Trieste", "Kiel"] {
+            assert!(map.find_by_name(name).unwrap().is_coastal(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn distances() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let distance = |from, to, unit| map.distance(province(from), province(to), unit);
+        assert_eq!(distance("Par", "Par", UnitType::Army), Some(0));
+        assert_eq!(distance("Par", "Mun", UnitType::Army), Some(2));
+        assert_eq!(distance("Par", "Mos", UnitType::Army), Some(5));
+        assert_eq!(distance("Par", "Lon", UnitType::Army), None);
+        assert_eq!(distance("Lon", "Nwy", UnitType::Fleet), Some(2));
+        assert_eq!(distance("Lon", "Mun", UnitType::Fleet), None);
+        // Spain's north coast does not lead to its south coast.
+        assert_eq!(distance("Gas", "Mar", UnitType::Fleet), Some(3));
+        assert_eq!(distance("StP", "Nwy", UnitType::Fleet), Some(1));
+        assert_eq!(distance("StP", "Bot", UnitType::Fleet), Some(1));
+
+        let path = map
+            .shortest_path(province("Gas"), province("Mar"), UnitType::Fleet)
+            .unwrap();
+        let path: Vec<_> = path.into_iter().map(Province::abbreviation).collect();
+        assert_eq!(path, ["Gas", "Mao", "Spa", "Mar"]);
+    }
+
+    #[test]
+    fn regions() {
+        let map = Map::standard();
+        let scandinavia: Vec<_> = map
+            .provinces_in_region("scandinavia")
+            .map(Province::abbreviation)
+            .collect();
+        assert_eq!(scandinavia, ["Den", "Fin", "Nwy", "Swe"]);
+        assert_eq!(map.provinces_in_region("Atlantis").count(), 0);
+        let greece = map.find_by_abbreviation("Gre").unwrap();
+        assert_eq!(greece.regions().collect::<Vec<_>>(), ["Balkans"]);
+        assert!(map.regions().any(|region| region == "Mid-Atlantic"));
+
+        let error = Map::parse("[provinces]\nNwy | Norway | coast | | |\n[regions]\nNorth: Swe\n");
+        assert_eq!(error.unwrap_err().line(), 4);
+    }
+
+    #[test]
+    fn localized_names() {
+        use crate::parser::{parse_order, parse_order_with, Locale, ParserOptions};
+
+        let standard = Map::standard();
+        for names in [Map::STANDARD_GERMAN_NAMES, Map::STANDARD_FRENCH_NAMES] {
+            let map = standard.with_names(names).unwrap();
+            for line in names
+                .lines()
+                .filter(|line| !line.starts_with('#') && line.contains('|'))
+            {
+                let (abbreviation, names) = line.split_once('|').unwrap();
+                for name in names.split(',') {
+                    let province = map.province_named(name.trim()).unwrap();
+                    assert_eq!(province.abbreviation(), abbreviation.trim(), "{}", name);
+                }
+            }
+        }
+
+        let german = standard.with_names(Map::STANDARD_GERMAN_NAMES).unwrap();
+        let options = ParserOptions {
+            locale: Locale::GERMAN,
+            ..ParserOptions::default()
+        };
+        assert_eq!(
+            parse_order_with(&german, "Armee München zieht nach Burgund", &options),
+            parse_order(standard, "A Mun - Bur")
+        );
+        assert_eq!(
+            parse_order(&german, "F Nordmeer - Nwy"),
+            parse_order(standard, "F Nwg - Nwy")
+        );
+        assert!(parse_order(standard, "A Muenchen - Ruh").is_err());
+
+        let error = standard.with_names("Mun | Munich\nBer | München, Kiel\n");
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "line 2: \"Kiel\" already names Kiel"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Sets of provinces, as bitsets indexed by province id.
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+use super::{Map, Province};
+
+const WORDS: usize = 4;
+
+/// A set of provinces on one map, held as one bit per province, so that
+/// it is `Copy` and its set operations are a few machine instructions.
+///
+/// Sets from different maps should not be combined; the result means
+/// nothing.
+#[derive(Clone, Copy)]
+pub struct ProvinceSet<'m> {
+    map: &'m Map,
+    bits: [u64; WORDS],
+}
+
+impl<'m> ProvinceSet<'m> {
+    /// The most provinces a map may have.
+    pub const CAPACITY: usize = WORDS * 64;
+
+    /// An empty set of `map`'s provinces.
+    pub fn new(map: &'m Map) -> Self {
+        ProvinceSet {
+            map,
+            bits: [0; WORDS],
+        }
+    }
+
+    /// Every province on `map`.
+    pub fn all(map: &'m Map) -> Self {
+        let mut set = ProvinceSet::new(map);
+        set.extend(map.provinces());
+        set
+    }
+
+    pub fn map(&self) -> &'m Map {
+        self.map
+    }
+
+    /// Adds `province`, returning whether it was not already there.
+    pub fn insert(&mut self, province: &Province) -> bool {
+        let (word, bit) = position(province);
+        let absent = self.bits[word] & bit == 0;
+        self.bits[word] |= bit;
+        absent
+    }
+
+    /// Takes `province` out, returning whether it was there.
+    pub fn remove(&mut self, province: &Province) -> bool {
+        let (word, bit) = position(province);
+        let present = self.bits[word] & bit != 0;
+        self.bits[word] &= !bit;
+        present
+    }
+
+    pub fn contains(&self, province: &Province) -> bool {
+        let (word, bit) = position(province);
+        self.bits[word] & bit != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&word| word == 0)
+    }
+
+    /// The provinces in either set.
+    pub fn union(&self, other: &Self) -> Self {
+        self.zip(other, |a, b| a | b)
+    }
+
+    /// The provinces in both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.zip(other, |a, b| a & b)
+    }
+
+    /// The provinces in this set and not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.zip(other, |a, b| a & !b)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    /// The provinces in the set, in map order.
+    pub fn iter(&self) -> impl Iterator<Item = &'m Province> + '_ {
+        let map = self.map;
+        self.bits.iter().enumerate().flat_map(move |(i, &word)| {
+            let mut word = word;
+            core::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(map.province(i * 64 + bit))
+            })
+        })
+    }
+
+    fn zip(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        let mut bits = self.bits;
+        for (word, &theirs) in bits.iter_mut().zip(&other.bits) {
+            *word = f(*word, theirs);
+        }
+        ProvinceSet {
+            map: self.map,
+            bits,
+        }
+    }
+}
+
+/// The word and bit of `province`'s place in a set.
+fn position(province: &Province) -> (usize, u64) {
+    let id = province.id();
+    (id / 64, 1 << (id % 64))
+}
+
+impl<'a, 'm> Extend<&'a Province> for ProvinceSet<'m> {
+    fn extend<I: IntoIterator<Item = &'a Province>>(&mut self, provinces: I) {
+        for province in provinces {
+            self.insert(province);
+        }
+    }
+}
+
+/// Two sets are equal if they are of the same map's provinces and hold
+/// the same ones.
+impl<'m> PartialEq for ProvinceSet<'m> {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.map, other.map) && self.bits == other.bits
+    }
+}
+
+impl<'m> Eq for ProvinceSet<'m> {}
+
+impl<'m> Hash for ProvinceSet<'m> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits.hash(state);
+    }
+}
+
+/// Lists the provinces' abbreviations, like `{Bur, Par}`.
+impl<'m> fmt::Debug for ProvinceSet<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("{")?;
+        for (i, province) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(province.abbreviation())?;
+        }
+        f.write_str("}")
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_operations() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut a = ProvinceSet::new(map);
+        assert!(a.is_empty());
+        assert!(a.insert(province("Par")));
+        assert!(!a.insert(province("Par")));
+        a.extend([province("Bur"), province("Yor")]);
+        let mut b = ProvinceSet::new(map);
+        b.extend([province("Bur"), province("Mun"), province("Yor")]);
+
+        assert_eq!(a.len(), 3);
+        assert!(a.contains(province("Yor")));
+        assert_eq!(format!("{:?}", a.intersection(&b)), "{Bur, Yor}");
+        assert_eq!(a.union(&b).len(), 4);
+        assert_eq!(format!("{:?}", a.difference(&b)), "{Par}");
+        assert!(a.intersection(&b).is_subset(&b));
+        assert!(a.remove(province("Par")));
+        assert!(!a.contains(province("Par")));
+        assert_eq!(ProvinceSet::all(map).len(), 75);
+        assert_eq!(ProvinceSet::all(map).iter().last(), map.provinces().last());
+    }
+}
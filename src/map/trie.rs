@@ -0,0 +1,128 @@
+//! A character trie for resolving province names and abbreviations.
+//!
+//! Keys are stored folded to lowercase without accents, and lookups fold
+//! the input as they walk it, so matching ignores case and accents and
+//! costs time proportional to the length of the match rather than the
+//! number of known names.
+
+use alloc::borrow::Cow;
+
+use super::unicode::fold;
+use crate::prelude::*;
+
+// The fields are the crate's so that `build.rs`, which compiles this file
+// into itself, can write out the standard map's trie.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Trie {
+    pub(crate) nodes: Cow<'static, [Node]>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Node {
+    /// Child node indices, sorted by edge character.
+    pub(crate) children: Cow<'static, [(char, usize)]>,
+    pub(crate) value: Option<usize>,
+}
+
+impl Trie {
+    pub(crate) fn new() -> Self {
+        Trie {
+            nodes: vec![Node::default()].into(),
+        }
+    }
+
+    /// Associates `key` with `value`, replacing any previous value.
+    pub(crate) fn insert(&mut self, key: &str, value: usize) {
+        let mut node = 0;
+        for c in key.chars().flat_map(fold) {
+            node = match self.child(node, c) {
+                Ok(child) => child,
+                Err(pos) => {
+                    let nodes = self.nodes.to_mut();
+                    let child = nodes.len();
+                    nodes.push(Node::default());
+                    nodes[node].children.to_mut().insert(pos, (c, child));
+                    child
+                }
+            };
+        }
+        self.nodes.to_mut()[node].value = Some(value);
+    }
+
+    /// The value associated with `key`, ignoring case and accents.
+    pub(crate) fn get(&self, key: &str) -> Option<usize> {
+        self.walk(key).and_then(|node| self.nodes[node].value)
+    }
+
+    /// Whether any key starts with `prefix`, ignoring case and accents.
+    pub(crate) fn has_prefix(&self, prefix: &str) -> bool {
+        self.walk(prefix).is_some()
+    }
+
+    /// The node reached by following `key` from the root, if any.
+    fn walk(&self, key: &str) -> Option<usize> {
+        key.chars()
+            .flat_map(fold)
+            .try_fold(0, |node, c| self.child(node, c).ok())
+    }
+
+    fn child(&self, node: usize, c: char) -> Result<usize, usize> {
+        let children = &self.nodes[node].children;
+        children
+            .binary_search_by_key(&c, |&(edge, _)| edge)
+            .map(|i| children[i].1)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::combinators::Parser;
+    use crate::map::Map;
+    use crate::parser::ParseProvince;
+
+    #[test]
+    fn keys_and_prefixes() {
+        let mut trie = Trie::new();
+        trie.insert("North Atlantic Ocean", 0);
+        trie.insert("North", 1);
+        trie.insert("München", 2);
+        assert_eq!(trie.get("north atlantic ocean"), Some(0));
+        assert_eq!(trie.get("NORTH Atlantic OCEAN"), Some(0));
+        assert_eq!(trie.get("North"), Some(1));
+        assert_eq!(trie.get("MUNCHEN"), Some(2));
+
+        // A prefix of a key is no key of its own.
+        assert_eq!(trie.get("North Atl"), None);
+        assert!(trie.has_prefix("North Atl"));
+        assert!(trie.has_prefix("NORTH a"));
+        assert_eq!(trie.get("North Atlantic Ocean Sea"), None);
+        assert!(!trie.has_prefix("North Atlantic Ocean Sea"));
+        assert!(!trie.has_prefix("South"));
+
+        trie.insert("north", 3);
+        assert_eq!(trie.get("North"), Some(3));
+    }
+
+    #[test]
+    fn longest_match_first() {
+        let map = Map::standard();
+        let input = "F North Atlantic Ocean - Nwg";
+        let at = &input[2..];
+        let matches: Vec<_> = ParseProvince::new(map)
+            .parse(at)
+            .map(|(province, rest)| (province.abbreviation(), at.len() - rest.len()))
+            .collect();
+        // The whole name, then the alias `North Atlantic`.
+        assert_eq!(matches, [("Nao", 20), ("Nao", 14)]);
+
+        let upper = input.to_uppercase();
+        let at = &upper[2..];
+        let (province, rest) = ParseProvince::new(map).parse(at).next().unwrap();
+        assert_eq!(province.abbreviation(), "Nao");
+        assert_eq!(rest, " - NWG");
+
+        // `North` starts several names but is none of them.
+        assert_eq!(ParseProvince::new(map).parse("North - Nwg").next(), None);
+    }
+}
@@ -0,0 +1,267 @@
+//! Checks of a map's data that parsing alone does not catch.
+
+use alloc::collections::VecDeque;
+use core::fmt;
+
+use super::{Coast, Map, Province, ProvinceKind};
+use crate::order::UnitType;
+use crate::prelude::*;
+
+/// Something wrong, or very likely wrong, with a map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapDiagnostic<'m> {
+    /// A unit can move from one place to another but not back.
+    OneWay {
+        unit: UnitType,
+        from: &'m Province,
+        from_coast: Option<Coast>,
+        to: &'m Province,
+        to_coast: Option<Coast>,
+    },
+    /// An army can move to or from a sea.
+    ArmyAtSea {
+        from: &'m Province,
+        to: &'m Province,
+    },
+    /// A fleet can move to or from an inland province.
+    FleetInland {
+        from: &'m Province,
+        to: &'m Province,
+    },
+    /// A province that is not on the coast has coasts, or a province has
+    /// just one, which needs no naming.
+    MisplacedCoasts(&'m Province),
+    /// No unit can move into or out of the province.
+    Isolated(&'m Province),
+    /// No unit can reach the supply center from any home center.
+    UnreachableCenter(&'m Province),
+}
+
+impl<'m> fmt::Display for MapDiagnostic<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let place = |province: &Province, coast: Option<Coast>| match coast {
+            Some(coast) => format!("{}/{}", province.abbreviation(), coast),
+            None => province.abbreviation().to_owned(),
+        };
+        match self {
+            MapDiagnostic::OneWay {
+                unit,
+                from,
+                from_coast,
+                to,
+                to_coast,
+            } => write!(
+                f,
+                "{} can move {} to {} but not back",
+                match unit {
+                    UnitType::Army => "armies",
+                    UnitType::Fleet => "fleets",
+                },
+                place(from, *from_coast),
+                place(to, *to_coast)
+            ),
+            MapDiagnostic::ArmyAtSea { from, to } => write!(
+                f,
+                "armies can move {} to {}, at sea",
+                from.abbreviation(),
+                to.abbreviation()
+            ),
+            MapDiagnostic::FleetInland { from, to } => write!(
+                f,
+                "fleets can move {} to {}, inland",
+                from.abbreviation(),
+                to.abbreviation()
+            ),
+            MapDiagnostic::MisplacedCoasts(province) if province.coasts().len() == 1 => write!(
+                f,
+                "{} has a single coast, which should not be listed",
+                province.abbreviation()
+            ),
+            MapDiagnostic::MisplacedCoasts(province) => write!(
+                f,
+                "{} has coasts but is not a coastal province",
+                province.abbreviation()
+            ),
+            MapDiagnostic::Isolated(province) => {
+                write!(f, "no unit can enter or leave {}", province.abbreviation())
+            }
+            MapDiagnostic::UnreachableCenter(province) => write!(
+                f,
+                "no unit can reach {} from a home center",
+                province.abbreviation()
+            ),
+        }
+    }
+}
+
+impl Map {
+    /// Checks the map for mistakes parsing lets through, like adjacencies
+    /// that go only one way or supply centers no unit can get to, and
+    /// lists them by kind, then in map order. A map with none returns an
+    /// empty list.
+    ///
+    /// Supply centers are checked only on maps with home centers.
+    pub fn validate(&self) -> Vec<MapDiagnostic<'_>> {
+        let mut diagnostics = Vec::new();
+        let provinces = &self.provinces;
+        for (from, neighbours) in self.army.iter().enumerate() {
+            for &to in neighbours.iter() {
+                if !self.army[to].contains(&from) {
+                    diagnostics.push(MapDiagnostic::OneWay {
+                        unit: UnitType::Army,
+                        from: &provinces[from],
+                        from_coast: None,
+                        to: &provinces[to],
+                        to_coast: None,
+                    });
+                }
+            }
+        }
+        for (from, edges) in self.fleet.iter().enumerate() {
+            for edge in edges.iter() {
+                let back = self.fleet[edge.to]
+                    .iter()
+                    .any(|back| back.to == from && back.to_coast == edge.from_coast);
+                if !back {
+                    diagnostics.push(MapDiagnostic::OneWay {
+                        unit: UnitType::Fleet,
+                        from: &provinces[from],
+                        from_coast: edge.from_coast,
+                        to: &provinces[edge.to],
+                        to_coast: edge.to_coast,
+                    });
+                }
+            }
+        }
+        for (from, neighbours) in self.army.iter().enumerate() {
+            for &to in neighbours.iter() {
+                if provinces[from].kind == ProvinceKind::Sea
+                    || provinces[to].kind == ProvinceKind::Sea
+                {
+                    diagnostics.push(MapDiagnostic::ArmyAtSea {
+                        from: &provinces[from],
+                        to: &provinces[to],
+                    });
+                }
+            }
+        }
+        for (from, edges) in self.fleet.iter().enumerate() {
+            for edge in edges.iter() {
+                let to = edge.to;
+                if provinces[from].kind == ProvinceKind::Land
+                    || provinces[to].kind == ProvinceKind::Land
+                {
+                    diagnostics.push(MapDiagnostic::FleetInland {
+                        from: &provinces[from],
+                        to: &provinces[to],
+                    });
+                }
+            }
+        }
+        diagnostics.extend(
+            provinces
+                .iter()
+                .filter(|province| {
+                    province.coasts.len() == 1
+                        || (province.is_bicoastal() && province.kind != ProvinceKind::Coast)
+                })
+                .map(MapDiagnostic::MisplacedCoasts),
+        );
+        let mut touched = vec![false; provinces.len()];
+        for (from, neighbours) in self.army.iter().enumerate() {
+            for &to in neighbours.iter() {
+                touched[from] = true;
+                touched[to] = true;
+            }
+        }
+        for (from, edges) in self.fleet.iter().enumerate() {
+            for edge in edges.iter() {
+                touched[from] = true;
+                touched[edge.to] = true;
+            }
+        }
+        diagnostics.extend(
+            provinces
+                .iter()
+                .filter(|province| !touched[province.id])
+                .map(MapDiagnostic::Isolated),
+        );
+        let homes: Vec<usize> = provinces
+            .iter()
+            .filter(|province| province.home.is_some())
+            .map(|province| province.id)
+            .collect();
+        if !homes.is_empty() {
+            let reached = self.reachable_from(&homes);
+            diagnostics.extend(
+                provinces
+                    .iter()
+                    .filter(|province| province.sc && !reached[province.id])
+                    .map(MapDiagnostic::UnreachableCenter),
+            );
+        }
+        diagnostics
+    }
+
+    /// Which provinces, by id, armies or fleets can reach from `starts`.
+    fn reachable_from(&self, starts: &[usize]) -> Vec<bool> {
+        let mut reached = vec![false; self.provinces.len()];
+        let mut queue: VecDeque<usize> = starts.iter().copied().collect();
+        for &start in starts {
+            reached[start] = true;
+        }
+        while let Some(from) = queue.pop_front() {
+            let fleet = self.fleet[from].iter().map(|edge| edge.to);
+            for to in self.army[from].iter().copied().chain(fleet) {
+                if !reached[to] {
+                    reached[to] = true;
+                    queue.push_back(to);
+                }
+            }
+        }
+        reached
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostics() {
+        assert_eq!(Map::standard().validate(), []);
+
+        let map = Map::parse(
+            "[provinces]\n\
+             Par | Paris    | land  | France  |       |\n\
+             Bre | Brest    | coast | France  |       |\n\
+             Eng | Channel  | sea   |         |       |\n\
+             Bur | Burgundy | land  |         | nc    |\n\
+             Ice | Iceland  | coast | neutral |       |\n\
+             [adjacencies]\n\
+             army Par: Bre Bur\n\
+             army Bre: Par Eng\n\
+             army Bur: Par\n\
+             fleet Bre: Eng\n\
+             fleet Eng: Bre Par\n",
+        )
+        .unwrap();
+        let messages: Vec<_> = map
+            .validate()
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "armies can move Bre to Eng but not back",
+                "fleets can move Eng to Par but not back",
+                "armies can move Bre to Eng, at sea",
+                "fleets can move Eng to Par, inland",
+                "Bur has a single coast, which should not be listed",
+                "no unit can enter or leave Ice",
+                "no unit can reach Ice from a home center",
+            ]
+        );
+    }
+}
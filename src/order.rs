@@ -0,0 +1,887 @@
+//! Orders, as given to units during the movement phase.
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::map::{Coast, Map, Province, ProvinceKind};
+use crate::prelude::*;
+
+/// The two kinds of unit on the standard board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UnitType {
+    Army,
+    Fleet,
+}
+
+impl fmt::Display for UnitType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            UnitType::Army => "A",
+            UnitType::Fleet => "F",
+        })
+    }
+}
+
+/// Why an order could not be constructed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OrderError {
+    /// A unit was ordered to move to the province it is already in.
+    MoveToSelf,
+    /// A unit was ordered to support itself.
+    SupportSelf,
+    /// A supported move ends in the supporting unit's own province.
+    SupportIntoSelf,
+    /// A unit was ordered to convoy itself.
+    ConvoySelf,
+    /// A convoyed army was to end up where it started, or where the
+    /// convoying fleet is.
+    InvalidConvoyDestination,
+    /// A convoy order named a fleet as the convoyed unit.
+    ConvoyedFleet,
+    /// A convoy order was given to an army.
+    ConvoyingArmy,
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            OrderError::MoveToSelf => "a unit cannot move to its own province",
+            OrderError::SupportSelf => "a unit cannot support itself",
+            OrderError::SupportIntoSelf => "a unit cannot support a move into its own province",
+            OrderError::ConvoySelf => "a unit cannot convoy itself",
+            OrderError::InvalidConvoyDestination => "a convoy must carry an army somewhere else",
+            OrderError::ConvoyedFleet => "only armies can be convoyed",
+            OrderError::ConvoyingArmy => "only fleets can convoy",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for OrderError {}
+
+/// The kind of an [`Order`], without its details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OrderKind {
+    Hold,
+    Move,
+    Support,
+    Convoy,
+}
+
+/// A single order to a single unit.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Order<'a> {
+    Hold(Hold<'a>),
+    Move(Move<'a>),
+    Support(Support<'a>),
+    Convoy(Convoy<'a>),
+}
+
+impl<'a> Order<'a> {
+    /// `A Par H`
+    pub fn hold(unit: impl Into<Option<UnitType>>, province: &'a Province) -> Self {
+        Order::Hold(Hold {
+            unit: unit.into(),
+            province,
+            coast: None,
+        })
+    }
+
+    /// `A Par - Bur`
+    pub fn move_(
+        unit: impl Into<Option<UnitType>>,
+        province: &'a Province,
+        destination: &'a Province,
+    ) -> Result<Self, OrderError> {
+        Order::Move(Move {
+            unit: unit.into(),
+            province,
+            destination,
+            coast: None,
+            via_convoy: false,
+        })
+        .validated()
+    }
+
+    /// `F Mao - Spa (nc)`
+    pub fn move_to_coast(
+        unit: impl Into<Option<UnitType>>,
+        province: &'a Province,
+        destination: &'a Province,
+        coast: Coast,
+    ) -> Result<Self, OrderError> {
+        Order::Move(Move {
+            unit: unit.into(),
+            province,
+            destination,
+            coast: Some(coast),
+            via_convoy: false,
+        })
+        .validated()
+    }
+
+    /// `A Lon - Bel via convoy`
+    pub fn move_via_convoy(
+        unit: impl Into<Option<UnitType>>,
+        province: &'a Province,
+        destination: &'a Province,
+    ) -> Result<Self, OrderError> {
+        Order::Move(Move {
+            unit: unit.into(),
+            province,
+            destination,
+            coast: None,
+            via_convoy: true,
+        })
+        .validated()
+    }
+
+    /// `A Mun S A Bur`
+    pub fn support_hold(
+        unit: impl Into<Option<UnitType>>,
+        province: &'a Province,
+        supported_unit: impl Into<Option<UnitType>>,
+        supported: &'a Province,
+    ) -> Result<Self, OrderError> {
+        Order::Support(Support {
+            unit: unit.into(),
+            province,
+            supported: SupportedOrder::Hold(Hold {
+                unit: supported_unit.into(),
+                province: supported,
+                coast: None,
+            }),
+        })
+        .validated()
+    }
+
+    /// `A Mun S A Par - Bur`
+    pub fn support_move(
+        unit: impl Into<Option<UnitType>>,
+        province: &'a Province,
+        supported_unit: impl Into<Option<UnitType>>,
+        from: &'a Province,
+        to: &'a Province,
+    ) -> Result<Self, OrderError> {
+        Order::Support(Support {
+            unit: unit.into(),
+            province,
+            supported: SupportedOrder::Move(Move {
+                unit: supported_unit.into(),
+                province: from,
+                destination: to,
+                coast: None,
+                via_convoy: false,
+            }),
+        })
+        .validated()
+    }
+
+    /// `F Nth C A Lon - Nwy`
+    pub fn convoy(
+        unit: impl Into<Option<UnitType>>,
+        province: &'a Province,
+        convoyed_unit: impl Into<Option<UnitType>>,
+        from: &'a Province,
+        to: &'a Province,
+    ) -> Result<Self, OrderError> {
+        Order::Convoy(Convoy {
+            unit: unit.into(),
+            province,
+            convoyed_unit: convoyed_unit.into(),
+            from,
+            to,
+        })
+        .validated()
+    }
+
+    /// Checks that the order makes sense on its own terms, without regard
+    /// to the map or the position.
+    ///
+    /// This is the check applied by the constructors above.
+    pub fn validate(&self) -> Result<(), OrderError> {
+        match self {
+            Order::Hold(_) => Ok(()),
+            Order::Move(move_) if move_.province == move_.destination => {
+                Err(OrderError::MoveToSelf)
+            }
+            Order::Move(_) => Ok(()),
+            Order::Support(support) if support.province == support.from() => {
+                Err(OrderError::SupportSelf)
+            }
+            Order::Support(support) if support.to() == Some(support.province) => {
+                Err(OrderError::SupportIntoSelf)
+            }
+            Order::Support(support) if support.to() == Some(support.from()) => {
+                Err(OrderError::MoveToSelf)
+            }
+            Order::Support(_) => Ok(()),
+            Order::Convoy(convoy) if convoy.province == convoy.from => Err(OrderError::ConvoySelf),
+            Order::Convoy(convoy) if convoy.from == convoy.to || convoy.province == convoy.to => {
+                Err(OrderError::InvalidConvoyDestination)
+            }
+            Order::Convoy(convoy) if convoy.unit == Some(UnitType::Army) => {
+                Err(OrderError::ConvoyingArmy)
+            }
+            Order::Convoy(convoy) if convoy.convoyed_unit == Some(UnitType::Fleet) => {
+                Err(OrderError::ConvoyedFleet)
+            }
+            Order::Convoy(_) => Ok(()),
+        }
+    }
+
+    fn validated(self) -> Result<Self, OrderError> {
+        self.validate().map(|()| self)
+    }
+
+    /// The type of the ordered unit, if the order named it.
+    pub fn unit(&self) -> Option<UnitType> {
+        match self {
+            Order::Hold(hold) => hold.unit,
+            Order::Move(move_) => move_.unit,
+            Order::Support(support) => support.unit,
+            Order::Convoy(convoy) => convoy.unit,
+        }
+    }
+
+    /// The province of the ordered unit.
+    pub fn province(&self) -> &'a Province {
+        match self {
+            Order::Hold(hold) => hold.province,
+            Order::Move(move_) => move_.province,
+            Order::Support(support) => support.province,
+            Order::Convoy(convoy) => convoy.province,
+        }
+    }
+
+    /// Where the ordered unit is moving to, for moves.
+    pub fn destination(&self) -> Option<&'a Province> {
+        match self {
+            Order::Move(move_) => Some(move_.destination),
+            _ => None,
+        }
+    }
+
+    /// The order being supported or convoyed, for supports and convoys.
+    pub fn target(&self) -> Option<Order<'a>> {
+        match self {
+            Order::Support(support) => Some(support.supported.clone().into()),
+            Order::Convoy(convoy) => Some(convoy.convoyed()),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> OrderKind {
+        match self {
+            Order::Hold(_) => OrderKind::Hold,
+            Order::Move(_) => OrderKind::Move,
+            Order::Support(_) => OrderKind::Support,
+            Order::Convoy(_) => OrderKind::Convoy,
+        }
+    }
+
+    /// True if the ordered unit is trying to leave its province.
+    pub fn is_move(&self) -> bool {
+        self.kind() == OrderKind::Move
+    }
+
+    /// The order in a standard form, so that orders meaning the same thing
+    /// on `map` compare and hash as equal.
+    ///
+    /// Unit types the order left out are filled in where the map allows
+    /// only one: from the terrain, from what can make the move, and
+    /// fleets for convoys and armies for what they carry. A fleet moving
+    /// to a province it can reach on only one coast is given that coast,
+    /// and coasts that mean nothing are dropped, including those of
+    /// supports and holds, which the rules ignore.
+    pub fn canonicalize(&self, map: &Map) -> CanonicalOrder<'a> {
+        let order = match self {
+            Order::Hold(hold) => Order::Hold(Hold {
+                unit: infer_unit(map, hold.unit, hold.province, None),
+                province: hold.province,
+                coast: None,
+            }),
+            Order::Move(move_) => Order::Move(canonical_move(map, move_)),
+            Order::Support(support) => Order::Support(Support {
+                unit: infer_unit(
+                    map,
+                    support.unit,
+                    support.province,
+                    Some(support.to().unwrap_or_else(|| support.from())),
+                ),
+                province: support.province,
+                supported: match &support.supported {
+                    SupportedOrder::Hold(hold) => SupportedOrder::Hold(Hold {
+                        unit: infer_unit(map, hold.unit, hold.province, None),
+                        province: hold.province,
+                        coast: None,
+                    }),
+                    SupportedOrder::Move(move_) => SupportedOrder::Move(Move {
+                        coast: None,
+                        via_convoy: false,
+                        ..canonical_move(map, move_)
+                    }),
+                },
+            }),
+            Order::Convoy(convoy) => Order::Convoy(Convoy {
+                unit: Some(UnitType::Fleet),
+                convoyed_unit: Some(UnitType::Army),
+                ..convoy.clone()
+            }),
+        };
+        CanonicalOrder(order)
+    }
+}
+
+impl<'a> fmt::Display for Order<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Order::Hold(hold) => hold.fmt(f),
+            Order::Move(move_) => move_.fmt(f),
+            Order::Support(support) => support.fmt(f),
+            Order::Convoy(convoy) => convoy.fmt(f),
+        }
+    }
+}
+
+/// An order in the standard form given by [`Order::canonicalize`].
+///
+/// Two orders that mean the same thing have equal canonical forms, which
+/// makes these suitable as keys when looking for duplicate orders or
+/// comparing one set of orders with another.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CanonicalOrder<'a>(Order<'a>);
+
+impl<'a> CanonicalOrder<'a> {
+    pub fn order(&self) -> &Order<'a> {
+        &self.0
+    }
+
+    pub fn into_order(self) -> Order<'a> {
+        self.0
+    }
+}
+
+impl<'a> From<CanonicalOrder<'a>> for Order<'a> {
+    fn from(canonical: CanonicalOrder<'a>) -> Self {
+        canonical.0
+    }
+}
+
+impl<'a> fmt::Display for CanonicalOrder<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// `move_` with its unit type inferred and its coast settled.
+fn canonical_move<'a>(map: &Map, move_: &Move<'a>) -> Move<'a> {
+    let unit = match move_.unit {
+        None if move_.coast.is_some() => Some(UnitType::Fleet),
+        None if move_.via_convoy => Some(UnitType::Army),
+        unit => infer_unit(map, unit, move_.province, Some(move_.destination)),
+    };
+    let coast = match (unit, move_.coast) {
+        _ if !move_.destination.is_bicoastal() => None,
+        (Some(UnitType::Army), _) => None,
+        (Some(UnitType::Fleet), None) => {
+            match arrival_coasts(map, move_.province, move_.destination)[..] {
+                [only] => only,
+                _ => None,
+            }
+        }
+        (_, coast) => coast,
+    };
+    Move {
+        unit,
+        coast,
+        ..move_.clone()
+    }
+}
+
+/// `unit`, or the only type of unit that could be in `province` and act
+/// on `target`, if there is just one.
+fn infer_unit(
+    map: &Map,
+    unit: Option<UnitType>,
+    province: &Province,
+    target: Option<&Province>,
+) -> Option<UnitType> {
+    unit.or_else(|| match province.kind() {
+        ProvinceKind::Land => Some(UnitType::Army),
+        ProvinceKind::Sea => Some(UnitType::Fleet),
+        ProvinceKind::Coast => {
+            let target = target?;
+            let army = map.army_can_move(province, target);
+            let fleet = !arrival_coasts(map, province, target).is_empty();
+            match (army, fleet) {
+                (true, false) => Some(UnitType::Army),
+                (false, true) => Some(UnitType::Fleet),
+                // Only armies go further than next door, by convoy.
+                (false, false) if target.is_coastal() => Some(UnitType::Army),
+                _ => None,
+            }
+        }
+    })
+}
+
+/// The coasts of `to` that a fleet in `from`, on any of its coasts, could
+/// move to directly.
+fn arrival_coasts(map: &Map, from: &Province, to: &Province) -> Vec<Option<Coast>> {
+    let mut origins: Vec<_> = from.coasts().iter().copied().map(Some).collect();
+    if origins.is_empty() {
+        origins.push(None);
+    }
+    let mut coasts = Vec::new();
+    for origin in origins {
+        for (province, coast) in map.fleet_neighbours(from, origin) {
+            if province == to && !coasts.contains(&coast) {
+                coasts.push(coast);
+            }
+        }
+    }
+    coasts
+}
+
+/// Writes a unit's location, prefixed with its type if known.
+fn fmt_unit(f: &mut fmt::Formatter, unit: Option<UnitType>, province: &Province) -> fmt::Result {
+    match unit {
+        Some(unit) => write!(f, "{} {}", unit, province),
+        None => write!(f, "{}", province),
+    }
+}
+
+/// `A Par H`, or `F Spa (sc) H`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Hold<'a> {
+    pub(crate) unit: Option<UnitType>,
+    pub(crate) province: &'a Province,
+    pub(crate) coast: Option<Coast>,
+}
+
+impl<'a> Hold<'a> {
+    pub fn unit(&self) -> Option<UnitType> {
+        self.unit
+    }
+
+    pub fn province(&self) -> &'a Province {
+        self.province
+    }
+
+    /// The coast the unit is on, if the order named one.
+    ///
+    /// This only describes the unit. Supports, like the rest of the
+    /// rules, go by province, so a support naming the wrong coast still
+    /// supports the unit.
+    pub fn coast(&self) -> Option<Coast> {
+        self.coast
+    }
+
+    fn fmt_unit(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_unit(f, self.unit, self.province)?;
+        if let Some(coast) = self.coast {
+            write!(f, " ({})", coast)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for Hold<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_unit(f)?;
+        f.write_str(" H")
+    }
+}
+
+/// `A Par - Bur`, or `A Lon - Bel via convoy`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Move<'a> {
+    pub(crate) unit: Option<UnitType>,
+    pub(crate) province: &'a Province,
+    pub(crate) destination: &'a Province,
+    pub(crate) coast: Option<Coast>,
+    pub(crate) via_convoy: bool,
+}
+
+impl<'a> Move<'a> {
+    pub fn unit(&self) -> Option<UnitType> {
+        self.unit
+    }
+
+    pub fn province(&self) -> &'a Province {
+        self.province
+    }
+
+    pub fn destination(&self) -> &'a Province {
+        self.destination
+    }
+
+    /// The coast of the destination, if the order named one.
+    pub fn coast(&self) -> Option<Coast> {
+        self.coast
+    }
+
+    /// True if the order explicitly asked to be convoyed.
+    ///
+    /// Under the 2000 rules, an army ordered to move via convoy will not
+    /// move over land, even when the destination is adjacent.
+    pub fn via_convoy(&self) -> bool {
+        self.via_convoy
+    }
+}
+
+impl<'a> fmt::Display for Move<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_unit(f, self.unit, self.province)?;
+        write!(f, " - {}", self.destination)?;
+        if let Some(coast) = self.coast {
+            write!(f, " ({})", coast)?;
+        }
+        if self.via_convoy {
+            f.write_str(" via convoy")?;
+        }
+        Ok(())
+    }
+}
+
+/// `A Mun S A Par - Bur` to support a move, or `A Mun S A Bur` to support
+/// a hold.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Support<'a> {
+    pub(crate) unit: Option<UnitType>,
+    pub(crate) province: &'a Province,
+    pub(crate) supported: SupportedOrder<'a>,
+}
+
+impl<'a> Support<'a> {
+    pub fn unit(&self) -> Option<UnitType> {
+        self.unit
+    }
+
+    pub fn province(&self) -> &'a Province {
+        self.province
+    }
+
+    /// The order being supported.
+    pub fn supported(&self) -> &SupportedOrder<'a> {
+        &self.supported
+    }
+
+    /// The supported unit's type, if the order named it.
+    pub fn supported_unit(&self) -> Option<UnitType> {
+        self.supported.unit()
+    }
+
+    /// The province of the supported unit.
+    pub fn from(&self) -> &'a Province {
+        self.supported.province()
+    }
+
+    /// Where the supported unit is moving to, unless it is supported to
+    /// hold.
+    pub fn to(&self) -> Option<&'a Province> {
+        self.supported.destination()
+    }
+
+    /// True if this supports a unit in place, rather than a move.
+    pub fn is_hold_support(&self) -> bool {
+        matches!(self.supported, SupportedOrder::Hold(_))
+    }
+}
+
+impl<'a> fmt::Display for Support<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_unit(f, self.unit, self.province)?;
+        f.write_str(" S ")?;
+        match &self.supported {
+            SupportedOrder::Hold(hold) => hold.fmt_unit(f),
+            SupportedOrder::Move(move_) => move_.fmt(f),
+        }
+    }
+}
+
+/// What a [`Support`] supports: a unit staying where it is, or a move.
+///
+/// Supporting a unit to hold also supports it when it has been ordered to
+/// support or convoy, since those units do not leave their province.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SupportedOrder<'a> {
+    Hold(Hold<'a>),
+    Move(Move<'a>),
+}
+
+impl<'a> SupportedOrder<'a> {
+    /// The supported unit's type, if the order named it.
+    pub fn unit(&self) -> Option<UnitType> {
+        match self {
+            SupportedOrder::Hold(hold) => hold.unit,
+            SupportedOrder::Move(move_) => move_.unit,
+        }
+    }
+
+    /// The province of the supported unit.
+    pub fn province(&self) -> &'a Province {
+        match self {
+            SupportedOrder::Hold(hold) => hold.province,
+            SupportedOrder::Move(move_) => move_.province,
+        }
+    }
+
+    /// Where the supported unit is moving to, for supported moves.
+    pub fn destination(&self) -> Option<&'a Province> {
+        match self {
+            SupportedOrder::Hold(_) => None,
+            SupportedOrder::Move(move_) => Some(move_.destination),
+        }
+    }
+}
+
+impl<'a> From<SupportedOrder<'a>> for Order<'a> {
+    fn from(supported: SupportedOrder<'a>) -> Self {
+        match supported {
+            SupportedOrder::Hold(hold) => Order::Hold(hold),
+            SupportedOrder::Move(move_) => Order::Move(move_),
+        }
+    }
+}
+
+/// `F Nth C A Lon - Nwy`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Convoy<'a> {
+    pub(crate) unit: Option<UnitType>,
+    pub(crate) province: &'a Province,
+    pub(crate) convoyed_unit: Option<UnitType>,
+    pub(crate) from: &'a Province,
+    pub(crate) to: &'a Province,
+}
+
+impl<'a> Convoy<'a> {
+    pub fn unit(&self) -> Option<UnitType> {
+        self.unit
+    }
+
+    pub fn province(&self) -> &'a Province {
+        self.province
+    }
+
+    /// The convoyed unit's type, if the order named it.
+    pub fn convoyed_unit(&self) -> Option<UnitType> {
+        self.convoyed_unit
+    }
+
+    /// The province of the convoyed army.
+    pub fn from(&self) -> &'a Province {
+        self.from
+    }
+
+    /// The convoyed army's destination.
+    pub fn to(&self) -> &'a Province {
+        self.to
+    }
+
+    /// The convoyed move.
+    pub fn convoyed(&self) -> Order<'a> {
+        Order::Move(Move {
+            unit: self.convoyed_unit,
+            province: self.from,
+            destination: self.to,
+            coast: None,
+            via_convoy: true,
+        })
+    }
+}
+
+impl<'a> fmt::Display for Convoy<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_unit(f, self.unit, self.province)?;
+        f.write_str(" C ")?;
+        fmt_unit(f, self.convoyed_unit, self.from)?;
+        write!(f, " - {}", self.to)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::parser::parse_order;
+    use std::collections::HashSet;
+
+    #[test]
+    fn canonical_orders() {
+        let map = Map::standard();
+        let canonical = |text| parse_order(map, text).unwrap().canonicalize(map);
+        let text = |text| canonical(text).to_string();
+
+        assert_eq!(text("Mun H"), "A Munich H");
+        assert_eq!(text("Nth - Nwy"), "F North Sea - Norway");
+        assert_eq!(text("Par - Bur"), "A Paris - Burgundy");
+        assert_eq!(text("Bre - Mao"), "F Brest - Mid-Atlantic Ocean");
+        assert_eq!(text("Bre - Pic"), "Brest - Picardy");
+        assert_eq!(text("Lon - Bel"), "A London - Belgium");
+        assert_eq!(text("F Gol - Spa"), "F Gulf of Lyon - Spain (sc)");
+        assert_eq!(text("A Gas - Spa (nc)"), "A Gascony - Spain");
+        assert_eq!(text("F Mao - Spa"), "F Mid-Atlantic Ocean - Spain");
+        assert_eq!(
+            text("Mun S F Mao - Spa (nc)"),
+            "A Munich S F Mid-Atlantic Ocean - Spain"
+        );
+        assert_eq!(text("Nth C Lon - Nwy"), "F North Sea C A London - Norway");
+
+        let orders: HashSet<_> = ["A Par - Bur", "Par - Bur", "Paris - Burgundy"]
+            .iter()
+            .map(|&order| canonical(order))
+            .collect();
+        assert_eq!(orders.len(), 1);
+    }
+
+    #[test]
+    fn invalid_orders() {
+        use UnitType::{Army, Fleet};
+
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let (par, bur, mar) = (province("Par"), province("Bur"), province("Mar"));
+        let (nth, lon, nwy) = (province("Nth"), province("Lon"), province("Nwy"));
+
+        let cases = [
+            (Order::move_(Army, par, par), OrderError::MoveToSelf),
+            (
+                Order::move_to_coast(Fleet, province("Spa"), province("Spa"), Coast::North),
+                OrderError::MoveToSelf,
+            ),
+            (
+                Order::move_via_convoy(Army, lon, lon),
+                OrderError::MoveToSelf,
+            ),
+            (
+                Order::support_hold(Army, par, None, par),
+                OrderError::SupportSelf,
+            ),
+            (
+                Order::support_move(Army, par, Army, par, bur),
+                OrderError::SupportSelf,
+            ),
+            (
+                Order::support_move(Army, par, Army, bur, par),
+                OrderError::SupportIntoSelf,
+            ),
+            (
+                Order::support_move(Army, mar, Army, bur, bur),
+                OrderError::MoveToSelf,
+            ),
+            (
+                Order::convoy(Fleet, nth, Army, nth, nwy),
+                OrderError::ConvoySelf,
+            ),
+            (
+                Order::convoy(Fleet, nth, Army, lon, lon),
+                OrderError::InvalidConvoyDestination,
+            ),
+            (
+                Order::convoy(Fleet, nth, Army, lon, nth),
+                OrderError::InvalidConvoyDestination,
+            ),
+            (
+                Order::convoy(Fleet, nth, Fleet, lon, nwy),
+                OrderError::ConvoyedFleet,
+            ),
+            (
+                Order::convoy(Army, nth, Army, lon, nwy),
+                OrderError::ConvoyingArmy,
+            ),
+        ];
+        for (order, error) in cases {
+            assert_eq!(order, Err(error));
+        }
+
+        // Orders built directly are checked by validate.
+        let order = Order::Move(Move {
+            unit: None,
+            province: par,
+            destination: par,
+            coast: None,
+            via_convoy: false,
+        });
+        assert_eq!(order.validate(), Err(OrderError::MoveToSelf));
+        assert_eq!(Order::hold(Army, par).validate(), Ok(()));
+        assert!(Order::convoy(None, nth, None, lon, nwy).is_ok());
+    }
+
+    #[test]
+    fn accessors() {
+        use UnitType::{Army, Fleet};
+
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+
+        let hold = order("F Spa (sc) H");
+        assert_eq!(hold.kind(), OrderKind::Hold);
+        assert_eq!(hold.unit(), Some(Fleet));
+        assert_eq!(hold.province(), province("Spa"));
+        assert_eq!(hold.destination(), None);
+        assert_eq!(hold.target(), None);
+        assert!(!hold.is_move());
+        match &hold {
+            Order::Hold(hold) => assert_eq!(hold.coast(), Some(Coast::South)),
+            _ => unreachable!(),
+        }
+
+        let move_ = order("Mao - Spa (nc)");
+        assert_eq!(move_.kind(), OrderKind::Move);
+        assert_eq!(move_.unit(), None);
+        assert_eq!(move_.province(), province("Mao"));
+        assert_eq!(move_.destination(), Some(province("Spa")));
+        assert!(move_.is_move());
+        match &move_ {
+            Order::Move(move_) => {
+                assert_eq!(move_.destination(), province("Spa"));
+                assert_eq!(move_.coast(), Some(Coast::North));
+                assert!(!move_.via_convoy());
+            }
+            _ => unreachable!(),
+        }
+        assert!(matches!(order("A Lon - Bel via convoy"), Order::Move(m) if m.via_convoy()));
+
+        let support = order("A Mun S F Kie - Ber");
+        assert_eq!(support.kind(), OrderKind::Support);
+        assert_eq!(support.unit(), Some(Army));
+        assert_eq!(support.province(), province("Mun"));
+        assert_eq!(support.destination(), None);
+        assert_eq!(support.target(), Some(order("F Kie - Ber")));
+        match &support {
+            Order::Support(support) => {
+                assert_eq!(support.supported_unit(), Some(Fleet));
+                assert_eq!(support.from(), province("Kie"));
+                assert_eq!(support.to(), Some(province("Ber")));
+                assert!(!support.is_hold_support());
+                assert_eq!(support.supported().destination(), Some(province("Ber")));
+            }
+            _ => unreachable!(),
+        }
+        match order("A Mun S Ber") {
+            Order::Support(support) => {
+                assert_eq!(support.supported_unit(), None);
+                assert_eq!(support.from(), province("Ber"));
+                assert_eq!(support.to(), None);
+                assert!(support.is_hold_support());
+            }
+            _ => unreachable!(),
+        }
+
+        let convoy = order("F Nth C A Lon - Nwy");
+        assert_eq!(convoy.kind(), OrderKind::Convoy);
+        assert_eq!(convoy.unit(), Some(Fleet));
+        assert_eq!(convoy.province(), province("Nth"));
+        assert_eq!(convoy.target(), Some(order("A Lon - Nwy via convoy")));
+        match &convoy {
+            Order::Convoy(convoy) => {
+                assert_eq!(convoy.convoyed_unit(), Some(Army));
+                assert_eq!(convoy.from(), province("Lon"));
+                assert_eq!(convoy.to(), province("Nwy"));
+            }
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Parsing the orders of an adjustment phase: builds and disbands.
+
+use super::normalize::Normalized;
+use super::{
+    destination, furthest_remaining, located_unit, unit_type, unknown_word, verb, ParseError,
+    ParserOptions,
+};
+use crate::adjudicator::Adjustment;
+use crate::combinators::{
+    boxed, chain, complete, context, either, preceded, skip_whitespace, terminated, with_budget,
+    Boxed, Either, Parser,
+};
+use crate::map::Map;
+
+/// Parses a single build or disband, like `Build F StP/nc`, `build fleet
+/// St Petersburg (north coast)` or `Disband A Par`, with the default
+/// [`ParserOptions`].
+pub fn parse_adjustment<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+) -> Result<Adjustment<'m>, ParseError<'a>> {
+    parse_adjustment_with(map, input, &ParserOptions::default())
+}
+
+/// Parses a single build or disband, which must make up the whole of
+/// `input` apart from surrounding whitespace.
+///
+/// A build names the coast of a bicoastal province as a move does; whether
+/// it names one the province has, and only when the unit needs one, is
+/// for [`adjudicate_adjustments`](crate::adjudicator::adjudicate_adjustments)
+/// to judge.
+pub fn parse_adjustment_with<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> Result<Adjustment<'m>, ParseError<'a>> {
+    if !options.normalize {
+        return parse_normalized(map, input, options);
+    }
+    let normalized = Normalized::new(input);
+    parse_normalized(map, &normalized.text, options).map_err(|e| ParseError {
+        remaining: normalized.original(input, e.remaining),
+        line: None,
+        unknown_province: e
+            .unknown_province
+            .map(|word| normalized.original_slice(input, word)),
+        expected: None,
+        budget_exceeded: e.budget_exceeded,
+    })
+}
+
+fn parse_normalized<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> Result<Adjustment<'m>, ParseError<'a>> {
+    let parser = preceded(skip_whitespace(), adjustment(map, options));
+    let whole = complete(terminated(parser.clone(), skip_whitespace()));
+    let result = with_budget(options.max_steps, || match whole.parse(input).next() {
+        Some((adjustment, _)) => Ok(adjustment),
+        None => Err(furthest_remaining(parser, input)),
+    });
+    match result {
+        Ok(result) => result.map_err(|remaining| ParseError {
+            remaining,
+            line: None,
+            unknown_province: unknown_word(map, input, options),
+            expected: None,
+            budget_exceeded: false,
+        }),
+        Err(_) => Err(ParseError {
+            remaining: input,
+            line: None,
+            unknown_province: None,
+            expected: None,
+            budget_exceeded: true,
+        }),
+    }
+}
+
+/// `Build F StP/nc`, `builds army Paris`, `Disband A Par`, `D Par`
+fn adjustment<'a, 'm, 'p>(map: &'m Map, options: &ParserOptions) -> Boxed<'a, 'p, Adjustment<'m>>
+where
+    'a: 'p,
+    'm: 'p,
+{
+    let build = preceded(
+        terminated(verb(options, options.locale.build), skip_whitespace()),
+        chain(
+            terminated(unit_type(options), skip_whitespace()),
+            destination(map, options),
+        ),
+    )
+    .map(|(unit, (province, coast))| Adjustment::Build {
+        unit,
+        province,
+        coast,
+    });
+    let disband = preceded(
+        terminated(verb(options, options.locale.disband), skip_whitespace()),
+        located_unit(map, options),
+    )
+    .map(|(_, province)| Adjustment::Disband(province));
+    let adjustment = either(build, disband).map(|adjustment| match adjustment {
+        Either::Left(adjustment) | Either::Right(adjustment) => adjustment,
+    });
+    boxed(context("build or disband", adjustment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Coast;
+    use crate::order::UnitType;
+
+    #[test]
+    fn builds_and_disbands() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let st_petersburg_north = Adjustment::Build {
+            unit: UnitType::Fleet,
+            province: province("StP"),
+            coast: Some(Coast::North),
+        };
+        for text in [
+            "Build F St Petersburg (nc)",
+            "Build F St. Petersburg (nc)",
+            "build fleet stp/nc",
+            "B F StP north coast",
+            "Build F StP (n)",
+        ] {
+            assert_eq!(
+                parse_adjustment(map, text),
+                Ok(st_petersburg_north.clone()),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(
+            parse_adjustment(map, "Build A Paris"),
+            Ok(Adjustment::Build {
+                unit: UnitType::Army,
+                province: province("Par"),
+                coast: None,
+            })
+        );
+        assert_eq!(
+            parse_adjustment(map, "Disband A Par"),
+            Ok(Adjustment::Disband(province("Par")))
+        );
+        assert_eq!(
+            parse_adjustment(map, "disband Kiel"),
+            Ok(Adjustment::Disband(province("Kie")))
+        );
+        let error = parse_adjustment(map, "Build F Atlantis").unwrap_err();
+        assert_eq!(error.unknown_province(), Some("Atlantis"));
+        assert!(parse_adjustment(map, "Build Paris").is_err());
+        assert!(parse_adjustment(map, "A Par - Bur").is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! Players' own names for provinces.
+
+use alloc::sync::Arc;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use super::Locale;
+use crate::map::trie::Trie;
+use crate::map::Map;
+use crate::prelude::*;
+
+/// Extra names for provinces, like a community's slang: `the gulf` for the
+/// Gulf of Bothnia, or `the channel` for the English Channel.
+///
+/// Set [`ParserOptions::aliases`](super::ParserOptions::aliases) to have
+/// the parser recognize them alongside the map's own names, though never
+/// as misspellings. Each is checked against a map as it is added, and
+/// kept as the abbreviation of the province it names, so it holds for any
+/// map with the same abbreviations. Cloning is cheap.
+#[derive(Clone, Debug, Default)]
+pub struct Aliases {
+    inner: Arc<Inner>,
+}
+
+#[derive(Clone, Debug)]
+struct Inner {
+    /// Each alias, as given, and the abbreviation of its province.
+    entries: Vec<(String, String)>,
+    /// Indices into `entries`.
+    matcher: Trie,
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Inner {
+            entries: Vec::new(),
+            matcher: Trie::new(),
+        }
+    }
+}
+
+impl Aliases {
+    pub fn new() -> Self {
+        Aliases::default()
+    }
+
+    /// Makes `alias` another name for the province `province` names on
+    /// `map`, by its name, abbreviation or one of its aliases.
+    ///
+    /// Fails if `alias` already names another province, on the map or
+    /// here, or is a word of the order grammar, like `a` or `s`. Adding
+    /// one of a province's own names again does nothing.
+    pub fn add(&mut self, map: &Map, alias: &str, province: &str) -> Result<(), AliasError> {
+        let alias = alias.trim();
+        let target = map
+            .province_named(province.trim())
+            .ok_or_else(|| AliasError::new(format!("no province is called {:?}", province)))?;
+        if alias.is_empty() {
+            return Err(AliasError::new("an alias cannot be empty".to_owned()));
+        }
+        if Locale::ALL.iter().any(|locale| locale.is_keyword(alias)) {
+            return Err(AliasError::new(format!(
+                "{:?} is a word of the order grammar",
+                alias
+            )));
+        }
+        match map.province_named(alias) {
+            Some(named) if named == target => return Ok(()),
+            Some(named) => {
+                return Err(AliasError::new(format!(
+                    "{:?} already names {}",
+                    alias, named
+                )))
+            }
+            None => {}
+        }
+        if let Some(existing) = self.get(alias) {
+            if existing.eq_ignore_ascii_case(target.abbreviation()) {
+                return Ok(());
+            }
+            return Err(AliasError::new(format!(
+                "{:?} is already an alias for {}",
+                alias, existing
+            )));
+        }
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.matcher.insert(alias, inner.entries.len());
+        inner
+            .entries
+            .push((alias.to_owned(), target.abbreviation().to_owned()));
+        Ok(())
+    }
+
+    /// Reads aliases from a TOML `[aliases]` table, checking each against
+    /// `map`:
+    ///
+    /// ```toml
+    /// [aliases]
+    /// "the gulf" = "Gulf of Bothnia"
+    /// "the channel" = "Eng"
+    /// ```
+    ///
+    /// Only this much of TOML is understood: comments, the one table, and
+    /// keys and values that are plain words or quoted strings.
+    pub fn from_toml(map: &Map, text: &str) -> Result<Self, AliasError> {
+        let mut aliases = Aliases::new();
+        let mut in_table = false;
+        for (i, line) in text.lines().enumerate() {
+            let at_line = |error: AliasError| AliasError {
+                line: Some(i + 1),
+                ..error
+            };
+            let error = |message: &str| AliasError {
+                line: Some(i + 1),
+                message: message.to_owned(),
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                if line.split('#').next().unwrap_or("").trim() != "[aliases]" {
+                    return Err(error("only an [aliases] table is allowed"));
+                }
+                in_table = true;
+                continue;
+            }
+            if !in_table {
+                return Err(error("expected [aliases]"));
+            }
+            let (alias, rest) = toml_string(line).ok_or_else(|| error("expected an alias"))?;
+            let rest = rest.trim_start();
+            let rest = rest
+                .strip_prefix('=')
+                .ok_or_else(|| error("expected '=' after the alias"))?;
+            let (province, rest) =
+                toml_string(rest.trim_start()).ok_or_else(|| error("expected a province"))?;
+            let rest = rest.trim();
+            if !rest.is_empty() && !rest.starts_with('#') {
+                return Err(error("expected the end of the line"));
+            }
+            aliases.add(map, &alias, &province).map_err(at_line)?;
+        }
+        Ok(aliases)
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.entries.is_empty()
+    }
+
+    /// Every alias, with the abbreviation of its province, in the order
+    /// they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.inner
+            .entries
+            .iter()
+            .map(|(alias, province)| (alias.as_str(), province.as_str()))
+    }
+
+    /// The abbreviation of the province `alias` names, ignoring case and
+    /// accents.
+    pub(crate) fn get(&self, alias: &str) -> Option<&str> {
+        let index = self.inner.matcher.get(alias)?;
+        Some(&self.inner.entries[index].1)
+    }
+
+    /// Whether some alias starts with `text`, ignoring case and accents.
+    pub(crate) fn is_prefix(&self, text: &str) -> bool {
+        !self.is_empty() && self.inner.matcher.has_prefix(text)
+    }
+}
+
+/// Two sets of aliases are equal if they have the same aliases for the
+/// same provinces, added in the same order.
+impl PartialEq for Aliases {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.entries == other.inner.entries
+    }
+}
+
+/// A TOML key or value at the start of `text`: a bare word, or a string in
+/// double quotes with `\"` and `\\` escapes. Returns it and the rest.
+fn toml_string(text: &str) -> Option<(String, &str)> {
+    let Some(quoted) = text.strip_prefix('"') else {
+        let end = text
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(text.len());
+        return (end > 0).then(|| (text[..end].to_owned(), &text[end..]));
+    };
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &quoted[i + 1..])),
+            '\\' => value.push(chars.next()?.1),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// The error returned when an alias cannot be added.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AliasError {
+    line: Option<usize>,
+    message: String,
+}
+
+impl AliasError {
+    fn new(message: String) -> Self {
+        AliasError {
+            line: None,
+            message,
+        }
+    }
+
+    /// The 1-based line number of the problem, when reading TOML.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+}
+
+impl fmt::Display for AliasError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for AliasError {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_order, parse_order_with, ParserOptions};
+
+    #[test]
+    fn aliases_from_toml() {
+        let map = Map::standard();
+        let aliases = Aliases::from_toml(
+            map,
+            "# Our league's names\n\
+             [aliases]\n\
+             \"the gulf\" = \"Gulf of Bothnia\"\n\
+             \"the channel\" = \"Eng\" # not the TV one\n\
+             nth = \"North Sea\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            aliases.iter().collect::<Vec<_>>(),
+            [("the gulf", "Bot"), ("the channel", "Eng")]
+        );
+        let options = ParserOptions {
+            aliases,
+            ..ParserOptions::default()
+        };
+        for (text, expected) in [
+            ("F The Gulf - Swe", "F Bot - Swe"),
+            ("F the channel S F Nth - Bel", "F Eng S F Nth - Bel"),
+            ("F Bre - the Channel", "F Bre - Eng"),
+        ] {
+            assert_eq!(
+                parse_order_with(map, text, &options),
+                parse_order(map, expected),
+                "{}",
+                text
+            );
+        }
+        assert!(parse_order(map, "F the gulf - Swe").is_err());
+
+        let error = Aliases::from_toml(map, "[aliases]\nnth = \"Nwg\"\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: \"nth\" already names North Sea");
+        let mut aliases = Aliases::new();
+        aliases.add(map, "bothnia", "Bot").unwrap();
+        assert!(aliases.add(map, "Bothnia", "Bal").is_err());
+        assert!(aliases.add(map, "s", "Swe").is_err());
+        assert!(Aliases::from_toml(map, "bothnia = \"Bot\"\n").is_err());
+    }
+}
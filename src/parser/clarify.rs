@@ -0,0 +1,234 @@
+//! Asking the player which reading they meant, for chat bots that take
+//! orders in one message and a clarification in the next.
+//!
+//! [`Clarifications`] parses orders like [`interpret_order_with`], but
+//! instead of settling an ambiguous order on the grammar's preferred
+//! reading it keeps the readings under a [`ClarificationToken`], for the
+//! bot to list to the player. The player's choice is then looked up with
+//! [`Clarifications::resume`], without parsing the order again. The
+//! token is a number, so that it can be sent with the question and read
+//! back from the reply.
+
+use alloc::collections::BTreeMap;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use super::{interpret_order_with, Interpretation, ParseError, ParserOptions};
+use crate::map::Map;
+use crate::order::Order;
+use crate::prelude::*;
+
+/// Identifies an order waiting for the player to choose a reading. It is
+/// written as a number, to fit in a chat message or a button's data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClarificationToken(u64);
+
+impl fmt::Display for ClarificationToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for ClarificationToken {
+    type Err = core::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse().map(ClarificationToken)
+    }
+}
+
+/// The result of parsing an order that may need the player's help.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Parsed<'m> {
+    /// The order could be read only one way.
+    Order(Order<'m>),
+    /// The order could be read several ways, which the player must choose
+    /// between.
+    NeedsClarification(NeedsClarification<'m>),
+}
+
+/// The readings of an ambiguous order, and the token to resume with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NeedsClarification<'m> {
+    token: ClarificationToken,
+    alternatives: Vec<Order<'m>>,
+}
+
+impl<'m> NeedsClarification<'m> {
+    pub fn token(&self) -> ClarificationToken {
+        self.token
+    }
+
+    /// The readings, in the order the grammar prefers them. The player's
+    /// choice is an index into these.
+    pub fn alternatives(&self) -> &[Order<'m>] {
+        &self.alternatives
+    }
+}
+
+/// Why a clarification could not be resumed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClarificationError {
+    /// No order is waiting under the token, because it was never given
+    /// out, or was already resumed or cancelled.
+    UnknownToken(ClarificationToken),
+    /// The choice is not the index of one of the alternatives.
+    NoSuchChoice { choice: usize, alternatives: usize },
+}
+
+impl fmt::Display for ClarificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClarificationError::UnknownToken(token) => {
+                write!(f, "no order is waiting for clarification as {}", token)
+            }
+            ClarificationError::NoSuchChoice {
+                choice,
+                alternatives,
+            } => write!(
+                f,
+                "choice {} is not one of the {} readings",
+                choice, alternatives
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ClarificationError {}
+
+/// The ambiguous orders waiting for players to choose a reading.
+#[derive(Clone, Debug, Default)]
+pub struct Clarifications<'m> {
+    next: u64,
+    pending: BTreeMap<ClarificationToken, Vec<Order<'m>>>,
+}
+
+impl<'m> Clarifications<'m> {
+    pub fn new() -> Self {
+        Clarifications::default()
+    }
+
+    /// Parses a single order with the default [`ParserOptions`]. See
+    /// [`parse_order_with`](Self::parse_order_with).
+    pub fn parse_order<'a>(
+        &mut self,
+        map: &'m Map,
+        input: &'a str,
+    ) -> Result<Parsed<'m>, ParseError<'a>> {
+        self.parse_order_with(map, input, &ParserOptions::default())
+    }
+
+    /// Parses a single order. If [`interpret_order_with`] finds it
+    /// ambiguous, the readings are kept until the player's choice is
+    /// given to [`resume`](Self::resume), or the order is
+    /// [`cancel`](Self::cancel)led.
+    pub fn parse_order_with<'a>(
+        &mut self,
+        map: &'m Map,
+        input: &'a str,
+        options: &ParserOptions,
+    ) -> Result<Parsed<'m>, ParseError<'a>> {
+        Ok(match interpret_order_with(map, input, options)? {
+            Interpretation::Order(order) => Parsed::Order(order),
+            Interpretation::Ambiguous(alternatives) => {
+                self.next += 1;
+                let token = ClarificationToken(self.next);
+                self.pending.insert(token, alternatives.clone());
+                Parsed::NeedsClarification(NeedsClarification {
+                    token,
+                    alternatives,
+                })
+            }
+        })
+    }
+
+    /// The reading the player chose, by its index among the alternatives,
+    /// for the order waiting under `token`, which is then forgotten. A
+    /// choice out of range leaves the order waiting.
+    pub fn resume(
+        &mut self,
+        token: ClarificationToken,
+        choice: usize,
+    ) -> Result<Order<'m>, ClarificationError> {
+        let alternatives = self
+            .pending
+            .get(&token)
+            .ok_or(ClarificationError::UnknownToken(token))?;
+        if choice >= alternatives.len() {
+            return Err(ClarificationError::NoSuchChoice {
+                choice,
+                alternatives: alternatives.len(),
+            });
+        }
+        let mut alternatives = self.pending.remove(&token).unwrap_or_default();
+        Ok(alternatives.swap_remove(choice))
+    }
+
+    /// Forgets the order waiting under `token`, returning whether there
+    /// was one.
+    pub fn cancel(&mut self, token: ClarificationToken) -> bool {
+        self.pending.remove(&token).is_some()
+    }
+
+    /// How many orders are waiting for a choice.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::parser::parse_order;
+
+    #[test]
+    fn clarify_and_resume() {
+        let map = Map::parse(
+            "[provinces]\n\
+             Spa | Spain      | coast | | nc sc |\n\
+             Cor | Corsica    | coast | |       | C\n\
+             Wes | Western    | sea   | |       |\n\
+             [adjacencies]\n\
+             fleet Spa/nc: Wes\n\
+             fleet Spa/sc: Wes\n\
+             fleet Cor: Wes\n\
+             fleet Wes: Spa/nc Spa/sc Cor\n",
+        )
+        .unwrap();
+        let order = |text| parse_order(&map, text).unwrap();
+        let mut pending = Clarifications::new();
+        assert_eq!(
+            pending.parse_order(&map, "F Wes - Cor"),
+            Ok(Parsed::Order(order("F Wes - Cor")))
+        );
+        let question = match pending.parse_order(&map, "F Spa S C H").unwrap() {
+            Parsed::NeedsClarification(question) => question,
+            Parsed::Order(order) => panic!("{} is ambiguous", order),
+        };
+        assert_eq!(
+            question.alternatives(),
+            [order("F Spa/sc H"), order("F Spa S Cor")]
+        );
+        let token: ClarificationToken = question.token().to_string().parse().unwrap();
+        assert_eq!(
+            pending.resume(token, 2),
+            Err(ClarificationError::NoSuchChoice {
+                choice: 2,
+                alternatives: 2
+            })
+        );
+        assert_eq!(pending.resume(token, 1), Ok(order("F Spa S Cor")));
+        assert_eq!(
+            pending.resume(token, 0),
+            Err(ClarificationError::UnknownToken(token))
+        );
+        assert!(pending.is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+//! How sure the parser can be that it read an order the way the player
+//! meant it, for bots deciding whether to accept an order as read or ask
+//! the player to confirm it.
+
+use core::fmt;
+
+use super::normalize::Normalized;
+use super::{
+    interpret_order_with, Interpretation, KnownProvince, ParseError, ParseProvince, ParserOptions,
+};
+use crate::combinators::{take_while1, Parser};
+use crate::map::unicode::is_word_char;
+use crate::map::{Map, Province};
+use crate::order::Order;
+use crate::prelude::*;
+
+/// An order, with how sure the parser is of it and what it took for
+/// granted in reading it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoredOrder<'a, 'm> {
+    order: Order<'m>,
+    confidence: f64,
+    assumptions: Vec<Assumption<'a, 'm>>,
+}
+
+impl<'a, 'm> ScoredOrder<'a, 'm> {
+    pub fn order(&self) -> &Order<'m> {
+        &self.order
+    }
+
+    pub fn into_order(self) -> Order<'m> {
+        self.order
+    }
+
+    /// From 0 to 1, where 1 means the order was read without guessing.
+    pub fn confidence(&self) -> f64 {
+        self.confidence
+    }
+
+    /// What the parser assumed, in the order it came up in the input.
+    pub fn assumptions(&self) -> &[Assumption<'a, 'm>] {
+        &self.assumptions
+    }
+}
+
+/// Something the parser took for granted in reading an order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Assumption<'a, 'm> {
+    /// A word that names no province was read as a misspelling of one.
+    Misspelling {
+        /// The input as written.
+        written: &'a str,
+        province: &'m Province,
+        /// How close the spelling was, from 0 to 1.
+        similarity: f64,
+        /// The closest other province the spelling could have meant, if
+        /// any was within [`ParserOptions::fuzzy_threshold`].
+        instead_of: Option<&'m Province>,
+    },
+    /// The order could be read more than one way, and the reading the
+    /// grammar prefers was taken. See [`interpret_order`](super::interpret_order).
+    Ambiguous {
+        reading: Order<'m>,
+        others: Vec<Order<'m>>,
+    },
+}
+
+impl<'a, 'm> fmt::Display for Assumption<'a, 'm> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Assumption::Misspelling {
+                written,
+                province,
+                instead_of,
+                ..
+            } => {
+                write!(f, "interpreted '{}' as {}", written, province.name())?;
+                match instead_of {
+                    Some(other) => write!(f, ", not {}", other.name()),
+                    None => Ok(()),
+                }
+            }
+            Assumption::Ambiguous { reading, others } => {
+                write!(f, "read as {}", reading)?;
+                for (i, other) in others.iter().enumerate() {
+                    let separator = if i == 0 { ", not" } else { " or" };
+                    write!(f, "{} {}", separator, other)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Parses a single order like [`parse_order`](super::parse_order), and
+/// scores it, with the default [`ParserOptions`]. See
+/// [`parse_order_scored_with`].
+pub fn parse_order_scored<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+) -> Result<ScoredOrder<'a, 'm>, ParseError<'a>> {
+    parse_order_scored_with(map, input, &ParserOptions::default())
+}
+
+/// Parses a single order, taking the reading [`interpret_order_with`]
+/// prefers, and scores how sure that reading is.
+///
+/// An order read exactly as written scores 1. Each misspelled province
+/// name multiplies the score by how close the spelling was, and halves
+/// it again if the spelling was as close to some other province. An order
+/// that could be read `n` ways scores `1 / n`.
+pub fn parse_order_scored_with<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> Result<ScoredOrder<'a, 'm>, ParseError<'a>> {
+    let (order, mut assumptions) = match interpret_order_with(map, input, options)? {
+        Interpretation::Order(order) => (order, Vec::new()),
+        Interpretation::Ambiguous(mut orders) => {
+            let reading = orders.remove(0);
+            let assumption = Assumption::Ambiguous {
+                reading: reading.clone(),
+                others: orders,
+            };
+            (reading, vec![assumption])
+        }
+    };
+    if let Some(threshold) = options.fuzzy() {
+        if options.normalize {
+            let normalized = Normalized::new(input);
+            for (written, province, similarity, instead_of) in
+                misspellings(map, &normalized.text, &order, threshold, options)
+            {
+                assumptions.push(Assumption::Misspelling {
+                    written: normalized.original_slice(input, written),
+                    province,
+                    similarity,
+                    instead_of,
+                });
+            }
+        } else {
+            for (written, province, similarity, instead_of) in
+                misspellings(map, input, &order, threshold, options)
+            {
+                assumptions.push(Assumption::Misspelling {
+                    written,
+                    province,
+                    similarity,
+                    instead_of,
+                });
+            }
+        }
+    }
+    let confidence = assumptions
+        .iter()
+        .map(|assumption| match assumption {
+            Assumption::Misspelling {
+                similarity,
+                instead_of: Some(_),
+                ..
+            } => similarity / 2.0,
+            Assumption::Misspelling { similarity, .. } => *similarity,
+            Assumption::Ambiguous { others, .. } => 1.0 / (others.len() + 1) as f64,
+        })
+        .product();
+    Ok(ScoredOrder {
+        order,
+        confidence,
+        assumptions,
+    })
+}
+
+/// A misspelling in `text`, with the province it was read as, how close
+/// it was, and the closest other province it might have meant.
+type Misspelling<'t, 'm> = (&'t str, &'m Province, f64, Option<&'m Province>);
+
+/// The words of `text`, from which `order` was parsed, that are neither
+/// keywords nor province names but misspell one of the provinces in the
+/// order.
+fn misspellings<'t, 'm>(
+    map: &'m Map,
+    text: &'t str,
+    order: &Order<'m>,
+    threshold: f64,
+    options: &ParserOptions,
+) -> Vec<Misspelling<'t, 'm>> {
+    let mut named = vec![order.province()];
+    named.extend(order.destination());
+    if let Some(target) = order.target() {
+        named.push(target.province());
+        named.extend(target.destination());
+    }
+    let word = take_while1(is_word_char);
+    let province = KnownProvince {
+        names: ParseProvince::new(map),
+        aliases: options.aliases.clone(),
+    };
+    let mut found = Vec::new();
+    let mut rest = text.trim_start_matches(|c: char| !is_word_char(c));
+    while let Some((written, after)) = word.parse(rest).next() {
+        rest = if let Some((_, after)) = province.parse(rest).next() {
+            after
+        } else if options.locale.is_keyword(written) {
+            after
+        } else {
+            let candidates = map.fuzzy_match_provinces(rest, threshold);
+            let matched = candidates
+                .iter()
+                .find(|(province, _, _)| named.iter().any(|named| named.id() == province.id()));
+            match matched {
+                Some(&(province, len, similarity)) => {
+                    let instead_of = candidates
+                        .iter()
+                        .map(|(other, _, _)| *other)
+                        .find(|other| other.id() != province.id());
+                    found.push((&rest[..len], province, similarity, instead_of));
+                    &rest[len..]
+                }
+                None => after,
+            }
+        };
+        rest = rest.trim_start_matches(|c: char| !is_word_char(c));
+    }
+    found
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::parser::parse_order;
+
+    #[test]
+    fn scores_and_assumptions() {
+        let map = Map::standard();
+        let scored = parse_order_scored(map, "A Par - Bur").unwrap();
+        assert_eq!(scored.confidence(), 1.0);
+        assert_eq!(scored.assumptions(), []);
+
+        let scored = parse_order_scored(map, "Army Paris moves to Burgandy").unwrap();
+        assert_eq!(scored.order(), &parse_order(map, "A Par - Bur").unwrap());
+        assert_eq!(scored.confidence(), 0.875);
+        let messages: Vec<_> = scored.assumptions().iter().map(|a| a.to_string()).collect();
+        assert_eq!(messages, ["interpreted 'Burgandy' as Burgundy"]);
+
+        let options = ParserOptions {
+            fuzzy_threshold: 0.5,
+            ..ParserOptions::default()
+        };
+        let scored = parse_order_scored_with(map, "A Tyrolea - Vie", &options).unwrap();
+        assert!(scored.confidence() < 0.5);
+        assert_eq!(
+            scored.assumptions()[0].to_string(),
+            "interpreted 'Tyrolea' as Tyrolia, not Tyrrhenian Sea"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Finding the orders in an email: the lines a player wrote, without the
+//! headers, the quoted message they replied to, or their signature.
+
+use super::{parse_order_with, ParseError, ParserOptions};
+use crate::map::Map;
+use crate::order::Order;
+use crate::prelude::*;
+
+/// Parses the orders in the body of an email like
+/// [`parse_orders_lossy`](super::parse_orders_lossy), with the default
+/// [`ParserOptions`].
+pub fn parse_email_orders<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+) -> (Vec<Order<'m>>, Vec<ParseError<'a>>) {
+    parse_email_orders_with(map, input, &ParserOptions::default())
+}
+
+/// Parses the orders in an email, one per line, carrying on past lines
+/// that are not orders, after leaving out:
+///
+/// - mail headers like `From:` and `Subject:`, whether at the top of the
+///   message or of one forwarded in it;
+/// - quoted lines, which start with `>`, and the `On ... wrote:` line
+///   before them;
+/// - everything from a signature separator (`--`) or an
+///   `-----Original Message-----` line on;
+/// - "Sent from my ..." lines.
+///
+/// Returns the orders that parsed, and an error for each line left that
+/// did not, with its line number in the whole email.
+pub fn parse_email_orders_with<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> (Vec<Order<'m>>, Vec<ParseError<'a>>) {
+    let mut orders = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in body_lines(input) {
+        match parse_order_with(map, line, options) {
+            Ok(order) => orders.push(order),
+            Err(error) => errors.push(error.at_line(i + 1)),
+        }
+    }
+    (orders, errors)
+}
+
+/// The non-blank lines of the email `input` that its sender wrote, with
+/// their 0-based line numbers.
+fn body_lines(input: &str) -> Vec<(usize, &str)> {
+    let mut body = Vec::new();
+    let mut in_headers = input
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(is_header);
+    for (i, line) in input.lines().enumerate() {
+        let trimmed = line.trim();
+        if in_headers {
+            // Headers run to the first blank line, and long ones carry on
+            // over lines that start with whitespace.
+            in_headers = !trimmed.is_empty();
+            continue;
+        }
+        if trimmed == "--" || is_original_message(trimmed) {
+            break;
+        }
+        let skipped = trimmed.is_empty()
+            || trimmed.starts_with('>')
+            || trimmed.ends_with(" wrote:")
+            || trimmed.starts_with("Sent from my ")
+            || is_header(trimmed);
+        if !skipped {
+            body.push((i, line));
+        }
+    }
+    body
+}
+
+/// Whether `line` is a mail header, like `Subject: orders`.
+fn is_header(line: &str) -> bool {
+    const HEADERS: &[&str] = &[
+        "bcc",
+        "cc",
+        "content-transfer-encoding",
+        "content-type",
+        "date",
+        "from",
+        "in-reply-to",
+        "message-id",
+        "mime-version",
+        "received",
+        "references",
+        "reply-to",
+        "return-path",
+        "sender",
+        "sent",
+        "subject",
+        "to",
+    ];
+    match line.split_once(':') {
+        Some((name, _)) => {
+            let name = name.to_ascii_lowercase();
+            HEADERS.contains(&name.as_str()) || name.starts_with("x-")
+        }
+        None => false,
+    }
+}
+
+/// Whether `line` begins a message quoted in full below a reply, as
+/// `-----Original Message-----` or `---------- Forwarded message ---------`.
+fn is_original_message(line: &str) -> bool {
+    let words = line.trim_matches('-').trim().to_ascii_lowercase();
+    line.starts_with("-----") && (words == "original message" || words == "forwarded message")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_orders;
+
+    #[test]
+    fn orders_from_emails() {
+        let map = Map::standard();
+
+        let (orders, errors) = parse_email_orders(map, include_str!("emails/reply.eml"));
+        assert_eq!(
+            orders,
+            parse_orders(map, "A Mun - Ruh\nA Ber - Kie\nF Kie - Den").unwrap()
+        );
+        // The greeting and sign-off are not orders, but nothing quoted or
+        // in the headers or signature was tried.
+        let lines: Vec<_> = errors.iter().map(|e| e.line()).collect();
+        assert_eq!(lines, [Some(8), Some(10), Some(16), Some(17)]);
+
+        let (orders, errors) = parse_email_orders(map, include_str!("emails/outlook.eml"));
+        assert_eq!(
+            orders,
+            parse_orders(
+                map,
+                "A War - Gal\nF Sev - Bla\nF StP/sc - Bot\nA Mos S A War - Gal"
+            )
+            .unwrap()
+        );
+        assert_eq!(errors, []);
+    }
+}
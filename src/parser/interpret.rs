@@ -0,0 +1,281 @@
+//! Every reading of an order, for input that might mean more than one.
+//!
+//! The grammar reads a `c` after a coast letter as part of the coast, as
+//! in `Spa s c`, and as the convoy verb anywhere else, but on maps where a
+//! province is called `C`, an order like `F Spa S C H` can still be read
+//! two ways: a hold on the south coast, or a support of the unit in `C`.
+//! [`interpret_order`] finds every reading, applies the precedence rules,
+//! and reports what is left. [`parse_all`] lists every reading, before
+//! the rules.
+
+use super::normalize::Normalized;
+use super::{order, parse_order_with, ParseError, ParserOptions};
+use crate::combinators::{complete, preceded, skip_whitespace, terminated, with_budget, Parser};
+use crate::map::{Coast, Map, Province, ProvinceKind};
+use crate::order::{Order, SupportedOrder, UnitType};
+use crate::prelude::*;
+
+/// The readings of an order that survive the precedence rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Interpretation<'m> {
+    /// There was only one.
+    Order(Order<'m>),
+    /// There were several, which the rules could not choose between, in
+    /// the order the grammar prefers them. [`parse_order`](super::parse_order)
+    /// would give the first.
+    Ambiguous(Vec<Order<'m>>),
+}
+
+impl<'m> Interpretation<'m> {
+    /// The reading the grammar prefers.
+    pub fn preferred(&self) -> &Order<'m> {
+        match self {
+            Interpretation::Order(order) => order,
+            Interpretation::Ambiguous(orders) => &orders[0],
+        }
+    }
+
+    pub fn is_ambiguous(&self) -> bool {
+        matches!(self, Interpretation::Ambiguous(_))
+    }
+}
+
+/// Reads a single order every way the grammar allows, with the default
+/// [`ParserOptions`]. See [`interpret_order_with`].
+pub fn interpret_order<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+) -> Result<Interpretation<'m>, ParseError<'a>> {
+    interpret_order_with(map, input, &ParserOptions::default())
+}
+
+/// Reads a single order every way the grammar allows, and settles between
+/// the readings by these rules, in turn:
+///
+/// 1. Province names spelled right beat misspellings. Input that can
+///    only be read with a misspelling gets the one reading
+///    [`parse_order_with`] gives, closest match first.
+/// 2. Readings that do not make sense on their own terms, such as a move
+///    to where the unit already is, are dropped.
+/// 3. Readings whose order names a coast its province does not have are
+///    dropped.
+/// 4. Readings that put a fleet inland or an army at sea are dropped.
+///
+/// If every reading breaks rule 2, 3 or 4, the one the grammar prefers is
+/// kept. If the search runs out of [`ParserOptions::max_steps`] after the
+/// first reading, that reading is all that is returned.
+pub fn interpret_order_with<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> Result<Interpretation<'m>, ParseError<'a>> {
+    let first = parse_order_with(map, input, options)?;
+    let exact = ParserOptions {
+        fuzzy_threshold: 1.0,
+        ..options.clone()
+    };
+    let readings = match readings(map, input, &exact) {
+        (readings, true) if readings.len() > 1 => readings,
+        _ => return Ok(Interpretation::Order(first)),
+    };
+    let mut sensible: Vec<_> = readings
+        .into_iter()
+        .filter(|order| order.validate().is_ok() && fits(order))
+        .collect();
+    Ok(match sensible.len() {
+        0 => Interpretation::Order(first),
+        1 => Interpretation::Order(sensible.remove(0)),
+        _ => Interpretation::Ambiguous(sensible),
+    })
+}
+
+/// Every reading of a single order, with the default [`ParserOptions`].
+/// See [`parse_all_with`].
+pub fn parse_all<'m>(map: &'m Map, input: &str) -> impl Iterator<Item = Order<'m>> {
+    parse_all_with(map, input, &ParserOptions::default())
+}
+
+/// Every reading the grammar finds of `input` as a single order, in the
+/// order it finds them, without the precedence rules of
+/// [`interpret_order_with`]: readings that make no sense, and, in lenient
+/// mode, readings of a misspelled name as each province it is close to,
+/// are all there. The first is the one [`parse_order_with`] gives.
+///
+/// Input that is not an order has no readings. If the search runs out of
+/// [`ParserOptions::max_steps`], the readings found by then are all there
+/// are.
+pub fn parse_all_with<'m>(
+    map: &'m Map,
+    input: &str,
+    options: &ParserOptions,
+) -> impl Iterator<Item = Order<'m>> {
+    readings(map, input, options).0.into_iter()
+}
+
+/// The distinct readings of `input` as an order, in the order the grammar
+/// yields them, and whether the search for them finished within the
+/// options' budget.
+fn readings<'m>(map: &'m Map, input: &str, options: &ParserOptions) -> (Vec<Order<'m>>, bool) {
+    let normalized;
+    let text = if options.normalize {
+        normalized = Normalized::new(input);
+        normalized.text.as_str()
+    } else {
+        input
+    };
+    let parser = preceded(skip_whitespace(), order(map, options));
+    let whole = complete(terminated(parser, skip_whitespace()));
+    let mut readings: Vec<Order<'m>> = Vec::new();
+    let finished = with_budget(options.max_steps, || {
+        for (order, _) in whole.parse(text) {
+            if !readings.contains(&order) {
+                readings.push(order);
+            }
+        }
+    });
+    (readings, finished.is_ok())
+}
+
+/// Whether every coast `order` names is on its province, and every unit
+/// it names could stand where it says.
+fn fits(order: &Order) -> bool {
+    let unit = (order.unit(), order.province());
+    match order {
+        Order::Hold(hold) => fits_unit(unit) && fits_coast(hold.province(), hold.coast()),
+        Order::Move(move_) => fits_unit(unit) && fits_coast(move_.destination(), move_.coast()),
+        Order::Support(support) => {
+            let supported = support.supported();
+            fits_unit(unit)
+                && fits_unit((supported.unit(), supported.province()))
+                && match supported {
+                    SupportedOrder::Hold(hold) => fits_coast(hold.province(), hold.coast()),
+                    SupportedOrder::Move(move_) => fits_coast(move_.destination(), move_.coast()),
+                }
+        }
+        Order::Convoy(convoy) => {
+            fits_unit(unit) && fits_unit((convoy.convoyed_unit(), convoy.from()))
+        }
+    }
+}
+
+fn fits_coast(province: &Province, coast: Option<Coast>) -> bool {
+    coast.is_none_or(|coast| province.coasts().contains(&coast))
+}
+
+fn fits_unit((unit, province): (Option<UnitType>, &Province)) -> bool {
+    !matches!(
+        (unit, province.kind()),
+        (Some(UnitType::Fleet), ProvinceKind::Land) | (Some(UnitType::Army), ProvinceKind::Sea)
+    )
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::parser::parse_order;
+
+    #[test]
+    fn convoy_and_coast() {
+        let standard = Map::standard();
+        let order = |text| parse_order(standard, text).unwrap();
+        for text in [
+            "F Mao - Spa s c",
+            "F Mao - Spa s.c",
+            "F Mao - Spa (s)",
+            "F Mao - Spa/s",
+        ] {
+            assert_eq!(
+                interpret_order(standard, text),
+                Ok(Interpretation::Order(order("F Mao - Spa/sc"))),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(
+            interpret_order(standard, "A Par - Burgandy"),
+            Ok(Interpretation::Order(order("A Par - Bur")))
+        );
+        assert_eq!(
+            interpret_order(standard, "F Spa s c C A Mar - Wes"),
+            Ok(Interpretation::Order(order("F Spa/sc C A Mar - Wes")))
+        );
+
+        let map = Map::parse(
+            "[provinces]\n\
+             Spa | Spain      | coast | | nc sc |\n\
+             Cor | Corsica    | coast | |       | C\n\
+             Wes | Western    | sea   | |       |\n\
+             Lyo | Lyon       | sea   | |       |\n\
+             Por | Portugal   | coast | |       |\n\
+             [adjacencies]\n\
+             fleet Spa/nc: Wes\n\
+             fleet Spa/sc: Wes\n\
+             fleet Cor: Wes Lyo\n\
+             fleet Wes: Spa/nc Spa/sc Cor Lyo Por\n\
+             fleet Por: Wes\n\
+             fleet Lyo: Cor Wes\n",
+        )
+        .unwrap();
+        let order = |text| parse_order(&map, text).unwrap();
+        assert_eq!(
+            interpret_order(&map, "F Spa S C H"),
+            Ok(Interpretation::Ambiguous(vec![
+                order("F Spa/sc H"),
+                order("F Spa S Cor"),
+            ]))
+        );
+        // Portugal has no south coast, so the `c` must be Corsica.
+        assert_eq!(
+            interpret_order(&map, "F Por S C H"),
+            Ok(Interpretation::Order(order("F Por S Cor")))
+        );
+    }
+
+    #[test]
+    fn all_readings() {
+        let map = Map::parse(
+            "[provinces]\n\
+             Spa | Spain      | coast | | nc sc |\n\
+             Cor | Corsica    | coast | |       | C\n\
+             Wes | Western    | sea   | |       |\n\
+             Por | Portugal   | coast | |       |\n\
+             [adjacencies]\n\
+             fleet Spa/nc: Wes\n\
+             fleet Spa/sc: Wes\n\
+             fleet Cor: Wes\n\
+             fleet Wes: Spa/nc Spa/sc Cor Por\n\
+             fleet Por: Wes\n",
+        )
+        .unwrap();
+        let readings = |map, text, options: &ParserOptions| -> Vec<String> {
+            parse_all_with(map, text, options)
+                .map(|order| order.to_string())
+                .collect()
+        };
+        let options = ParserOptions::default();
+        assert_eq!(
+            readings(&map, "F Spa - Wes", &options),
+            ["F Spain - Western"]
+        );
+        assert!(readings(&map, "F Spa - Atlantis", &options).is_empty());
+        assert_eq!(
+            readings(&map, "F Spa S C H", &options),
+            ["F Spain (sc) H", "F Spain S Corsica"]
+        );
+        // Portugal has no south coast, so the grammar does not read one.
+        assert_eq!(
+            readings(&map, "F Por S C H", &options),
+            ["F Portugal S Corsica"]
+        );
+
+        let standard = Map::standard();
+        let loose = ParserOptions {
+            fuzzy_threshold: 0.5,
+            ..ParserOptions::default()
+        };
+        let all = readings(standard, "A Tyrolea - Vie", &loose);
+        let first = parse_order_with(standard, "A Tyrolea - Vie", &loose).unwrap();
+        assert_eq!(all[0], first.to_string());
+        assert!(all.iter().any(|order| order == "A Tyrrhenian Sea - Vienna"));
+    }
+}
@@ -0,0 +1,297 @@
+//! The words of the order grammar, in the languages players write orders
+//! in.
+
+/// The words for one part of an order, in each [`Dialect`](super::Dialect).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Words {
+    pub short: &'static [&'static str],
+    pub long: &'static [&'static str],
+}
+
+/// The keywords of the order grammar in one language, which
+/// [`ParserOptions::locale`](super::ParserOptions::locale) chooses.
+///
+/// Words are matched as whole words, ignoring case, and may be more than
+/// one word long, like `moves to`. The arrows of the short dialect, like
+/// `-` and `->`, are the same in every language. The filler words are
+/// only accepted in [lenient](super::Mode::Lenient) mode. Province names
+/// in the language come from the map: see
+/// [`Map::with_names`](crate::map::Map::with_names).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Locale {
+    /// The language's name, in English.
+    pub name: &'static str,
+    pub army: Words,
+    pub fleet: Words,
+    pub hold: Words,
+    /// The words for a move, besides the arrows.
+    pub moves: Words,
+    pub support: Words,
+    pub convoy: Words,
+    /// The words after a move that say it goes by convoy.
+    pub via_convoy: Words,
+    pub build: Words,
+    pub disband: Words,
+    /// Filler before a unit or province, like "the".
+    pub article: &'static [&'static str],
+    /// Filler after a hold, like "in place".
+    pub in_place: &'static [&'static str],
+    /// Filler after a unit supported to hold, like "to hold".
+    pub to_hold: &'static [&'static str],
+    /// Filler before a unit supported to move, like "the move of".
+    pub the_move: &'static [&'static str],
+}
+
+impl Locale {
+    pub const ENGLISH: Locale = Locale {
+        name: "English",
+        army: Words {
+            short: &["a"],
+            long: &["army"],
+        },
+        fleet: Words {
+            short: &["f"],
+            long: &["fleet"],
+        },
+        hold: Words {
+            short: &["h"],
+            long: &["holds", "hold"],
+        },
+        moves: Words {
+            short: &["m"],
+            long: &["moves to", "move to", "moves", "move", "to"],
+        },
+        support: Words {
+            short: &["s"],
+            long: &["supports", "support"],
+        },
+        convoy: Words {
+            short: &["c"],
+            long: &["convoys", "convoy"],
+        },
+        via_convoy: Words {
+            short: &["vc"],
+            long: &["via convoy", "by convoy"],
+        },
+        build: Words {
+            short: &["b"],
+            long: &["build", "builds"],
+        },
+        disband: Words {
+            short: &["d"],
+            long: &["disband", "disbands"],
+        },
+        article: &["the"],
+        in_place: &["in place"],
+        to_hold: &["to hold", "in place", "holding"],
+        the_move: &["the move of", "the move", "the", "move"],
+    };
+
+    /// `A Mar S A Par - Bur`, `Armée Paris tient`, `Flotte Eng
+    /// convoie Armée London vers Brest`.
+    pub const FRENCH: Locale = Locale {
+        name: "French",
+        army: Words {
+            short: &["a"],
+            long: &["armée", "armee"],
+        },
+        fleet: Words {
+            short: &["f"],
+            long: &["flotte"],
+        },
+        hold: Words {
+            short: &["t"],
+            long: &["tient", "reste"],
+        },
+        moves: Words {
+            short: &[],
+            long: &[
+                "se déplace vers",
+                "se deplace vers",
+                "va vers",
+                "va en",
+                "vers",
+            ],
+        },
+        support: Words {
+            short: &["s"],
+            long: &["soutient", "soutien"],
+        },
+        convoy: Words {
+            short: &["c"],
+            long: &["convoie", "convoi"],
+        },
+        via_convoy: Words {
+            short: &["vc"],
+            long: &["par convoi", "via convoi"],
+        },
+        build: Words {
+            short: &["c"],
+            long: &["construit", "construire"],
+        },
+        disband: Words {
+            short: &["d"],
+            long: &["dissout", "dissoudre"],
+        },
+        article: &["la", "le", "les"],
+        in_place: &["sur place"],
+        to_hold: &["sur place", "qui tient"],
+        the_move: &["le mouvement de", "le mouvement", "la", "le"],
+    };
+
+    /// `A Mar U A Par - Bur`, `Armee Paris hält`, `Flotte Eng konvoiert
+    /// Armee London nach Brest`.
+    pub const GERMAN: Locale = Locale {
+        name: "German",
+        army: Words {
+            short: &["a"],
+            long: &["armee"],
+        },
+        fleet: Words {
+            short: &["f"],
+            long: &["flotte"],
+        },
+        hold: Words {
+            short: &["h"],
+            long: &["hält", "halt", "haelt"],
+        },
+        moves: Words {
+            short: &[],
+            long: &["zieht nach", "nach"],
+        },
+        support: Words {
+            short: &["u"],
+            long: &["unterstützt", "unterstuetzt"],
+        },
+        convoy: Words {
+            short: &["k"],
+            long: &["konvoiert", "transportiert"],
+        },
+        via_convoy: Words {
+            short: &[],
+            long: &["per konvoi", "über konvoi", "ueber konvoi"],
+        },
+        build: Words {
+            short: &["b"],
+            long: &["baut"],
+        },
+        disband: Words {
+            short: &[],
+            long: &["löst auf", "loest auf", "entfernt"],
+        },
+        article: &["die", "den", "der"],
+        in_place: &["auf der stelle"],
+        to_hold: &["beim halten"],
+        the_move: &["den zug von", "den zug", "die", "den", "der"],
+    };
+
+    /// Every locale shipped.
+    pub const ALL: [Locale; 3] = [Locale::ENGLISH, Locale::FRENCH, Locale::GERMAN];
+
+    /// Whether `word` is one of the locale's keywords, or one of the words
+    /// of a keyword of more than one, ignoring case.
+    pub fn is_keyword(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        let words = [
+            self.army,
+            self.fleet,
+            self.hold,
+            self.moves,
+            self.support,
+            self.convoy,
+            self.via_convoy,
+            self.build,
+            self.disband,
+        ];
+        let fillers = [self.article, self.in_place, self.to_hold, self.the_move];
+        words
+            .iter()
+            .flat_map(|words| words.short.iter().chain(words.long))
+            .chain(fillers.iter().copied().flatten())
+            .flat_map(|keyword| keyword.split(' '))
+            .any(|keyword| keyword == word)
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::ENGLISH
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::map::Map;
+    use crate::parser::{parse_adjustment_with, parse_order, parse_order_with, ParserOptions};
+
+    #[test]
+    fn orders_in_other_languages() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let in_locale = |locale| ParserOptions {
+            locale,
+            ..ParserOptions::default()
+        };
+        let french = in_locale(Locale::FRENCH);
+        let german = in_locale(Locale::GERMAN);
+        let read = |text, options: &ParserOptions| parse_order_with(map, text, options);
+
+        let same = [
+            (
+                "A Par S A Mar - Bur",
+                "A Paris soutient A Mar - Bur",
+                "A Par U A Mar - Bur",
+            ),
+            ("A Par H", "Armée Paris tient", "Armee Paris Hält"),
+            (
+                "A Par - Bur",
+                "Armée Par va vers Bur",
+                "Armee Par zieht nach Bur",
+            ),
+            (
+                "F Eng C A Lon - Bre",
+                "F Eng convoie A Lon - Bre",
+                "Flotte Eng konvoiert A Lon nach Bre",
+            ),
+            (
+                "A Lon - Bre via convoy",
+                "A Lon - Bre par convoi",
+                "A Lon nach Bre per Konvoi",
+            ),
+            (
+                "A Par S A Bur H",
+                "A Paris soutient Armée Bur sur place",
+                "A Par U die A Bur beim Halten",
+            ),
+        ];
+        for (english, in_french, in_german) in same {
+            assert_eq!(
+                read(in_french, &french),
+                Ok(order(english)),
+                "{}",
+                in_french
+            );
+            assert_eq!(
+                read(in_german, &german),
+                Ok(order(english)),
+                "{}",
+                in_german
+            );
+        }
+
+        // The words are the locale's, not English ones as well.
+        assert!(read("A Par supports A Mar - Bur", &french).is_err());
+        assert!(read("A Paris soutient A Mar - Bur", &ParserOptions::default()).is_err());
+        assert_eq!(
+            read("Armée Paris soutient Atlantis", &french)
+                .unwrap_err()
+                .unknown_province(),
+            Some("Atlantis")
+        );
+        assert_eq!(
+            parse_adjustment_with(map, "Construit A Par", &french),
+            parse_adjustment_with(map, "Build A Par", &ParserOptions::default())
+        );
+    }
+}
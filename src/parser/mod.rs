@@ -0,0 +1,1555 @@
+//! Parsing orders from text.
+//!
+//! The grammar accepts the usual shorthand (`A Par - Bur`, `F Nth C A Lon -
+//! Nwy`) as well as spelled-out forms (`Army Paris moves to Burgundy`).
+//! Province names, abbreviations and aliases are all recognized, ignoring
+//! case. [`ParserOptions`] narrows or widens what is accepted, and can pin
+//! it to a [`GrammarVersion`] as the grammar grows.
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::combinators::{
+    alt, any_tag, boxed, chain, char_where, complete, context, delimited, either, memo, not,
+    optional, preceded, skip_whitespace, tag, take_while1, terminated, with_budget, Boxed, Either,
+    Parser,
+};
+#[cfg(feature = "std")]
+use crate::combinators::{parse_with_trace, Trace};
+use crate::map::unicode::is_word_char;
+use crate::map::{Coast, Map, Province};
+use crate::order::{Convoy, Hold, Move, Order, Support, SupportedOrder, UnitType};
+use crate::power::Power;
+use crate::prelude::*;
+
+#[cfg(feature = "std")]
+mod adjustment;
+mod aliases;
+mod clarify;
+mod confidence;
+mod email;
+mod interpret;
+mod locale;
+mod normalize;
+mod options;
+mod snippet;
+
+#[cfg(feature = "std")]
+pub use adjustment::{parse_adjustment, parse_adjustment_with};
+pub use aliases::{AliasError, Aliases};
+pub use clarify::{
+    ClarificationError, ClarificationToken, Clarifications, NeedsClarification, Parsed,
+};
+pub use confidence::{parse_order_scored, parse_order_scored_with, Assumption, ScoredOrder};
+pub use email::{parse_email_orders, parse_email_orders_with};
+pub use interpret::{
+    interpret_order, interpret_order_with, parse_all, parse_all_with, Interpretation,
+};
+pub use locale::{Locale, Words};
+pub use normalize::normalize;
+pub use options::{Dialect, GrammarVersion, Mode, ParserOptions};
+pub use snippet::Snippet;
+
+use normalize::Normalized;
+
+/// The error returned when input is not a valid order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseError<'a> {
+    remaining: &'a str,
+    line: Option<usize>,
+    unknown_province: Option<&'a str>,
+    expected: Option<Expected<'a>>,
+    budget_exceeded: bool,
+}
+
+/// The part of an order the parser was looking for when it gave up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Expected<'a> {
+    label: &'static str,
+    /// The word before where the part was expected, if any.
+    after: Option<&'a str>,
+}
+
+impl<'a> ParseError<'a> {
+    /// The input left over after the longest partial parse.
+    pub fn remaining(&self) -> &'a str {
+        self.remaining
+    }
+
+    /// The 1-based line number of the failed order, when parsing several
+    /// orders at once.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// The word that was taken to be a province name but matched none, if
+    /// that is why the order failed to parse.
+    pub fn unknown_province(&self) -> Option<&'a str> {
+        self.unknown_province
+    }
+
+    /// The part of the order, such as "province", that the parser was
+    /// looking for where it got furthest, if it can tell. It can only tell
+    /// with the `std` feature.
+    pub fn expected(&self) -> Option<&'static str> {
+        self.expected.map(|expected| expected.label)
+    }
+
+    /// The word just before where the [`expected`](Self::expected) part
+    /// was missing, unless that was the start of the order.
+    pub fn expected_after(&self) -> Option<&'a str> {
+        self.expected.and_then(|expected| expected.after)
+    }
+
+    /// Whether the parser gave up because the input was too ambiguous to
+    /// search within [`ParserOptions::max_steps`].
+    pub fn budget_exceeded(&self) -> bool {
+        self.budget_exceeded
+    }
+
+    /// Copies the error's text, so that it can outlive the input.
+    pub fn into_owned(self) -> OwnedParseError {
+        OwnedParseError {
+            remaining: self.remaining.to_owned(),
+            line: self.line,
+            unknown_province: self.unknown_province.map(str::to_owned),
+            expected: self
+                .expected
+                .map(|expected| (expected.label, expected.after.map(str::to_owned))),
+            budget_exceeded: self.budget_exceeded,
+        }
+    }
+
+    fn at_line(self, line: usize) -> Self {
+        ParseError {
+            line: Some(line),
+            ..self
+        }
+    }
+}
+
+impl<'a> fmt::Display for ParseError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if self.budget_exceeded {
+            f.write_str("order too ambiguous to parse")
+        } else if let Some(word) = self.unknown_province {
+            write!(f, "unknown province {:?}", word)
+        } else if let Some(expected) = self.expected {
+            write!(f, "expected {}", expected.label)?;
+            match expected.after {
+                Some(word) => write!(f, " after '{}'", word),
+                None => Ok(()),
+            }
+        } else if self.remaining.is_empty() {
+            f.write_str("unexpected end of order")
+        } else {
+            write!(f, "could not parse order at {:?}", self.remaining)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Error for ParseError<'a> {}
+
+/// A [`ParseError`] that owns its text, returned by the functions that
+/// parse input they do not borrow.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedParseError {
+    remaining: String,
+    line: Option<usize>,
+    unknown_province: Option<String>,
+    expected: Option<(&'static str, Option<String>)>,
+    budget_exceeded: bool,
+}
+
+impl OwnedParseError {
+    /// The input left over after the longest partial parse.
+    pub fn remaining(&self) -> &str {
+        &self.remaining
+    }
+
+    /// The 1-based line number of the failed order, when parsing several
+    /// orders at once.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// The word that was taken to be a province name but matched none, if
+    /// that is why the order failed to parse.
+    pub fn unknown_province(&self) -> Option<&str> {
+        self.unknown_province.as_deref()
+    }
+
+    /// The part of the order the parser was looking for where it got
+    /// furthest, if it can tell.
+    pub fn expected(&self) -> Option<&'static str> {
+        self.expected.as_ref().map(|(label, _)| *label)
+    }
+
+    /// The word just before where the expected part was missing.
+    pub fn expected_after(&self) -> Option<&str> {
+        self.expected
+            .as_ref()
+            .and_then(|(_, after)| after.as_deref())
+    }
+
+    /// Whether the parser gave up because the input was too ambiguous.
+    pub fn budget_exceeded(&self) -> bool {
+        self.budget_exceeded
+    }
+
+    /// The error, borrowing its text.
+    pub fn as_parse_error(&self) -> ParseError<'_> {
+        ParseError {
+            remaining: &self.remaining,
+            line: self.line,
+            unknown_province: self.unknown_province.as_deref(),
+            expected: self.expected.as_ref().map(|(label, after)| Expected {
+                label,
+                after: after.as_deref(),
+            }),
+            budget_exceeded: self.budget_exceeded,
+        }
+    }
+}
+
+impl<'a> From<ParseError<'a>> for OwnedParseError {
+    fn from(error: ParseError<'a>) -> Self {
+        error.into_owned()
+    }
+}
+
+impl fmt::Display for OwnedParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_parse_error().fmt(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for OwnedParseError {}
+
+/// Parses a single order, which must make up the whole of `input` apart
+/// from surrounding whitespace, with the default [`ParserOptions`].
+pub fn parse_order<'a, 'm>(map: &'m Map, input: &'a str) -> Result<Order<'m>, ParseError<'a>> {
+    parse_order_with(map, input, &ParserOptions::default())
+}
+
+/// Parses a single order, which must make up the whole of `input` apart
+/// from surrounding whitespace.
+pub fn parse_order_with<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> Result<Order<'m>, ParseError<'a>> {
+    trace_span!("parse_order", input);
+    if options.normalize {
+        let normalized = Normalized::new(input);
+        parse_normalized(map, &normalized.text, options).map_err(|e| ParseError {
+            remaining: normalized.original(input, e.remaining),
+            line: e.line,
+            unknown_province: e
+                .unknown_province
+                .map(|word| normalized.original_slice(input, word)),
+            expected: e.expected.map(|expected| Expected {
+                after: expected
+                    .after
+                    .map(|word| normalized.original_slice(input, word)),
+                ..expected
+            }),
+            budget_exceeded: e.budget_exceeded,
+        })
+    } else {
+        parse_normalized(map, input, options)
+    }
+}
+
+fn parse_normalized<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> Result<Order<'m>, ParseError<'a>> {
+    let parser = preceded(skip_whitespace(), order(map, options));
+    let whole = complete(terminated(parser.clone(), skip_whitespace()));
+    let result = with_budget(options.max_steps, || match whole.parse(input).next() {
+        Some((order, _)) => Ok(order),
+        None => Err(furthest_remaining(parser, input)),
+    });
+    match result {
+        Ok(result) => result.map_err(|remaining| ParseError {
+            remaining,
+            line: None,
+            unknown_province: unknown_word(map, input, options),
+            expected: with_budget(options.max_steps, || expected(map, input, options))
+                .unwrap_or(None),
+            budget_exceeded: false,
+        }),
+        Err(_) => Err(ParseError {
+            remaining: input,
+            line: None,
+            unknown_province: None,
+            expected: None,
+            budget_exceeded: true,
+        }),
+    }
+}
+
+/// Traces the grammar over `input`, which did not parse, to find the part
+/// of the order it was looking for where it got furthest.
+#[cfg(feature = "std")]
+fn expected<'a>(map: &Map, input: &'a str, options: &ParserOptions) -> Option<Expected<'a>> {
+    let (_, trace) = parse_with_trace(&preceded(skip_whitespace(), order(map, options)), input);
+    let (position, labels) = trace.expected()?;
+    let after = input[..position].split_whitespace().next_back();
+    Some(Expected {
+        label: labels[0],
+        after,
+    })
+}
+
+#[cfg(not(feature = "std"))]
+fn expected<'a>(_: &Map, _: &'a str, _: &ParserOptions) -> Option<Expected<'a>> {
+    None
+}
+
+/// Records the attempts the order grammar makes on `input`, with the
+/// default [`ParserOptions`], to show why it was or was not recognized.
+#[cfg(feature = "std")]
+pub fn trace_order(map: &Map, input: &str) -> Trace {
+    trace_order_with(map, input, &ParserOptions::default())
+}
+
+/// Records the attempts the order grammar makes on `input`.
+///
+/// Each named part of the grammar (`order`, `unit`, `move`, `support`,
+/// `province` and so on) appears wherever it was tried, with the byte
+/// offset it was tried at. If the options normalize input, the offsets are
+/// into the normalized text.
+#[cfg(feature = "std")]
+pub fn trace_order_with(map: &Map, input: &str, options: &ParserOptions) -> Trace {
+    let normalized;
+    let input = if options.normalize {
+        normalized = Normalized::new(input);
+        &normalized.text
+    } else {
+        input
+    };
+    let (_, trace) = parse_with_trace(&preceded(skip_whitespace(), order(map, options)), input);
+    trace
+}
+
+/// Parses a single order like [`parse_order`], from input that need not
+/// outlive the call, such as a line read into a temporary `String`.
+///
+/// Orders borrow only from the map, so with [`Map::standard`] they are
+/// `'static`.
+pub fn parse_order_owned<'m>(
+    map: &'m Map,
+    input: impl AsRef<str>,
+) -> Result<Order<'m>, OwnedParseError> {
+    parse_order_owned_with(map, input, &ParserOptions::default())
+}
+
+/// Parses a single order like [`parse_order_with`], from input that need
+/// not outlive the call.
+pub fn parse_order_owned_with<'m>(
+    map: &'m Map,
+    input: impl AsRef<str>,
+    options: &ParserOptions,
+) -> Result<Order<'m>, OwnedParseError> {
+    parse_order_with(map, input.as_ref(), options).map_err(ParseError::into_owned)
+}
+
+/// Parses one order per line, skipping blank lines, with the default
+/// [`ParserOptions`].
+///
+/// Fails with the first line that is not a valid order.
+pub fn parse_orders<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+) -> Result<Vec<Order<'m>>, ParseError<'a>> {
+    parse_orders_with(map, input, &ParserOptions::default())
+}
+
+/// Parses one order per line, skipping blank lines.
+///
+/// Fails with the first line that is not a valid order. Lines naming a
+/// province that does not exist are skipped instead, unless the options
+/// say they are errors.
+pub fn parse_orders_with<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> Result<Vec<Order<'m>>, ParseError<'a>> {
+    parse_lines(map, input, options)
+        .filter(|result| match result {
+            Err(e) => options.unknown_province_is_error || e.unknown_province.is_none(),
+            Ok(_) => true,
+        })
+        .collect()
+}
+
+/// Parses one order per line like [`parse_orders`], from input that need
+/// not outlive the call.
+pub fn parse_orders_owned<'m>(
+    map: &'m Map,
+    input: impl AsRef<str>,
+) -> Result<Vec<Order<'m>>, OwnedParseError> {
+    parse_orders_owned_with(map, input, &ParserOptions::default())
+}
+
+/// Parses one order per line like [`parse_orders_with`], from input that
+/// need not outlive the call.
+pub fn parse_orders_owned_with<'m>(
+    map: &'m Map,
+    input: impl AsRef<str>,
+    options: &ParserOptions,
+) -> Result<Vec<Order<'m>>, OwnedParseError> {
+    parse_orders_with(map, input.as_ref(), options).map_err(ParseError::into_owned)
+}
+
+/// Parses one order per line, skipping blank lines, and carries on past
+/// lines that are not valid orders, with the default [`ParserOptions`].
+///
+/// Returns the orders that parsed, and an error (with its line number) for
+/// each line that did not.
+pub fn parse_orders_lossy<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+) -> (Vec<Order<'m>>, Vec<ParseError<'a>>) {
+    parse_orders_lossy_with(map, input, &ParserOptions::default())
+}
+
+/// Parses one order per line, skipping blank lines, and carries on past
+/// lines that are not valid orders.
+///
+/// Returns the orders that parsed, and an error (with its line number) for
+/// each line that did not, including lines naming a province that does
+/// not exist, whatever the options say.
+pub fn parse_orders_lossy_with<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> (Vec<Order<'m>>, Vec<ParseError<'a>>) {
+    let mut orders = Vec::new();
+    let mut errors = Vec::new();
+    for result in parse_lines(map, input, options) {
+        match result {
+            Ok(order) => orders.push(order),
+            Err(error) => errors.push(error),
+        }
+    }
+    (orders, errors)
+}
+
+/// Parses a single order that may begin with the power that gave it, as
+/// in `France: A Paris - Burgundy` or `FRENCH A Par-Bur`, with the default
+/// [`ParserOptions`].
+pub fn parse_attributed_order<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+) -> Result<(Option<Power>, Order<'m>), ParseError<'a>> {
+    parse_attributed_order_with(map, input, &ParserOptions::default())
+}
+
+/// Parses a single order that may begin with the power that gave it.
+///
+/// The power is named by its name or adjective, ignoring case, and may be
+/// followed by a colon. Without a colon, a power's name might begin the
+/// order itself on some maps, so the input is also tried as an order
+/// without a power.
+pub fn parse_attributed_order_with<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> Result<(Option<Power>, Order<'m>), ParseError<'a>> {
+    let prefix = preceded(
+        skip_whitespace(),
+        chain(
+            terminated(keyword(NATIONALITIES), skip_whitespace()),
+            optional(tag(":")),
+        ),
+    );
+    let (word, colon, rest) = match prefix.parse(input).next() {
+        Some(((word, colon), rest)) => (word, colon, rest),
+        None => return parse_order_with(map, input, options).map(|order| (None, order)),
+    };
+    let power = Power::ALL
+        .iter()
+        .copied()
+        .find(|power| {
+            power.name().eq_ignore_ascii_case(word) || power.adjective().eq_ignore_ascii_case(word)
+        })
+        .expect("nationalities name powers");
+    match parse_order_with(map, rest, options) {
+        Ok(order) => Ok((Some(power), order)),
+        Err(e) if colon.is_some() => Err(e),
+        Err(e) => parse_order_with(map, input, options)
+            .map(|order| (None, order))
+            .map_err(|_| e),
+    }
+}
+
+/// Parses one order per line, each of which may begin with the power that
+/// gave it, skipping blank lines, with the default [`ParserOptions`].
+pub fn parse_attributed_orders<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+) -> Result<Vec<(Option<Power>, Order<'m>)>, ParseError<'a>> {
+    parse_attributed_orders_with(map, input, &ParserOptions::default())
+}
+
+/// Parses one order per line, each of which may begin with the power that
+/// gave it, skipping blank lines, like a block of results pasted from a
+/// judge.
+///
+/// Fails with the first line that is not a valid order, skipping lines
+/// naming a province that does not exist as
+/// [`parse_orders_with`] does.
+pub fn parse_attributed_orders_with<'a, 'm>(
+    map: &'m Map,
+    input: &'a str,
+    options: &ParserOptions,
+) -> Result<Vec<(Option<Power>, Order<'m>)>, ParseError<'a>> {
+    lines(input)
+        .map(|(i, line)| {
+            parse_attributed_order_with(map, line, options).map_err(|e| e.at_line(i + 1))
+        })
+        .filter(|result| match result {
+            Err(e) => options.unknown_province_is_error || e.unknown_province.is_none(),
+            Ok(_) => true,
+        })
+        .collect()
+}
+
+/// The names and adjectives of the powers, as they begin orders copied
+/// from results.
+const NATIONALITIES: &[&str] = &[
+    "austrian", "austria", "english", "england", "french", "france", "german", "germany",
+    "italian", "italy", "russian", "russia", "turkish", "turkey",
+];
+
+/// Parses each non-blank line.
+fn parse_lines<'a, 'm, 'o>(
+    map: &'m Map,
+    input: &'a str,
+    options: &'o ParserOptions,
+) -> impl Iterator<Item = Result<Order<'m>, ParseError<'a>>> + 'o
+where
+    'a: 'o,
+    'm: 'o,
+{
+    lines(input)
+        .map(move |(i, line)| parse_order_with(map, line, options).map_err(|e| e.at_line(i + 1)))
+}
+
+/// The non-blank lines of `input`, with their 0-based line numbers.
+fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+}
+
+/// The input left over after the longest partial parse of `input`, or all
+/// of it if there is none.
+fn furthest_remaining<'a, P: Parser<'a>>(parser: P, input: &'a str) -> &'a str {
+    parser
+        .parse(input)
+        .map(|(_, rest)| rest.trim_start())
+        .min_by_key(|rest| rest.len())
+        .unwrap_or(input)
+}
+
+/// Finds the first word in `input` that is neither a keyword nor the start
+/// of a province name, to explain why an order did not parse.
+fn unknown_word<'a>(map: &Map, input: &'a str, options: &ParserOptions) -> Option<&'a str> {
+    let word = take_while1(is_word_char);
+    let province = KnownProvince {
+        names: ParseProvince::new(map),
+        aliases: options.aliases.clone(),
+    };
+    let mut rest = input.trim_start_matches(|c: char| !is_word_char(c));
+    while let Some((word, after)) = word.parse(rest).next() {
+        rest = match province.parse(rest).next() {
+            Some((_, after)) => after,
+            None if options.locale.is_keyword(word) => after,
+            None => return Some(word),
+        };
+        rest = rest.trim_start_matches(|c: char| !is_word_char(c));
+    }
+    None
+}
+
+/// Succeeds, consuming nothing, unless the input continues a word.
+fn word_boundary<'a>() -> impl Parser<'a, Item = ()> {
+    not(char_where(is_word_char))
+}
+
+/// Matches any of `words`, ignoring case, as whole words.
+fn keyword<'a>(words: &'static [&'static str]) -> impl Parser<'a, Item = &'a str> {
+    terminated(any_tag(words), word_boundary())
+}
+
+/// Matches one of `words` from the short or long dialect, whichever are
+/// allowed.
+fn verb<'a>(options: &ParserOptions, words: Words) -> impl Parser<'a, Item = &'a str> {
+    let short = if options.allows(Dialect::Short) {
+        words.short
+    } else {
+        &[]
+    };
+    let long = if options.allows(Dialect::Long) {
+        words.long
+    } else {
+        &[]
+    };
+    either(keyword(short), keyword(long)).map(|word| match word {
+        Either::Left(word) | Either::Right(word) => word,
+    })
+}
+
+/// Matches any of `words` as a whole word in lenient mode, and nothing
+/// in strict mode or before [`GrammarVersion::V2`]. These are the words
+/// players write around an order's parts, like the "the move" in
+/// "supports the move Burgundy to Munich".
+fn filler<'a>(
+    options: &ParserOptions,
+    words: &'static [&'static str],
+) -> impl Parser<'a, Item = ()> {
+    let words = match options.mode {
+        Mode::Lenient if options.since(GrammarVersion::V2) => words,
+        _ => &[],
+    };
+    keyword(words).map(|_| ())
+}
+
+/// Matches a province name, abbreviation or alias, as a whole word, or a
+/// misspelling of one if the options allow.
+///
+/// Misspellings are costly to look for, so the matches are memoized.
+fn province<'a, 'm, 'p>(map: &'m Map, options: &ParserOptions) -> Boxed<'a, 'p, &'m Province>
+where
+    'a: 'p,
+    'm: 'p,
+{
+    let exact = KnownProvince {
+        names: ParseProvince::new(map),
+        aliases: options.aliases.clone(),
+    };
+    boxed(context(
+        "province",
+        memo(FuzzyProvince {
+            map,
+            threshold: options.fuzzy(),
+            exact,
+        }),
+    ))
+}
+
+/// Matches a province by the map's names for it or by a user's
+/// [`Aliases`], longest match first.
+#[derive(Clone, Debug)]
+struct KnownProvince<'m> {
+    names: ParseProvince<'m>,
+    aliases: Aliases,
+}
+
+impl<'a, 'm> Parser<'a> for KnownProvince<'m> {
+    type Item = &'m Province;
+    type Iter = alloc::vec::IntoIter<(&'m Province, &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        let mut found: Vec<_> = self.names.parse(input).collect();
+        if !self.aliases.is_empty() {
+            let map = self.names.map;
+            found.extend(match_words(
+                input,
+                |name| self.aliases.is_prefix(name),
+                |name| {
+                    self.aliases
+                        .get(name)
+                        .and_then(|abbreviation| map.find_by_abbreviation(abbreviation))
+                },
+            ));
+            found.sort_by_key(|(_, rest)| rest.len());
+        }
+        found.into_iter()
+    }
+}
+
+/// Falls back on approximate matching where `exact` finds nothing.
+#[derive(Clone, Copy, Debug)]
+struct FuzzyProvince<'m, P> {
+    map: &'m Map,
+    threshold: Option<f64>,
+    exact: P,
+}
+
+impl<'a, 'm, P> Parser<'a> for FuzzyProvince<'m, P>
+where
+    P: Parser<'a, Item = &'m Province>,
+{
+    type Item = &'m Province;
+    type Iter = alloc::vec::IntoIter<(&'m Province, &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        let exact: Vec<_> = self.exact.parse(input).collect();
+        match self.threshold {
+            Some(threshold) if exact.is_empty() => self
+                .map
+                .fuzzy_match_provinces(input, threshold)
+                .into_iter()
+                .map(|(province, len, _)| (province, &input[len..]))
+                .collect::<Vec<_>>()
+                .into_iter(),
+            _ => exact.into_iter(),
+        }
+    }
+}
+
+/// Matches a province name, abbreviation or alias at the start of the input,
+/// as whole words.
+///
+/// Every match is yielded, longest first: `Mid Atlantic Ocean` is found
+/// both as itself and as the alias `Mid`.
+#[derive(Clone, Copy, Debug)]
+pub struct ParseProvince<'m> {
+    map: &'m Map,
+}
+
+impl<'m> ParseProvince<'m> {
+    pub fn new(map: &'m Map) -> Self {
+        ParseProvince { map }
+    }
+}
+
+impl<'a, 'm> Parser<'a> for ParseProvince<'m> {
+    type Item = &'m Province;
+    type Iter = alloc::vec::IntoIter<(&'m Province, &'a str)>;
+
+    fn parse(&self, input: &'a str) -> Self::Iter {
+        match_words(
+            input,
+            |name| self.map.is_name_prefix(name),
+            |name| self.map.province_named(name),
+        )
+        .into_iter()
+    }
+}
+
+/// Every name at the start of `input`, longest first, with what follows
+/// it, where `is_prefix` says whether some name starts with the given
+/// words and `named` looks a whole name up.
+fn match_words<T>(
+    input: &str,
+    is_prefix: impl Fn(&str) -> bool,
+    named: impl Fn(&str) -> Option<T>,
+) -> Vec<(T, &str)> {
+    let word = take_while1(is_word_char);
+    let next_word = preceded(take_while1(is_name_separator), word);
+    let mut found = Vec::new();
+    let mut words = word.parse(input).next();
+    // Names are read a word at a time, for as long as some name starts
+    // with the words read so far.
+    while let Some((_, rest)) = words {
+        let name = &input[..input.len() - rest.len()];
+        if !is_prefix(name) {
+            break;
+        }
+        if let Some(value) = named(name) {
+            found.push((value, rest));
+        }
+        words = next_word.parse(rest).next();
+    }
+    found.reverse();
+    found
+}
+
+/// Whether `c` can come between the words of a province name, as in
+/// `St. Petersburg` or `Mid-Atlantic Ocean`.
+fn is_name_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '-' | '.' | '\'')
+}
+
+/// A unit's type, if given, and its province.
+type LocatedUnit<'m> = (Option<UnitType>, &'m Province);
+
+/// A province to move to, and the coast if one is given.
+type Destination<'m> = (&'m Province, Option<Coast>);
+
+fn unit_type<'a>(options: &ParserOptions) -> impl Parser<'a, Item = UnitType> {
+    let unit = either(
+        verb(options, options.locale.army),
+        verb(options, options.locale.fleet),
+    )
+    .map(|unit| match unit {
+        Either::Left(_) => UnitType::Army,
+        Either::Right(_) => UnitType::Fleet,
+    });
+    context("unit type", unit)
+}
+
+/// `A Par`, `Army Paris`, or just `Par` unless the options require a unit.
+fn located_unit<'a, 'm, 'p>(map: &'m Map, options: &ParserOptions) -> Boxed<'a, 'p, LocatedUnit<'m>>
+where
+    'a: 'p,
+    'm: 'p,
+{
+    let require_unit = options.require_unit;
+    boxed(chain(
+        optional(terminated(unit_type(options), skip_whitespace()))
+            .filter(move |unit, _| unit.is_some() || !require_unit),
+        province(map, options),
+    ))
+}
+
+/// A located unit, with the coast it is on if one is given.
+///
+/// The coast only matters to a hold, or to a support to hold, where it is
+/// kept to describe the unit; elsewhere it is dropped. Either way, it must
+/// be one of the province's coasts.
+fn located_unit_on_coast<'a, 'm, 'p>(
+    map: &'m Map,
+    options: &ParserOptions,
+) -> Boxed<'a, 'p, (LocatedUnit<'m>, Option<Coast>)>
+where
+    'a: 'p,
+    'm: 'p,
+{
+    let unit = chain(
+        located_unit(map, options),
+        optional(preceded(skip_whitespace(), coast(options))),
+    )
+    .filter(|((_, province), at), _| at.is_none_or(|coast| province.coasts().contains(&coast)));
+    boxed(context("unit", unit))
+}
+
+/// `(nc)`, `/nc`, `nc`, `n c`, `n.c` or `north coast`, or just the
+/// letter between brackets or after a slash, as in `(n)` or `/n`. Before
+/// [`GrammarVersion::V3`], only `(nc)`, `/nc`, `nc` or `north coast`.
+fn coast<'a>(options: &ParserOptions) -> impl Parser<'a, Item = Coast> {
+    let (names, letters): (&'static [&'static str], &'static [&'static str]) =
+        if options.since(GrammarVersion::V3) {
+            (
+                &[
+                    "nc",
+                    "sc",
+                    "ec",
+                    "wc",
+                    "n c",
+                    "s c",
+                    "e c",
+                    "w c",
+                    "n.c",
+                    "s.c",
+                    "e.c",
+                    "w.c",
+                    "north coast",
+                    "south coast",
+                    "east coast",
+                    "west coast",
+                ],
+                &["n", "s", "e", "w"],
+            )
+        } else {
+            (
+                &[
+                    "nc",
+                    "sc",
+                    "ec",
+                    "wc",
+                    "north coast",
+                    "south coast",
+                    "east coast",
+                    "west coast",
+                ],
+                &[],
+            )
+        };
+    let name = keyword(names).map(direction);
+    let letter = keyword(letters).map(direction);
+    let marked = either(name.clone(), letter).map(|coast| match coast {
+        Either::Left(coast) | Either::Right(coast) => coast,
+    });
+    let coast = either(
+        either(
+            delimited(
+                terminated(tag("("), skip_whitespace()),
+                marked.clone(),
+                preceded(skip_whitespace(), tag(")")),
+            ),
+            preceded(tag("/"), marked),
+        ),
+        name,
+    )
+    .map(|coast| match coast {
+        Either::Left(Either::Left(coast) | Either::Right(coast)) | Either::Right(coast) => coast,
+    });
+    context("coast", coast)
+}
+
+/// The coast a coast's name starts with the direction of.
+fn direction(name: &str) -> Coast {
+    match name.as_bytes()[0].to_ascii_lowercase() {
+        b'n' => Coast::North,
+        b's' => Coast::South,
+        b'e' => Coast::East,
+        _ => Coast::West,
+    }
+}
+
+/// A destination province, with a coast if one is given.
+fn destination<'a, 'm, 'p>(map: &'m Map, options: &ParserOptions) -> Boxed<'a, 'p, Destination<'m>>
+where
+    'a: 'p,
+    'm: 'p,
+{
+    let destination = chain(
+        province(map, options),
+        optional(preceded(skip_whitespace(), coast(options))),
+    );
+    boxed(context("destination", destination))
+}
+
+/// `- Bur`, `-> Bur`, `to Bur`, `moves to Bur`, `- Spa (nc)`
+fn move_tail<'a, 'm, 'p>(map: &'m Map, options: &ParserOptions) -> Boxed<'a, 'p, Destination<'m>>
+where
+    'a: 'p,
+    'm: 'p,
+{
+    let short = if options.allows(Dialect::Short) {
+        &["->", "=>", "-"][..]
+    } else {
+        &[]
+    };
+    let arrow = either(any_tag(short), verb(options, options.locale.moves));
+    boxed(preceded(
+        preceded(skip_whitespace(), arrow),
+        preceded(
+            skip_whitespace(),
+            preceded(
+                optional(terminated(
+                    filler(options, options.locale.article),
+                    skip_whitespace(),
+                )),
+                destination(map, options),
+            ),
+        ),
+    ))
+}
+
+/// `A Lon - Bel via convoy`
+fn move_<'a, 'm, 'p>(
+    map: &'m Map,
+    options: &ParserOptions,
+) -> Boxed<'a, 'p, (Destination<'m>, Option<&'a str>)>
+where
+    'a: 'p,
+    'm: 'p,
+{
+    let move_ = chain(
+        move_tail(map, options),
+        optional(preceded(
+            skip_whitespace(),
+            verb(options, options.locale.via_convoy),
+        )),
+    );
+    boxed(context("move", move_))
+}
+
+/// `H`, `holds`, or in lenient mode `holds in place`
+fn hold<'a>(options: &ParserOptions) -> impl Parser<'a, Item = ()> {
+    let hold = chain(
+        verb(options, options.locale.hold),
+        optional(preceded(
+            skip_whitespace(),
+            filler(options, options.locale.in_place),
+        )),
+    );
+    context("hold", hold.map(|_| ()))
+}
+
+fn support<'a, 'm, 'p>(map: &'m Map, options: &ParserOptions) -> Boxed<'a, 'p, SupportedOrder<'m>>
+where
+    'a: 'p,
+    'm: 'p,
+{
+    let to_hold = preceded(
+        skip_whitespace(),
+        either(hold(options), filler(options, options.locale.to_hold)),
+    );
+    let support = preceded(
+        terminated(verb(options, options.locale.support), skip_whitespace()),
+        preceded(
+            optional(terminated(
+                filler(options, options.locale.the_move),
+                skip_whitespace(),
+            )),
+            chain(
+                located_unit_on_coast(map, options),
+                optional(either(move_tail(map, options), to_hold)),
+            ),
+        ),
+    )
+    .map(|(((unit, province), at), tail)| match tail {
+        Some(Either::Left((destination, coast))) => SupportedOrder::Move(Move {
+            unit,
+            province,
+            destination,
+            coast,
+            via_convoy: false,
+        }),
+        Some(Either::Right(_)) | None => SupportedOrder::Hold(Hold {
+            unit,
+            province,
+            coast: at,
+        }),
+    });
+    boxed(context("support", support))
+}
+
+fn convoy<'a, 'm, 'p>(
+    map: &'m Map,
+    options: &ParserOptions,
+) -> Boxed<'a, 'p, (LocatedUnit<'m>, &'m Province)>
+where
+    'a: 'p,
+    'm: 'p,
+{
+    let convoy = preceded(
+        terminated(verb(options, options.locale.convoy), skip_whitespace()),
+        preceded(
+            optional(terminated(
+                filler(options, options.locale.article),
+                skip_whitespace(),
+            )),
+            chain(
+                context("unit", located_unit(map, options)),
+                move_tail(map, options),
+            ),
+        ),
+    )
+    .map(|(unit, (to, _))| (unit, to));
+    boxed(context("convoy", convoy))
+}
+
+/// What an order tells its unit to do, before the unit is known.
+#[derive(Clone)]
+enum Body<'m> {
+    Hold,
+    Move {
+        destination: &'m Province,
+        coast: Option<Coast>,
+        via_convoy: bool,
+    },
+    Support(SupportedOrder<'m>),
+    Convoy {
+        convoyed_unit: Option<UnitType>,
+        from: &'m Province,
+        to: &'m Province,
+    },
+}
+
+fn order<'a, 'm, 'p>(map: &'m Map, options: &ParserOptions) -> Boxed<'a, 'p, Order<'m>>
+where
+    'a: 'p,
+    'm: 'p,
+{
+    // The body is memoized, since a unit whose province is misspelled may
+    // be read as several provinces that all leave the same remainder.
+    let body = context(
+        "hold, move, support or convoy",
+        memo(alt((
+            hold(options).map(|()| Body::Hold),
+            move_(map, options).map(|((destination, coast), via_convoy)| Body::Move {
+                destination,
+                coast,
+                via_convoy: via_convoy.is_some(),
+            }),
+            support(map, options).map(Body::Support),
+            convoy(map, options).map(|((convoyed_unit, from), to)| Body::Convoy {
+                convoyed_unit,
+                from,
+                to,
+            }),
+        ))),
+    );
+    let order = chain(
+        located_unit_on_coast(map, options),
+        preceded(skip_whitespace(), body),
+    )
+    .map(|(((unit, province), at), body)| match body {
+        Body::Hold => Order::Hold(Hold {
+            unit,
+            province,
+            coast: at,
+        }),
+        Body::Move {
+            destination,
+            coast,
+            via_convoy,
+        } => Order::Move(Move {
+            unit,
+            province,
+            destination,
+            coast,
+            via_convoy,
+        }),
+        Body::Support(supported) => Order::Support(Support {
+            unit,
+            province,
+            supported,
+        }),
+        Body::Convoy {
+            convoyed_unit,
+            from,
+            to,
+        } => Order::Convoy(Convoy {
+            unit,
+            province,
+            convoyed_unit,
+            from,
+            to,
+        }),
+    });
+    boxed(context("order", order))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use proptest::prelude::*;
+    use proptest::sample::{select, Index};
+
+    use super::*;
+    use crate::map::Province;
+    use crate::rng::Rng;
+
+    /// Orders that make sense on their own terms, though not necessarily
+    /// on the map.
+    fn orders(map: &'static Map) -> impl Strategy<Value = Order<'static>> {
+        let provinces: Vec<&'static Province> = map.provinces().collect();
+        let province = || select(provinces.clone());
+        let unit = || select(vec![None, Some(UnitType::Army), Some(UnitType::Fleet)]);
+        (
+            0..6u8,
+            (unit(), unit()),
+            (province(), province(), province()),
+            (any::<bool>(), any::<Index>(), any::<Index>()),
+        )
+            .prop_filter_map(
+                "the order must be valid",
+                |(kind, (unit, other), (at, from, to), (coasted, coast, to_coast))| {
+                    let coast = match at.coasts() {
+                        coasts if coasted && !coasts.is_empty() => Some(*coast.get(coasts)),
+                        _ => None,
+                    };
+                    let order = match kind {
+                        0 => Ok(Order::Hold(Hold {
+                            unit,
+                            province: at,
+                            coast,
+                        })),
+                        1 => match to.coasts() {
+                            [] => Order::move_(unit, at, to),
+                            coasts => Order::move_to_coast(unit, at, to, *to_coast.get(coasts)),
+                        },
+                        2 => Order::move_via_convoy(unit, at, to),
+                        3 => {
+                            let order = Order::Support(Support {
+                                unit: other,
+                                province: from,
+                                supported: SupportedOrder::Hold(Hold {
+                                    unit,
+                                    province: at,
+                                    coast,
+                                }),
+                            });
+                            order.validate().map(|()| order)
+                        }
+                        4 => Order::support_move(unit, at, other, from, to),
+                        _ => Order::convoy(unit, at, other, from, to),
+                    };
+                    order.ok()
+                },
+            )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn displayed_orders_parse_back(order in orders(Map::standard())) {
+            let text = order.to_string();
+            prop_assert_eq!(parse_order(Map::standard(), &text), Ok(order), "{}", text);
+        }
+    }
+
+    #[test]
+    fn coasts_of_held_and_supported_units() {
+        let map = Map::standard();
+        let order = parse_order(map, "F MAO S F Spain (sc)").unwrap();
+        let Order::Support(support) = &order else {
+            panic!("{}", order)
+        };
+        let SupportedOrder::Hold(hold) = support.supported() else {
+            panic!("{}", order)
+        };
+        assert_eq!(hold.coast(), Some(Coast::South));
+        assert_eq!(order.to_string(), "F Mid-Atlantic Ocean S F Spain (sc)");
+        assert_eq!(
+            parse_order(map, "F Spa/nc H").unwrap().to_string(),
+            "F Spain (nc) H"
+        );
+        assert_eq!(
+            parse_order(map, "F Spa (nc) S F Mao - Por")
+                .unwrap()
+                .to_string(),
+            "F Spain S F Mid-Atlantic Ocean - Portugal"
+        );
+
+        // A coast the province does not have is an error, not dropped.
+        for text in [
+            "F Mar/nc - Spa/sc",
+            "F Mar (nc) H",
+            "F Spa/ec - Por",
+            "A Par S F Mar/nc - Spa/sc",
+            "F Mao S F Spa/ec",
+        ] {
+            assert!(parse_order(map, text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn arbitrary_input_does_not_panic() {
+        const PIECES: &[&str] = &[
+            "a", "f", " ", "-", "->", "(", ")", "/", "s", "c", "h", "via", "convoy", "nc", "par",
+            "bur", "spa", "st", ".", "ü", "è", "ß", "İ", "Ⅻ", "日本", "\u{301}", "\u{a0}", "—",
+            "\n", "\t",
+        ];
+        let map = Map::standard();
+        let options = [ParserOptions::default(), ParserOptions::strict()];
+        let mut rng = Rng::new(1571);
+        for _ in 0..2000 {
+            let input: String = (0..rng.below(12)).map(|_| *rng.choose(PIECES)).collect();
+            for options in &options {
+                let _ = parse_order_with(map, &input, options);
+                let _ = parse_orders_lossy_with(map, &input, options);
+            }
+            for (_, rest) in ParseProvince::new(map).parse(&input) {
+                assert!(input.ends_with(rest));
+            }
+        }
+    }
+
+    #[test]
+    fn corpus_of_real_orders() {
+        let map = Map::standard();
+        let corpus = include_str!("corpus.txt")
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'));
+        for line in corpus {
+            let (input, expected) = line.split_once("=>").unwrap();
+            let input = input.trim();
+            let order = parse_order(map, input).unwrap_or_else(|e| panic!("{}: {}", input, e));
+            assert_eq!(order.to_string(), expected.trim(), "{}", input);
+        }
+
+        // The filler words are only understood in lenient mode.
+        let strict = ParserOptions {
+            mode: Mode::Strict,
+            ..ParserOptions::default()
+        };
+        for input in [
+            "A Paris supports the move Burgundy to Munich",
+            "A Paris supports Burgundy to hold",
+            "A Paris holds in place",
+        ] {
+            assert!(parse_order_with(map, input, &strict).is_err(), "{}", input);
+        }
+        assert!(parse_order_with(map, "A Par S A Bur H", &strict).is_ok());
+    }
+
+    #[test]
+    fn real_world_corpus() {
+        // The corpus keeps lines that are not orders, so not every line
+        // parses; but far fewer than this failing means the parser has
+        // stopped reading something players write.
+        const MINIMUM_SUCCESS_RATE: f64 = 0.85;
+        let map = Map::standard();
+        let corpus = [
+            ("chat", include_str!("../../corpus/chat.txt")),
+            (
+                "face_to_face",
+                include_str!("../../corpus/face_to_face.txt"),
+            ),
+            ("forum", include_str!("../../corpus/forum.txt")),
+            ("judge", include_str!("../../corpus/judge.txt")),
+        ];
+        for (name, text) in corpus {
+            let lines: Vec<_> = text
+                .lines()
+                .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+                .collect();
+            let parsed = lines
+                .iter()
+                .filter(|line| parse_order(map, line).is_ok())
+                .count();
+            let rate = parsed as f64 / lines.len() as f64;
+            assert!(
+                rate >= MINIMUM_SUCCESS_RATE,
+                "{}: {} of {} lines parsed",
+                name,
+                parsed,
+                lines.len()
+            );
+        }
+    }
+
+    #[test]
+    fn pinned_grammar_versions() {
+        let map = Map::standard();
+        let pinned = |grammar| ParserOptions {
+            grammar,
+            ..ParserOptions::default()
+        };
+        let parses = |input, grammar| parse_order_with(map, input, &pinned(grammar)).is_ok();
+        for grammar in [GrammarVersion::V1, GrammarVersion::V2, GrammarVersion::V3] {
+            assert!(parses("Army Paris moves to Burgundy via convoy", grammar));
+            assert!(parses("F Mao - Spa/nc", grammar));
+            assert!(parses("F Mao - Spa north coast", grammar));
+        }
+        let added = [
+            ("A Paris supports Burgundy to hold", GrammarVersion::V2),
+            ("F Mao - Spa (n)", GrammarVersion::V3),
+            ("F Mao - Spa n.c", GrammarVersion::V3),
+        ];
+        for (input, since) in added {
+            for grammar in [GrammarVersion::V1, GrammarVersion::V2, GrammarVersion::V3] {
+                assert_eq!(parses(input, grammar), grammar >= since, "{}", input);
+            }
+        }
+        assert_eq!(ParserOptions::default().grammar, GrammarVersion::LATEST);
+    }
+
+    #[test]
+    fn nationality_prefixes() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        assert_eq!(
+            parse_attributed_order(map, "France: A Paris - Burgundy"),
+            Ok((Some(Power::France), order("A Par - Bur")))
+        );
+        assert_eq!(
+            parse_attributed_order(map, "FRENCH A Par-Bur"),
+            Ok((Some(Power::France), order("A Par - Bur")))
+        );
+        assert_eq!(
+            parse_attributed_order(map, "A Par - Bur"),
+            Ok((None, order("A Par - Bur")))
+        );
+        let error = parse_attributed_order(map, "Germany: A Mun - Atlantis").unwrap_err();
+        assert_eq!(error.unknown_province(), Some("Atlantis"));
+
+        let block = "England: F Lon - Eng\n\nturkish F Ank - Bla\nA Ven H\n";
+        assert_eq!(
+            parse_attributed_orders(map, block),
+            Ok(vec![
+                (Some(Power::England), order("F Lon - Eng")),
+                (Some(Power::Turkey), order("F Ank - Bla")),
+                (None, order("A Ven H")),
+            ])
+        );
+        let error = parse_attributed_orders(map, "Italy: A Ven H\nItaly: A Ven -").unwrap_err();
+        assert_eq!(error.line(), Some(2));
+    }
+
+    #[test]
+    fn province_names() {
+        let map = Map::standard();
+        let mao = map.find_by_abbreviation("Mao").unwrap();
+        let found: Vec<_> = ParseProvince::new(map)
+            .parse("mid atlantic ocean - Bre")
+            .collect();
+        assert_eq!(found, [(mao, " - Bre"), (mao, " atlantic ocean - Bre")]);
+        let stp = map.find_by_abbreviation("StP").unwrap();
+        let found: Vec<_> = ParseProvince::new(map).parse("St. Petersburg/nc").collect();
+        assert_eq!(found, [(stp, "/nc")]);
+        assert_eq!(ParseProvince::new(map).parse("Burg").next(), None);
+    }
+
+    #[test]
+    fn accented_provinces() {
+        let standard = Map::standard();
+        let munich = standard.find_by_abbreviation("Mun").unwrap();
+        for text in ["A Münich H", "A Mu\u{308}nich H", "A MÜNICH H"] {
+            assert_eq!(
+                parse_order(standard, text).map(|order| order.province()),
+                Ok(munich),
+                "{}",
+                text
+            );
+        }
+        for text in ["A Zürich H", "A Zu\u{308}rich H"] {
+            let error = parse_order_with(standard, text, &ParserOptions::strict()).unwrap_err();
+            assert_eq!(error.unknown_province(), Some(&text[2..text.len() - 2]));
+        }
+
+        let map = Map::parse(
+            "[provinces]\n\
+             Lie | Liège   | land | | |\n\
+             Ess | Essen   | land | | |\n\
+             [adjacencies]\n\
+             army Lie: Ess\n\
+             army Ess: Lie\n",
+        )
+        .unwrap();
+        let liege = map.find_by_abbreviation("Lie").unwrap();
+        for text in [
+            "A Liège - Essen",
+            "A Liege - Ess",
+            "A LIÈGE - Ess",
+            "A Lie\u{300}ge - Ess",
+        ] {
+            assert_eq!(
+                parse_order(&map, text).map(|order| order.province()),
+                Ok(liege),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn owned_input() {
+        use std::sync::Arc;
+
+        fn read(line: &str) -> Result<Order<'static>, OwnedParseError> {
+            let buffer = format!("{}\n", line);
+            parse_order_owned(Map::standard(), buffer)
+        }
+
+        let order = read("A Par - Bur").unwrap();
+        assert_eq!(order, parse_order(Map::standard(), "A Par - Bur").unwrap());
+        let error = read("A Par - Atlantis").unwrap_err();
+        assert_eq!(error.unknown_province(), Some("Atlantis"));
+        assert_eq!(error.to_string(), "unknown province \"Atlantis\"");
+
+        let text: Arc<str> = Arc::from("A Par H\nF Bre - Mao\n");
+        assert_eq!(parse_orders_owned(Map::standard(), text).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn errors_name_what_was_expected() {
+        let map = Map::standard();
+        let message = |input| parse_order(map, input).unwrap_err().to_string();
+        assert_eq!(message("A Par supports"), "expected unit after 'supports'");
+        assert_eq!(message("A Par -  "), "expected destination after '-'");
+        assert_eq!(
+            message("A Par"),
+            "expected hold, move, support or convoy after 'Par'"
+        );
+        assert_eq!(message("Holds"), "expected order");
+
+        let error = parse_orders(map, "A Par H\nF Nth C A Lon -").unwrap_err();
+        assert_eq!(error.expected(), Some("destination"));
+        assert_eq!(error.expected_after(), Some("-"));
+        assert_eq!(error.to_string(), "line 2: expected destination after '-'");
+
+        let options = ParserOptions {
+            normalize: true,
+            ..ParserOptions::default()
+        };
+        let error = parse_order_with(map, "A Par  SUPPORTS", &options).unwrap_err();
+        assert_eq!(error.expected_after(), Some("SUPPORTS"));
+    }
+
+    #[test]
+    fn misspelled_provinces() {
+        let map = Map::standard();
+        let input = "A Burgundyy - Parris";
+        assert_eq!(parse_order(map, input), parse_order(map, "A Bur - Par"));
+        let error = parse_order_with(map, input, &ParserOptions::strict()).unwrap_err();
+        assert_eq!(error.unknown_province(), Some("Burgundyy"));
+    }
+
+    #[test]
+    fn unknown_provinces_in_several_orders() {
+        let map = Map::standard();
+        let input = "A Par - Bur\nA Xyzzy - Bur\nA Par ?";
+        let options = ParserOptions {
+            unknown_province_is_error: false,
+            ..ParserOptions::default()
+        };
+        assert_eq!(
+            parse_orders_with(map, "A Par - Bur\nA Xyzzy - Bur", &options),
+            parse_orders(map, "A Par - Bur")
+        );
+        let (orders, errors) = parse_orders_lossy_with(map, input, &options);
+        assert_eq!(orders.len(), 1);
+        let errors: Vec<_> = errors
+            .iter()
+            .map(|e| (e.line(), e.unknown_province()))
+            .collect();
+        assert_eq!(errors, [(Some(2), Some("Xyzzy")), (Some(3), None)]);
+        assert_eq!(
+            parse_orders_with(map, input, &ParserOptions::default())
+                .unwrap_err()
+                .line(),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn step_budget() {
+        let map = Map::standard();
+        let input = "F Nth C A Lon - Nwy";
+        let tight = ParserOptions {
+            max_steps: Some(1),
+            ..ParserOptions::default()
+        };
+        let error = parse_order_with(map, input, &tight).unwrap_err();
+        assert!(error.budget_exceeded());
+        assert_eq!(error.unknown_province(), None);
+        assert_eq!(error.to_string(), "order too ambiguous to parse");
+        assert!(error.into_owned().budget_exceeded());
+
+        let unlimited = ParserOptions {
+            max_steps: None,
+            ..ParserOptions::default()
+        };
+        assert_eq!(
+            parse_order_with(map, input, &unlimited),
+            parse_order(map, input)
+        );
+        assert!(parse_order(map, input).is_ok());
+    }
+
+    #[test]
+    fn traces() {
+        let map = Map::standard();
+        let trace = trace_order(map, "A Par supports");
+        assert!(!trace.succeeded());
+        let order = &trace.children[0];
+        let body = &order.children[1];
+        let support = body
+            .children
+            .iter()
+            .find(|attempt| attempt.name == "support")
+            .unwrap();
+        assert_eq!((support.position, support.matches), (6, 0));
+        assert_eq!(support.children[0].name, "unit");
+        assert_eq!(support.children[0].position, 14);
+        assert!(trace
+            .to_string()
+            .starts_with("parse at 0: failed\n  order at 0: failed\n    unit at 0: 1 match\n"));
+
+        let trace = trace_order(map, "A Par - Bur");
+        assert_eq!(trace.matches, 1);
+        assert_eq!(trace.children[0].name, "order");
+        assert!(trace.children[0].succeeded());
+    }
+}
@@ -0,0 +1,162 @@
+//! Tidying up pasted text before it is parsed.
+//!
+//! Orders pasted from chat clients and word processors arrive with smart
+//! punctuation, odd spacing and trailing full stops. Normalizing them first
+//! lets the grammar stay in plain ASCII.
+
+use crate::prelude::*;
+
+/// Rewrites `input` into the plain form the grammar expects.
+///
+/// Unicode dashes become `-`, arrows become `->`, curly quotes become
+/// straight ones, every run of whitespace (including non-breaking spaces)
+/// becomes a single space, trailing punctuation is dropped, and the whole
+/// is lowercased.
+pub fn normalize(input: &str) -> String {
+    Normalized::new(input).text
+}
+
+/// Normalized text, along with where each byte of it came from.
+pub(crate) struct Normalized {
+    pub(crate) text: String,
+    /// The offset in the original input of each byte of `text`, plus one
+    /// for the end of the text.
+    offsets: Vec<usize>,
+}
+
+impl Normalized {
+    pub(crate) fn new(input: &str) -> Self {
+        let mut normalized = Normalized {
+            text: String::with_capacity(input.len()),
+            offsets: Vec::with_capacity(input.len() + 1),
+        };
+        let mut space = None;
+        for (i, c) in input.char_indices() {
+            if c.is_whitespace() {
+                space = space.or(Some(i));
+                continue;
+            }
+            if let Some(at) = space.take() {
+                if !normalized.text.is_empty() {
+                    normalized.push(' ', at);
+                }
+            }
+            match c {
+                '\u{2010}'..='\u{2015}' | '\u{2212}' | '\u{fe58}' | '\u{fe63}' | '\u{ff0d}' => {
+                    normalized.push('-', i)
+                }
+                '\u{2192}' | '\u{27f6}' | '\u{21d2}' => {
+                    normalized.push('-', i);
+                    normalized.push('>', i);
+                }
+                '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' => normalized.push('\'', i),
+                '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' => normalized.push('"', i),
+                c => {
+                    for lower in c.to_lowercase() {
+                        normalized.push(lower, i);
+                    }
+                }
+            }
+        }
+        while normalized.text.ends_with(['.', '!', ',', ';']) {
+            normalized.text.pop();
+            normalized.offsets.pop();
+        }
+        normalized.offsets.push(input.len());
+        normalized
+    }
+
+    fn push(&mut self, c: char, offset: usize) {
+        self.text.push(c);
+        self.offsets
+            .extend(core::iter::repeat_n(offset, c.len_utf8()));
+    }
+
+    /// Maps `rest`, a suffix of the normalized text, back to the
+    /// corresponding suffix of `input`.
+    pub(crate) fn original<'a>(&self, input: &'a str, rest: &str) -> &'a str {
+        &input[self.offsets[self.text.len() - rest.len()]..]
+    }
+
+    /// Maps `part`, a slice of the normalized text, back to the
+    /// corresponding slice of `input`.
+    pub(crate) fn original_slice<'a>(&self, input: &'a str, part: &str) -> &'a str {
+        let start = part.as_ptr() as usize - self.text.as_ptr() as usize;
+        if part.is_empty() {
+            return &input[self.offsets[start]..self.offsets[start]];
+        }
+        // End after the input character that the last byte of `part` came
+        // from, rather than at the next one, which may be past whitespace.
+        let last = self.offsets[start + part.len() - 1];
+        let end = last + input[last..].chars().next().map_or(0, char::len_utf8);
+        &input[self.offsets[start]..end]
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::map::Map;
+    use crate::parser::parse_order;
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(normalize("A Par \u{2013} Bur"), "a par - bur");
+        assert_eq!(normalize("A Par \u{2014} Bur"), "a par - bur");
+        assert_eq!(normalize("A\u{a0}Par\u{a0}-\u{a0}Bur"), "a par - bur");
+        assert_eq!(normalize("F Lon \u{2192} Nth"), "f lon -> nth");
+        assert_eq!(
+            normalize("\u{201c}Paris\u{201d} \u{2018}holds\u{2019}"),
+            "\"paris\" 'holds'"
+        );
+        assert_eq!(normalize("  A   Par \t\n - Bur  "), "a par - bur");
+        assert_eq!(normalize("A Par - Bur."), "a par - bur");
+        assert_eq!(normalize("A Par H!.;"), "a par h");
+        assert_eq!(normalize("\u{c9}T\u{c9}"), "\u{e9}t\u{e9}");
+        assert_eq!(normalize(" \u{a0}. "), "");
+    }
+
+    #[test]
+    fn pasted_orders_parse() {
+        let map = Map::standard();
+        let order = parse_order(map, "A Par - Bur").unwrap();
+        for text in [
+            "A Par \u{2013} Bur.",
+            "A\u{a0}Par  \u{2212}  Bur",
+            "  a PARIS \u{2192} burgundy!",
+        ] {
+            assert_eq!(parse_order(map, text), Ok(order.clone()), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn offsets_into_the_original() {
+        let input = "A\u{a0}P\u{e0}r \u{2013}  Xyz.";
+        let normalized = Normalized::new(input);
+        assert_eq!(normalized.text, "a p\u{e0}r - xyz");
+
+        let at = |part: &str| normalized.text.find(part).unwrap();
+        let rest = &normalized.text[at("xyz")..];
+        assert_eq!(normalized.original(input, rest), "Xyz.");
+        assert_eq!(normalized.original(input, ""), "");
+        let part = |part: &str| &normalized.text[at(part)..at(part) + part.len()];
+        assert_eq!(
+            normalized.original_slice(input, part("p\u{e0}r")),
+            "P\u{e0}r"
+        );
+        assert_eq!(normalized.original_slice(input, part("-")), "\u{2013}");
+        assert_eq!(normalized.original_slice(input, part("xyz")), "Xyz");
+
+        // An error after multi-byte characters points into the input as
+        // it was pasted.
+        let input = "A\u{a0}Par \u{2013} Xyzzy.";
+        let error = parse_order(Map::standard(), input).unwrap_err();
+        let unknown = error.unknown_province().unwrap();
+        assert_eq!(unknown, "Xyzzy");
+        let offset = unknown.as_ptr() as usize - input.as_ptr() as usize;
+        assert_eq!(offset, input.find("Xyzzy").unwrap());
+        let input = "A Par \u{2013} Bur \u{2013}\u{a0}\u{2013} x";
+        let error = parse_order(Map::standard(), input).unwrap_err();
+        assert_eq!(error.remaining(), "\u{2013}\u{a0}\u{2013} x");
+    }
+}
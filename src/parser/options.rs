@@ -0,0 +1,135 @@
+//! Settings that tune how permissive the parser is.
+
+use super::{Aliases, Locale};
+use crate::prelude::*;
+
+/// How forgiving the parser is of input that is not quite right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Accept only exact province names, abbreviations and aliases.
+    Strict,
+    /// Also accept misspelled province names, subject to
+    /// [`ParserOptions::fuzzy_threshold`], and the filler words players
+    /// write around an order's parts, as in `A Paris supports the move
+    /// Burgundy to Munich` or `A Paris supports Burgundy to hold`.
+    Lenient,
+}
+
+/// A family of order notation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// Single-letter units and verbs and arrows: `A Par - Bur`,
+    /// `F Nth C A Lon - Nwy`.
+    Short,
+    /// Spelled-out units and verbs: `Army Paris moves to Burgundy`,
+    /// `Fleet North Sea convoys Army London to Norway`.
+    Long,
+}
+
+/// A version of the order grammar, to pin parsing to.
+///
+/// New syntax is added in a new version, so an order that parses under a
+/// version always parses the same way under it, whatever the parser
+/// learns later. Versions compare in the order they were released.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GrammarVersion {
+    /// Units, provinces and coasts, holds, moves (`via convoy` or not),
+    /// supports and convoys, in the short and long dialects. Coasts are
+    /// written `nc`, `(nc)`, `/nc` or `north coast`.
+    V1,
+    /// Adds the filler words of lenient mode, as in `A Paris supports the
+    /// move Burgundy to Munich` or `A Paris holds in place`.
+    V2,
+    /// Adds spaced and single-letter coasts: `n c`, `n.c`, `(n)` and
+    /// `/n`.
+    V3,
+}
+
+impl GrammarVersion {
+    /// The newest version, which the default [`ParserOptions`] parse.
+    pub const LATEST: GrammarVersion = GrammarVersion::V3;
+}
+
+/// Options accepted by the `_with` variants of the parse functions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParserOptions {
+    pub mode: Mode,
+    /// The notations to accept. Forms from different dialects may be mixed
+    /// within one order.
+    pub dialects: Vec<Dialect>,
+    /// In lenient mode, the minimum similarity (from 0 to 1, where 1 means
+    /// identical) for a misspelled province name to be accepted.
+    pub fuzzy_threshold: f64,
+    /// Reject orders that do not say whether the unit is an army or fleet.
+    pub require_unit: bool,
+    /// When parsing several orders, report lines naming a province that
+    /// does not exist as errors. If false,
+    /// [`parse_orders_with`](super::parse_orders_with) skips such lines, as
+    /// though they were orders for some other map, though
+    /// [`parse_orders_lossy_with`](super::parse_orders_lossy_with) still
+    /// lists them among its errors.
+    pub unknown_province_is_error: bool,
+    /// Tidy up Unicode punctuation, whitespace, trailing full stops and
+    /// case before parsing. See [`normalize`](super::normalize).
+    pub normalize: bool,
+    /// The most alternatives the parser may try in search of a parse of
+    /// one order, or `None` for no limit. Input that needs more fails with
+    /// [`ParseError::budget_exceeded`](super::ParseError::budget_exceeded)
+    /// set. Only enforced with the `std` feature.
+    pub max_steps: Option<usize>,
+    /// Players' own names for provinces, recognized as well as the map's.
+    pub aliases: Aliases,
+    /// The version of the grammar to accept. Bots that must keep reading
+    /// orders as they always have should pin one, rather than take the
+    /// default [`GrammarVersion::LATEST`].
+    pub grammar: GrammarVersion,
+    /// The language of the order grammar's keywords.
+    pub locale: Locale,
+}
+
+impl ParserOptions {
+    /// Options that accept only well-formed orders.
+    pub fn strict() -> Self {
+        ParserOptions {
+            mode: Mode::Strict,
+            require_unit: true,
+            ..ParserOptions::default()
+        }
+    }
+
+    pub fn allows(&self, dialect: Dialect) -> bool {
+        self.dialects.contains(&dialect)
+    }
+
+    /// Whether the pinned grammar has what `version` added.
+    pub(crate) fn since(&self, version: GrammarVersion) -> bool {
+        self.grammar >= version
+    }
+
+    /// The similarity threshold for misspellings, if they are accepted at
+    /// all.
+    pub(crate) fn fuzzy(&self) -> Option<f64> {
+        match self.mode {
+            Mode::Strict => None,
+            Mode::Lenient if self.fuzzy_threshold >= 1.0 => None,
+            Mode::Lenient => Some(self.fuzzy_threshold),
+        }
+    }
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            mode: Mode::Lenient,
+            dialects: vec![Dialect::Short, Dialect::Long],
+            fuzzy_threshold: 0.8,
+            require_unit: false,
+            unknown_province_is_error: true,
+            normalize: true,
+            max_steps: Some(100_000),
+            aliases: Aliases::new(),
+            grammar: GrammarVersion::LATEST,
+            locale: Locale::ENGLISH,
+        }
+    }
+}
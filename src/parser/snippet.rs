@@ -0,0 +1,154 @@
+//! Showing where an order went wrong, as a compiler does: the line with a
+//! caret under the failing part, for bots to quote back to the player.
+
+use core::fmt;
+
+use super::ParseError;
+use crate::map::unicode::is_word_char;
+use crate::prelude::*;
+
+/// A [`ParseError`] with the line it is about and a caret under the part
+/// that failed:
+///
+/// ```text
+/// line 2: expected destination after '-'
+///   |
+/// 2 | F Nth C A Lon -
+///   |                ^ expected destination
+/// ```
+///
+/// Made by [`ParseError::snippet`].
+#[derive(Clone, Copy, Debug)]
+pub struct Snippet<'e, 'a> {
+    error: &'e ParseError<'a>,
+    input: &'a str,
+}
+
+impl<'a> ParseError<'a> {
+    /// The error with a caret under where it went wrong in `input`, which
+    /// must be the text given to the parse function, all of it when several
+    /// orders were parsed. Given other text, the snippet is just the
+    /// error's message.
+    pub fn snippet<'e>(&'e self, input: &'a str) -> Snippet<'e, 'a> {
+        Snippet { error: self, input }
+    }
+}
+
+impl<'e, 'a> Snippet<'e, 'a> {
+    /// The byte range in the input to put the caret under, and what to say
+    /// beside it.
+    fn span(&self) -> Option<(usize, usize, String)> {
+        let error = self.error;
+        if error.budget_exceeded {
+            return None;
+        }
+        if let Some(word) = error.unknown_province {
+            let start = self.offset(word)?;
+            return Some((start, start + word.len(), String::from("unknown province")));
+        }
+        let (start, note) = match error.expected {
+            Some(expected) => {
+                let start = match expected.after {
+                    Some(after) => self.offset(after)? + after.len(),
+                    None => self.offset(error.remaining)?,
+                };
+                (start, format!("expected {}", expected.label))
+            }
+            None => (
+                self.offset(error.remaining)?,
+                String::from("not understood"),
+            ),
+        };
+        let rest = &self.input[start..];
+        let start = start + (rest.len() - rest.trim_start_matches([' ', '\t']).len());
+        let word = self.input[start..]
+            .find(|c: char| !is_word_char(c))
+            .unwrap_or(self.input.len() - start);
+        Some((start, start + word, note))
+    }
+
+    /// Where `part`, a slice of the input, starts in it.
+    fn offset(&self, part: &str) -> Option<usize> {
+        let start = (part.as_ptr() as usize).checked_sub(self.input.as_ptr() as usize)?;
+        (start + part.len() <= self.input.len()).then_some(start)
+    }
+}
+
+impl<'e, 'a> fmt::Display for Snippet<'e, 'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        let (start, end, note) = match self.span() {
+            Some(span) => span,
+            None => return Ok(()),
+        };
+        let line_start = self.input[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.input[start..]
+            .find(['\r', '\n'])
+            .map_or(self.input.len(), |i| start + i);
+        let number = self
+            .error
+            .line
+            .unwrap_or_else(|| self.input[..start].matches('\n').count() + 1);
+        let number = format!("{}", number);
+        let gutter = " ".repeat(number.len());
+        let column = self.input[line_start..start].chars().count();
+        let width = self.input[start..end].chars().count().max(1);
+        writeln!(f)?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", number, &self.input[line_start..line_end])?;
+        write!(
+            f,
+            "{} | {}{} {}",
+            gutter,
+            " ".repeat(column),
+            "^".repeat(width),
+            note
+        )
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::map::Map;
+    use crate::parser::{parse_order, parse_orders};
+
+    #[test]
+    fn carets_under_the_failing_part() {
+        let map = Map::standard();
+        let input = "A Par - Burgandi";
+        let error = parse_order(map, input).unwrap_err();
+        assert_eq!(
+            error.snippet(input).to_string(),
+            "unknown province \"Burgandi\"\n  \
+             |\n\
+             1 | A Par - Burgandi\n  \
+             |         ^^^^^^^^ unknown province"
+        );
+
+        let input = "A Par H\n\nF Nth C A Lon -\nA Mun H";
+        let error = parse_orders(map, input).unwrap_err();
+        assert_eq!(
+            error.snippet(input).to_string(),
+            "line 3: expected destination after '-'\n  \
+             |\n\
+             3 | F Nth C A Lon -\n  \
+             |                ^ expected destination"
+        );
+
+        let input = "A Par  supports";
+        let error = parse_order(map, input).unwrap_err();
+        assert_eq!(
+            error.snippet(input).to_string(),
+            "expected unit after 'supports'\n  \
+             |\n\
+             1 | A Par  supports\n  \
+             |                ^ expected unit"
+        );
+
+        // Text the error is not about gets just the message.
+        assert_eq!(
+            error.snippet("A Par S").to_string(),
+            "expected unit after 'supports'"
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Exhaustive play of small positions, to count the orders and positions
+//! reachable from them and to check the adjudicator along the way.
+//!
+//! Named after the move-path enumeration used to test chess engines. The
+//! number of order combinations grows as the product of every unit's
+//! legal orders, so this is only practical for a handful of units and a
+//! depth of one or two phases.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+use crate::adjudicator::adjudicate;
+use crate::map::{Province, ProvinceKind};
+use crate::order::{Order, UnitType};
+use crate::phase::{Phase, PhaseKind, Season};
+use crate::power::Power;
+use crate::state::{GameState, Unit};
+
+/// What [`perft`] found.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Perft {
+    /// The order combinations adjudicated, over every phase played.
+    pub combinations: u64,
+    /// The distinct positions reached after the last phase.
+    pub positions: u64,
+}
+
+/// A position that the adjudicator should never have produced, with the
+/// orders that produced it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvariantViolation {
+    phase: Phase,
+    orders: Vec<String>,
+    message: String,
+}
+
+impl InvariantViolation {
+    /// The phase whose adjudication went wrong.
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// The orders adjudicated, as "Power: order".
+    pub fn orders(&self) -> &[String] {
+        &self.orders
+    }
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} after {}",
+            self.phase,
+            self.message,
+            self.orders.join("; ")
+        )
+    }
+}
+
+impl Error for InvariantViolation {}
+
+/// The number of order combinations for the units on the board: the
+/// product of the number of legal orders for each unit.
+pub fn count_orders(state: &GameState) -> u128 {
+    state
+        .units()
+        .map(|(province, _)| state.legal_orders(province).len() as u128)
+        .product()
+}
+
+/// Plays every combination of legal orders from `state`, then every
+/// combination from each position that results, `depth` phases deep.
+///
+/// In a retreat phase, each dislodged unit either retreats to each place
+/// it may or is disbanded; an adjustment phase is played without orders.
+/// Positions reached more than one way are only played on from once.
+/// Each result is checked: no unit may appear or vanish except by being
+/// dislodged, disbanded or removed, every unit must stand where it can,
+/// and no supply center may be lost without being captured.
+pub fn perft<'m>(state: &GameState<'m>, depth: u32) -> Result<Perft, InvariantViolation> {
+    let mut counts = Perft::default();
+    let mut positions = vec![state.clone()];
+    for _ in 0..depth {
+        let mut seen = HashSet::new();
+        let mut next = Vec::new();
+        for position in &positions {
+            for_each_combination(position, |orders| {
+                counts.combinations += 1;
+                let result = adjudicate(orders.iter().cloned().collect(), position);
+                let after = position.apply(&result);
+                check(position, &after).map_err(|message| InvariantViolation {
+                    phase: position.phase(),
+                    orders: orders
+                        .iter()
+                        .flat_map(|(power, orders)| {
+                            orders
+                                .iter()
+                                .map(move |order| format!("{}: {}", power, order))
+                        })
+                        .collect(),
+                    message,
+                })?;
+                if seen.insert(after.to_json()) {
+                    next.push(after);
+                }
+                Ok(())
+            })?;
+        }
+        positions = next;
+    }
+    counts.positions = positions.len() as u64;
+    Ok(counts)
+}
+
+/// Calls `f` with every combination of legal orders for the units to be
+/// ordered in the phase, grouped by power.
+fn for_each_combination<'m, E>(
+    state: &GameState<'m>,
+    mut f: impl FnMut(&[(Power, Vec<Order<'m>>)]) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut units: Vec<_> = match state.phase().kind {
+        PhaseKind::Movement => state
+            .units()
+            .map(|(province, unit)| (province.id(), unit.power(), state.legal_orders(province)))
+            .collect(),
+        PhaseKind::Retreats => state
+            .dislodged()
+            .map(|(province, unit)| {
+                (
+                    province.id(),
+                    unit.power(),
+                    retreat_orders(state, province, unit),
+                )
+            })
+            .collect(),
+        PhaseKind::Adjustments => Vec::new(),
+    };
+    units.sort_by_key(|&(id, power, _)| (power, id));
+    let mut choice = vec![0; units.len()];
+    loop {
+        let mut orders: Vec<(Power, Vec<Order<'m>>)> = Vec::new();
+        for ((_, power, options), &i) in units.iter().zip(&choice) {
+            match orders.last_mut() {
+                Some((last, orders)) if last == power => orders.push(options[i].clone()),
+                _ => orders.push((*power, vec![options[i].clone()])),
+            }
+        }
+        f(&orders)?;
+
+        // Count up, like an odometer.
+        let mut digit = 0;
+        loop {
+            if digit == units.len() {
+                return Ok(());
+            }
+            choice[digit] += 1;
+            if choice[digit] < units[digit].2.len() {
+                break;
+            }
+            choice[digit] = 0;
+            digit += 1;
+        }
+    }
+}
+
+/// The dislodged `unit`'s disband, as a hold, and its retreats.
+fn retreat_orders<'m>(
+    state: &GameState<'m>,
+    province: &'m Province,
+    unit: &Unit,
+) -> Vec<Order<'m>> {
+    let mut orders = vec![Order::hold(unit.unit_type(), province)];
+    orders.extend(
+        state
+            .retreats(province)
+            .iter()
+            .filter_map(|&(to, coast)| match coast {
+                Some(coast) => Order::move_to_coast(unit.unit_type(), province, to, coast).ok(),
+                None => Order::move_(unit.unit_type(), province, to).ok(),
+            }),
+    );
+    orders
+}
+
+/// Checks `after` against the position it was adjudicated from.
+fn check(before: &GameState, after: &GameState) -> Result<(), String> {
+    let units = before.units().count();
+    let remaining = after.units().count() + after.dislodged().count();
+    let expected = match before.phase().kind {
+        PhaseKind::Movement => units..=units,
+        PhaseKind::Retreats => units..=units + before.dislodged().count(),
+        PhaseKind::Adjustments => {
+            let removals: usize = Power::ALL
+                .iter()
+                .map(|&power| (-before.adjustment(power)).max(0) as usize)
+                .sum();
+            units - removals..=units - removals
+        }
+    };
+    if !expected.contains(&remaining) {
+        return Err(format!("{} units became {}", units, remaining));
+    }
+    for (province, unit) in after.units() {
+        if !can_stand(province, unit) {
+            return Err(format!(
+                "{} {} {} cannot stand there",
+                unit.power().adjective(),
+                unit.unit_type(),
+                province
+            ));
+        }
+    }
+    for province in before.map().provinces() {
+        let (owner, new_owner) = (before.owner(province), after.owner(province));
+        if owner.is_some() && new_owner.is_none() {
+            return Err(format!("{} lost its owner", province));
+        }
+        if owner != new_owner {
+            let captured = before.phase().season == Season::Fall
+                && after
+                    .unit_at(province)
+                    .is_some_and(|unit| Some(unit.power()) == new_owner);
+            if !captured {
+                return Err(format!("{} changed hands without a capture", province));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether a unit could stand in `province`, on the coast it claims.
+fn can_stand(province: &Province, unit: &Unit) -> bool {
+    match (unit.unit_type(), province.kind()) {
+        (UnitType::Army, ProvinceKind::Sea) | (UnitType::Fleet, ProvinceKind::Land) => false,
+        (UnitType::Army, _) => unit.coast().is_none(),
+        (UnitType::Fleet, _) => match unit.coast() {
+            Some(coast) => province.coasts().contains(&coast),
+            None => !province.is_bicoastal(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Map;
+
+    #[test]
+    fn small_positions() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut state = GameState::new(map);
+        state.place_unit(province("Par"), Unit::army(Power::France));
+        state.place_unit(province("Bre"), Unit::fleet(Power::France));
+        state.place_unit(province("Mun"), Unit::army(Power::Germany));
+        state.set_owner(province("Par"), Power::France);
+
+        let orders = count_orders(&state);
+        assert_eq!(orders, 9 * 7 * 9);
+        let one = perft(&state, 1).unwrap();
+        assert_eq!(u128::from(one.combinations), orders);
+        assert!(one.positions > 1);
+
+        state.remove_unit(province("Bre"));
+        let two = perft(&state, 2).unwrap();
+        assert!(u128::from(two.combinations) > count_orders(&state));
+        assert!(two.positions > 1);
+    }
+}
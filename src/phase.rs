@@ -0,0 +1,328 @@
+//! Seasons, years, and the phases of a game year.
+
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::prelude::*;
+
+/// The seasons of a game year.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Season {
+    Spring,
+    Fall,
+    Winter,
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Season::Spring => "Spring",
+            Season::Fall => "Fall",
+            Season::Winter => "Winter",
+        })
+    }
+}
+
+/// What happens in a phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PhaseKind {
+    Movement,
+    Retreats,
+    Adjustments,
+}
+
+impl fmt::Display for PhaseKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            PhaseKind::Movement => "Movement",
+            PhaseKind::Retreats => "Retreats",
+            PhaseKind::Adjustments => "Adjustments",
+        })
+    }
+}
+
+/// A phase of the game, such as Spring 1901 Movement.
+///
+/// Phases order chronologically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Phase {
+    pub year: u16,
+    pub season: Season,
+    pub kind: PhaseKind,
+}
+
+impl Phase {
+    /// The first phase of a standard game, Spring 1901 Movement.
+    pub const FIRST: Phase = Phase::movement(Season::Spring, 1901);
+
+    pub const fn new(season: Season, year: u16, kind: PhaseKind) -> Self {
+        Phase { year, season, kind }
+    }
+
+    pub const fn movement(season: Season, year: u16) -> Self {
+        Phase::new(season, year, PhaseKind::Movement)
+    }
+
+    /// The phase after this one, counting every retreat and adjustment
+    /// phase whether or not it will be needed.
+    pub fn next(self) -> Self {
+        match (self.season, self.kind) {
+            (Season::Spring, PhaseKind::Movement) => {
+                Phase::new(Season::Spring, self.year, PhaseKind::Retreats)
+            }
+            (Season::Spring, _) => Phase::movement(Season::Fall, self.year),
+            (Season::Fall, PhaseKind::Movement) => {
+                Phase::new(Season::Fall, self.year, PhaseKind::Retreats)
+            }
+            (Season::Fall, _) => Phase::new(Season::Winter, self.year, PhaseKind::Adjustments),
+            (Season::Winter, _) => Phase::movement(Season::Spring, self.year + 1),
+        }
+    }
+
+    /// Whether a game can reach the phase: Spring and Fall have movement
+    /// and retreat phases, and Winter only an adjustment phase.
+    fn exists(self) -> bool {
+        matches!(
+            (self.season, self.kind),
+            (
+                Season::Spring | Season::Fall,
+                PhaseKind::Movement | PhaseKind::Retreats
+            ) | (Season::Winter, PhaseKind::Adjustments)
+        )
+    }
+
+    /// The next movement phase: Fall of the same year after Spring, and
+    /// Spring of the next year otherwise.
+    pub fn next_movement(self) -> Self {
+        match self.season {
+            Season::Spring => Phase::movement(Season::Fall, self.year),
+            Season::Fall | Season::Winter => Phase::movement(Season::Spring, self.year + 1),
+        }
+    }
+}
+
+impl Default for Phase {
+    fn default() -> Self {
+        Phase::FIRST
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.season, self.year, self.kind)
+    }
+}
+
+/// The error returned when a string does not name a phase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownPhase(pub String);
+
+impl fmt::Display for UnknownPhase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown phase {:?}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for UnknownPhase {}
+
+impl FromStr for Phase {
+    type Err = UnknownPhase;
+
+    /// Accepts phases as they are displayed, like "Spring 1901 Movement",
+    /// ignoring case, and the other ways results write them: with
+    /// "Autumn" for Fall, "Retreat" or "Builds" for the kind, or no kind,
+    /// which is movement in the Spring and Fall and adjustments in the
+    /// Winter; or as a code like "F1905R" or "W1901A". Phases no game has,
+    /// like "Winter 1901 Movement" or "S1901B", are not accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || UnknownPhase(s.to_owned());
+        let mut words = s.split_whitespace();
+        let first = words.next().ok_or_else(unknown)?;
+        let phase = match words.next() {
+            None => Phase::from_code(first),
+            Some(year) => {
+                let season = season(first).ok_or_else(unknown)?;
+                let year = year.parse().map_err(|_| unknown())?;
+                let kind = match words.next() {
+                    None if season == Season::Winter => Some(PhaseKind::Adjustments),
+                    None => Some(PhaseKind::Movement),
+                    Some(kind) => phase_kind(kind),
+                };
+                kind.map(|kind| Phase::new(season, year, kind))
+            }
+        };
+        match phase {
+            Some(phase) if phase.exists() && words.next().is_none() => Ok(phase),
+            _ => Err(unknown()),
+        }
+    }
+}
+
+impl Phase {
+    /// The phase's code, as in "S1901M" or "F1905R".
+    pub fn code(self) -> String {
+        let season = match self.season {
+            Season::Spring => 'S',
+            Season::Fall => 'F',
+            Season::Winter => 'W',
+        };
+        let kind = match self.kind {
+            PhaseKind::Movement => 'M',
+            PhaseKind::Retreats => 'R',
+            PhaseKind::Adjustments => 'A',
+        };
+        format!("{}{}{}", season, self.year, kind)
+    }
+
+    /// Reads a code like "F1905R", ignoring case. Adjustments may also be
+    /// written with a "B", for builds.
+    fn from_code(code: &str) -> Option<Phase> {
+        if !code.is_ascii() || code.len() < 3 {
+            return None;
+        }
+        let (season, rest) = code.split_at(1);
+        let (year, kind) = rest.split_at(rest.len() - 1);
+        let season = match season.to_ascii_lowercase().as_str() {
+            "s" => Season::Spring,
+            "f" | "a" => Season::Fall,
+            "w" => Season::Winter,
+            _ => return None,
+        };
+        let kind = match kind.to_ascii_lowercase().as_str() {
+            "m" => PhaseKind::Movement,
+            "r" => PhaseKind::Retreats,
+            "a" | "b" => PhaseKind::Adjustments,
+            _ => return None,
+        };
+        if !year.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some(Phase::new(season, year.parse().ok()?, kind))
+    }
+}
+
+fn season(word: &str) -> Option<Season> {
+    match word.to_ascii_lowercase().as_str() {
+        "spring" => Some(Season::Spring),
+        "fall" | "autumn" => Some(Season::Fall),
+        "winter" => Some(Season::Winter),
+        _ => None,
+    }
+}
+
+fn phase_kind(word: &str) -> Option<PhaseKind> {
+    match word.to_ascii_lowercase().as_str() {
+        "movement" | "moves" | "orders" => Some(PhaseKind::Movement),
+        "retreats" | "retreat" => Some(PhaseKind::Retreats),
+        "adjustments" | "adjustment" | "builds" | "build" => Some(PhaseKind::Adjustments),
+        _ => None,
+    }
+}
+
+/// Reads a line that heads a phase's orders or results, like "Spring 1903
+/// Movement:", "== F1905R ==" or "Winter 1901 Adjustments.", allowing the
+/// punctuation around it that [`Phase::from_str`] does not.
+pub fn phase_header(line: &str) -> Option<Phase> {
+    line.trim_matches(|c: char| !c.is_alphanumeric())
+        .parse()
+        .ok()
+}
+
+/// Splits `text` into the parts headed by each [phase header](phase_header)
+/// line, with the phase, not counting the header itself. Any text before
+/// the first header comes first, without a phase, unless it is blank.
+pub fn split_phases(text: &str) -> Vec<(Option<Phase>, &str)> {
+    let mut parts = Vec::new();
+    let mut current = (None, 0);
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if let Some(phase) = phase_header(line) {
+            let (previous, start) = current;
+            let part = &text[start..offset];
+            if previous.is_some() || !part.trim().is_empty() {
+                parts.push((previous, part));
+            }
+            current = (Some(phase), offset + line.len());
+        }
+        offset += line.len();
+    }
+    let (previous, start) = current;
+    let part = &text[start..];
+    if previous.is_some() || !part.trim().is_empty() {
+        parts.push((previous, part));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_names_and_codes() {
+        let phase = |text: &str| text.parse::<Phase>();
+        let fall_retreats = Phase::new(Season::Fall, 1905, PhaseKind::Retreats);
+        assert_eq!(phase("Fall 1905 Retreats"), Ok(fall_retreats));
+        assert_eq!(phase("autumn 1905 retreat"), Ok(fall_retreats));
+        assert_eq!(phase("F1905R"), Ok(fall_retreats));
+        assert_eq!(fall_retreats.code(), "F1905R");
+        assert_eq!(phase("f1905r"), Ok(fall_retreats));
+        assert_eq!(
+            phase("Winter 1901"),
+            Ok(Phase::new(Season::Winter, 1901, PhaseKind::Adjustments))
+        );
+        assert_eq!(phase("W1901B"), phase("Winter 1901 Builds"));
+        assert_eq!(
+            phase("Spring 1903"),
+            Ok(Phase::movement(Season::Spring, 1903))
+        );
+        for text in [
+            "",
+            "Spring",
+            "X1901M",
+            "S19x1M",
+            "Spring 1901 Movement now",
+            "S1901",
+            "Winter 1901 Movement",
+            "Spring 1901 Adjustments",
+            "Fall 1901 Builds",
+            "W1901R",
+            "S1901B",
+            "F1901A",
+        ] {
+            assert!(phase(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn splitting_by_phase() {
+        let text = "Game 7\n\
+                    Spring 1901 Movement:\n\
+                    A Par - Bur\n\
+                    == F1901M ==\n\
+                    Winter 1901 Adjustments.\n\
+                    Build A Par\n";
+        let parts = split_phases(text);
+        let phases: Vec<_> = parts
+            .iter()
+            .map(|(phase, _)| phase.map(Phase::code))
+            .collect();
+        assert_eq!(
+            phases,
+            [
+                None,
+                Some("S1901M".to_owned()),
+                Some("F1901M".to_owned()),
+                Some("W1901A".to_owned())
+            ]
+        );
+        assert_eq!(parts[1].1, "A Par - Bur\n");
+        assert_eq!(parts[2].1, "");
+        assert_eq!(parts[3].1, "Build A Par\n");
+        assert_eq!(phase_header("A Par - Bur"), None);
+        assert_eq!(phase_header("Winter 1901 Movement:"), None);
+    }
+}
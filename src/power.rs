@@ -0,0 +1,90 @@
+//! The great powers.
+
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::prelude::*;
+
+/// One of the seven great powers of the standard game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Power {
+    Austria,
+    England,
+    France,
+    Germany,
+    Italy,
+    Russia,
+    Turkey,
+}
+
+impl Power {
+    /// Every power, in alphabetical order.
+    pub const ALL: [Power; 7] = [
+        Power::Austria,
+        Power::England,
+        Power::France,
+        Power::Germany,
+        Power::Italy,
+        Power::Russia,
+        Power::Turkey,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Power::Austria => "Austria",
+            Power::England => "England",
+            Power::France => "France",
+            Power::Germany => "Germany",
+            Power::Italy => "Italy",
+            Power::Russia => "Russia",
+            Power::Turkey => "Turkey",
+        }
+    }
+
+    /// The adjective for the power's units, e.g. "Austrian".
+    pub fn adjective(self) -> &'static str {
+        match self {
+            Power::Austria => "Austrian",
+            Power::England => "English",
+            Power::France => "French",
+            Power::Germany => "German",
+            Power::Italy => "Italian",
+            Power::Russia => "Russian",
+            Power::Turkey => "Turkish",
+        }
+    }
+}
+
+impl fmt::Display for Power {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The error returned when a string does not name a power.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownPower(pub String);
+
+impl fmt::Display for UnknownPower {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown power {:?}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for UnknownPower {}
+
+impl FromStr for Power {
+    type Err = UnknownPower;
+
+    /// Accepts power names, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Power::ALL
+            .iter()
+            .copied()
+            .find(|power| power.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownPower(s.to_owned()))
+    }
+}
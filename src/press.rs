@@ -0,0 +1,575 @@
+//! Diplomatic messages in the DAIDE press syntax, as used by negotiating
+//! bots.
+//!
+//! This covers the proposals and replies of press level 10 (peace,
+//! alliances, draws and solos) and the order suggestions and
+//! demilitarized zones of press level 20, joined with `AND`, `ORR` and
+//! `NOT`.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::map::{Coast, Map, Province, ProvinceKind};
+use crate::order::{Order, OrderError, SupportedOrder, UnitType};
+use crate::power::Power;
+
+/// A press message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Press<'m> {
+    /// `PRP (arrangement)`
+    Propose(Arrangement<'m>),
+    /// `YES (PRP (arrangement))`
+    Accept(Arrangement<'m>),
+    /// `REJ (PRP (arrangement))`
+    Reject(Arrangement<'m>),
+    /// `CCL (PRP (arrangement))`, withdrawing an earlier proposal.
+    Cancel(Arrangement<'m>),
+    /// `FCT (arrangement)`, stating that something is so.
+    Fact(Arrangement<'m>),
+}
+
+impl<'m> Press<'m> {
+    /// The arrangement the message is about.
+    pub fn arrangement(&self) -> &Arrangement<'m> {
+        match self {
+            Press::Propose(arrangement)
+            | Press::Accept(arrangement)
+            | Press::Reject(arrangement)
+            | Press::Cancel(arrangement)
+            | Press::Fact(arrangement) => arrangement,
+        }
+    }
+}
+
+impl<'m> fmt::Display for Press<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Press::Propose(arrangement) => write!(f, "PRP ({})", arrangement),
+            Press::Accept(arrangement) => write!(f, "YES (PRP ({}))", arrangement),
+            Press::Reject(arrangement) => write!(f, "REJ (PRP ({}))", arrangement),
+            Press::Cancel(arrangement) => write!(f, "CCL (PRP ({}))", arrangement),
+            Press::Fact(arrangement) => write!(f, "FCT ({})", arrangement),
+        }
+    }
+}
+
+/// Something powers can agree to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Arrangement<'m> {
+    /// `PCE (powers)`: the powers will not attack each other.
+    Peace(Vec<Power>),
+    /// `ALY (allies) VSS (enemies)`
+    Alliance {
+        allies: Vec<Power>,
+        against: Vec<Power>,
+    },
+    /// `DRW`: the game ends in a draw between the survivors.
+    Draw,
+    /// `SLO (power)`: the game ends in a win for one power.
+    Solo(Power),
+    /// `DMZ (powers) (provinces)`: the powers will keep their units out of
+    /// the provinces.
+    Demilitarize {
+        powers: Vec<Power>,
+        provinces: Vec<&'m Province>,
+    },
+    /// `XDO (order)`: a unit will be given an order.
+    Order(SuggestedOrder<'m>),
+    /// `AND (arrangement) (arrangement) ...`
+    And(Vec<Arrangement<'m>>),
+    /// `ORR (arrangement) (arrangement) ...`
+    Or(Vec<Arrangement<'m>>),
+    /// `NOT (arrangement)`
+    Not(Box<Arrangement<'m>>),
+}
+
+impl<'m> fmt::Display for Arrangement<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arrangement::Peace(powers) => write!(f, "PCE ({})", Powers(powers)),
+            Arrangement::Alliance { allies, against } => {
+                write!(f, "ALY ({}) VSS ({})", Powers(allies), Powers(against))
+            }
+            Arrangement::Draw => f.write_str("DRW"),
+            Arrangement::Solo(power) => write!(f, "SLO ({})", power_token(*power)),
+            Arrangement::Demilitarize { powers, provinces } => {
+                write!(f, "DMZ ({}) (", Powers(powers))?;
+                for (i, province) in provinces.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    f.write_str(&province_token(province))?;
+                }
+                f.write_str(")")
+            }
+            Arrangement::Order(order) => write!(f, "XDO ({})", order),
+            Arrangement::And(arrangements) => write_joined(f, "AND", arrangements),
+            Arrangement::Or(arrangements) => write_joined(f, "ORR", arrangements),
+            Arrangement::Not(arrangement) => write!(f, "NOT ({})", arrangement),
+        }
+    }
+}
+
+fn write_joined(f: &mut fmt::Formatter, token: &str, arrangements: &[Arrangement]) -> fmt::Result {
+    f.write_str(token)?;
+    for arrangement in arrangements {
+        write!(f, " ({})", arrangement)?;
+    }
+    Ok(())
+}
+
+/// Powers separated by spaces, as in `PCE (FRA GER)`.
+struct Powers<'a>(&'a [Power]);
+
+impl<'a> fmt::Display for Powers<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, power) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(power_token(*power))?;
+        }
+        Ok(())
+    }
+}
+
+/// An order suggested in press, with the powers of the units it names.
+///
+/// DAIDE has no way to leave out a unit's type. Orders that do not name
+/// one are written with an army, unless the unit is at sea. Convoy routes
+/// are not kept, so moves via convoy are written as plain moves.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SuggestedOrder<'m> {
+    /// The power of the ordered unit.
+    pub power: Power,
+    pub order: Order<'m>,
+    /// The power of the supported or convoyed unit, if it is not `power`.
+    pub target: Option<Power>,
+}
+
+impl<'m> SuggestedOrder<'m> {
+    /// An order to one of `power`'s units, supporting or convoying only
+    /// its own units.
+    pub fn new(power: Power, order: Order<'m>) -> Self {
+        SuggestedOrder {
+            power,
+            order,
+            target: None,
+        }
+    }
+}
+
+impl<'m> fmt::Display for SuggestedOrder<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let target = self.target.unwrap_or(self.power);
+        let order = &self.order;
+        write_unit(f, self.power, order.unit(), order.province())?;
+        match order {
+            Order::Hold(_) => f.write_str(" HLD"),
+            Order::Move(move_) => {
+                f.write_str(" MTO ")?;
+                match move_.coast() {
+                    Some(coast) => write!(
+                        f,
+                        "({} {})",
+                        province_token(move_.destination()),
+                        coast_token(coast)
+                    ),
+                    None => f.write_str(&province_token(move_.destination())),
+                }
+            }
+            Order::Support(support) => {
+                f.write_str(" SUP ")?;
+                let supported = support.supported();
+                write_unit(f, target, supported.unit(), supported.province())?;
+                match supported {
+                    SupportedOrder::Hold(_) => Ok(()),
+                    SupportedOrder::Move(move_) => {
+                        write!(f, " MTO {}", province_token(move_.destination()))
+                    }
+                }
+            }
+            Order::Convoy(convoy) => {
+                f.write_str(" CVY ")?;
+                write_unit(f, target, Some(UnitType::Army), convoy.from())?;
+                write!(f, " CTO {}", province_token(convoy.to()))
+            }
+        }
+    }
+}
+
+/// `(FRA AMY PAR)`
+fn write_unit(
+    f: &mut fmt::Formatter,
+    power: Power,
+    unit: Option<UnitType>,
+    province: &Province,
+) -> fmt::Result {
+    let unit = match unit {
+        Some(UnitType::Fleet) => "FLT",
+        None if province.kind() == ProvinceKind::Sea => "FLT",
+        _ => "AMY",
+    };
+    write!(
+        f,
+        "({} {} {})",
+        power_token(power),
+        unit,
+        province_token(province)
+    )
+}
+
+/// Why some text is not a press message that dipboy understands.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PressError {
+    message: String,
+}
+
+impl PressError {
+    fn new(message: impl Into<String>) -> Self {
+        PressError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for PressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for PressError {}
+
+impl From<OrderError> for PressError {
+    fn from(error: OrderError) -> Self {
+        PressError::new(error.to_string())
+    }
+}
+
+/// Parses a press message, like `PRP (PCE (FRA GER))`.
+///
+/// Tokens are read without regard to case.
+pub fn parse<'m>(map: &'m Map, text: &str) -> Result<Press<'m>, PressError> {
+    let reader = Reader { map };
+    match tokenize(text)?.as_slice() {
+        [Expr::Atom(token), Expr::List(body)] => match token.as_str() {
+            "PRP" => Ok(Press::Propose(reader.arrangement(body)?)),
+            "FCT" => Ok(Press::Fact(reader.arrangement(body)?)),
+            "YES" | "REJ" | "CCL" => {
+                let arrangement = match body.as_slice() {
+                    [Expr::Atom(prp), Expr::List(body)] if prp == "PRP" => {
+                        reader.arrangement(body)?
+                    }
+                    _ => return Err(PressError::new(format!("expected PRP after {}", token))),
+                };
+                Ok(match token.as_str() {
+                    "YES" => Press::Accept(arrangement),
+                    "REJ" => Press::Reject(arrangement),
+                    _ => Press::Cancel(arrangement),
+                })
+            }
+            token => Err(PressError::new(format!("unknown message {}", token))),
+        },
+        _ => Err(PressError::new(
+            "expected a message like PRP (...)".to_owned(),
+        )),
+    }
+}
+
+/// A token, or a parenthesized list of them.
+#[derive(Debug)]
+enum Expr {
+    Atom(String),
+    List(Vec<Expr>),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Expr>, PressError> {
+    let mut stack = vec![Vec::new()];
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '(' => stack.push(Vec::new()),
+            ')' => {
+                let list = stack
+                    .pop()
+                    .filter(|_| !stack.is_empty())
+                    .ok_or_else(|| PressError::new("unbalanced ')'"))?;
+                stack.last_mut().unwrap().push(Expr::List(list));
+            }
+            c if c.is_whitespace() => {}
+            c if c.is_ascii_alphanumeric() => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if !c.is_ascii_alphanumeric() {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let atom = text[start..end].to_ascii_uppercase();
+                stack.last_mut().unwrap().push(Expr::Atom(atom));
+            }
+            c => return Err(PressError::new(format!("unexpected {:?}", c))),
+        }
+    }
+    match stack.pop() {
+        Some(exprs) if stack.is_empty() => Ok(exprs),
+        _ => Err(PressError::new("unbalanced '('")),
+    }
+}
+
+struct Reader<'m> {
+    map: &'m Map,
+}
+
+impl<'m> Reader<'m> {
+    fn arrangement(&self, body: &[Expr]) -> Result<Arrangement<'m>, PressError> {
+        let token = match body.first() {
+            Some(Expr::Atom(token)) => token.as_str(),
+            _ => return Err(PressError::new("expected an arrangement")),
+        };
+        let args = &body[1..];
+        match (token, args) {
+            ("PCE", [Expr::List(powers)]) => Ok(Arrangement::Peace(powers_of(powers)?)),
+            ("ALY", [Expr::List(allies), Expr::Atom(vss), Expr::List(against)]) if vss == "VSS" => {
+                Ok(Arrangement::Alliance {
+                    allies: powers_of(allies)?,
+                    against: powers_of(against)?,
+                })
+            }
+            ("DRW", []) => Ok(Arrangement::Draw),
+            ("SLO", [Expr::List(power)]) => match power.as_slice() {
+                [Expr::Atom(power)] => Ok(Arrangement::Solo(parse_power(power)?)),
+                _ => Err(PressError::new("SLO takes one power")),
+            },
+            ("DMZ", [Expr::List(powers), Expr::List(provinces)]) => Ok(Arrangement::Demilitarize {
+                powers: powers_of(powers)?,
+                provinces: provinces
+                    .iter()
+                    .map(|province| self.province(province).map(|(province, _)| province))
+                    .collect::<Result<_, _>>()?,
+            }),
+            ("XDO", [Expr::List(order)]) => Ok(Arrangement::Order(self.order(order)?)),
+            ("AND", _) | ("ORR", _) if args.len() >= 2 => {
+                let arrangements = args
+                    .iter()
+                    .map(|arg| match arg {
+                        Expr::List(body) => self.arrangement(body),
+                        Expr::Atom(_) => Err(PressError::new(format!(
+                            "{} takes parenthesized arrangements",
+                            token
+                        ))),
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(match token {
+                    "AND" => Arrangement::And(arrangements),
+                    _ => Arrangement::Or(arrangements),
+                })
+            }
+            ("NOT", [Expr::List(body)]) => Ok(Arrangement::Not(Box::new(self.arrangement(body)?))),
+            _ => Err(PressError::new(format!("unknown or malformed {}", token))),
+        }
+    }
+
+    fn order(&self, body: &[Expr]) -> Result<SuggestedOrder<'m>, PressError> {
+        let (unit, rest) = match body.split_first() {
+            Some((Expr::List(unit), rest)) => (unit, rest),
+            _ => return Err(PressError::new("expected a unit like (FRA AMY PAR)")),
+        };
+        let (power, unit_type, province) = self.unit(unit)?;
+        let mut target = None;
+        let order = match rest {
+            [Expr::Atom(hld)] if hld == "HLD" => Order::hold(unit_type, province),
+            [Expr::Atom(mto), to] if mto == "MTO" => match self.province(to)? {
+                (to, Some(coast)) => Order::move_to_coast(unit_type, province, to, coast)?,
+                (to, None) => Order::move_(unit_type, province, to)?,
+            },
+            [Expr::Atom(cto), to, Expr::Atom(via), Expr::List(_)]
+                if cto == "CTO" && via == "VIA" =>
+            {
+                Order::move_via_convoy(unit_type, province, self.province(to)?.0)?
+            }
+            [Expr::Atom(sup), Expr::List(supported), rest @ ..] if sup == "SUP" => {
+                let (power, supported_type, from) = self.unit(supported)?;
+                target = Some(power);
+                match rest {
+                    [] => Order::support_hold(unit_type, province, supported_type, from)?,
+                    [Expr::Atom(mto), to] if mto == "MTO" => {
+                        let to = self.province(to)?.0;
+                        Order::support_move(unit_type, province, supported_type, from, to)?
+                    }
+                    _ => return Err(PressError::new("malformed SUP order")),
+                }
+            }
+            [Expr::Atom(cvy), Expr::List(convoyed), Expr::Atom(cto), to]
+                if cvy == "CVY" && cto == "CTO" =>
+            {
+                let (power, convoyed_type, from) = self.unit(convoyed)?;
+                target = Some(power);
+                let to = self.province(to)?.0;
+                Order::convoy(unit_type, province, convoyed_type, from, to)?
+            }
+            _ => return Err(PressError::new("unknown or malformed order")),
+        };
+        Ok(SuggestedOrder {
+            power,
+            order,
+            target: target.filter(|&target| target != power),
+        })
+    }
+
+    /// `(FRA AMY PAR)`, or `(RUS FLT (STP SCS))`; the coast is ignored.
+    fn unit(&self, body: &[Expr]) -> Result<(Power, UnitType, &'m Province), PressError> {
+        match body {
+            [Expr::Atom(power), Expr::Atom(unit), province] => {
+                let unit = match unit.as_str() {
+                    "AMY" => UnitType::Army,
+                    "FLT" => UnitType::Fleet,
+                    unit => return Err(PressError::new(format!("unknown unit type {}", unit))),
+                };
+                Ok((parse_power(power)?, unit, self.province(province)?.0))
+            }
+            _ => Err(PressError::new("expected a unit like (FRA AMY PAR)")),
+        }
+    }
+
+    /// `PAR`, or `(SPA NCS)`.
+    fn province(&self, expr: &Expr) -> Result<(&'m Province, Option<Coast>), PressError> {
+        let (token, coast) = match expr {
+            Expr::Atom(token) => (token, None),
+            Expr::List(list) => match list.as_slice() {
+                [Expr::Atom(token), Expr::Atom(coast)] => (token, Some(parse_coast(coast)?)),
+                _ => return Err(PressError::new("expected a province like (SPA NCS)")),
+            },
+        };
+        let abbreviation = PROVINCE_TOKENS
+            .iter()
+            .find(|(_, daide)| daide == token)
+            .map_or(token.as_str(), |(abbreviation, _)| abbreviation);
+        let province = self
+            .map
+            .find_by_abbreviation(abbreviation)
+            .ok_or_else(|| PressError::new(format!("unknown province {}", token)))?;
+        Ok((province, coast))
+    }
+}
+
+fn powers_of(exprs: &[Expr]) -> Result<Vec<Power>, PressError> {
+    exprs
+        .iter()
+        .map(|expr| match expr {
+            Expr::Atom(token) => parse_power(token),
+            Expr::List(_) => Err(PressError::new("expected a power")),
+        })
+        .collect()
+}
+
+fn power_token(power: Power) -> &'static str {
+    match power {
+        Power::Austria => "AUS",
+        Power::England => "ENG",
+        Power::France => "FRA",
+        Power::Germany => "GER",
+        Power::Italy => "ITA",
+        Power::Russia => "RUS",
+        Power::Turkey => "TUR",
+    }
+}
+
+fn parse_power(token: &str) -> Result<Power, PressError> {
+    Power::ALL
+        .iter()
+        .copied()
+        .find(|&power| power_token(power) == token)
+        .ok_or_else(|| PressError::new(format!("unknown power {}", token)))
+}
+
+fn coast_token(coast: Coast) -> &'static str {
+    match coast {
+        Coast::North => "NCS",
+        Coast::South => "SCS",
+        Coast::East => "ECS",
+        Coast::West => "WCS",
+    }
+}
+
+fn parse_coast(token: &str) -> Result<Coast, PressError> {
+    [Coast::North, Coast::South, Coast::East, Coast::West]
+        .iter()
+        .copied()
+        .find(|&coast| coast_token(coast) == token)
+        .ok_or_else(|| PressError::new(format!("unknown coast {}", token)))
+}
+
+/// Provinces whose DAIDE tokens are not their abbreviations in capitals.
+const PROVINCE_TOKENS: &[(&str, &str)] = &[("Eng", "ECH"), ("Lyo", "GOL")];
+
+fn province_token(province: &Province) -> String {
+    PROVINCE_TOKENS
+        .iter()
+        .find(|(abbreviation, _)| *abbreviation == province.abbreviation())
+        .map_or_else(
+            || province.abbreviation().to_ascii_uppercase(),
+            |(_, daide)| (*daide).to_owned(),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(text: &str) -> Press<'static> {
+        let press = parse(Map::standard(), text).unwrap();
+        assert_eq!(press.to_string(), text);
+        press
+    }
+
+    #[test]
+    fn proposals() {
+        assert_eq!(
+            round_trip("PRP (PCE (FRA GER))"),
+            Press::Propose(Arrangement::Peace(vec![Power::France, Power::Germany]))
+        );
+        round_trip("YES (PRP (ALY (ENG FRA) VSS (GER)))");
+        round_trip("REJ (PRP (DMZ (FRA ENG) (ECH GOL BUR)))");
+        round_trip("PRP (ORR (DRW) (NOT (SLO (TUR))))");
+        assert_eq!(
+            parse(Map::standard(), " prp( pce(fra ger) ) ").unwrap(),
+            parse(Map::standard(), "PRP (PCE (FRA GER))").unwrap()
+        );
+        assert!(parse(Map::standard(), "PRP (PCE (FRA GER)").is_err());
+        assert!(parse(Map::standard(), "PRP (AND (DRW))").is_err());
+    }
+
+    #[test]
+    fn suggested_orders() {
+        round_trip("PRP (XDO ((FRA AMY PAR) HLD))");
+        round_trip("PRP (XDO ((FRA FLT MAO) MTO (SPA NCS)))");
+        round_trip("PRP (XDO ((FRA AMY BUR) SUP (GER AMY MUN) MTO RUH))");
+        let press = round_trip(
+            "PRP (AND (XDO ((ENG FLT NTH) CVY (ENG AMY LON) CTO NWY)) \
+             (XDO ((FRA FLT BRE) SUP (FRA FLT ECH))))",
+        );
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        match press.arrangement() {
+            Arrangement::And(arrangements) => assert_eq!(
+                arrangements[0],
+                Arrangement::Order(SuggestedOrder::new(
+                    Power::England,
+                    Order::convoy(
+                        UnitType::Fleet,
+                        province("Nth"),
+                        UnitType::Army,
+                        province("Lon"),
+                        province("Nwy")
+                    )
+                    .unwrap()
+                ))
+            ),
+            arrangement => panic!("unexpected {:?}", arrangement),
+        }
+        assert!(parse(map, "PRP (XDO ((FRA AMY PAR) MTO PAR))").is_err());
+    }
+}
@@ -0,0 +1,1019 @@
+//! Many games at once, as a bot that runs a community's games keeps them:
+//! each game's position and the orders submitted for its current phase,
+//! by the game's id.
+//!
+//! The registry notes which powers have submitted, and when the last
+//! power needed does, raises an [`AllOrdersIn`](RegistryEvent::AllOrdersIn)
+//! event for the game, so the GM can adjudicate early rather than wait for
+//! the deadline.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+
+use crate::adjudicator::{
+    adjudicate, adjudicate_adjustments, legality, Adjustment, AdjustmentError, AdjustmentResult,
+    IllegalOrder, OrderLegality, Rules,
+};
+use crate::json::{self, Value};
+use crate::map::{Map, Province};
+use crate::order::{Order, UnitType};
+use crate::phase::{Phase, PhaseKind};
+use crate::power::Power;
+use crate::schedule::Deadline;
+use crate::state::{parse_unit, unit_text, GameState, StateError, Unit};
+use crate::submission::{Envelope, OrderSet};
+
+/// Why the registry could not do what was asked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    /// There is no game with the id.
+    UnknownGame(String),
+    /// There is already a game with the id.
+    DuplicateGame(String),
+    /// The order or adjustment is for the wrong kind of phase: orders for
+    /// movement and retreat phases, adjustments for adjustment phases.
+    WrongPhase(Phase),
+    /// The order is for a unit the power does not have.
+    Rejected(IllegalOrder),
+    /// The build or disband could not be made, or the power has none
+    /// left to make.
+    AdjustmentRejected(AdjustmentError),
+    /// The order came after the phase's deadline closed.
+    TooLate(Deadline),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::UnknownGame(id) => write!(f, "no game {:?}", id),
+            RegistryError::DuplicateGame(id) => write!(f, "there is already a game {:?}", id),
+            RegistryError::WrongPhase(phase) => write!(f, "not accepted in {}", phase),
+            RegistryError::Rejected(reason) => write!(f, "order rejected: {}", reason),
+            RegistryError::AdjustmentRejected(reason) => {
+                write!(f, "adjustment rejected: {}", reason)
+            }
+            RegistryError::TooLate(deadline) => {
+                write!(f, "orders for {} have closed", deadline.phase())
+            }
+        }
+    }
+}
+
+impl Error for RegistryError {}
+
+/// Something that happened in a registry's games.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegistryEvent {
+    /// Every power that must submit for the phase has.
+    AllOrdersIn { game: String, phase: Phase },
+}
+
+/// One game in a [`GameRegistry`].
+#[derive(Clone, Debug)]
+pub struct Game<'m> {
+    state: GameState<'m>,
+    orders: OrderSet<'m>,
+    adjustments: BTreeMap<Power, Vec<Adjustment<'m>>>,
+    done: BTreeSet<Power>,
+    /// Whether the phase's [`RegistryEvent::AllOrdersIn`] has been raised.
+    announced: bool,
+    /// The orders of each movement and retreat phase played, in order.
+    archive: Vec<(Phase, OrderSet<'m>)>,
+}
+
+impl<'m> Game<'m> {
+    fn new(state: GameState<'m>) -> Self {
+        Game {
+            orders: OrderSet::new(state.map()),
+            state,
+            adjustments: BTreeMap::new(),
+            done: BTreeSet::new(),
+            announced: false,
+            archive: Vec::new(),
+        }
+    }
+
+    pub fn state(&self) -> &GameState<'m> {
+        &self.state
+    }
+
+    /// The orders submitted for a movement or retreat phase.
+    pub fn orders(&self) -> &OrderSet<'m> {
+        &self.orders
+    }
+
+    /// The orders of each movement and retreat phase the game has played
+    /// in the registry, in the order played, with their audit trails and
+    /// the orders refused as late.
+    pub fn archive(&self) -> &[(Phase, OrderSet<'m>)] {
+        &self.archive
+    }
+
+    /// `power`'s builds and disbands submitted for an adjustment phase.
+    pub fn adjustments(&self, power: Power) -> &[Adjustment<'m>] {
+        self.adjustments.get(&power).map_or(&[], Vec::as_slice)
+    }
+
+    /// The powers that must submit for the phase: those with units to
+    /// order, dislodged units to retreat, or builds or disbands to make,
+    /// other than those in civil disorder. A power with builds but no
+    /// empty home center of its own to make them in has none to make.
+    pub fn expected(&self) -> Vec<Power> {
+        let state = &self.state;
+        Power::ALL
+            .iter()
+            .copied()
+            .filter(|&power| !state.in_civil_disorder(power))
+            .filter(|&power| match state.phase().kind {
+                PhaseKind::Movement => state.units().any(|(_, unit)| unit.power() == power),
+                PhaseKind::Retreats => state.dislodged().any(|(_, unit)| unit.power() == power),
+                PhaseKind::Adjustments => state.adjustments_owed(power) != 0,
+            })
+            .collect()
+    }
+
+    /// Whether `power` has submitted for the phase: it has said it is
+    /// done, or has given an order for each of its units, or as many
+    /// builds or disbands as it owes.
+    pub fn has_submitted(&self, power: Power) -> bool {
+        if self.done.contains(&power) {
+            return true;
+        }
+        self.missing_orders(power).is_empty() && self.unassigned_adjustments(power) == 0
+    }
+
+    /// The provinces of `power`'s units that still need orders, in map
+    /// order, as [`OrderSet::missing_orders`] finds them.
+    pub fn missing_orders(&self, power: Power) -> Vec<&'m Province> {
+        self.orders.missing_orders(&self.state, power)
+    }
+
+    /// How many of the builds, counted up, or disbands, counted down,
+    /// that `power` owes in an adjustment phase it has yet to submit; 0
+    /// in other phases. Builds are owed only as far as the power has
+    /// empty home centers of its own to make them in, and only the
+    /// adjustments the adjudicator would make count against them.
+    pub fn unassigned_adjustments(&self, power: Power) -> isize {
+        if self.state.phase().kind != PhaseKind::Adjustments {
+            return 0;
+        }
+        let owed = self.state.adjustments_owed(power);
+        let result = self.try_adjustments(power, self.adjustments(power).to_vec());
+        let made = if owed > 0 {
+            result.builds().len()
+        } else {
+            result.disbands().len()
+        };
+        owed.signum() * (owed.abs() - made as isize).max(0)
+    }
+
+    /// What adjudicating `power`'s `adjustments` alone would make of them.
+    fn try_adjustments(
+        &self,
+        power: Power,
+        adjustments: Vec<Adjustment<'m>>,
+    ) -> AdjustmentResult<'m> {
+        let adjustments = std::iter::once((power, adjustments)).collect();
+        adjudicate_adjustments(adjustments, &self.state, &Rules::default())
+    }
+
+    /// Whether every [expected](Self::expected) power has submitted.
+    pub fn is_ready(&self) -> bool {
+        self.expected()
+            .into_iter()
+            .all(|power| self.has_submitted(power))
+    }
+
+    fn to_value(&self) -> Value {
+        let by_power = |lists: Vec<(Power, Vec<String>)>| {
+            Value::Object(
+                lists
+                    .into_iter()
+                    .filter(|(_, list)| !list.is_empty())
+                    .map(|(power, list)| {
+                        let list = list.into_iter().map(Value::String).collect();
+                        (power.name().to_owned(), Value::Array(list))
+                    })
+                    .collect(),
+            )
+        };
+        let adjustments = self
+            .adjustments
+            .iter()
+            .map(|(&power, adjustments)| {
+                let adjustments = adjustments
+                    .iter()
+                    .map(|adjustment| adjustment_text(self.state.map(), power, adjustment))
+                    .collect();
+                (power, adjustments)
+            })
+            .collect();
+        let done = self
+            .done
+            .iter()
+            .map(|power| Value::String(power.name().to_owned()))
+            .collect();
+        let archive = self
+            .archive
+            .iter()
+            .map(|(phase, orders)| {
+                let mut members = vec![("phase".to_owned(), Value::String(phase.to_string()))];
+                members.extend(orders.to_members());
+                Value::Object(members)
+            })
+            .collect();
+        let mut members = vec![("state".to_owned(), self.state.to_value())];
+        members.extend(self.orders.to_members());
+        members.extend(vec![
+            ("adjustments".to_owned(), by_power(adjustments)),
+            ("done".to_owned(), Value::Array(done)),
+            ("announced".to_owned(), Value::Bool(self.announced)),
+            ("archive".to_owned(), Value::Array(archive)),
+        ]);
+        Value::Object(members)
+    }
+
+    fn from_value(map: &'m Map, value: &Value) -> Result<Self, StateError> {
+        let state = value
+            .get("state")
+            .ok_or_else(|| StateError::new("a game needs a \"state\""))?;
+        let mut game = Game::new(GameState::from_value(map, state)?);
+        let by_power = |key: &str| -> Result<Vec<(Power, &str)>, StateError> {
+            let bad = || StateError::new(format!("{:?} must map powers to lists", key));
+            let mut entries = Vec::new();
+            let members = match value.get(key) {
+                None => return Ok(entries),
+                Some(members) => members.as_object().ok_or_else(bad)?,
+            };
+            for (power, list) in members {
+                let power: Power = power
+                    .parse()
+                    .map_err(|e| StateError::new(format!("{}", e)))?;
+                for item in list.as_array().ok_or_else(bad)? {
+                    entries.push((power, item.as_str().ok_or_else(bad)?));
+                }
+            }
+            Ok(entries)
+        };
+        game.orders = OrderSet::from_value(map, value)?;
+        for (power, text) in by_power("adjustments")? {
+            let adjustment = parse_adjustment(map, power, text)?;
+            game.adjustments.entry(power).or_default().push(adjustment);
+        }
+        if let Some(done) = value.get("done") {
+            let bad = || StateError::new("\"done\" must list powers");
+            for power in done.as_array().ok_or_else(bad)? {
+                let power: Power = power
+                    .as_str()
+                    .ok_or_else(bad)?
+                    .parse()
+                    .map_err(|e| StateError::new(format!("{}", e)))?;
+                game.done.insert(power);
+            }
+        }
+        game.announced = value.get("announced") == Some(&Value::Bool(true));
+        if let Some(archive) = value.get("archive") {
+            let bad = || StateError::new("\"archive\" must list phases and their orders");
+            for played in archive.as_array().ok_or_else(bad)? {
+                let phase: Phase = played
+                    .get("phase")
+                    .and_then(Value::as_str)
+                    .ok_or_else(bad)?
+                    .parse()
+                    .map_err(|e| StateError::new(format!("{}", e)))?;
+                game.archive
+                    .push((phase, OrderSet::from_value(map, played)?));
+            }
+        }
+        Ok(game)
+    }
+}
+
+/// Games by id, all on the same map.
+#[derive(Clone, Debug)]
+pub struct GameRegistry<'m> {
+    map: &'m Map,
+    games: BTreeMap<String, Game<'m>>,
+    events: Vec<RegistryEvent>,
+}
+
+impl<'m> GameRegistry<'m> {
+    pub fn new(map: &'m Map) -> Self {
+        GameRegistry {
+            map,
+            games: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn map(&self) -> &'m Map {
+        self.map
+    }
+
+    /// Starts keeping a game from `state` under `id`.
+    pub fn create(&mut self, id: &str, state: GameState<'m>) -> Result<&Game<'m>, RegistryError> {
+        if self.games.contains_key(id) {
+            return Err(RegistryError::DuplicateGame(id.to_owned()));
+        }
+        self.games.insert(id.to_owned(), Game::new(state));
+        self.check_ready(id);
+        Ok(&self.games[id])
+    }
+
+    /// Stops keeping the game `id`, returning it.
+    pub fn remove(&mut self, id: &str) -> Option<Game<'m>> {
+        self.games.remove(id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Game<'m>> {
+        self.games.get(id)
+    }
+
+    /// Every game, by id in alphabetical order.
+    pub fn games(&self) -> impl Iterator<Item = (&str, &Game<'m>)> {
+        self.games.iter().map(|(id, game)| (id.as_str(), game))
+    }
+
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    /// Records `power`'s `order` in the game `id`, returning the order it
+    /// replaces for the same unit, if any.
+    ///
+    /// Orders the adjudicator would [reject](OrderLegality::Rejected) are
+    /// refused; those it would turn into holds are kept, as the rulebook
+    /// says.
+    pub fn submit(
+        &mut self,
+        id: &str,
+        power: Power,
+        order: Order<'m>,
+    ) -> Result<Option<Order<'m>>, RegistryError> {
+        let game = self.game_mut(id)?;
+        let phase = game.state.phase();
+        if phase.kind == PhaseKind::Adjustments {
+            return Err(RegistryError::WrongPhase(phase));
+        }
+        if let OrderLegality::Rejected(reason) =
+            legality(&game.state, power, &order, &Rules::default())
+        {
+            return Err(RegistryError::Rejected(reason));
+        }
+        let replaced = game.orders.submit(power, order);
+        self.check_ready(id);
+        Ok(replaced)
+    }
+
+    /// Records `power`'s order in the game `id`, as [`submit`](Self::submit)
+    /// does, unless it comes after the game's deadline closed, when it is
+    /// kept among the [late](OrderSet::late) orders and refused. The
+    /// envelope is kept in the [audit trail](OrderSet::trail).
+    ///
+    /// A GM can still accept a late order with
+    /// [`submit_override`](Self::submit_override).
+    pub fn submit_on_time(
+        &mut self,
+        id: &str,
+        power: Power,
+        envelope: Envelope<Order<'m>>,
+    ) -> Result<Option<Order<'m>>, RegistryError> {
+        let game = self.game_mut(id)?;
+        let phase = game.state.phase();
+        if phase.kind == PhaseKind::Adjustments {
+            return Err(RegistryError::WrongPhase(phase));
+        }
+        if let OrderLegality::Rejected(reason) =
+            legality(&game.state, power, envelope.contents(), &Rules::default())
+        {
+            return Err(RegistryError::Rejected(reason));
+        }
+        let replaced = game
+            .orders
+            .submit_on_time(power, envelope)
+            .map_err(|late| RegistryError::TooLate(late.deadline()))?;
+        self.check_ready(id);
+        Ok(replaced)
+    }
+
+    /// Records `power`'s order in the game `id` whatever the deadline, as
+    /// a GM overriding it does, keeping the envelope in the
+    /// [audit trail](OrderSet::trail).
+    pub fn submit_override(
+        &mut self,
+        id: &str,
+        power: Power,
+        envelope: Envelope<Order<'m>>,
+    ) -> Result<Option<Order<'m>>, RegistryError> {
+        let game = self.game_mut(id)?;
+        let phase = game.state.phase();
+        if phase.kind == PhaseKind::Adjustments {
+            return Err(RegistryError::WrongPhase(phase));
+        }
+        if let OrderLegality::Rejected(reason) =
+            legality(&game.state, power, envelope.contents(), &Rules::default())
+        {
+            return Err(RegistryError::Rejected(reason));
+        }
+        let replaced = game.orders.submit_enveloped(power, envelope);
+        self.check_ready(id);
+        Ok(replaced)
+    }
+
+    /// Takes back `power`'s order for the unit in the province in
+    /// `envelope` in the game `id`, returning it, unless the game's
+    /// deadline has closed, when the withdrawal is kept among the
+    /// [late](OrderSet::late) ones and refused. The envelope is kept in
+    /// the [audit trail](OrderSet::trail).
+    pub fn withdraw_on_time(
+        &mut self,
+        id: &str,
+        power: Power,
+        envelope: Envelope<&'m Province>,
+    ) -> Result<Option<Order<'m>>, RegistryError> {
+        self.game_mut(id)?
+            .orders
+            .withdraw_on_time(power, envelope)
+            .map_err(|late| RegistryError::TooLate(late.deadline()))
+    }
+
+    /// Takes back `power`'s order in the game `id` whatever the deadline,
+    /// as a GM overriding it does, keeping the envelope in the
+    /// [audit trail](OrderSet::trail).
+    pub fn withdraw_override(
+        &mut self,
+        id: &str,
+        power: Power,
+        envelope: Envelope<&'m Province>,
+    ) -> Result<Option<Order<'m>>, RegistryError> {
+        Ok(self
+            .game_mut(id)?
+            .orders
+            .withdraw_enveloped(power, envelope))
+    }
+
+    /// Sets or clears the deadline for the current phase of the game `id`,
+    /// after which [`submit_on_time`](Self::submit_on_time) refuses
+    /// orders. Adjudicating the game clears it.
+    pub fn set_deadline(
+        &mut self,
+        id: &str,
+        deadline: Option<Deadline>,
+    ) -> Result<(), RegistryError> {
+        self.game_mut(id)?.orders.set_deadline(deadline);
+        Ok(())
+    }
+
+    /// Records a build or disband of `power`'s in the game `id`.
+    ///
+    /// Adjustments the adjudicator would reject alongside those `power`
+    /// has already submitted are refused: a build outside its empty home
+    /// centers or beyond those it [owes](GameState::adjustments_owed), a
+    /// disband of a unit it does not have, or either when it owes the
+    /// other.
+    pub fn submit_adjustment(
+        &mut self,
+        id: &str,
+        power: Power,
+        adjustment: Adjustment<'m>,
+    ) -> Result<(), RegistryError> {
+        let game = self.game_mut(id)?;
+        let phase = game.state.phase();
+        if phase.kind != PhaseKind::Adjustments {
+            return Err(RegistryError::WrongPhase(phase));
+        }
+        let left = game.unassigned_adjustments(power);
+        let sign = match adjustment {
+            Adjustment::Build { .. } => 1,
+            Adjustment::Disband(_) => -1,
+        };
+        if left.signum() != sign {
+            return Err(RegistryError::AdjustmentRejected(AdjustmentError::TooMany));
+        }
+        let mut adjustments = game.adjustments(power).to_vec();
+        adjustments.push(adjustment.clone());
+        let result = game.try_adjustments(power, adjustments);
+        if let Some(rejected) = result
+            .rejected()
+            .iter()
+            .find(|rejected| rejected.adjustment == adjustment)
+        {
+            return Err(RegistryError::AdjustmentRejected(rejected.reason));
+        }
+        game.adjustments.entry(power).or_default().push(adjustment);
+        self.check_ready(id);
+        Ok(())
+    }
+
+    /// Notes that `power` has submitted all it means to for the phase in
+    /// the game `id`, as when it leaves units to hold or waives builds.
+    pub fn done(&mut self, id: &str, power: Power) -> Result<(), RegistryError> {
+        self.game_mut(id)?.done.insert(power);
+        self.check_ready(id);
+        Ok(())
+    }
+
+    /// Adjudicates the current phase of the game `id` with the orders
+    /// submitted, under the default rules, and moves it on to the next
+    /// phase with nothing submitted. The orders of a movement or retreat
+    /// phase go to the game's [archive](Game::archive).
+    pub fn adjudicate(&mut self, id: &str) -> Result<&GameState<'m>, RegistryError> {
+        let game = self.game_mut(id)?;
+        let state = &game.state;
+        let phase = state.phase();
+        let mut archive = std::mem::take(&mut game.archive);
+        let next = if phase.kind == PhaseKind::Adjustments {
+            let adjustments: HashMap<_, _> =
+                std::mem::take(&mut game.adjustments).into_iter().collect();
+            let result = adjudicate_adjustments(adjustments, state, &Rules::default());
+            state.apply_adjustments(&result)
+        } else {
+            let next = state.apply(&adjudicate(game.orders.to_orders(), state));
+            let orders = std::mem::replace(&mut game.orders, OrderSet::new(state.map()));
+            archive.push((phase, orders));
+            next
+        };
+        *game = Game::new(next);
+        game.archive = archive;
+        self.check_ready(id);
+        Ok(&self.games[id].state)
+    }
+
+    /// The events raised since this was last called, in the order they
+    /// happened.
+    pub fn take_events(&mut self) -> Vec<RegistryEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn game_mut(&mut self, id: &str) -> Result<&mut Game<'m>, RegistryError> {
+        self.games
+            .get_mut(id)
+            .ok_or_else(|| RegistryError::UnknownGame(id.to_owned()))
+    }
+
+    /// Raises the game's [`RegistryEvent::AllOrdersIn`] if it has just
+    /// become ready.
+    fn check_ready(&mut self, id: &str) {
+        let game = self.games.get_mut(id).expect("the game exists");
+        if !game.announced && game.is_ready() {
+            game.announced = true;
+            self.events.push(RegistryEvent::AllOrdersIn {
+                game: id.to_owned(),
+                phase: game.state.phase(),
+            });
+        }
+    }
+
+    /// Writes every game as JSON, in the form read by
+    /// [`from_json`](Self::from_json). Events not yet taken are not
+    /// written.
+    pub fn to_json(&self) -> String {
+        self.to_value().pretty()
+    }
+
+    fn to_value(&self) -> Value {
+        let games = self
+            .games
+            .iter()
+            .map(|(id, game)| (id.clone(), game.to_value()))
+            .collect();
+        Value::Object(vec![("games".to_owned(), Value::Object(games))])
+    }
+
+    /// Reads games written by [`to_json`](Self::to_json), like
+    ///
+    /// ```json
+    /// {
+    ///   "games": {
+    ///     "fleet-street": {
+    ///       "state": {"phase": "Spring 1901 Movement", "units": {}},
+    ///       "orders": {"France": ["A Paris - Burgundy"]},
+    ///       "deadline": {"phase": "Spring 1901 Movement", "due": 1700000000, "grace": 0},
+    ///       "trail": [
+    ///         {"power": "France", "order": "A Paris - Burgundy", "submitter": "alice",
+    ///          "timestamp": 1699990000, "revision": 1}
+    ///       ],
+    ///       "late": [],
+    ///       "adjustments": {},
+    ///       "done": ["England"],
+    ///       "announced": false,
+    ///       "archive": []
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// Each game's state is written as by [`GameState::to_json`], and
+    /// adjustments like `"Build F StP/nc"` or `"Disband Par"`. A game's
+    /// deadline is optional, with its times in seconds since the Unix
+    /// epoch. The audit trail and the orders refused as late are lists of
+    /// envelopes like `{"power": "France", "order": "A Paris - Burgundy",
+    /// "submitter": "alice", "timestamp": 1700000000, "revision": 1}`,
+    /// with `"withdraw": "Par"` in place of the order for a withdrawal;
+    /// the archive lists each phase played with its orders, deadline,
+    /// trail and late orders in the same form.
+    pub fn from_json(map: &'m Map, text: &str) -> Result<Self, StateError> {
+        let value = json::parse(text).map_err(|e| StateError::new(e.to_string()))?;
+        Self::from_value(map, &value)
+    }
+
+    /// Reads games on `map` with serde, in the form
+    /// [`from_json`](Self::from_json) reads, from any format serde
+    /// supports. The registry's [`Serialize`](serde::Serialize) impl
+    /// writes them.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        map: &'m Map,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let value: Value = serde::Deserialize::deserialize(deserializer)?;
+        Self::from_value(map, &value).map_err(serde::de::Error::custom)
+    }
+
+    fn from_value(map: &'m Map, value: &Value) -> Result<Self, StateError> {
+        let games = value
+            .get("games")
+            .and_then(Value::as_object)
+            .ok_or_else(|| StateError::new("\"games\" must map ids to games"))?;
+        let mut registry = GameRegistry::new(map);
+        for (id, game) in games {
+            let game = Game::from_value(map, game)
+                .map_err(|e| StateError::new(format!("game {:?}: {}", id, e)))?;
+            registry.games.insert(id.clone(), game);
+        }
+        Ok(registry)
+    }
+}
+
+/// Writes the games in the form [`GameRegistry::to_json`] does, in any
+/// format serde supports. Read them back with
+/// [`GameRegistry::deserialize`].
+#[cfg(feature = "serde")]
+impl<'m> serde::Serialize for GameRegistry<'m> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+/// Writes an adjustment like "Build F StP/nc" or "Disband Par".
+fn adjustment_text(map: &Map, power: Power, adjustment: &Adjustment) -> String {
+    match *adjustment {
+        Adjustment::Build {
+            unit,
+            province,
+            coast,
+        } => {
+            let unit = match (unit, coast) {
+                (UnitType::Army, _) => Unit::army(power),
+                (UnitType::Fleet, None) => Unit::fleet(power),
+                (UnitType::Fleet, Some(coast)) => Unit::fleet_on(power, coast),
+            };
+            format!("Build {}", unit_text(map, province, &unit))
+        }
+        Adjustment::Disband(province) => format!("Disband {}", province.abbreviation()),
+    }
+}
+
+/// Reads an adjustment written by [`adjustment_text`].
+fn parse_adjustment<'m>(
+    map: &'m Map,
+    power: Power,
+    text: &str,
+) -> Result<Adjustment<'m>, StateError> {
+    if let Some(unit) = text.strip_prefix("Build ") {
+        let (province, unit) = parse_unit(map, power, unit)?;
+        return Ok(Adjustment::Build {
+            unit: unit.unit_type(),
+            province,
+            coast: unit.coast(),
+        });
+    }
+    let province = text
+        .strip_prefix("Disband ")
+        .and_then(|province| map.find_by_abbreviation(province.trim()))
+        .ok_or_else(|| {
+            StateError::new(format!(
+                "expected an adjustment like \"Build A Par\" or \"Disband Par\", not {:?}",
+                text
+            ))
+        })?;
+    Ok(Adjustment::Disband(province))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_order;
+    use crate::phase::Season;
+    use crate::schedule::Cadence;
+
+    #[test]
+    fn orders_in() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let mut registry = GameRegistry::new(map);
+        registry.create("one", GameState::standard()).unwrap();
+        registry.create("two", GameState::standard()).unwrap();
+        assert_eq!(
+            registry.create("one", GameState::standard()).unwrap_err(),
+            RegistryError::DuplicateGame("one".to_owned())
+        );
+        assert!(registry
+            .submit("three", Power::France, order("A Par H"))
+            .is_err());
+
+        registry
+            .submit("one", Power::France, order("A Par - Bur"))
+            .unwrap();
+        let missing: Vec<_> = registry
+            .get("one")
+            .unwrap()
+            .missing_orders(Power::France)
+            .into_iter()
+            .map(Province::abbreviation)
+            .collect();
+        assert_eq!(missing, ["Bre", "Mar"]);
+        registry
+            .submit("one", Power::France, order("A Mar H"))
+            .unwrap();
+        registry
+            .submit("one", Power::France, order("F Bre - Mao"))
+            .unwrap();
+        let game = registry.get("one").unwrap();
+        assert!(game.has_submitted(Power::France));
+        assert!(!game.has_submitted(Power::Germany));
+        assert!(!registry.get("two").unwrap().has_submitted(Power::France));
+
+        for power in Power::ALL {
+            if power != Power::France {
+                registry.done("one", power).unwrap();
+            }
+        }
+        let phase = Phase::FIRST;
+        assert_eq!(
+            registry.take_events(),
+            [RegistryEvent::AllOrdersIn {
+                game: "one".to_owned(),
+                phase
+            }]
+        );
+        // Changing an order once everyone is in raises no second event.
+        registry
+            .submit("one", Power::France, order("A Par - Pic"))
+            .unwrap();
+        assert!(registry.take_events().is_empty());
+
+        let saved = registry.to_json();
+        let restored = GameRegistry::from_json(map, &saved).unwrap();
+        assert_eq!(restored.to_json(), saved);
+        assert_eq!(restored.len(), 2);
+        #[cfg(feature = "serde")]
+        {
+            let serialized = serde_json::to_string(&registry).unwrap();
+            let mut deserializer = serde_json::Deserializer::from_str(&serialized);
+            let restored = GameRegistry::deserialize(map, &mut deserializer).unwrap();
+            assert_eq!(restored.to_json(), saved);
+        }
+
+        let state = registry.adjudicate("one").unwrap();
+        assert_eq!(state.phase(), Phase::movement(Season::Fall, 1901));
+        let pic = map.find_by_abbreviation("Pic").unwrap();
+        assert!(state.unit_at(pic).is_some());
+        let game = registry.get("one").unwrap();
+        assert!(game.orders().is_empty());
+        assert!(!game.is_ready());
+    }
+
+    #[test]
+    fn deadlines() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let mut registry = GameRegistry::new(map);
+        registry.create("game", GameState::standard()).unwrap();
+        let start = std::time::UNIX_EPOCH;
+        let deadline = Cadence::default().deadline(Phase::FIRST, start);
+        registry.set_deadline("game", Some(deadline)).unwrap();
+
+        let envelope = |text, at| Envelope::new(order(text), "alice", at);
+        registry
+            .submit_on_time("game", Power::France, envelope("A Par - Bur", start))
+            .unwrap();
+        assert_eq!(
+            registry.submit_on_time(
+                "game",
+                Power::France,
+                envelope("A Par - Pic", deadline.closes())
+            ),
+            Err(RegistryError::TooLate(deadline))
+        );
+        let restored = GameRegistry::from_json(map, &registry.to_json()).unwrap();
+        let game = restored.get("game").unwrap();
+        assert_eq!(game.orders().deadline(), Some(deadline));
+        assert_eq!(game.orders().orders(Power::France), [order("A Par - Bur")]);
+        assert_eq!(
+            game.orders().late(),
+            registry.get("game").unwrap().orders().late()
+        );
+
+        // A late withdrawal is refused too, but the GM can override both.
+        let marseilles = map.find_by_abbreviation("Mar").unwrap();
+        registry
+            .submit_on_time("game", Power::France, envelope("A Mar H", start))
+            .unwrap();
+        let withdrawal = |by, at| Envelope::new(marseilles, by, at);
+        assert_eq!(
+            registry.withdraw_on_time(
+                "game",
+                Power::France,
+                withdrawal("alice", deadline.closes())
+            ),
+            Err(RegistryError::TooLate(deadline))
+        );
+        let late = Envelope::new(order("A Par - Pic"), "gm", deadline.closes());
+        assert_eq!(
+            registry.submit_override("game", Power::France, late),
+            Ok(Some(order("A Par - Bur")))
+        );
+        assert_eq!(
+            registry.withdraw_override("game", Power::France, withdrawal("gm", deadline.closes())),
+            Ok(Some(order("A Mar H")))
+        );
+        let game = registry.get("game").unwrap();
+        assert_eq!(game.orders().orders(Power::France), [order("A Par - Pic")]);
+        let trail: Vec<_> = game
+            .orders()
+            .trail()
+            .iter()
+            .map(|(_, envelope)| (envelope.submitter(), envelope.revision()))
+            .collect();
+        assert_eq!(trail, [("alice", 1), ("alice", 1), ("gm", 2), ("gm", 2)]);
+        assert_eq!(game.orders().late().len(), 2);
+
+        // The trail and late orders are kept once the phase is played, and
+        // across a restart.
+        let phase_orders = game.orders().clone();
+        registry.adjudicate("game").unwrap();
+        let game = registry.get("game").unwrap();
+        assert_eq!(game.orders().deadline(), None);
+        assert!(game.orders().trail().is_empty());
+        let saved = registry.to_json();
+        let restored = GameRegistry::from_json(map, &saved).unwrap();
+        assert_eq!(restored.to_json(), saved);
+        for game in [registry.get("game").unwrap(), restored.get("game").unwrap()] {
+            let (phase, orders) = &game.archive()[0];
+            assert_eq!(*phase, Phase::FIRST);
+            assert_eq!(orders.trail(), phase_orders.trail());
+            assert_eq!(orders.late(), phase_orders.late());
+            assert_eq!(orders.deadline(), Some(deadline));
+        }
+    }
+
+    #[test]
+    fn adjustments() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut state = GameState::standard();
+        state.set_phase(Phase::new(Season::Winter, 1901, PhaseKind::Adjustments));
+        state.remove_unit(province("Par"));
+        // Germany may build, but has nowhere to.
+        state.set_owner(province("Hol"), Power::Germany);
+        let mut registry = GameRegistry::new(map);
+        registry.create("game", state).unwrap();
+        let game = registry.get("game").unwrap();
+        assert_eq!(game.expected(), [Power::France]);
+        assert_eq!(game.unassigned_adjustments(Power::France), 1);
+        assert_eq!(game.unassigned_adjustments(Power::Germany), 0);
+        assert!(game.has_submitted(Power::Germany));
+        assert!(game.missing_orders(Power::France).is_empty());
+        assert_eq!(
+            registry.submit("game", Power::France, parse_order(map, "A Par H").unwrap()),
+            Err(RegistryError::WrongPhase(Phase::new(
+                Season::Winter,
+                1901,
+                PhaseKind::Adjustments
+            )))
+        );
+
+        let build = Adjustment::Build {
+            unit: UnitType::Army,
+            province: province("Par"),
+            coast: None,
+        };
+        registry
+            .submit_adjustment("game", Power::France, build.clone())
+            .unwrap();
+        assert_eq!(registry.take_events().len(), 1);
+        let game = registry.get("game").unwrap();
+        assert_eq!(game.unassigned_adjustments(Power::France), 0);
+        let restored = GameRegistry::from_json(map, &registry.to_json()).unwrap();
+        assert_eq!(
+            restored.get("game").unwrap().adjustments(Power::France),
+            [build]
+        );
+
+        let state = registry.adjudicate("game").unwrap();
+        assert_eq!(state.phase(), Phase::movement(Season::Spring, 1902));
+        assert_eq!(
+            state.unit_at(province("Par")),
+            Some(&Unit::army(Power::France))
+        );
+    }
+
+    #[test]
+    fn rejected_adjustments() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let adjustment = |text| Adjustment::parse(map, text).unwrap();
+        let mut state = GameState::standard();
+        state.set_phase(Phase::new(Season::Winter, 1901, PhaseKind::Adjustments));
+        // France may build two units, and Italy must disband one.
+        state.remove_unit(province("Par"));
+        state.remove_unit(province("Mar"));
+        state.set_owner(province("Nap"), Power::Austria);
+        let mut registry = GameRegistry::new(map);
+        registry.create("game", state).unwrap();
+        let rejected = |reason| Err(RegistryError::AdjustmentRejected(reason));
+        let mut submit = |power, text| registry.submit_adjustment("game", power, adjustment(text));
+
+        assert_eq!(
+            submit(Power::France, "Build A Bre"),
+            rejected(AdjustmentError::Occupied)
+        );
+        assert_eq!(
+            submit(Power::France, "Build A Bur"),
+            rejected(AdjustmentError::NotHomeCenter)
+        );
+        assert_eq!(
+            submit(Power::France, "Build A Mun"),
+            rejected(AdjustmentError::NotHomeCenter)
+        );
+        assert_eq!(
+            submit(Power::France, "Disband Bre"),
+            rejected(AdjustmentError::TooMany)
+        );
+        assert_eq!(submit(Power::France, "Build A Par"), Ok(()));
+        assert_eq!(
+            submit(Power::France, "Build F Par"),
+            rejected(AdjustmentError::Occupied)
+        );
+        assert_eq!(submit(Power::France, "Build F Mar"), Ok(()));
+        assert_eq!(
+            submit(Power::France, "Build F Mar"),
+            rejected(AdjustmentError::TooMany)
+        );
+
+        assert_eq!(
+            submit(Power::Italy, "Disband Vie"),
+            rejected(AdjustmentError::ForeignUnit)
+        );
+        assert_eq!(
+            submit(Power::Italy, "Disband Pie"),
+            rejected(AdjustmentError::NoUnit)
+        );
+        assert_eq!(
+            submit(Power::Italy, "Build A Nap"),
+            rejected(AdjustmentError::TooMany)
+        );
+        // Austria has a center to spare, but no empty home center.
+        assert_eq!(
+            submit(Power::Austria, "Build A Vie"),
+            rejected(AdjustmentError::TooMany)
+        );
+        assert_eq!(submit(Power::Italy, "Disband Rom"), Ok(()));
+
+        let game = registry.get("game").unwrap();
+        assert_eq!(game.adjustments(Power::France).len(), 2);
+        assert_eq!(game.adjustments(Power::Italy), [adjustment("Disband Rom")]);
+        assert!(game.is_ready());
+    }
+
+    #[test]
+    fn only_accepted_adjustments_count() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut state = GameState::standard();
+        state.set_phase(Phase::new(Season::Winter, 1901, PhaseKind::Adjustments));
+        state.remove_unit(province("Par"));
+        state.remove_unit(province("Mar"));
+        let mut registry = GameRegistry::new(map);
+        registry.create("game", state).unwrap();
+        // As a saved game might hold them: disbands, and a build twice.
+        let game = registry.games.get_mut("game").unwrap();
+        let disbands = ["Disband Bre", "Disband Bre"];
+        let builds = ["Build A Par", "Build A Par"];
+        for text in disbands.iter().chain(&builds) {
+            let adjustment = Adjustment::parse(map, text).unwrap();
+            game.adjustments
+                .entry(Power::France)
+                .or_default()
+                .push(adjustment);
+        }
+        assert_eq!(game.unassigned_adjustments(Power::France), 1);
+        assert!(!game.has_submitted(Power::France));
+    }
+}
@@ -0,0 +1,737 @@
+//! SVG drawings of game states.
+//!
+//! Maps carry a position for each province but no outlines, so the board
+//! is drawn schematically: each province is a circle at its position,
+//! joined to its neighbours, filled with the colour of the power that
+//! owns it, and marked with the unit standing in it.
+//!
+//! The colours come from a [`Theme`]. Each drawing function takes the
+//! default theme, whose power colours stay apart for colour-blind
+//! players, and has a `_themed` form that takes any other.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+#[cfg(feature = "png")]
+use std::sync::{Arc, OnceLock};
+
+#[cfg(feature = "png")]
+use resvg::{tiny_skia, usvg};
+
+use crate::adjudicator::AdjudicationResult;
+use crate::history::History;
+use crate::map::{Map, Province, ProvinceKind};
+use crate::order::{Order, UnitType};
+use crate::power::Power;
+use crate::state::GameState;
+
+const WIDTH: u32 = 1120;
+const HEIGHT: u32 = 990;
+const RADIUS: f64 = 20.0;
+
+/// The colours of a drawing, and the outlines that mark supply centers
+/// and units.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// The fill of each power's centers and units, and the colour of its
+    /// orders, in the order of [`Power::ALL`].
+    pub powers: [String; 7],
+    pub background: String,
+    /// The fill of land and coastal provinces no power owns.
+    pub land: String,
+    pub sea: String,
+    /// The lines between neighbouring provinces.
+    pub borders: String,
+    /// The outline of provinces that are not supply centers.
+    pub outline: String,
+    /// The outline of supply centers.
+    pub center_outline: String,
+    /// How much thicker a supply center's outline is than the others.
+    pub center_outline_width: u32,
+    /// The outline of units, and the crosses on bounced moves.
+    pub unit_outline: String,
+    /// Province abbreviations.
+    pub text: String,
+    /// Supports that were cut or void.
+    pub void: String,
+    /// The rings around dislodged units.
+    pub dislodged: String,
+}
+
+impl Theme {
+    /// The default: power colours from the Okabe–Ito palette, which
+    /// readers with any common colour blindness can tell apart, on a
+    /// parchment board.
+    pub fn colour_blind() -> Self {
+        Theme::with_powers(COLOUR_BLIND)
+    }
+
+    /// Black outlines and borders, a white board and saturated power
+    /// colours, for low vision and bright screens.
+    pub fn high_contrast() -> Self {
+        Theme {
+            background: "#ffffff".to_owned(),
+            land: "#ffffff".to_owned(),
+            sea: "#7fbfff".to_owned(),
+            borders: "#000000".to_owned(),
+            outline: "#000000".to_owned(),
+            center_outline: "#000000".to_owned(),
+            center_outline_width: 5,
+            void: "#808080".to_owned(),
+            dislodged: "#ff00ff".to_owned(),
+            ..Theme::with_powers([
+                "#e00000", "#0000e0", "#00c0ff", "#606060", "#00a000", "#a000c0", "#ffd700",
+            ])
+        }
+    }
+
+    /// The colours of the usual printed board.
+    pub fn classic() -> Self {
+        Theme::with_powers([
+            "#e03c31", "#1f4e9c", "#6ab0e6", "#6b6b6b", "#3e9b3e", "#9b59b6", "#e6c229",
+        ])
+    }
+
+    /// The parchment board, with `powers` for the powers' colours.
+    fn with_powers(powers: [&str; 7]) -> Self {
+        Theme {
+            powers: powers.map(str::to_owned),
+            background: "#f4efe1".to_owned(),
+            land: "#e8dcc0".to_owned(),
+            sea: "#b5d3e7".to_owned(),
+            borders: "#d5cbb3".to_owned(),
+            outline: "#999999".to_owned(),
+            center_outline: "#333333".to_owned(),
+            center_outline_width: 3,
+            unit_outline: "#000000".to_owned(),
+            text: "#000000".to_owned(),
+            void: "#888888".to_owned(),
+            dislodged: "#ff0000".to_owned(),
+        }
+    }
+
+    /// The colour of `power`'s centers, units and orders.
+    pub fn power(&self, power: Power) -> &str {
+        &self.powers[power as usize]
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::colour_blind()
+    }
+}
+
+/// The default theme's power colours: vermilion, blue, sky blue, grey,
+/// bluish green, reddish purple and yellow.
+const COLOUR_BLIND: [&str; 7] = [
+    "#d55e00", "#0072b2", "#56b4e9", "#666666", "#009e73", "#cc79a7", "#f0e442",
+];
+
+/// Draws `state`.
+pub fn render(state: &GameState) -> String {
+    render_themed(state, &Theme::default())
+}
+
+/// Draws `state` in the colours of `theme`.
+pub fn render_themed(state: &GameState, theme: &Theme) -> String {
+    render_with_orders_themed(state, &[], theme)
+}
+
+/// Draws `state` with an arrow for each of `orders`: solid for moves,
+/// dashed for supports and dotted for convoys.
+pub fn render_with_orders(state: &GameState, orders: &[Order]) -> String {
+    render_with_orders_themed(state, orders, &Theme::default())
+}
+
+/// Draws `state` and `orders` in the colours of `theme`.
+pub fn render_with_orders_themed(state: &GameState, orders: &[Order], theme: &Theme) -> String {
+    let mut body = String::new();
+    background(&mut body, theme);
+    board(&mut body, state, theme);
+    units(&mut body, state, theme);
+    if !orders.is_empty() {
+        order_arrows(&mut body, state, orders, theme);
+    }
+    document(&body)
+}
+
+/// Draws `state` with the outcome of `result`, the adjudication of its
+/// orders, on top.
+pub fn render_result(state: &GameState, result: &AdjudicationResult) -> String {
+    render_result_themed(state, result, &Theme::default())
+}
+
+/// Draws `state` and the outcome of `result` in the colours of `theme`.
+pub fn render_result_themed(
+    state: &GameState,
+    result: &AdjudicationResult,
+    theme: &Theme,
+) -> String {
+    document(&result_body(state, result, theme))
+}
+
+/// The elements of [`render_result_themed`]'s drawing.
+fn result_body(state: &GameState, result: &AdjudicationResult, theme: &Theme) -> String {
+    let overlay = Overlay::themed(state, result, theme);
+    let mut body = String::new();
+    background(&mut body, theme);
+    board(&mut body, state, theme);
+    units(&mut body, state, theme);
+    for layer in overlay.layers() {
+        body.push_str(layer.body());
+    }
+    body
+}
+
+/// Draws each phase of `history`, for stitching into a replay video: the
+/// position each turn was played from with the outcome of its orders, as
+/// [`render_result`] draws them, and then the position the game is in
+/// now. Every frame is the same size and captioned with its phase.
+pub fn replay_frames(history: &History) -> Vec<String> {
+    replay_frames_themed(history, &Theme::default())
+}
+
+/// The frames of [`replay_frames`] in the colours of `theme`.
+pub fn replay_frames_themed(history: &History, theme: &Theme) -> Vec<String> {
+    frame_bodies(history, theme)
+        .iter()
+        .map(|body| document(body))
+        .collect()
+}
+
+/// The frames of [`replay_frames`] as one animated SVG document, which
+/// shows each for `seconds_per_frame` seconds and then starts over.
+pub fn animated_replay(history: &History, seconds_per_frame: f64) -> String {
+    animated_replay_themed(history, seconds_per_frame, &Theme::default())
+}
+
+/// The replay of [`animated_replay`] in the colours of `theme`.
+pub fn animated_replay_themed(history: &History, seconds_per_frame: f64, theme: &Theme) -> String {
+    let frames = frame_bodies(history, theme);
+    let count = frames.len();
+    let mut body = String::new();
+    for (i, frame) in frames.iter().enumerate() {
+        let at = |n: usize| n as f64 / count as f64;
+        // Each frame is visible from its start until the next one's, and
+        // hidden for the rest of the loop.
+        let (values, key_times) = match (i, i + 1 == count) {
+            (0, true) => ("visible".to_owned(), "0".to_owned()),
+            (0, false) => ("visible;hidden".to_owned(), format!("0;{}", at(1))),
+            (i, true) => ("hidden;visible".to_owned(), format!("0;{}", at(i))),
+            (i, false) => (
+                "hidden;visible;hidden".to_owned(),
+                format!("0;{};{}", at(i), at(i + 1)),
+            ),
+        };
+        writeln!(
+            body,
+            r#"<g id="frame-{}" visibility="{}">"#,
+            i,
+            if i == 0 { "visible" } else { "hidden" }
+        )
+        .unwrap();
+        writeln!(
+            body,
+            r#"<animate attributeName="visibility" values="{}" keyTimes="{}" dur="{}s" calcMode="discrete" repeatCount="indefinite"/>"#,
+            values,
+            key_times,
+            seconds_per_frame * count as f64
+        )
+        .unwrap();
+        body.push_str(frame);
+        body.push_str("</g>\n");
+    }
+    document(&body)
+}
+
+/// The elements of each frame of a replay of `history`.
+fn frame_bodies(history: &History, theme: &Theme) -> Vec<String> {
+    let mut frames: Vec<String> = history
+        .turns()
+        .iter()
+        .enumerate()
+        .map(|(n, turn)| {
+            let state = history.position(n).expect("each turn has a position");
+            let mut body = result_body(state, turn.result(), theme);
+            caption(&mut body, &turn.phase().to_string(), theme);
+            body
+        })
+        .collect();
+    let current = history.current();
+    let mut body = String::new();
+    background(&mut body, theme);
+    board(&mut body, current, theme);
+    units(&mut body, current, theme);
+    caption(&mut body, &current.phase().to_string(), theme);
+    frames.push(body);
+    frames
+}
+
+/// Writes `text` in the board's top left corner.
+fn caption(svg: &mut String, text: &str, theme: &Theme) {
+    writeln!(
+        svg,
+        r#"<text x="16" y="32" font-size="24" fill="{}">{}</text>"#,
+        theme.text, text
+    )
+    .unwrap();
+}
+
+/// Draws `state` as a PNG image the size of [`render`]'s drawing, for
+/// places like chat embeds that take only raster images.
+#[cfg(feature = "png")]
+pub fn render_png(state: &GameState) -> Vec<u8> {
+    svg_to_png(&render(state), 1.0)
+}
+
+/// Rasterizes `svg`, one of this module's drawings, as a PNG image
+/// `scale` times its size, so 2.0 gives a board twice as wide and high.
+/// Province names are set in the system's fonts.
+///
+/// Panics if `svg` is not an SVG document, or if `scale` leaves the image
+/// with no pixels.
+#[cfg(feature = "png")]
+pub fn svg_to_png(svg: &str, scale: f32) -> Vec<u8> {
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    let fonts = FONTS.get_or_init(|| {
+        let mut fonts = usvg::fontdb::Database::new();
+        fonts.load_system_fonts();
+        Arc::new(fonts)
+    });
+    let options = usvg::Options {
+        fontdb: Arc::clone(fonts),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options).expect("the drawing is an SVG document");
+    let size = tree
+        .size()
+        .to_int_size()
+        .scale_by(scale)
+        .expect("the scaled image has pixels");
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).expect("the image has pixels");
+    let transform = tiny_skia::Transform::from_scale(scale, scale);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    pixmap.encode_png().expect("the image encodes as PNG")
+}
+
+/// The outcome of an adjudication, drawn as transparent layers the size
+/// of the board, each a complete SVG document that can be laid over the
+/// output of [`render`].
+#[derive(Clone, Debug)]
+pub struct Overlay {
+    /// Moves: solid arrows for those that succeeded, and crossed-out
+    /// arrows for those that bounced.
+    pub moves: Layer,
+    /// Supports: thin arrows for those given, and dashed grey ones for
+    /// those that were cut or void.
+    pub supports: Layer,
+    /// Convoys: dotted arrows to the convoyed army.
+    pub convoys: Layer,
+    /// A red ring around each dislodged unit.
+    pub dislodged: Layer,
+}
+
+/// One layer of an [`Overlay`].
+#[derive(Clone, Debug, Default)]
+pub struct Layer {
+    body: String,
+}
+
+impl Layer {
+    /// The layer's elements, without the enclosing `<svg>`.
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// The layer as a standalone SVG document.
+    pub fn to_svg(&self) -> String {
+        document(&self.body)
+    }
+}
+
+impl Overlay {
+    /// Draws `result`, the adjudication of orders given in `state`.
+    pub fn new(state: &GameState, result: &AdjudicationResult) -> Self {
+        Overlay::themed(state, result, &Theme::default())
+    }
+
+    /// Draws `result` in the colours of `theme`.
+    pub fn themed(state: &GameState, result: &AdjudicationResult, theme: &Theme) -> Self {
+        let map = state.map();
+        let mut resolutions: Vec<_> = result.resolutions().collect();
+        resolutions.sort_by_key(|(province, _)| province.id());
+
+        let mut overlay = Overlay {
+            moves: Layer::default(),
+            supports: Layer::default(),
+            convoys: Layer::default(),
+            dislodged: Layer::default(),
+        };
+        for (province, resolution) in resolutions {
+            let colour = theme.power(resolution.power());
+            match resolution.order() {
+                Order::Hold(_) => {}
+                Order::Move(move_) => {
+                    let layer = &mut overlay.moves.body;
+                    arrow(layer, map, province, move_.destination(), colour, 3, "");
+                    if !resolution.succeeded() {
+                        cross(layer, map, province, move_.destination(), theme);
+                    }
+                }
+                Order::Support(support) => {
+                    let to = support.to().unwrap_or_else(|| support.from());
+                    let layer = &mut overlay.supports.body;
+                    if resolution.succeeded() {
+                        arrow(layer, map, province, to, colour, 2, "");
+                    } else {
+                        arrow(layer, map, province, to, &theme.void, 2, DASHED);
+                    }
+                }
+                Order::Convoy(convoy) => {
+                    let layer = &mut overlay.convoys.body;
+                    arrow(layer, map, province, convoy.from(), colour, 2, DOTTED);
+                }
+            }
+            if resolution.is_dislodged() {
+                let (x, y) = position(map, province);
+                writeln!(
+                    overlay.dislodged.body,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="4"/>"#,
+                    x,
+                    y,
+                    RADIUS + 4.0,
+                    theme.dislodged
+                )
+                .unwrap();
+            }
+        }
+        for (layer, id) in &mut [
+            (&mut overlay.moves, "moves"),
+            (&mut overlay.supports, "supports"),
+            (&mut overlay.convoys, "convoys"),
+            (&mut overlay.dislodged, "dislodged"),
+        ] {
+            layer.body = format!("<g id=\"{}\">\n{}</g>\n", id, layer.body);
+        }
+        overlay
+    }
+
+    /// The layers, bottom first.
+    pub fn layers(&self) -> [&Layer; 4] {
+        [&self.supports, &self.convoys, &self.moves, &self.dislodged]
+    }
+}
+
+const DASHED: &str = r#" stroke-dasharray="8 4""#;
+const DOTTED: &str = r#" stroke-dasharray="2 4""#;
+
+/// Wraps `body` in an `<svg>` element the size of the board.
+fn document(body: &str) -> String {
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            "\n<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"9\" refY=\"5\" ",
+            "markerWidth=\"6\" markerHeight=\"6\" orient=\"auto-start-reverse\">",
+            "<path d=\"M0,0 L10,5 L0,10 z\" fill=\"context-stroke\"/></marker></defs>\n",
+            "{body}</svg>\n",
+        ),
+        w = WIDTH,
+        h = HEIGHT,
+        body = body
+    )
+}
+
+fn background(svg: &mut String, theme: &Theme) {
+    writeln!(
+        svg,
+        r#"<rect width="{}" height="{}" fill="{}"/>"#,
+        WIDTH, HEIGHT, theme.background
+    )
+    .unwrap();
+}
+
+/// The fill colour for a power's centers and units in the default theme.
+pub fn colour(power: Power) -> &'static str {
+    COLOUR_BLIND[power as usize]
+}
+
+fn position(map: &Map, province: &Province) -> (f64, f64) {
+    let (x, y) = map.position(province).unwrap_or((0, 0));
+    (f64::from(x), f64::from(y))
+}
+
+/// The provinces and the lines between neighbours.
+fn board(svg: &mut String, state: &GameState, theme: &Theme) {
+    let map = state.map();
+    svg.push_str("<g id=\"board\">\n");
+    let mut drawn = HashSet::new();
+    for from in map.provinces() {
+        let neighbours = map
+            .army_neighbours(from)
+            .chain(map.fleet_neighbours(from, None).map(|(p, _)| p))
+            .chain(
+                from.coasts()
+                    .iter()
+                    .flat_map(|&coast| map.fleet_neighbours(from, Some(coast)))
+                    .map(|(p, _)| p),
+            );
+        for to in neighbours {
+            let key = (from.id().min(to.id()), from.id().max(to.id()));
+            if drawn.insert(key) {
+                let ((x1, y1), (x2, y2)) = (position(map, from), position(map, to));
+                writeln!(
+                    svg,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}"/>"#,
+                    x1, y1, x2, y2, theme.borders
+                )
+                .unwrap();
+            }
+        }
+    }
+    for province in map.provinces() {
+        let (x, y) = position(map, province);
+        let fill = match (province.kind(), state.owner(province)) {
+            (ProvinceKind::Sea, _) => &theme.sea,
+            (_, Some(power)) => theme.power(power),
+            (_, None) => &theme.land,
+        };
+        let stroke = if province.is_supply_center() {
+            format!(
+                r#"stroke="{}" stroke-width="{}""#,
+                theme.center_outline, theme.center_outline_width
+            )
+        } else {
+            format!(r#"stroke="{}""#, theme.outline)
+        };
+        writeln!(
+            svg,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}" {}/>"#,
+            x, y, RADIUS, fill, stroke
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{}" y="{}" font-size="11" text-anchor="middle" fill="{}">{}</text>"#,
+            x,
+            y + RADIUS + 12.0,
+            theme.text,
+            province.abbreviation()
+        )
+        .unwrap();
+    }
+    svg.push_str("</g>\n");
+}
+
+/// A marker for each unit: a square for armies and a diamond for fleets.
+fn units(svg: &mut String, state: &GameState, theme: &Theme) {
+    let map = state.map();
+    let mut units: Vec<_> = state.units().collect();
+    units.sort_by_key(|(province, _)| province.id());
+    svg.push_str("<g id=\"units\">\n");
+    for (province, unit) in units {
+        let (x, y) = position(map, province);
+        let fill = theme.power(unit.power());
+        match unit.unit_type() {
+            UnitType::Army => writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="16" height="16" fill="{}" stroke="{}"/>"#,
+                x - 8.0,
+                y - 8.0,
+                fill,
+                theme.unit_outline
+            ),
+            UnitType::Fleet => writeln!(
+                svg,
+                r#"<polygon points="{},{} {},{} {},{} {},{}" fill="{}" stroke="{}"/>"#,
+                x,
+                y - 10.0,
+                x + 10.0,
+                y,
+                x,
+                y + 10.0,
+                x - 10.0,
+                y,
+                fill,
+                theme.unit_outline
+            ),
+        }
+        .unwrap();
+    }
+    svg.push_str("</g>\n");
+}
+
+/// An arrow for each order, in the colour of the ordering power.
+fn order_arrows(svg: &mut String, state: &GameState, orders: &[Order], theme: &Theme) {
+    svg.push_str("<g id=\"orders\">\n");
+    for order in orders {
+        let colour = state
+            .unit_at(order.province())
+            .map_or(theme.unit_outline.as_str(), |unit| {
+                theme.power(unit.power())
+            });
+        let (to, dash) = match order {
+            Order::Hold(_) => continue,
+            Order::Move(move_) => (move_.destination(), ""),
+            Order::Support(support) => (support.to().unwrap_or_else(|| support.from()), DASHED),
+            Order::Convoy(convoy) => (convoy.from(), DOTTED),
+        };
+        arrow(svg, state.map(), order.province(), to, colour, 3, dash);
+    }
+    svg.push_str("</g>\n");
+}
+
+/// The ends of an arrow between the edges of two provinces' circles.
+fn ends(map: &Map, from: &Province, to: &Province) -> ((f64, f64), (f64, f64)) {
+    let ((x1, y1), (x2, y2)) = (position(map, from), position(map, to));
+    let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt().max(1.0);
+    let (dx, dy) = ((x2 - x1) / length * RADIUS, (y2 - y1) / length * RADIUS);
+    ((x1 + dx, y1 + dy), (x2 - dx, y2 - dy))
+}
+
+fn arrow(
+    svg: &mut String,
+    map: &Map,
+    from: &Province,
+    to: &Province,
+    colour: &str,
+    width: u32,
+    extra: &str,
+) {
+    let ((x1, y1), (x2, y2)) = ends(map, from, to);
+    writeln!(
+        svg,
+        r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="{}" marker-end="url(#arrow)"{}/>"#,
+        x1, y1, x2, y2, colour, width, extra
+    )
+    .unwrap();
+}
+
+/// A cross at the middle of the arrow from `from` to `to`.
+fn cross(svg: &mut String, map: &Map, from: &Province, to: &Province, theme: &Theme) {
+    let ((x1, y1), (x2, y2)) = ends(map, from, to);
+    let (x, y) = ((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+    for (dx, dy) in &[(6.0, 6.0), (6.0, -6.0)] {
+        writeln!(
+            svg,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="3"/>"#,
+            x - dx,
+            y - dy,
+            x + dx,
+            y + dy,
+            theme.unit_outline
+        )
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_order;
+    use crate::state::Unit;
+    use std::collections::HashMap;
+
+    #[test]
+    fn draws_units_and_arrows() {
+        let map = Map::standard();
+        let paris = map.find_by_abbreviation("Par").unwrap();
+        let mut state = GameState::new(map);
+        state.place_unit(paris, Unit::army(Power::France));
+        state.set_owner(paris, Power::France);
+
+        let svg = render_with_orders(&state, &[parse_order(map, "A Par - Bur").unwrap()]);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<circle").count(), 75);
+        assert_eq!(svg.matches("<rect x=").count(), 1);
+        assert_eq!(svg.matches("marker-end").count(), 1);
+        assert!(svg.contains(colour(Power::France)));
+    }
+
+    #[test]
+    fn draws_adjudication_layers() {
+        use crate::adjudicator::adjudicate;
+
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut state = GameState::new(map);
+        state.place_unit(province("Par"), Unit::army(Power::France));
+        state.place_unit(province("Mun"), Unit::army(Power::Germany));
+        state.place_unit(province("Ruh"), Unit::army(Power::Germany));
+        state.place_unit(province("Bur"), Unit::army(Power::France));
+        let order = |text| parse_order(map, text).unwrap();
+        let orders = HashMap::from([
+            (Power::France, vec![order("A Par - Pic")]),
+            (
+                Power::Germany,
+                vec![order("A Mun - Bur"), order("A Ruh S A Mun - Bur")],
+            ),
+        ]);
+        let result = adjudicate(orders, &state);
+
+        let overlay = Overlay::new(&state, &result);
+        assert_eq!(overlay.moves.body().matches("marker-end").count(), 2);
+        assert_eq!(overlay.supports.body().matches("marker-end").count(), 1);
+        assert_eq!(overlay.dislodged.body().matches("<circle").count(), 1);
+        assert!(overlay.dislodged.to_svg().starts_with("<svg"));
+        assert!(render_result(&state, &result).contains("id=\"dislodged\""));
+    }
+
+    #[test]
+    fn themes() {
+        let map = Map::standard();
+        let mut state = GameState::new(map);
+        let paris = map.find_by_abbreviation("Par").unwrap();
+        state.place_unit(paris, Unit::army(Power::France));
+
+        assert_eq!(render(&state), render_themed(&state, &Theme::default()));
+        assert!(render(&state).contains(r##"fill="#56b4e9" stroke="#000000""##));
+        let high_contrast = Theme::high_contrast();
+        let svg = render_themed(&state, &high_contrast);
+        assert!(svg.contains(r##"fill="#00c0ff" stroke="#000000""##));
+        assert!(svg.contains(r#"stroke-width="5""#));
+        assert!(!svg.contains(Theme::default().background.as_str()));
+        assert_eq!(Theme::classic().power(Power::Turkey), "#e6c229");
+    }
+
+    #[test]
+    fn replays() {
+        let map = Map::standard();
+        let mut state = GameState::new(map);
+        let paris = map.find_by_abbreviation("Par").unwrap();
+        state.place_unit(paris, Unit::army(Power::France));
+        let mut history = History::new(state);
+        for order in ["A Par - Bur", "A Bur - Bel"] {
+            let orders = HashMap::from([(Power::France, vec![parse_order(map, order).unwrap()])]);
+            history.play(orders).unwrap();
+        }
+
+        let frames = replay_frames(&history);
+        assert_eq!(frames.len(), 3);
+        let header = |svg: &str| svg.lines().next().unwrap().to_owned();
+        assert!(frames
+            .iter()
+            .all(|frame| header(frame) == header(&frames[0])));
+        assert!(frames[0].contains(">Spring 1901 Movement</text>"));
+        assert_eq!(frames[0].matches("marker-end").count(), 1);
+        assert!(frames[2].contains(&format!(">{}</text>", history.current().phase())));
+
+        let animated = animated_replay(&history, 1.5);
+        assert_eq!(header(&animated), header(&frames[0]));
+        assert_eq!(animated.matches("<animate ").count(), 3);
+        assert!(animated.contains(r#"values="hidden;visible;hidden" keyTimes="0;0.3333333333333333;0.6666666666666666" dur="4.5s""#));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn rasterizes_to_png() {
+        let png = render_png(&GameState::standard());
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // The width and height, big-endian, in the IHDR chunk.
+        assert_eq!(png[16..24], [0, 0, 4, 96, 0, 0, 3, 222]);
+
+        let png = svg_to_png(&render(&GameState::standard()), 0.5);
+        assert_eq!(png[16..24], [0, 0, 2, 48, 0, 0, 1, 239]);
+    }
+}
@@ -0,0 +1,362 @@
+//! Private reports of a phase's results, one for each power, for a GM to
+//! send to its player: the power's orders and how they fared, its units
+//! that were dislodged and where they may retreat to, and the builds or
+//! disbands it owes.
+//!
+//! How a report is written is up to a [`Template`], whose methods each
+//! write one part of it. [`PlainTemplate`] writes them like
+//!
+//! ```text
+//! France: Spring 1901 Movement
+//!
+//! A Paris - Burgundy.  (*bounce*)
+//! F Brest - Mid-Atlantic Ocean.
+//! ```
+
+use std::fmt::{self, Write};
+
+use crate::adjudicator::{Adjustment, OrderStatus, RejectedOrder, Resolution};
+use crate::history::Turn;
+use crate::judge::{location, notes};
+use crate::map::{Coast, Province};
+use crate::phase::{Phase, PhaseKind};
+use crate::power::Power;
+use crate::state::Unit;
+
+/// What one power needs to know about one turn.
+#[derive(Clone, Copy, Debug)]
+pub struct Report<'t, 'm> {
+    turn: &'t Turn<'m>,
+    power: Power,
+}
+
+impl<'t, 'm> Report<'t, 'm> {
+    pub fn new(turn: &'t Turn<'m>, power: Power) -> Self {
+        Report { turn, power }
+    }
+
+    pub fn power(&self) -> Power {
+        self.power
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.turn.phase()
+    }
+
+    /// What happened to each of the power's units, by the province it
+    /// started in.
+    pub fn orders(&self) -> Vec<(&'m Province, &'t Resolution<'m>)> {
+        let mut orders: Vec<_> = self
+            .turn
+            .result()
+            .resolutions()
+            .filter(|(_, resolution)| resolution.power() == self.power)
+            .collect();
+        orders.sort_by_key(|(province, _)| province.id());
+        orders
+    }
+
+    /// The power's orders for provinces without a unit of its own.
+    pub fn rejected(&self) -> impl Iterator<Item = &'t RejectedOrder<'m>> + '_ {
+        self.turn
+            .result()
+            .rejected()
+            .iter()
+            .filter(move |rejected| rejected.power == self.power)
+    }
+
+    /// The power's dislodged units, with the places each may retreat to.
+    #[allow(clippy::type_complexity)]
+    pub fn dislodged(&self) -> Vec<(&'m Province, &'t Unit, &'t [(&'m Province, Option<Coast>)])> {
+        let position = self.turn.position();
+        let mut dislodged: Vec<_> = position
+            .dislodged()
+            .filter(|(_, unit)| unit.power() == self.power)
+            .map(|(province, unit)| (province, unit, position.retreats(province)))
+            .collect();
+        dislodged.sort_by_key(|(province, _, _)| province.id());
+        dislodged
+    }
+
+    /// The builds and disbands the power made in an adjustment phase,
+    /// each with whether it was removed for the power rather than
+    /// disbanded by it.
+    pub fn adjustments(&self) -> Vec<(Adjustment<'m>, bool)> {
+        let result = match self.turn.adjustment_result() {
+            Some(result) => result,
+            None => return Vec::new(),
+        };
+        let builds = result
+            .builds()
+            .iter()
+            .filter(|(_, unit)| unit.power() == self.power)
+            .map(|&(province, unit)| {
+                let build = Adjustment::Build {
+                    unit: unit.unit_type(),
+                    province,
+                    coast: unit.coast(),
+                };
+                (build, false)
+            });
+        let disbands = |removed| {
+            move |&(province, unit): &(&'m Province, Unit)| {
+                (unit.power() == self.power).then_some((Adjustment::Disband(province), removed))
+            }
+        };
+        builds
+            .chain(result.disbands().iter().filter_map(disbands(false)))
+            .chain(result.removals().iter().filter_map(disbands(true)))
+            .collect()
+    }
+
+    /// How many units the power may build, or, if negative, must
+    /// disband, when the turn leads to an adjustment phase.
+    pub fn owed(&self) -> Option<isize> {
+        let position = self.turn.position();
+        match position.phase().kind {
+            PhaseKind::Adjustments => Some(position.adjustment(self.power)),
+            _ => None,
+        }
+    }
+
+    /// Whether there is nothing to tell the power.
+    pub fn is_empty(&self) -> bool {
+        self.orders().is_empty()
+            && self.rejected().next().is_none()
+            && self.dislodged().is_empty()
+            && self.adjustments().is_empty()
+            && self.owed().is_none_or(|owed| owed == 0)
+    }
+
+    /// Writes the report with `template`.
+    pub fn write(&self, template: &impl Template) -> String {
+        let mut out = String::new();
+        template.header(&mut out, self.power, self.phase());
+        let (orders, dislodged) = (self.orders(), self.dislodged());
+        let adjustments = self.adjustments();
+        if !orders.is_empty() || self.rejected().next().is_some() || !adjustments.is_empty() {
+            template.section(&mut out, "Orders");
+        }
+        for (_, resolution) in orders {
+            template.order(&mut out, resolution, &notes(resolution, self.turn.result()));
+        }
+        for rejected in self.rejected() {
+            template.rejected(&mut out, rejected);
+        }
+        for (adjustment, removed) in &adjustments {
+            template.adjustment(&mut out, adjustment, *removed);
+        }
+        if !dislodged.is_empty() {
+            template.section(&mut out, "Dislodged");
+        }
+        for (province, unit, retreats) in dislodged {
+            template.dislodged(&mut out, province, unit, retreats);
+        }
+        match self.owed() {
+            Some(owed) if owed != 0 => template.owed(&mut out, owed),
+            _ => {}
+        }
+        out
+    }
+}
+
+impl<'t, 'm> fmt::Display for Report<'t, 'm> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.write(&PlainTemplate))
+    }
+}
+
+/// The reports for every power with something to be told, in
+/// alphabetical order.
+pub fn reports<'t, 'm>(turn: &'t Turn<'m>) -> Vec<Report<'t, 'm>> {
+    Power::ALL
+        .iter()
+        .map(|&power| Report::new(turn, power))
+        .filter(|report| !report.is_empty())
+        .collect()
+}
+
+/// How a [`Report`] is written, a part at a time.
+///
+/// Every method has a default that writes what [`PlainTemplate`] does, so
+/// a template need only override the parts it words differently.
+pub trait Template {
+    /// The first line, naming the power and phase.
+    fn header(&self, out: &mut String, power: Power, phase: Phase) {
+        writeln!(out, "{}: {}", power, phase).unwrap();
+    }
+
+    /// The start of a part of the report, "Orders" or "Dislodged", which
+    /// is only written when there is something to put in it.
+    fn section(&self, out: &mut String, _title: &str) {
+        out.push('\n');
+    }
+
+    /// One of the power's units' orders, with the judges' notes on how it
+    /// fared, like "bounce" or "dislodged".
+    fn order(&self, out: &mut String, resolution: &Resolution, notes: &[&str]) {
+        let order = match resolution.status() {
+            OrderStatus::Illegal(_) => resolution.submitted().unwrap_or(resolution.order()),
+            _ => resolution.order(),
+        };
+        let mut notes = notes.to_vec();
+        if resolution.status() == OrderStatus::Unordered {
+            notes.insert(0, "no order");
+        }
+        if notes.is_empty() {
+            writeln!(out, "{}.", order).unwrap();
+        } else {
+            writeln!(out, "{}.  (*{}*)", order, notes.join(", ")).unwrap();
+        }
+    }
+
+    /// An order for a province without a unit of the power's.
+    fn rejected(&self, out: &mut String, rejected: &RejectedOrder) {
+        writeln!(
+            out,
+            "{}.  (*rejected: {}*)",
+            rejected.order, rejected.reason
+        )
+        .unwrap();
+    }
+
+    /// A build or disband made in an adjustment phase.
+    fn adjustment(&self, out: &mut String, adjustment: &Adjustment, removed: bool) {
+        if removed {
+            writeln!(out, "{}.  (*removed*)", adjustment).unwrap();
+        } else {
+            writeln!(out, "{}.", adjustment).unwrap();
+        }
+    }
+
+    /// A dislodged unit and where it may retreat to.
+    fn dislodged(
+        &self,
+        out: &mut String,
+        province: &Province,
+        unit: &Unit,
+        retreats: &[(&Province, Option<Coast>)],
+    ) {
+        write!(out, "{} {}", unit.unit_type(), province).unwrap();
+        let retreats: Vec<String> = retreats
+            .iter()
+            .map(|&(province, coast)| location(province, coast))
+            .collect();
+        match retreats.split_last() {
+            None => out.push_str(" has no retreats and will be disbanded.\n"),
+            Some((last, [])) => writeln!(out, " may retreat to {}.", last).unwrap(),
+            Some((last, rest)) => {
+                writeln!(out, " may retreat to {} or {}.", rest.join(", "), last).unwrap()
+            }
+        }
+    }
+
+    /// The number of units the power may build, if positive, or must
+    /// disband, if negative, in the coming adjustment phase.
+    fn owed(&self, out: &mut String, owed: isize) {
+        let (verb, count) = match owed {
+            owed if owed > 0 => ("may build", owed),
+            owed => ("must disband", -owed),
+        };
+        let units = if count == 1 { "unit" } else { "units" };
+        writeln!(out, "\nYou {} {} {}.", verb, count, units).unwrap();
+    }
+}
+
+/// Writes reports in plain text, with the judges' notes on each order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlainTemplate;
+
+impl Template for PlainTemplate {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::history::History;
+    use crate::map::Map;
+    use crate::parser::parse_order;
+    use crate::state::GameState;
+
+    #[test]
+    fn reports_for_each_power() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let mut history = History::new(GameState::standard());
+        let mut orders = HashMap::new();
+        orders.insert(
+            Power::France,
+            vec![order("A Par - Bur"), order("F Bre - Mao")],
+        );
+        orders.insert(Power::Germany, vec![order("A Mun - Bur")]);
+        history.play(orders).unwrap();
+
+        let mut orders = HashMap::new();
+        orders.insert(
+            Power::France,
+            vec![
+                order("A Par - Pic"),
+                order("F Mao - Spa sc"),
+                order("A Mar H"),
+            ],
+        );
+        orders.insert(
+            Power::Germany,
+            vec![order("A Mun - Bur"), order("A Ruh S A Mun - Bur")],
+        );
+        history.play(orders).unwrap();
+
+        let turn = &history.turns()[0];
+        let france = Report::new(turn, Power::France);
+        assert_eq!(france.orders().len(), 3);
+        assert_eq!(france.owed(), None);
+        assert_eq!(
+            france.to_string(),
+            "\
+France: Spring 1901 Movement
+
+F Brest - Mid-Atlantic Ocean.
+A Marseilles H.  (*no order*)
+A Paris - Burgundy.  (*bounce*)
+"
+        );
+        assert_eq!(reports(turn).len(), 7);
+
+        let turn = &history.turns()[1];
+        let germany = Report::new(turn, Power::Germany);
+        assert!(germany.to_string().contains("(*rejected:"));
+        let france = Report::new(turn, Power::France);
+        assert_eq!(france.owed(), Some(1));
+        assert!(france.to_string().ends_with("\nYou may build 1 unit.\n"));
+    }
+
+    #[test]
+    fn custom_templates() {
+        struct Terse;
+
+        impl Template for Terse {
+            fn header(&self, out: &mut String, power: Power, _: Phase) {
+                writeln!(out, "To {}:", power).unwrap();
+            }
+
+            fn section(&self, _: &mut String, _: &str) {}
+        }
+
+        let map = Map::standard();
+        let mut history = History::new(GameState::standard());
+        let mut orders = HashMap::new();
+        orders.insert(Power::Italy, vec![parse_order(map, "A Ven - Tyr").unwrap()]);
+        history.play(orders).unwrap();
+        let report = Report::new(&history.turns()[0], Power::Italy);
+        assert_eq!(
+            report.write(&Terse),
+            "\
+To Italy:
+F Naples H.  (*no order*)
+A Rome H.  (*no order*)
+A Venice - Tyrolia.
+"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Deadlines for the phases of a game.
+//!
+//! Times are [`SystemTime`]s; reminders are saved as whole seconds since
+//! the Unix epoch.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::json::{self, Value};
+use crate::phase::{Phase, PhaseKind};
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+/// How long each kind of phase lasts, and how late orders are still
+/// accepted after a deadline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cadence {
+    pub movement: Duration,
+    pub retreats: Duration,
+    pub adjustments: Duration,
+    /// How long after a deadline late orders are still accepted.
+    pub grace: Duration,
+}
+
+impl Default for Cadence {
+    /// 24 hours for movement, 12 for retreats and adjustments, and no
+    /// grace period.
+    fn default() -> Self {
+        Cadence {
+            movement: 24 * HOUR,
+            retreats: 12 * HOUR,
+            adjustments: 12 * HOUR,
+            grace: Duration::from_secs(0),
+        }
+    }
+}
+
+impl Cadence {
+    /// How long phases of `kind` last.
+    pub fn length(&self, kind: PhaseKind) -> Duration {
+        match kind {
+            PhaseKind::Movement => self.movement,
+            PhaseKind::Retreats => self.retreats,
+            PhaseKind::Adjustments => self.adjustments,
+        }
+    }
+
+    /// The deadline for `phase`, if it starts at `start`.
+    pub fn deadline(&self, phase: Phase, start: SystemTime) -> Deadline {
+        Deadline {
+            phase,
+            due: start + self.length(phase.kind),
+            grace: self.grace,
+        }
+    }
+}
+
+/// When orders for a phase are due.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Deadline {
+    phase: Phase,
+    due: SystemTime,
+    grace: Duration,
+}
+
+impl Deadline {
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn due(&self) -> SystemTime {
+        self.due
+    }
+
+    /// The last moment late orders are accepted.
+    pub fn closes(&self) -> SystemTime {
+        self.due + self.grace
+    }
+
+    /// The time left before the deadline, or `None` if it has passed.
+    pub fn remaining(&self, now: SystemTime) -> Option<Duration> {
+        self.due.duration_since(now).ok()
+    }
+
+    /// True once the deadline has passed, even if late orders are still
+    /// accepted.
+    pub fn is_past(&self, now: SystemTime) -> bool {
+        now >= self.due
+    }
+
+    /// True once the grace period has also run out, and the phase should
+    /// be adjudicated.
+    pub fn is_closed(&self, now: SystemTime) -> bool {
+        now >= self.closes()
+    }
+
+    /// The deadline for the following phase, which starts when this one
+    /// closes.
+    pub fn next(&self, cadence: &Cadence) -> Deadline {
+        cadence.deadline(self.phase.next(), self.closes())
+    }
+
+    /// The deadline as a JSON object, with its times in whole seconds.
+    pub(crate) fn to_value(self) -> Value {
+        Value::Object(vec![
+            ("phase".to_owned(), Value::String(self.phase.to_string())),
+            (
+                "due".to_owned(),
+                Value::Number(unix_seconds(self.due) as f64),
+            ),
+            (
+                "grace".to_owned(),
+                Value::Number(self.grace.as_secs() as f64),
+            ),
+        ])
+    }
+
+    /// Reads a deadline written by [`to_value`](Self::to_value).
+    pub(crate) fn from_value(value: &Value) -> Option<Deadline> {
+        Some(Deadline {
+            phase: value.get("phase")?.as_str()?.parse().ok()?,
+            due: UNIX_EPOCH + seconds(value, "due")?,
+            grace: seconds(value, "grace")?,
+        })
+    }
+
+    /// A reminder for each of `before`, that long before the deadline,
+    /// skipping any that would come before `now`.
+    pub fn reminders(&self, before: &[Duration], now: SystemTime) -> Vec<Reminder> {
+        before
+            .iter()
+            .filter_map(|&before| {
+                let at = self.due.checked_sub(before)?;
+                if at < now {
+                    return None;
+                }
+                Some(Reminder {
+                    phase: self.phase,
+                    at,
+                    before,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A reminder to send players before a deadline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reminder {
+    pub phase: Phase,
+    /// When to send the reminder.
+    pub at: SystemTime,
+    /// How long before the deadline that is.
+    pub before: Duration,
+}
+
+impl Reminder {
+    /// Writes the reminder as a JSON object.
+    pub fn to_json(&self) -> String {
+        Value::Object(vec![
+            ("phase".to_owned(), Value::String(self.phase.to_string())),
+            ("at".to_owned(), Value::Number(unix_seconds(self.at) as f64)),
+            (
+                "before".to_owned(),
+                Value::Number(self.before.as_secs() as f64),
+            ),
+        ])
+        .to_string()
+    }
+
+    /// Reads a reminder written by [`to_json`](Self::to_json).
+    pub fn from_json(text: &str) -> Option<Reminder> {
+        let value = json::parse(text).ok()?;
+        Some(Reminder {
+            phase: value.get("phase")?.as_str()?.parse().ok()?,
+            at: UNIX_EPOCH + seconds(&value, "at")?,
+            before: seconds(&value, "before")?,
+        })
+    }
+}
+
+/// The whole number of seconds under `key` in `value`.
+fn seconds(value: &Value, key: &str) -> Option<Duration> {
+    match value.get(key)? {
+        Value::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(Duration::from_secs(*n as u64)),
+        _ => None,
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phase::Season;
+
+    #[test]
+    fn deadlines_and_reminders() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let cadence = Cadence {
+            grace: HOUR,
+            ..Cadence::default()
+        };
+        let deadline = cadence.deadline(Phase::FIRST, start);
+        assert_eq!(deadline.due(), start + 24 * HOUR);
+        assert!(deadline.is_past(start + 24 * HOUR));
+        assert!(!deadline.is_closed(start + 24 * HOUR));
+
+        let next = deadline.next(&cadence);
+        assert_eq!(
+            next.phase(),
+            Phase::new(Season::Spring, 1901, PhaseKind::Retreats)
+        );
+        assert_eq!(next.due(), start + 37 * HOUR);
+
+        let reminders = deadline.reminders(&[HOUR, 30 * HOUR], start);
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].at, start + 23 * HOUR);
+        assert_eq!(
+            Reminder::from_json(&reminders[0].to_json()),
+            Some(reminders[0])
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Orders collected in a spreadsheet, as at face-to-face tournaments, read
+//! from CSV with the columns `power`, `unit` and `order_text`:
+//!
+//! ```text
+//! power,unit,order_text
+//! France,A Par,- Bur
+//! France,F Bre,H
+//! Germany,A Mun,"S A Par - Bur"
+//! ```
+//!
+//! The order text may also be the whole order, unit and all. A header row
+//! is optional, and fields may be quoted as spreadsheets export them.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::adjudicator::{legality, OrderLegality, Rules};
+use crate::order::Order;
+use crate::parser::parse_order;
+use crate::power::Power;
+use crate::state::GameState;
+use crate::submission::OrderSet;
+
+/// A row of a spreadsheet of orders that could not be loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowError {
+    row: usize,
+    message: String,
+}
+
+impl RowError {
+    /// The 1-based number of the row, counting any header, as the
+    /// spreadsheet numbers it.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "row {}: {}", self.row, self.message)
+    }
+}
+
+impl Error for RowError {}
+
+/// Reads a phase's orders for the position `state` from CSV `text`, or
+/// every row that could not be read or names a unit the power does not
+/// have.
+///
+/// An order its unit cannot carry out is loaded as given, since the rules
+/// have the unit hold instead. Rows with no order text leave their unit
+/// unordered, and two rows for the same unit are an error. Builds and
+/// disbands are not read, so every row of an adjustment phase is an error.
+pub fn read_orders<'m>(state: &GameState<'m>, text: &str) -> Result<OrderSet<'m>, Vec<RowError>> {
+    let map = state.map();
+    let mut orders = OrderSet::new(map);
+    let mut errors = Vec::new();
+    for (i, fields) in records(text).into_iter().enumerate() {
+        let row = i + 1;
+        if i == 0
+            && fields
+                .first()
+                .is_some_and(|f| f.eq_ignore_ascii_case("power"))
+        {
+            continue;
+        }
+        let field = |n: usize| fields.get(n).map_or("", |field| field.trim());
+        let (power, unit, text) = (field(0), field(1), field(2));
+        if fields.iter().all(|field| field.trim().is_empty()) || text.is_empty() {
+            continue;
+        }
+        let read = || -> Result<(Power, Order<'m>), String> {
+            let power: Power = power.parse().map_err(|e| format!("{}", e))?;
+            let order = match parse_order(map, text) {
+                Ok(order) if unit.is_empty() => order,
+                Ok(order) => {
+                    let held = parse_order(map, &format!("{} H", unit))
+                        .map_err(|e| format!("unit {:?}: {}", unit, e))?;
+                    let same_type = match (held.unit(), order.unit()) {
+                        (Some(a), Some(b)) => a == b,
+                        _ => true,
+                    };
+                    if held.province() != order.province() || !same_type {
+                        return Err(format!("the order {} is not for {}", order, unit));
+                    }
+                    order
+                }
+                Err(_) => {
+                    parse_order(map, &format!("{} {}", unit, text)).map_err(|e| e.to_string())?
+                }
+            };
+            match legality(state, power, &order, &Rules::default()) {
+                OrderLegality::Rejected(reason) => Err(format!("{}: {}", order, reason)),
+                _ => Ok((power, order)),
+            }
+        };
+        match read() {
+            Ok((power, order)) if orders.get(power, order.province()).is_some() => {
+                errors.push(RowError {
+                    row,
+                    message: format!("a second order for the unit in {}", order.province()),
+                });
+            }
+            Ok((power, order)) => {
+                orders.submit(power, order);
+            }
+            Err(message) => errors.push(RowError { row, message }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(orders)
+    } else {
+        Err(errors)
+    }
+}
+
+/// The fields of each record of CSV `text`. A quoted field may hold
+/// commas, line breaks, and quotes written twice.
+fn records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Map;
+
+    #[test]
+    fn reads_orders_and_reports_rows() {
+        let state = GameState::standard();
+        let map = Map::standard();
+        let text = "Power,Unit,Order Text\r\n\
+                    France,A Par,- Bur\r\n\
+                    France,F Bre,\"F Bre - Mao\"\r\n\
+                    Germany,A Mun,\"S A Par - Bur\"\r\n\
+                    Germany,F Kie,\r\n";
+        let orders = read_orders(&state, text).unwrap();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        assert_eq!(
+            orders.get(Power::France, province("Par")),
+            Some(&parse_order(map, "A Par - Bur").unwrap())
+        );
+        assert_eq!(orders.orders(Power::France).len(), 2);
+        assert_eq!(orders.orders(Power::Germany).len(), 1);
+
+        let text = "France,A Par,- Bur\n\
+                    Frankia,A Mar,H\n\
+                    France,A Mar,- Atlantis\n\
+                    France,A Par,H\n\
+                    France,A Mar,A Par - Pic\n\
+                    England,A Mar,H\n";
+        let errors = read_orders(&state, text).unwrap_err();
+        let rows: Vec<usize> = errors.iter().map(RowError::row).collect();
+        assert_eq!(rows, [2, 3, 4, 5, 6]);
+        assert_eq!(errors[0].to_string(), "row 2: unknown power \"Frankia\"");
+        assert_eq!(
+            errors[4].to_string(),
+            "row 6: A Marseilles H: the unit belongs to another power"
+        );
+    }
+}
@@ -0,0 +1,1965 @@
+//! The position on the board: which units stand where, and who owns what.
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::sync::Arc;
+
+use crate::adjudicator::{
+    adjudicate_adjustments, AdjudicationResult, AdjustmentResult, RemovalRules, RngSource, Rules,
+    WhatIf,
+};
+use crate::json::{self, Value};
+use crate::map::{Coast, Map, Province, ProvinceKind, ProvinceSet, UnitKind};
+use crate::order::{Order, UnitType};
+use crate::phase::{Phase, PhaseKind, Season};
+use crate::power::Power;
+
+/// A unit on the board: an army, a fleet, or a unit of one of the kinds
+/// its map adds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Unit {
+    pub(crate) power: Power,
+    pub(crate) unit_type: UnitType,
+    pub(crate) coast: Option<Coast>,
+    /// The id of the map's unit kind, for units that are not plain armies
+    /// and fleets.
+    pub(crate) kind: Option<u8>,
+}
+
+impl Unit {
+    pub fn army(power: Power) -> Self {
+        Unit {
+            power,
+            unit_type: UnitType::Army,
+            coast: None,
+            kind: None,
+        }
+    }
+
+    pub fn fleet(power: Power) -> Self {
+        Unit {
+            power,
+            unit_type: UnitType::Fleet,
+            coast: None,
+            kind: None,
+        }
+    }
+
+    /// A fleet on one coast of a bicoastal province.
+    pub fn fleet_on(power: Power, coast: Coast) -> Self {
+        Unit {
+            power,
+            unit_type: UnitType::Fleet,
+            coast: Some(coast),
+            kind: None,
+        }
+    }
+
+    /// A unit of one of the kinds a map adds, on `coast` if it moves as a
+    /// fleet and is in a bicoastal province.
+    pub fn of_kind(power: Power, kind: &UnitKind, coast: Option<Coast>) -> Self {
+        Unit {
+            power,
+            unit_type: kind.moves_as(),
+            coast,
+            kind: Some(kind.id()),
+        }
+    }
+
+    pub fn power(&self) -> Power {
+        self.power
+    }
+
+    /// Whether the unit goes where armies or fleets go. For units of the
+    /// kinds a map adds, this is what the kind [moves as](UnitKind::moves_as).
+    pub fn unit_type(&self) -> UnitType {
+        self.unit_type
+    }
+
+    /// The kind of unit it is, if `map` adds that kind; `None` for plain
+    /// armies and fleets.
+    pub fn kind<'m>(&self, map: &'m Map) -> Option<&'m UnitKind> {
+        self.kind.map(|id| map.unit_kind_by_id(id))
+    }
+
+    /// Whether the unit can move, which every army and fleet can.
+    pub fn is_mobile(&self, map: &Map) -> bool {
+        self.kind(map).is_none_or(UnitKind::is_mobile)
+    }
+
+    /// The coast the unit is on, for fleets in bicoastal provinces.
+    pub fn coast(&self) -> Option<Coast> {
+        self.coast
+    }
+}
+
+/// The error returned when a saved game state cannot be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateError {
+    message: String,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for StateError {}
+
+impl StateError {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        StateError {
+            message: message.into(),
+        }
+    }
+}
+
+/// The units on a map, the ownership of its supply centers, the phase
+/// the game is in, and the powers whose players have abandoned it.
+///
+/// Each of these is shared between clones until one of them changes it,
+/// so cloning a state is cheap, and the state after a phase shares
+/// whatever the phase left alone with the one before: the owners, say,
+/// after a Spring.
+#[derive(Clone, Debug)]
+pub struct GameState<'m> {
+    map: &'m Map,
+    phase: Phase,
+    units: Arc<HashMap<&'m Province, Unit>>,
+    owners: Arc<HashMap<&'m Province, Power>>,
+    dislodged: Arc<HashMap<&'m Province, Unit>>,
+    retreats: Arc<HashMap<&'m Province, Vec<Place<'m>>>>,
+    disorder: Arc<HashSet<Power>>,
+    /// The provinces with units in them.
+    occupied: ProvinceSet<'m>,
+    /// The supply centers each power owns, in [`Power::ALL`] order.
+    centers: [ProvinceSet<'m>; 7],
+    /// The [Zobrist hash](GameState::zobrist) of everything but the phase,
+    /// kept up to date as the state changes.
+    hash: u64,
+}
+
+/// What [`GameState::undo`] needs to take back a phase applied in place.
+///
+/// It holds the state from before the phase, which shares everything the
+/// phase did not change with the state after it.
+#[derive(Clone, Debug)]
+#[must_use = "a phase applied in place can only be undone with its token"]
+pub struct Undo<'m> {
+    previous: GameState<'m>,
+}
+
+/// A province, with the coast for a fleet in a bicoastal one.
+type Place<'m> = (&'m Province, Option<Coast>);
+
+/// Two states are equal if they are on the same map and have the same
+/// phase, units, dislodged units and their retreats, owners and powers in
+/// civil disorder.
+impl<'m> PartialEq for GameState<'m> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.map, other.map)
+            && self.phase == other.phase
+            && self.units == other.units
+            && self.owners == other.owners
+            && self.dislodged == other.dislodged
+            && self.retreats == other.retreats
+            && self.disorder == other.disorder
+    }
+}
+
+impl<'m> Eq for GameState<'m> {}
+
+/// States hash as their [Zobrist hash](GameState::zobrist).
+impl<'m> Hash for GameState<'m> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.zobrist().hash(state);
+    }
+}
+
+/// What a Zobrist key is for, so that, say, a unit and a dislodged unit
+/// in the same place have different keys.
+#[derive(Clone, Copy)]
+enum Feature {
+    Unit,
+    Owner,
+    Dislodged,
+    Retreat,
+    Disorder,
+    Phase,
+}
+
+/// The Zobrist key of `feature` in the province with id `province`, with
+/// `detail` saying which unit, power or place it is.
+///
+/// Keys are a fixed function of their inputs, a SplitMix64 finalizer,
+/// rather than drawn at random, so hashes are the same from run to run.
+fn zobrist_key(feature: Feature, province: usize, detail: u64) -> u64 {
+    let mut x = (feature as u64) << 56 ^ (province as u64) << 32 ^ detail;
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+fn coast_bits(coast: Option<Coast>) -> u64 {
+    coast.map_or(0, |coast| coast as u64 + 1)
+}
+
+/// 0 for armies, 1 for fleets, and 2 on for the map's own unit kinds.
+fn type_bits(unit: &Unit) -> u64 {
+    unit.kind
+        .map_or(unit.unit_type as u64, |id| u64::from(id) + 2)
+}
+
+fn unit_key(feature: Feature, province: &Province, unit: &Unit) -> u64 {
+    let detail = (unit.power as u64) << 8 | type_bits(unit) << 4 | coast_bits(unit.coast);
+    zobrist_key(feature, province.id(), detail)
+}
+
+fn retreat_key(province: &Province, (place, coast): Place) -> u64 {
+    let detail = (place.id() as u64) << 4 | coast_bits(coast);
+    zobrist_key(Feature::Retreat, province.id(), detail)
+}
+
+fn phase_key(phase: Phase) -> u64 {
+    let detail = u64::from(phase.year) << 8 | (phase.season as u64) << 4 | phase.kind as u64;
+    zobrist_key(Feature::Phase, 0, detail)
+}
+
+impl<'m> GameState<'m> {
+    /// The standard game's starting position, as a
+    /// [scenario](Self::from_scenario).
+    pub const STANDARD: &'static str = r#"{
+  "year": 1901,
+  "units": {
+    "Austria": ["A Bud", "F Tri", "A Vie"],
+    "England": ["F Edi", "F Lon", "A Lvp"],
+    "France": ["F Bre", "A Mar", "A Par"],
+    "Germany": ["A Ber", "F Kie", "A Mun"],
+    "Italy": ["F Nap", "A Rom", "A Ven"],
+    "Russia": ["A Mos", "F Sev", "F StP/sc", "A War"],
+    "Turkey": ["F Ank", "A Con", "A Smy"]
+  },
+  "centers": "home"
+}"#;
+
+    /// An empty board, in Spring 1901.
+    pub fn new(map: &'m Map) -> Self {
+        GameState {
+            map,
+            phase: Phase::FIRST,
+            units: Arc::default(),
+            owners: Arc::default(),
+            dislodged: Arc::default(),
+            retreats: Arc::default(),
+            disorder: Arc::default(),
+            occupied: ProvinceSet::new(map),
+            centers: [ProvinceSet::new(map); 7],
+            hash: 0,
+        }
+    }
+
+    /// The starting position of the standard game, with every home
+    /// center owned by its power.
+    pub fn standard() -> GameState<'static> {
+        GameState::from_scenario(Map::standard(), GameState::STANDARD)
+            .expect("the standard start is valid")
+    }
+
+    /// Sets up a position from a scenario: a state in the form read by
+    /// [`from_json`](Self::from_json), which may give a `"year"` instead of
+    /// a phase, to start in its Spring, and `"centers": "home"` to give
+    /// every power its home centers. [`STANDARD`](Self::STANDARD) is the
+    /// standard start; a DATC test case might be
+    ///
+    /// ```json
+    /// {
+    ///   "year": 1901,
+    ///   "units": {"England": ["F Lon", "F Nth"], "France": ["F Bre"]},
+    ///   "centers": "home"
+    /// }
+    /// ```
+    pub fn from_scenario(map: &'m Map, text: &str) -> Result<Self, StateError> {
+        let value = json::parse(text).map_err(|e| StateError::new(e.to_string()))?;
+        let mut members = match value {
+            Value::Object(members) => members,
+            _ => return Err(StateError::new("a scenario must be an object")),
+        };
+        if let Some(i) = members.iter().position(|(key, _)| key == "year") {
+            let year = match members.remove(i).1 {
+                Value::Number(year) if year.fract() == 0.0 && (0.0..=65535.0).contains(&year) => {
+                    year as u16
+                }
+                _ => return Err(StateError::new("\"year\" must be a year, like 1901")),
+            };
+            if members.iter().any(|(key, _)| key == "phase") {
+                return Err(StateError::new("give a \"year\" or a \"phase\", not both"));
+            }
+            let phase = Phase::movement(Season::Spring, year).to_string();
+            members.push(("phase".to_owned(), Value::String(phase)));
+        }
+        for (key, centers) in &mut members {
+            if key == "centers" && centers.as_str() == Some("home") {
+                let homes = Power::ALL
+                    .iter()
+                    .map(|&power| {
+                        let homes = map
+                            .provinces()
+                            .filter(|p| p.home_power() == Some(power))
+                            .map(|p| Value::String(p.abbreviation().to_owned()))
+                            .collect();
+                        (power.name().to_owned(), Value::Array(homes))
+                    })
+                    .collect();
+                *centers = Value::Object(homes);
+            }
+        }
+        GameState::from_value(map, &Value::Object(members))
+    }
+
+    pub fn map(&self) -> &'m Map {
+        self.map
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn set_phase(&mut self, phase: Phase) {
+        self.phase = phase;
+    }
+
+    /// A 64-bit Zobrist hash of the phase, units, dislodged units and their
+    /// retreats, owners and powers in civil disorder, for transposition
+    /// tables and spotting repeated positions.
+    ///
+    /// Equal states have equal hashes, and the hash of a state does not
+    /// change from run to run. It is kept up to date as the state changes,
+    /// so costs nothing to read.
+    pub fn zobrist(&self) -> u64 {
+        self.hash ^ phase_key(self.phase)
+    }
+
+    /// Puts `unit` in `province`, returning whatever unit was there before.
+    pub fn place_unit(&mut self, province: &'m Province, unit: Unit) -> Option<Unit> {
+        self.hash ^= unit_key(Feature::Unit, province, &unit);
+        self.occupied.insert(province);
+        let old = Arc::make_mut(&mut self.units).insert(province, unit);
+        if let Some(old) = &old {
+            self.hash ^= unit_key(Feature::Unit, province, old);
+        }
+        old
+    }
+
+    /// Takes the unit out of `province`, if there is one.
+    pub fn remove_unit(&mut self, province: &Province) -> Option<Unit> {
+        if !self.units.contains_key(province) {
+            return None;
+        }
+        self.occupied.remove(province);
+        let old = Arc::make_mut(&mut self.units).remove(province);
+        if let Some(old) = &old {
+            self.hash ^= unit_key(Feature::Unit, province, old);
+        }
+        old
+    }
+
+    /// The unit in `province`, if there is one.
+    pub fn unit_at(&self, province: &Province) -> Option<&Unit> {
+        self.units.get(province)
+    }
+
+    /// The provinces with units in them.
+    pub fn occupied(&self) -> ProvinceSet<'m> {
+        self.occupied
+    }
+
+    /// Every unit on the board, with its province, in map order.
+    pub fn units(&self) -> impl Iterator<Item = (&'m Province, &Unit)> {
+        self.occupied
+            .iter()
+            .map(move |province| (province, &self.units[province]))
+    }
+
+    /// The power that owns the supply center `province`.
+    pub fn owner(&self, province: &Province) -> Option<Power> {
+        self.owners.get(province).copied()
+    }
+
+    /// Gives the supply center `province` to `power`.
+    pub fn set_owner(&mut self, province: &'m Province, power: Power) {
+        let key = |power: Power| zobrist_key(Feature::Owner, province.id(), power as u64);
+        self.hash ^= key(power);
+        self.centers[power as usize].insert(province);
+        if let Some(old) = Arc::make_mut(&mut self.owners).insert(province, power) {
+            self.hash ^= key(old);
+            if old != power {
+                self.centers[old as usize].remove(province);
+            }
+        }
+    }
+
+    /// The supply centers `power` owns.
+    pub fn centers(&self, power: Power) -> ProvinceSet<'m> {
+        self.centers[power as usize]
+    }
+
+    /// The number of supply centers `power` owns.
+    pub fn center_count(&self, power: Power) -> usize {
+        self.centers[power as usize].len()
+    }
+
+    /// The powers still in the game, with a unit or a supply center, in
+    /// [`Power::ALL`] order.
+    pub fn survivors(&self) -> Vec<Power> {
+        Power::ALL
+            .iter()
+            .copied()
+            .filter(|&power| {
+                self.center_count(power) > 0 || self.units.values().any(|unit| unit.power == power)
+            })
+            .collect()
+    }
+
+    /// The power that owns a majority of the map's supply centers, and so
+    /// has won the game: 18 of the 34 on the standard map.
+    pub fn solo_winner(&self) -> Option<Power> {
+        let centers = self
+            .map
+            .provinces()
+            .filter(|p| p.is_supply_center())
+            .count();
+        Power::ALL
+            .iter()
+            .copied()
+            .find(|&power| self.center_count(power) > centers / 2)
+    }
+
+    /// How many units `power` may build, or, if negative, must disband:
+    /// its supply centers less its units.
+    pub fn adjustment(&self, power: Power) -> isize {
+        let units = self.units.values().filter(|unit| unit.power == power);
+        self.center_count(power) as isize - units.count() as isize
+    }
+
+    /// How many units `power` can build, or, if negative, must disband,
+    /// in an adjustment phase: its [`adjustment`](Self::adjustment), with
+    /// builds capped at the empty home centers it owns to build in.
+    pub fn adjustments_owed(&self, power: Power) -> isize {
+        match self.adjustment(power) {
+            builds if builds > 0 => {
+                let room = self
+                    .centers(power)
+                    .difference(&self.occupied)
+                    .iter()
+                    .filter(|province| province.home_power() == Some(power))
+                    .count();
+                builds.min(room as isize)
+            }
+            adjustment => adjustment,
+        }
+    }
+
+    /// The units dislodged in the last movement phase, by the province
+    /// they were dislodged from, in map order. They are off the board
+    /// until they retreat or are disbanded.
+    pub fn dislodged(&self) -> impl Iterator<Item = (&'m Province, &Unit)> {
+        let mut dislodged: Vec<_> = self
+            .dislodged
+            .iter()
+            .map(|(&province, unit)| (province, unit))
+            .collect();
+        dislodged.sort_by_key(|(province, _)| province.id());
+        dislodged.into_iter()
+    }
+
+    /// The unit dislodged from `province`, if there is one.
+    pub fn dislodged_unit(&self, province: &Province) -> Option<&Unit> {
+        self.dislodged.get(province)
+    }
+
+    /// Where the unit dislodged from `province` may retreat to, with the
+    /// coast for a fleet going to a bicoastal province: the neighbouring
+    /// provinces left empty by the movement phase, other than those where
+    /// a standoff happened and the one the attack came from, unless it
+    /// came by convoy.
+    pub fn retreats(&self, province: &Province) -> &[(&'m Province, Option<Coast>)] {
+        self.retreats.get(province).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether `power` is in civil disorder: its player has left the game,
+    /// and its units are played by the rulebook's defaults.
+    pub fn in_civil_disorder(&self, power: Power) -> bool {
+        self.disorder.contains(&power)
+    }
+
+    /// Puts `power` into civil disorder, or takes it out again when a
+    /// replacement player is found.
+    ///
+    /// A power in civil disorder has its orders ignored, so its units
+    /// hold. Its dislodged units are disbanded rather than retreated, and
+    /// when it has more units than centers after a Fall phase, the units
+    /// farthest from home are removed.
+    pub fn set_civil_disorder(&mut self, power: Power, disorder: bool) {
+        if self.disorder.contains(&power) == disorder {
+            return;
+        }
+        let changed = if disorder {
+            Arc::make_mut(&mut self.disorder).insert(power)
+        } else {
+            Arc::make_mut(&mut self.disorder).remove(&power)
+        };
+        if changed {
+            self.hash ^= zobrist_key(Feature::Disorder, 0, power as u64);
+        }
+    }
+
+    /// Sets the unit dislodged from `province` aside, with the places it
+    /// may retreat to.
+    fn set_dislodged(&mut self, province: &'m Province, unit: Unit, places: Vec<Place<'m>>) {
+        if let Some(old) = Arc::make_mut(&mut self.dislodged).insert(province, unit) {
+            self.hash ^= unit_key(Feature::Dislodged, province, &old);
+        }
+        self.hash ^= unit_key(Feature::Dislodged, province, &unit);
+        self.set_retreats(province, places);
+    }
+
+    fn set_retreats(&mut self, province: &'m Province, places: Vec<Place<'m>>) {
+        for &place in &places {
+            self.hash ^= retreat_key(province, place);
+        }
+        let retreats = Arc::make_mut(&mut self.retreats);
+        for place in retreats.insert(province, places).into_iter().flatten() {
+            self.hash ^= retreat_key(province, place);
+        }
+    }
+
+    /// Disbands every dislodged unit, and forgets where they could go.
+    fn clear_dislodged(&mut self) {
+        if self.dislodged.is_empty() && self.retreats.is_empty() {
+            return;
+        }
+        for (province, unit) in Arc::make_mut(&mut self.dislodged).drain() {
+            self.hash ^= unit_key(Feature::Dislodged, province, &unit);
+        }
+        for (province, places) in Arc::make_mut(&mut self.retreats).drain() {
+            for place in places {
+                self.hash ^= retreat_key(province, place);
+            }
+        }
+    }
+
+    /// The units `power` would have removed for it in civil disorder, in
+    /// the order they would go: as many as it has units beyond its supply
+    /// centers, farthest from home first.
+    pub fn civil_disorder_removals(&self, power: Power, rules: RemovalRules) -> Vec<&'m Province> {
+        self.removals_by(power, rules, |units| {
+            units.sort_by_cached_key(|&(province, unit)| {
+                (
+                    unit.unit_type != UnitType::Fleet,
+                    province.name().to_lowercase(),
+                )
+            })
+        })
+    }
+
+    /// The units `power` would have removed for it in civil disorder, like
+    /// [`civil_disorder_removals`](Self::civil_disorder_removals), but
+    /// choosing between units as far from home as each other at random,
+    /// with numbers from `rng`.
+    pub fn civil_disorder_removals_with(
+        &self,
+        power: Power,
+        rules: RemovalRules,
+        rng: &mut dyn RngSource,
+    ) -> Vec<&'m Province> {
+        self.removals_by(power, rules, |units| {
+            // Shuffled from alphabetical order, so that the same numbers
+            // always choose the same units.
+            units.sort_by_cached_key(|(province, _)| province.name().to_lowercase());
+            for i in (1..units.len()).rev() {
+                units.swap(i, rng.below(i + 1));
+            }
+        })
+    }
+
+    /// The units `power` would have removed for it in civil disorder, with
+    /// units as far from home as each other in the order `ties` puts them.
+    fn removals_by(
+        &self,
+        power: Power,
+        rules: RemovalRules,
+        ties: impl FnOnce(&mut Vec<(&'m Province, Unit)>),
+    ) -> Vec<&'m Province> {
+        let centers = self.center_count(power);
+        let mut units: Vec<(&'m Province, Unit)> = self
+            .units()
+            .filter(|(_, unit)| unit.power == power)
+            .map(|(province, &unit)| (province, unit))
+            .collect();
+        let excess = units.len().saturating_sub(centers);
+        ties(&mut units);
+        // Units that cannot get home at all are the farthest of all. The
+        // sort is stable, so ties keep their order.
+        units.sort_by_cached_key(|&(province, unit)| {
+            std::cmp::Reverse(
+                self.distance_home(province, unit, rules)
+                    .unwrap_or(usize::MAX),
+            )
+        });
+        units
+            .into_iter()
+            .take(excess)
+            .map(|(province, _)| province)
+            .collect()
+    }
+
+    /// The fewest moves `unit` would take from `province` to any of its
+    /// power's home centers, counted as `rules` say: by the fleet's own
+    /// moves for a fleet under the 2000 rules, and otherwise across any
+    /// border, by land or sea.
+    fn distance_home(
+        &self,
+        province: &'m Province,
+        unit: Unit,
+        rules: RemovalRules,
+    ) -> Option<usize> {
+        let by = match unit.unit_type {
+            UnitType::Fleet if rules == RemovalRules::Rules2000 => Some(UnitType::Fleet),
+            _ => None,
+        };
+        let coast = by.and(unit.coast);
+        self.map
+            .search(province, &[coast], by, |p| {
+                p.home_power() == Some(unit.power)
+            })
+            .map(|path| path.len() - 1)
+    }
+
+    /// Every order the unit in `province` could be given that the
+    /// adjudicator would accept, or none if the province is empty.
+    ///
+    /// Moves by convoy are offered wherever fleets now stand in an unbroken
+    /// line of seas to the destination, and convoys likewise. Supports are
+    /// offered for every unit this one could support in place, and for
+    /// every move another unit could make to a province this one borders.
+    pub fn legal_orders(&self, province: &'m Province) -> Vec<Order<'m>> {
+        let unit = match self.unit_at(province) {
+            Some(&unit) => unit,
+            None => return Vec::new(),
+        };
+        let mut orders = vec![Order::hold(unit.unit_type, province)];
+        orders.extend(self.moves(province, unit));
+
+        let mut others: Vec<_> = self
+            .units()
+            .filter(|&(other, _)| other != province)
+            .map(|(other, &unit)| (other, unit))
+            .collect();
+        others.sort_by_key(|(other, _)| other.id());
+        for &(from, other) in &others {
+            if self.can_reach(province, unit, from) {
+                orders.extend(
+                    Order::support_hold(unit.unit_type, province, other.unit_type, from).ok(),
+                );
+            }
+            let mut destinations = Vec::new();
+            for order in self.moves(from, other) {
+                let to = order.destination().expect("moves have destinations");
+                if !destinations.contains(&to) && self.can_reach(province, unit, to) {
+                    destinations.push(to);
+                }
+            }
+            orders.extend(destinations.into_iter().filter_map(|to| {
+                Order::support_move(unit.unit_type, province, other.unit_type, from, to).ok()
+            }));
+        }
+
+        if unit.unit_type == UnitType::Fleet && province.kind() == ProvinceKind::Sea {
+            for &(from, other) in &others {
+                if other.unit_type != UnitType::Army || from.kind() != ProvinceKind::Coast {
+                    continue;
+                }
+                let seas = self.convoy_fleets(from);
+                if seas.contains(&province) {
+                    orders.extend(
+                        self.convoy_destinations(from, &seas)
+                            .into_iter()
+                            .filter_map(|to| {
+                                Order::convoy(UnitType::Fleet, province, UnitType::Army, from, to)
+                                    .ok()
+                            }),
+                    );
+                }
+            }
+        }
+        orders
+    }
+
+    /// Every move `unit` could make from `province`, including by convoy.
+    fn moves(&self, province: &'m Province, unit: Unit) -> Vec<Order<'m>> {
+        if !unit.is_mobile(self.map) {
+            return Vec::new();
+        }
+        match unit.unit_type {
+            UnitType::Army => {
+                let mut moves: Vec<_> = self
+                    .map
+                    .army_neighbours(province)
+                    .filter_map(|to| Order::move_(UnitType::Army, province, to).ok())
+                    .collect();
+                if province.kind() == ProvinceKind::Coast {
+                    let seas = self.convoy_fleets(province);
+                    moves.extend(
+                        self.convoy_destinations(province, &seas)
+                            .into_iter()
+                            .filter_map(|to| {
+                                Order::move_via_convoy(UnitType::Army, province, to).ok()
+                            }),
+                    );
+                }
+                moves
+            }
+            UnitType::Fleet => self
+                .map
+                .fleet_neighbours(province, unit.coast)
+                .filter_map(|(to, coast)| match coast {
+                    Some(coast) => Order::move_to_coast(UnitType::Fleet, province, to, coast).ok(),
+                    None => Order::move_(UnitType::Fleet, province, to).ok(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether `unit` in `province` could support a unit into or in
+    /// `target`.
+    fn can_reach(&self, province: &Province, unit: Unit, target: &Province) -> bool {
+        match unit.unit_type {
+            UnitType::Army => self.map.army_can_move(province, target),
+            UnitType::Fleet => self.map.fleet_can_reach(province, unit.coast, target),
+        }
+    }
+
+    /// The seas with fleets in them that join up with `from`, through
+    /// other seas with fleets in them.
+    fn convoy_fleets(&self, from: &Province) -> Vec<&'m Province> {
+        let map = self.map;
+        let fleet_at_sea = |province: &Province| {
+            province.kind() == ProvinceKind::Sea
+                && self
+                    .unit_at(province)
+                    .is_some_and(|unit| unit.unit_type == UnitType::Fleet)
+        };
+        let mut seas: Vec<&'m Province> = map
+            .provinces()
+            .filter(|&sea| {
+                fleet_at_sea(sea) && map.fleet_neighbours(sea, None).any(|(p, _)| p == from)
+            })
+            .collect();
+        let mut i = 0;
+        while i < seas.len() {
+            for (sea, _) in map.fleet_neighbours(seas[i], None) {
+                if fleet_at_sea(sea) && !seas.contains(&sea) {
+                    seas.push(sea);
+                }
+            }
+            i += 1;
+        }
+        seas
+    }
+
+    /// The coastal provinces other than `from` that border `seas`.
+    fn convoy_destinations(&self, from: &Province, seas: &[&'m Province]) -> Vec<&'m Province> {
+        let mut destinations: Vec<&'m Province> = Vec::new();
+        for &sea in seas {
+            for (to, _) in self.map.fleet_neighbours(sea, None) {
+                if to.kind() == ProvinceKind::Coast && to != from && !destinations.contains(&to) {
+                    destinations.push(to);
+                }
+            }
+        }
+        destinations.sort_by_key(|to| to.id());
+        destinations
+    }
+
+    /// The position after the phase described by `result`.
+    ///
+    /// After a movement phase, units that moved successfully are moved,
+    /// and dislodged units are set aside with the places they may
+    /// [retreat](Self::retreats) to, except those of powers in civil
+    /// disorder, which are disbanded. If any were set aside, the game goes
+    /// on to a retreat phase, after which units that retreated
+    /// successfully are put back and the rest are disbanded.
+    ///
+    /// After the Spring, the game goes on to the Fall. After the Fall, each
+    /// supply center with a unit in it passes to that unit's power, and
+    /// the game goes on to an adjustment phase if any power has units to
+    /// build or disband. Applying a result to an adjustment phase makes no
+    /// builds and only the [removals](Self::civil_disorder_removals) the
+    /// rulebook makes for powers that disband too few; use
+    /// [`apply_adjustments`](Self::apply_adjustments) to make the powers'
+    /// own.
+    pub fn apply(&self, result: &AdjudicationResult<'m>) -> GameState<'m> {
+        let mut next = match self.phase.kind {
+            PhaseKind::Movement => self.after_movement(result),
+            PhaseKind::Retreats => self.after_retreats(result),
+            PhaseKind::Adjustments => {
+                let adjustments = adjudicate_adjustments(HashMap::new(), self, result.rules());
+                return self.apply_adjustments(&adjustments);
+            }
+        };
+        if next.dislodged.is_empty() {
+            next.end_season(self.phase);
+        } else {
+            next.phase = Phase::new(self.phase.season, self.phase.year, PhaseKind::Retreats);
+        }
+        next
+    }
+
+    /// The position after the adjustment phase described by `result`: the
+    /// builds are placed, the disbands and removals taken off the board,
+    /// and the game goes on to the next Spring.
+    pub fn apply_adjustments(&self, result: &AdjustmentResult<'m>) -> GameState<'m> {
+        let mut next = self.clone();
+        for &(province, _) in result.disbands().iter().chain(result.removals()) {
+            next.remove_unit(province);
+        }
+        for &(province, unit) in result.builds() {
+            next.place_unit(province, unit);
+        }
+        next.phase = self.phase.next_movement();
+        next
+    }
+
+    /// Like [`apply`](Self::apply), but moves this state on in place,
+    /// returning what [`undo`](Self::undo) needs to move it back.
+    ///
+    /// A search can go down a branch and back up again this way, and only
+    /// what the phase changed is ever copied.
+    pub fn apply_in_place(&mut self, result: &AdjudicationResult<'m>) -> Undo<'m> {
+        let next = self.apply(result);
+        Undo {
+            previous: mem::replace(self, next),
+        }
+    }
+
+    /// Like [`apply_adjustments`](Self::apply_adjustments), but in place,
+    /// as with [`apply_in_place`](Self::apply_in_place).
+    pub fn apply_adjustments_in_place(&mut self, result: &AdjustmentResult<'m>) -> Undo<'m> {
+        let next = self.apply_adjustments(result);
+        Undo {
+            previous: mem::replace(self, next),
+        }
+    }
+
+    /// Puts the state back as it was before the phase `undo` was returned
+    /// for. Undoing phases out of order puts the state back to whichever
+    /// was undone last.
+    pub fn undo(&mut self, undo: Undo<'m>) {
+        *self = undo.previous;
+    }
+
+    /// Adjudicates the phase with `orders`, and again with `power`'s
+    /// replaced by `alternative`, everyone else's held fixed, to see what
+    /// difference they would make.
+    pub fn what_if(
+        &self,
+        orders: &HashMap<Power, Vec<Order<'m>>>,
+        power: Power,
+        alternative: Vec<Order<'m>>,
+    ) -> WhatIf<'m> {
+        self.what_if_with(orders, power, alternative, &Rules::default())
+    }
+
+    /// Like [`what_if`](Self::what_if), following `rules` where rulebooks
+    /// differ.
+    pub fn what_if_with(
+        &self,
+        orders: &HashMap<Power, Vec<Order<'m>>>,
+        power: Power,
+        alternative: Vec<Order<'m>>,
+        rules: &Rules,
+    ) -> WhatIf<'m> {
+        WhatIf::new(orders, power, alternative, self, rules)
+    }
+
+    /// Moves on from the movement or retreat phase `phase` once every unit
+    /// is back on the board.
+    fn end_season(&mut self, phase: Phase) {
+        if phase.season != Season::Fall {
+            self.phase = phase.next_movement();
+            return;
+        }
+        let occupied: Vec<_> = self
+            .units()
+            .filter(|(province, _)| province.is_supply_center())
+            .map(|(province, unit)| (province, unit.power))
+            .collect();
+        for (province, power) in occupied {
+            self.set_owner(province, power);
+        }
+        self.phase = if self.needs_adjustments() {
+            Phase::new(Season::Winter, phase.year, PhaseKind::Adjustments)
+        } else {
+            phase.next_movement()
+        };
+    }
+
+    /// Whether any power must disband units, or has a build it could make
+    /// in one of its empty home centers.
+    fn needs_adjustments(&self) -> bool {
+        Power::ALL
+            .iter()
+            .any(|&power| self.adjustments_owed(power) != 0)
+    }
+
+    /// The units after the movement phase described by `result`, still in
+    /// that phase.
+    ///
+    /// Only the units that moved or were dislodged are touched, so the
+    /// [Zobrist hash](Self::zobrist) is updated rather than recomputed.
+    fn after_movement(&self, result: &AdjudicationResult<'m>) -> GameState<'m> {
+        let mut next = self.clone();
+        next.clear_dislodged();
+        let mut arrivals = Vec::new();
+        let mut dislodged = Vec::new();
+        for (province, &unit) in self.units() {
+            let resolution = match result.get(province) {
+                Some(resolution) => resolution,
+                None => continue,
+            };
+            match resolution.order() {
+                Order::Move(move_) if resolution.succeeded() => {
+                    let unit = Unit {
+                        coast: move_.coast(),
+                        ..unit
+                    };
+                    arrivals.push((move_.destination(), unit));
+                }
+                _ if resolution.is_dislodged() => {
+                    if !self.in_civil_disorder(unit.power) {
+                        dislodged.push((province, unit));
+                    }
+                }
+                _ => continue,
+            }
+            next.remove_unit(province);
+        }
+        for (province, unit) in arrivals {
+            next.place_unit(province, unit);
+        }
+        for (province, unit) in dislodged {
+            // A unit may retreat to where its attacker came from only if
+            // the attacker came by convoy.
+            let attacker = result
+                .get(province)
+                .and_then(|resolution| resolution.dislodged_by())
+                .filter(|&from| !result.get(from).is_some_and(|r| r.convoyed()));
+            next.dislodge(province, unit, attacker, result.standoffs());
+        }
+        next
+    }
+
+    /// Sets `unit` aside as dislodged from `province` by a unit from
+    /// `attacker`, with the places it may retreat to: its neighbours, other
+    /// than those with units in them, `attacker` and the `standoffs`.
+    pub(crate) fn dislodge(
+        &mut self,
+        province: &'m Province,
+        unit: Unit,
+        attacker: Option<&Province>,
+        standoffs: ProvinceSet,
+    ) {
+        let map = self.map;
+        let neighbours: Vec<Place<'m>> = match unit.unit_type {
+            _ if !unit.is_mobile(map) => Vec::new(),
+            UnitType::Army => map.army_neighbours(province).map(|p| (p, None)).collect(),
+            UnitType::Fleet => map.fleet_neighbours(province, unit.coast).collect(),
+        };
+        let places = neighbours
+            .into_iter()
+            .filter(|&(p, _)| Some(p) != attacker)
+            .filter(|&(p, _)| !self.occupied.contains(p))
+            .filter(|&(p, _)| !standoffs.contains(p))
+            .collect();
+        self.set_dislodged(province, unit, places);
+    }
+
+    /// The units after the retreat phase described by `result`, still in
+    /// that phase.
+    fn after_retreats(&self, result: &AdjudicationResult<'m>) -> GameState<'m> {
+        let mut next = self.clone();
+        next.clear_dislodged();
+        for (province, unit) in self.dislodged() {
+            let retreated = result
+                .get(province)
+                .and_then(|resolution| match resolution.order() {
+                    Order::Move(move_) if resolution.succeeded() => Some(move_),
+                    _ => None,
+                });
+            if let Some(move_) = retreated {
+                let unit = Unit {
+                    coast: move_.coast(),
+                    ..*unit
+                };
+                next.place_unit(move_.destination(), unit);
+            }
+        }
+        next
+    }
+
+    /// Writes the state as JSON, in the form read by
+    /// [`from_json`](Self::from_json).
+    pub fn to_json(&self) -> String {
+        self.to_value().pretty()
+    }
+
+    pub(crate) fn to_value(&self) -> Value {
+        let by_power = |units: &HashMap<&'m Province, Unit>| {
+            let mut units: Vec<_> = units.iter().collect();
+            units.sort_by_key(|(province, unit)| (unit.power, province.id()));
+            let mut members: Vec<(String, Value)> = Vec::new();
+            for (province, unit) in units {
+                let text = Value::String(unit_text(self.map, province, unit));
+                match members.last_mut() {
+                    Some((power, Value::Array(list))) if *power == unit.power.name() => {
+                        list.push(text)
+                    }
+                    _ => members.push((unit.power.name().to_owned(), Value::Array(vec![text]))),
+                }
+            }
+            Value::Object(members)
+        };
+        let mut centers: Vec<(String, Value)> = Vec::new();
+        for &power in &Power::ALL {
+            let mut owned: Vec<&Province> = self
+                .owners
+                .iter()
+                .filter(|&(_, &owner)| owner == power)
+                .map(|(&province, _)| province)
+                .collect();
+            owned.sort_by_key(|province| province.id());
+            if !owned.is_empty() {
+                let owned = owned
+                    .into_iter()
+                    .map(|province| Value::String(province.abbreviation().to_owned()))
+                    .collect();
+                centers.push((power.name().to_owned(), Value::Array(owned)));
+            }
+        }
+        let mut members = vec![
+            ("phase".to_owned(), Value::String(self.phase.to_string())),
+            ("units".to_owned(), by_power(&self.units)),
+            ("centers".to_owned(), Value::Object(centers)),
+        ];
+        if !self.dislodged.is_empty() {
+            members.push(("dislodged".to_owned(), by_power(&self.dislodged)));
+            let mut retreats: Vec<_> = self.retreats.iter().collect();
+            retreats.sort_by_key(|(province, _)| province.id());
+            let retreats = retreats
+                .into_iter()
+                .map(|(province, places)| {
+                    let places = places
+                        .iter()
+                        .map(|&(place, coast)| Value::String(place_text(place, coast)))
+                        .collect();
+                    (province.abbreviation().to_owned(), Value::Array(places))
+                })
+                .collect();
+            members.push(("retreats".to_owned(), Value::Object(retreats)));
+        }
+        if !self.disorder.is_empty() {
+            let disorder = Power::ALL
+                .iter()
+                .filter(|&power| self.disorder.contains(power))
+                .map(|power| Value::String(power.name().to_owned()))
+                .collect();
+            members.push(("civil disorder".to_owned(), Value::Array(disorder)));
+        }
+        Value::Object(members)
+    }
+
+    /// Reads a state written by [`to_json`](Self::to_json), like
+    ///
+    /// ```json
+    /// {
+    ///   "phase": "Spring 1901 Movement",
+    ///   "units": {"Russia": ["A War", "F StP/sc"]},
+    ///   "centers": {"Russia": ["StP", "War"]}
+    /// }
+    /// ```
+    ///
+    /// In a retreat phase, the dislodged units are listed by power like the
+    /// units, and the places they may retreat to by the province they were
+    /// dislodged from, as in `"retreats": {"Bur": ["Pic", "Gas"]}`.
+    pub fn from_json(map: &'m Map, text: &str) -> Result<Self, StateError> {
+        let value = json::parse(text).map_err(|e| StateError::new(e.to_string()))?;
+        GameState::from_value(map, &value)
+    }
+
+    pub(crate) fn from_value(map: &'m Map, value: &Value) -> Result<Self, StateError> {
+        let mut state = GameState::new(map);
+        if let Some(phase) = value.get("phase") {
+            state.phase = phase
+                .as_str()
+                .ok_or_else(|| StateError::new("\"phase\" must be a string"))?
+                .parse()
+                .map_err(|e: crate::phase::UnknownPhase| StateError::new(e.to_string()))?;
+        }
+        let by_power = |key: &str| -> Result<Vec<(Power, &str)>, StateError> {
+            let mut entries = Vec::new();
+            let members = match value.get(key) {
+                None => return Ok(entries),
+                Some(members) => members.as_object().ok_or_else(|| {
+                    StateError::new(format!("{:?} must map powers to lists", key))
+                })?,
+            };
+            for (power, list) in members {
+                let power: Power = power
+                    .parse()
+                    .map_err(|e| StateError::new(format!("{}", e)))?;
+                let list = list.as_array().ok_or_else(|| {
+                    StateError::new(format!("{:?} must map powers to lists", key))
+                })?;
+                for item in list {
+                    let item = item
+                        .as_str()
+                        .ok_or_else(|| StateError::new(format!("{:?} must list strings", key)))?;
+                    entries.push((power, item));
+                }
+            }
+            Ok(entries)
+        };
+        for (power, text) in by_power("units")? {
+            let (province, unit) = parse_unit(map, power, text)?;
+            if state.place_unit(province, unit).is_some() {
+                return Err(StateError::new(format!(
+                    "two units in {}",
+                    province.abbreviation()
+                )));
+            }
+        }
+        for (power, text) in by_power("dislodged")? {
+            let (province, unit) = parse_unit(map, power, text)?;
+            state.set_dislodged(province, unit, Vec::new());
+        }
+        if let Some(retreats) = value.get("retreats") {
+            let bad = || StateError::new("\"retreats\" must map provinces to lists");
+            for (province, places) in retreats.as_object().ok_or_else(bad)? {
+                let province = find_province(map, province)?;
+                let places = places
+                    .as_array()
+                    .ok_or_else(bad)?
+                    .iter()
+                    .map(|place| parse_place(map, place.as_str().ok_or_else(bad)?))
+                    .collect::<Result<_, _>>()?;
+                state.set_retreats(province, places);
+            }
+        }
+        for (power, text) in by_power("centers")? {
+            let province = find_province(map, text)?;
+            if !province.is_supply_center() {
+                return Err(StateError::new(format!(
+                    "{} is not a supply center",
+                    province.abbreviation()
+                )));
+            }
+            state.set_owner(province, power);
+        }
+        if let Some(disorder) = value.get("civil disorder") {
+            let disorder = disorder
+                .as_array()
+                .ok_or_else(|| StateError::new("\"civil disorder\" must list powers"))?;
+            for power in disorder {
+                let power: Power = power
+                    .as_str()
+                    .ok_or_else(|| StateError::new("\"civil disorder\" must list powers"))?
+                    .parse()
+                    .map_err(|e| StateError::new(format!("{}", e)))?;
+                state.set_civil_disorder(power, true);
+            }
+        }
+        Ok(state)
+    }
+
+    /// The version of the [binary encoding](Self::to_bytes) written now.
+    pub const BYTES_VERSION: u8 = 1;
+
+    /// Writes the state in a compact binary encoding, read by
+    /// [`from_bytes`](Self::from_bytes), for storing many positions.
+    ///
+    /// Version 1 of the encoding is, in order:
+    ///
+    /// - the bytes `DIP` and the version, 1;
+    /// - the phase: the year as two bytes, little-endian, then the season
+    ///   (0 Spring, 1 Fall, 2 Winter) and the kind (0 Movement,
+    ///   1 Retreats, 2 Adjustments) in a byte each;
+    /// - the number of units, and each unit as three bytes: its province's
+    ///   index among the map's provinces, its power's index in
+    ///   [`Power::ALL`], and its type (0 army, 1 fleet) times 16 plus its
+    ///   coast (0 none, 1 north, 2 south, 3 east, 4 west);
+    /// - the number of owned supply centers, and each as its province's
+    ///   index and its owner's;
+    /// - the number of dislodged units, and each as three bytes like a
+    ///   unit, then the number of places it may retreat to and each place
+    ///   as a province index and a coast;
+    /// - the powers in civil disorder, as a byte with bit `i` set for the
+    ///   power with index `i`.
+    ///
+    /// Counts are a byte each, and everything is listed in province order,
+    /// so equal states have equal encodings. The standard start takes 122
+    /// bytes.
+    ///
+    /// # Panics
+    ///
+    /// If the map has more than 256 provinces.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        assert!(
+            self.map.provinces().count() <= 256,
+            "the binary encoding is for maps of up to 256 provinces"
+        );
+        let mut bytes = b"DIP".to_vec();
+        bytes.push(Self::BYTES_VERSION);
+        bytes.extend_from_slice(&self.phase.year.to_le_bytes());
+        bytes.push(self.phase.season as u8);
+        bytes.push(self.phase.kind as u8);
+        let write_unit = |bytes: &mut Vec<u8>, province: &Province, unit: &Unit| {
+            bytes.push(province.id() as u8);
+            bytes.push(unit.power as u8);
+            bytes.push((type_bits(unit) as u8) << 4 | coast_bits(unit.coast) as u8);
+        };
+
+        let mut units: Vec<_> = self.units().collect();
+        units.sort_by_key(|(province, _)| province.id());
+        bytes.push(units.len() as u8);
+        for (province, unit) in units {
+            write_unit(&mut bytes, province, unit);
+        }
+        let mut owners: Vec<_> = self.owners.iter().collect();
+        owners.sort_by_key(|(province, _)| province.id());
+        bytes.push(owners.len() as u8);
+        for (province, &power) in owners {
+            bytes.extend_from_slice(&[province.id() as u8, power as u8]);
+        }
+        let mut dislodged: Vec<_> = self.dislodged().collect();
+        dislodged.sort_by_key(|(province, _)| province.id());
+        bytes.push(dislodged.len() as u8);
+        for (province, unit) in dislodged {
+            write_unit(&mut bytes, province, unit);
+            let places = self.retreats(province);
+            bytes.push(places.len() as u8);
+            for &(place, coast) in places {
+                bytes.extend_from_slice(&[place.id() as u8, coast_bits(coast) as u8]);
+            }
+        }
+        let disorder = self
+            .disorder
+            .iter()
+            .fold(0, |bits, &power| bits | 1 << power as u8);
+        bytes.push(disorder);
+        bytes
+    }
+
+    /// Reads a state written by [`to_bytes`](Self::to_bytes) for `map`.
+    pub fn from_bytes(map: &'m Map, bytes: &[u8]) -> Result<Self, StateError> {
+        let mut reader = ByteReader { bytes, at: 0 };
+        if reader.take(3)? != b"DIP" {
+            return Err(StateError::new("not a dipboy game state"));
+        }
+        let version = reader.byte()?;
+        if version != Self::BYTES_VERSION {
+            return Err(StateError::new(format!(
+                "unknown game state version {}",
+                version
+            )));
+        }
+        let mut state = GameState::new(map);
+        let year = reader.take(2)?;
+        let season = [Season::Spring, Season::Fall, Season::Winter];
+        let kind = [
+            PhaseKind::Movement,
+            PhaseKind::Retreats,
+            PhaseKind::Adjustments,
+        ];
+        state.phase = Phase::new(
+            reader.pick(&season, "season")?,
+            u16::from_le_bytes([year[0], year[1]]),
+            reader.pick(&kind, "phase kind")?,
+        );
+        for _ in 0..reader.byte()? {
+            let (province, unit) = reader.unit(map)?;
+            if state.place_unit(province, unit).is_some() {
+                return Err(StateError::new(format!(
+                    "two units in {}",
+                    province.abbreviation()
+                )));
+            }
+        }
+        for _ in 0..reader.byte()? {
+            let province = reader.province(map)?;
+            if !province.is_supply_center() {
+                return Err(StateError::new(format!(
+                    "{} is not a supply center",
+                    province.abbreviation()
+                )));
+            }
+            let power = reader.pick(&Power::ALL, "power")?;
+            state.set_owner(province, power);
+        }
+        for _ in 0..reader.byte()? {
+            let (province, unit) = reader.unit(map)?;
+            let places = (0..reader.byte()?)
+                .map(|_| {
+                    let place = reader.province(map)?;
+                    let coast = reader.coast(place)?;
+                    Ok((place, coast))
+                })
+                .collect::<Result<_, StateError>>()?;
+            state.set_dislodged(province, unit, places);
+        }
+        let disorder = reader.byte()?;
+        for (i, &power) in Power::ALL.iter().enumerate() {
+            state.set_civil_disorder(power, disorder & 1 << i != 0);
+        }
+        if reader.at != bytes.len() {
+            return Err(StateError::new("trailing bytes after the game state"));
+        }
+        Ok(state)
+    }
+}
+
+/// Reads the parts of a state's [binary encoding](GameState::to_bytes).
+struct ByteReader<'b> {
+    bytes: &'b [u8],
+    at: usize,
+}
+
+impl<'b> ByteReader<'b> {
+    fn take(&mut self, n: usize) -> Result<&'b [u8], StateError> {
+        let taken = self
+            .bytes
+            .get(self.at..self.at + n)
+            .ok_or_else(|| StateError::new("the game state is cut short"))?;
+        self.at += n;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, StateError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// One of `items`, by the index in the next byte.
+    fn pick<T: Copy>(&mut self, items: &[T], what: &str) -> Result<T, StateError> {
+        let index = self.byte()?;
+        items
+            .get(usize::from(index))
+            .copied()
+            .ok_or_else(|| StateError::new(format!("unknown {} {}", what, index)))
+    }
+
+    fn province<'m>(&mut self, map: &'m Map) -> Result<&'m Province, StateError> {
+        let id = usize::from(self.byte()?);
+        if id >= map.provinces().count() {
+            return Err(StateError::new(format!("unknown province {}", id)));
+        }
+        Ok(map.province(id))
+    }
+
+    /// A coast of `province`, from the low four bits of the next byte.
+    fn coast(&mut self, province: &Province) -> Result<Option<Coast>, StateError> {
+        let coasts = [Coast::North, Coast::South, Coast::East, Coast::West];
+        match self.byte()? & 0xf {
+            0 => Ok(None),
+            n => coasts
+                .get(usize::from(n) - 1)
+                .copied()
+                .filter(|coast| province.coasts().contains(coast))
+                .map(Some)
+                .ok_or_else(|| {
+                    StateError::new(format!("{} has no coast {}", province.abbreviation(), n))
+                }),
+        }
+    }
+
+    fn unit<'m>(&mut self, map: &'m Map) -> Result<(&'m Province, Unit), StateError> {
+        let province = self.province(map)?;
+        let power = self.pick(&Power::ALL, "power")?;
+        let (unit_type, kind) = match self.bytes.get(self.at).map(|byte| byte >> 4) {
+            Some(0) => (UnitType::Army, None),
+            Some(1) => (UnitType::Fleet, None),
+            Some(n) => {
+                let kind = map
+                    .unit_kinds()
+                    .nth(usize::from(n) - 2)
+                    .ok_or_else(|| StateError::new("unknown unit type"))?;
+                (kind.moves_as(), Some(kind))
+            }
+            None => return Err(StateError::new("unknown unit type")),
+        };
+        let coast = self.coast(province)?;
+        let unit = match (kind, unit_type, coast) {
+            (_, UnitType::Army, Some(_)) => return Err(no_unit(province)),
+            (_, UnitType::Fleet, None) if province.is_bicoastal() => return Err(no_unit(province)),
+            (Some(kind), _, coast) => Unit::of_kind(power, kind, coast),
+            (None, UnitType::Army, None) => Unit::army(power),
+            (None, UnitType::Fleet, None) => Unit::fleet(power),
+            (None, UnitType::Fleet, Some(coast)) => Unit::fleet_on(power, coast),
+        };
+        Ok((province, unit))
+    }
+}
+
+fn no_unit(province: &Province) -> StateError {
+    StateError::new(format!("no such unit in {}", province.abbreviation()))
+}
+
+fn find_province<'m>(map: &'m Map, text: &str) -> Result<&'m Province, StateError> {
+    map.find_by_abbreviation(text)
+        .or_else(|| map.find_by_name(text))
+        .ok_or_else(|| StateError::new(format!("unknown province {:?}", text)))
+}
+
+/// Reads a unit written like "A Par" or "F StP/sc".
+pub(crate) fn parse_unit<'m>(
+    map: &'m Map,
+    power: Power,
+    text: &str,
+) -> Result<(&'m Province, Unit), StateError> {
+    let bad = || {
+        StateError::new(format!(
+            "expected a unit like \"A Par\" or \"F StP/sc\", not {:?}",
+            text
+        ))
+    };
+    let (unit_type, location) = text.trim().split_once(' ').ok_or_else(bad)?;
+    let (province, coast) = split_coast(map, location).ok_or_else(bad)??;
+    if let Some(kind) = map.unit_kind(unit_type) {
+        let fits = match (kind.moves_as(), coast) {
+            (UnitType::Army, coast) => coast.is_none(),
+            (UnitType::Fleet, None) => !province.is_bicoastal(),
+            (UnitType::Fleet, Some(coast)) => province.coasts().contains(&coast),
+        };
+        return match fits {
+            true => Ok((province, Unit::of_kind(power, kind, coast))),
+            false => Err(bad()),
+        };
+    }
+    let unit = match (unit_type, coast) {
+        ("A", None) => Unit::army(power),
+        ("F", None) if !province.is_bicoastal() => Unit::fleet(power),
+        ("F", Some(coast)) if province.coasts().contains(&coast) => Unit::fleet_on(power, coast),
+        _ => return Err(bad()),
+    };
+    Ok((province, unit))
+}
+
+/// Reads a province and an optional coast written like "Par" or "StP/sc",
+/// or returns `None` if the coast is not one.
+fn split_coast<'m>(
+    map: &'m Map,
+    text: &str,
+) -> Option<Result<(&'m Province, Option<Coast>), StateError>> {
+    let (province, coast) = match text.split_once('/') {
+        Some((province, coast)) => (province, Some(coast.parse::<Coast>().ok()?)),
+        None => (text, None),
+    };
+    Some(find_province(map, province.trim()).map(|province| (province, coast)))
+}
+
+/// Reads a place a unit may retreat to, written like "Pic" or "Spa/nc".
+fn parse_place<'m>(map: &'m Map, text: &str) -> Result<Place<'m>, StateError> {
+    let bad = || StateError::new(format!("expected a place like \"Spa/nc\", not {:?}", text));
+    let (province, coast) = split_coast(map, text).ok_or_else(bad)??;
+    match coast {
+        Some(coast) if !province.coasts().contains(&coast) => Err(bad()),
+        _ => Ok((province, coast)),
+    }
+}
+
+/// Writes a unit like "A Par" or "F StP/sc", or with the abbreviation of
+/// its kind on `map`, like "G Flo".
+pub(crate) fn unit_text(map: &Map, province: &Province, unit: &Unit) -> String {
+    let place = place_text(province, unit.coast);
+    match unit.kind(map) {
+        Some(kind) => format!("{} {}", kind.abbreviation(), place),
+        None => format!("{} {}", unit.unit_type, place),
+    }
+}
+
+/// Writes a place like "Par" or "StP/sc".
+fn place_text(province: &Province, coast: Option<Coast>) -> String {
+    match coast {
+        Some(coast) => format!("{}/{}", province.abbreviation(), coast),
+        None => province.abbreviation().to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_start() {
+        let state = GameState::standard();
+        assert_eq!(state.units().count(), 22);
+        assert_eq!(state.owners.len(), 22);
+        assert_eq!(state.occupied().len(), 22);
+        assert_eq!(
+            format!("{:?}", state.centers(Power::France)),
+            "{Bre, Mar, Par}"
+        );
+        let stp = state.map().find_by_abbreviation("StP").unwrap();
+        assert_eq!(
+            state.unit_at(stp),
+            Some(&Unit::fleet_on(Power::Russia, Coast::South))
+        );
+    }
+
+    #[test]
+    fn scenarios() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let state = GameState::from_scenario(
+            map,
+            r#"{
+                "year": 1905,
+                "units": {"England": ["F Lon", "F Nth"], "Russia": ["F StP/nc"]},
+                "centers": "home"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(state.phase(), Phase::movement(Season::Spring, 1905));
+        assert_eq!(state.units().count(), 3);
+        assert_eq!(
+            state.unit_at(province("StP")),
+            Some(&Unit::fleet_on(Power::Russia, Coast::North))
+        );
+        assert_eq!(state.center_count(Power::Russia), 4);
+        assert_eq!(state.owner(province("Bel")), None);
+
+        let scenario = |text| GameState::from_scenario(map, text);
+        assert!(scenario(r#"{"year": 1901.5}"#).is_err());
+        assert!(scenario(r#"{"year": 1901, "phase": "Fall 1901 Movement"}"#).is_err());
+        assert!(scenario(r#"{"units": {"Russia": ["F StP"]}}"#).is_err());
+        assert_eq!(
+            scenario(r#"{"phase": "Fall 1910 Movement", "centers": {"Italy": ["Tun"]}}"#)
+                .unwrap()
+                .owner(province("Tun")),
+            Some(Power::Italy)
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let state = GameState::standard();
+        let json = state.to_json();
+        assert_eq!(GameState::from_json(state.map(), &json), Ok(state));
+        assert!(
+            GameState::from_json(Map::standard(), r#"{"units": {"Russia": ["F StP"]}}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn apply_and_undo_in_place() {
+        use crate::adjudicator::adjudicate;
+        use crate::parser::parse_order;
+
+        let map = Map::standard();
+        let start = GameState::standard();
+        let mut state = start.clone();
+        assert!(Arc::ptr_eq(&state.units, &start.units));
+
+        let orders = HashMap::from([(
+            Power::France,
+            vec![parse_order(map, "A Par - Bur").unwrap()],
+        )]);
+        let undo = state.apply_in_place(&adjudicate(orders, &state));
+        assert_eq!(state.phase(), Phase::movement(Season::Fall, 1901));
+        assert!(!Arc::ptr_eq(&state.units, &start.units));
+        assert!(Arc::ptr_eq(&state.owners, &start.owners));
+
+        let fall = state.clone();
+        let again = state.apply_in_place(&adjudicate(HashMap::new(), &state));
+        state.undo(again);
+        assert_eq!(state, fall);
+        assert_eq!(state.zobrist(), fall.zobrist());
+        state.undo(undo);
+        assert_eq!(state, start);
+        assert!(Arc::ptr_eq(&state.units, &start.units));
+    }
+
+    #[test]
+    fn zobrist_hash() {
+        use crate::adjudicator::adjudicate;
+        use crate::parser::parse_order;
+
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let order = |text| parse_order(map, text).unwrap();
+        let start = GameState::standard();
+        let rebuilt = |state: &GameState<'static>| {
+            GameState::from_json(map, &state.to_json())
+                .unwrap()
+                .zobrist()
+        };
+        assert_eq!(start.zobrist(), rebuilt(&start));
+
+        // The hash is updated as units move and are dislodged, and comes
+        // back when the position does.
+        let mut state = start.clone();
+        state.place_unit(province("Bur"), Unit::army(Power::Germany));
+        state.place_unit(province("Pic"), Unit::army(Power::Germany));
+        let orders = HashMap::from([(
+            Power::Germany,
+            vec![order("A Pic S A Bur - Par"), order("A Bur - Par")],
+        )]);
+        let retreats = state.apply(&adjudicate(orders, &state));
+        assert!(retreats.dislodged().count() > 0);
+        assert_eq!(retreats.zobrist(), rebuilt(&retreats));
+        assert_ne!(retreats.zobrist(), state.zobrist());
+        let mut back = state.clone();
+        back.set_owner(province("Par"), Power::Germany);
+        back.remove_unit(province("Pic"));
+        assert_ne!(back.zobrist(), state.zobrist());
+        back.set_owner(province("Par"), Power::France);
+        back.place_unit(province("Pic"), Unit::army(Power::Germany));
+        assert_eq!(back.zobrist(), state.zobrist());
+        back.set_phase(Phase::movement(Season::Fall, 1901));
+        assert_ne!(back.zobrist(), state.zobrist());
+
+        let seen: HashSet<_> = vec![start.clone(), state, start].into_iter().collect();
+        assert_eq!(seen.len(), 2);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let state = GameState::standard();
+        let bytes = state.to_bytes();
+        assert_eq!(bytes.len(), 122);
+        assert_eq!(GameState::from_bytes(map, &bytes), Ok(state));
+
+        let mut state = GameState::new(map);
+        state.set_phase(Phase::new(Season::Fall, 1905, PhaseKind::Retreats));
+        state.place_unit(province("Spa"), Unit::fleet_on(Power::France, Coast::North));
+        state.set_owner(province("Spa"), Power::Italy);
+        state.set_dislodged(
+            province("Mao"),
+            Unit::fleet(Power::England),
+            vec![
+                (province("Spa"), Some(Coast::South)),
+                (province("Naf"), None),
+            ],
+        );
+        state.set_civil_disorder(Power::Turkey, true);
+        let bytes = state.to_bytes();
+        assert_eq!(GameState::from_bytes(map, &bytes), Ok(state.clone()));
+        assert_eq!(state.to_bytes(), bytes);
+
+        assert!(GameState::from_bytes(map, &bytes[..bytes.len() - 1]).is_err());
+        assert!(GameState::from_bytes(map, b"DIP\x02").is_err());
+        let mut fleet_in_spain = bytes.clone();
+        fleet_in_spain[11] = 0x10;
+        assert!(GameState::from_bytes(map, &fleet_in_spain).is_err());
+    }
+
+    #[test]
+    fn legal_orders() {
+        use crate::adjudicator::{adjudicate, OrderStatus};
+        use crate::parser::parse_order;
+
+        let map = Map::standard();
+        let state = GameState::standard();
+        for (province, unit) in state.units() {
+            let orders = state.legal_orders(province);
+            assert!(orders.len() > 1);
+            for order in orders {
+                let result = adjudicate(HashMap::from([(unit.power, vec![order])]), &state);
+                assert_eq!(result.get(province).unwrap().status(), OrderStatus::Ordered);
+            }
+        }
+
+        let mut state = GameState::new(map);
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        state.place_unit(province("Lon"), Unit::army(Power::England));
+        state.place_unit(province("Nth"), Unit::fleet(Power::England));
+        state.place_unit(province("Ska"), Unit::fleet(Power::Russia));
+        state.place_unit(province("Par"), Unit::army(Power::France));
+        let order = |text| parse_order(map, text).unwrap();
+        let army = state.legal_orders(province("Lon"));
+        assert!(army.contains(&order("A Lon - Swe via convoy")));
+        assert!(army.contains(&order("A Lon S F Nth - Yor")));
+        assert!(!army.contains(&order("A Lon - Bre via convoy")));
+        let fleet = state.legal_orders(province("Ska"));
+        assert!(fleet.contains(&order("F Ska C A Lon - Swe")));
+        assert!(fleet.contains(&order("F Ska C A Lon - Edi")));
+        assert!(!fleet.contains(&order("F Ska C A Par - Bre")));
+        assert_eq!(state.legal_orders(province("Par")).len(), 5);
+        assert!(state.legal_orders(province("Bur")).is_empty());
+    }
+
+    #[test]
+    fn unit_kinds() {
+        use crate::adjudicator::{adjudicate, IllegalOrder, OrderStatus};
+        use crate::parser::parse_order;
+
+        let map = Map::parse(
+            "[provinces]\n\
+             Flo | Florence | land | Italy |  |\n\
+             Rom | Rome     | land | Italy |  |\n\
+             Ven | Venice   | land |       |  |\n\
+             [adjacencies]\n\
+             army Flo: Rom Ven\n\
+             army Rom: Flo Ven\n\
+             army Ven: Flo Rom\n\
+             [units]\n\
+             G | Garrison | army | fixed\n",
+        )
+        .unwrap();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let garrison = map.unit_kind("g").unwrap();
+        assert_eq!(garrison.moves_as(), UnitType::Army);
+        assert!(!garrison.is_mobile());
+
+        let mut state = GameState::new(&map);
+        let unit = Unit::of_kind(Power::Italy, garrison, None);
+        state.place_unit(province("Flo"), unit);
+        state.place_unit(province("Rom"), Unit::army(Power::Italy));
+        assert_eq!(unit.kind(&map), Some(garrison));
+        assert_eq!(Unit::army(Power::Italy).kind(&map), None);
+        assert_eq!(
+            GameState::from_json(&map, &state.to_json()),
+            Ok(state.clone())
+        );
+        assert!(state.to_json().contains("\"G Flo\""));
+        assert_eq!(
+            GameState::from_bytes(&map, &state.to_bytes()),
+            Ok(state.clone())
+        );
+
+        let order = |text| parse_order(&map, text).unwrap();
+        assert!(!state
+            .legal_orders(province("Flo"))
+            .contains(&order("A Flo - Ven")));
+        assert!(state
+            .legal_orders(province("Flo"))
+            .contains(&order("A Flo S A Rom - Ven")));
+        let orders = HashMap::from([(
+            Power::Italy,
+            vec![order("A Flo - Ven"), order("A Rom - Ven")],
+        )]);
+        let result = adjudicate(orders, &state);
+        assert_eq!(
+            result.get(province("Flo")).unwrap().status(),
+            OrderStatus::Illegal(IllegalOrder::Immobile)
+        );
+        assert!(result.get(province("Rom")).unwrap().succeeded());
+
+        let error = Map::parse("[units]\nA | Artillery | army |\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: duplicate unit kind \"A\"");
+    }
+
+    #[test]
+    fn civil_disorder() {
+        use crate::adjudicator::{adjudicate, adjudicate_with, OrderStatus, Rules};
+        use crate::parser::parse_order;
+
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut state = GameState::new(map);
+        state.set_phase(Phase::movement(Season::Fall, 1901));
+        state.place_unit(province("Mos"), Unit::army(Power::Russia));
+        state.place_unit(province("Apu"), Unit::army(Power::Russia));
+        state.place_unit(province("Adr"), Unit::fleet(Power::Russia));
+        state.set_owner(province("Mos"), Power::Russia);
+        state.set_owner(province("Sev"), Power::Russia);
+        state.set_civil_disorder(Power::Russia, true);
+
+        // The fleet in the Adriatic is five fleet moves from Sevastopol,
+        // but four moves from Warsaw by land; the army in Apulia is five
+        // moves from home either way.
+        let by = |removals| Rules {
+            removals,
+            ..Rules::default()
+        };
+        assert_eq!(
+            state.civil_disorder_removals(Power::Russia, RemovalRules::Rules2000),
+            [province("Adr")]
+        );
+        assert_eq!(
+            state.civil_disorder_removals(Power::Russia, RemovalRules::Rules1982),
+            [province("Apu")]
+        );
+
+        state.place_unit(province("Tri"), Unit::fleet(Power::Austria));
+        state.place_unit(province("Alb"), Unit::fleet(Power::Austria));
+        let order = |text| parse_order(map, text).unwrap();
+        let orders = HashMap::from([
+            (Power::Russia, vec![order("A Apu - Nap")]),
+            (
+                Power::Austria,
+                vec![order("F Tri - Adr"), order("F Alb S F Tri - Adr")],
+            ),
+        ]);
+        let result = adjudicate(orders.clone(), &state);
+        assert_eq!(
+            result.get(province("Apu")).unwrap().status(),
+            OrderStatus::Unordered
+        );
+        assert!(result.get(province("Adr")).unwrap().is_dislodged());
+
+        // The dislodged fleet is disbanded without a retreat phase, and
+        // Russia, with one center and two armies, loses the one in Apulia
+        // in the adjustment phase.
+        state.set_owner(province("Sev"), Power::Turkey);
+        for rules in [by(RemovalRules::Rules2000), by(RemovalRules::Rules1982)] {
+            let next = state.apply(&adjudicate_with(orders.clone(), &state, &rules));
+            assert_eq!(next.dislodged().count(), 0);
+            assert_eq!(
+                next.phase(),
+                Phase::new(Season::Winter, 1901, PhaseKind::Adjustments)
+            );
+            assert_eq!(next.adjustment(Power::Russia), -1);
+            let next = next.apply(&adjudicate_with(HashMap::new(), &next, &rules));
+            let russian: Vec<_> = next
+                .units()
+                .filter(|(_, unit)| unit.power == Power::Russia)
+                .map(|(province, _)| province)
+                .collect();
+            assert_eq!(russian, [province("Mos")]);
+            assert_eq!(next.phase(), Phase::movement(Season::Spring, 1902));
+            assert!(next.in_civil_disorder(Power::Russia));
+            assert_eq!(GameState::from_json(map, &next.to_json()), Ok(next));
+        }
+    }
+
+    #[test]
+    fn random_removal_ties() {
+        use crate::adjudicator::{
+            adjudicate_adjustments, adjudicate_adjustments_with, RemovalTies, Rules, SeededRng,
+        };
+
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut state = GameState::new(map);
+        state.set_phase(Phase::new(Season::Winter, 1901, PhaseKind::Adjustments));
+        // The armies in Berlin and Vienna are both two moves from Warsaw.
+        for abbreviation in ["Mos", "Ber", "Vie"] {
+            state.place_unit(province(abbreviation), Unit::army(Power::Russia));
+        }
+        state.set_owner(province("Mos"), Power::Russia);
+        state.set_owner(province("Sev"), Power::Russia);
+        state.set_civil_disorder(Power::Russia, true);
+
+        let removed = |seed| {
+            let mut rng = SeededRng::new(seed);
+            state.civil_disorder_removals_with(Power::Russia, RemovalRules::Rules2000, &mut rng)
+        };
+        assert_eq!(
+            state.civil_disorder_removals(Power::Russia, RemovalRules::Rules2000),
+            [province("Ber")]
+        );
+        assert_eq!(removed(7), removed(7));
+        let mut chosen: Vec<_> = (0..16).map(|seed| removed(seed)[0]).collect();
+        chosen.sort_by_key(|province| province.name());
+        chosen.dedup();
+        assert_eq!(chosen, [province("Ber"), province("Vie")]);
+
+        let rules = Rules {
+            removal_ties: RemovalTies::Random,
+            ..Rules::default()
+        };
+        let mut rng = SeededRng::new(7);
+        let result = adjudicate_adjustments_with(HashMap::new(), &state, &rules, &mut rng);
+        assert_eq!(result.removals()[0].0, removed(7)[0]);
+        assert_eq!(
+            adjudicate_adjustments(HashMap::new(), &state, &rules),
+            adjudicate_adjustments(HashMap::new(), &state, &rules)
+        );
+    }
+
+    #[test]
+    fn retreat_and_adjustment_phases() {
+        use crate::adjudicator::{adjudicate, Adjustment, AdjustmentResult};
+        use crate::parser::parse_order;
+
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let order = |text| parse_order(map, text).unwrap();
+        let mut state = GameState::new(map);
+        state.set_phase(Phase::movement(Season::Fall, 1901));
+        state.place_unit(province("Bur"), Unit::army(Power::Germany));
+        state.place_unit(province("Ruh"), Unit::army(Power::Germany));
+        state.place_unit(province("Bel"), Unit::army(Power::France));
+        state.set_owner(province("Bel"), Power::France);
+        state.set_owner(province("Mun"), Power::Germany);
+        state.set_owner(province("Par"), Power::France);
+
+        let orders = HashMap::from([(
+            Power::Germany,
+            vec![order("A Bur S A Ruh - Bel"), order("A Ruh - Bel")],
+        )]);
+        let retreats = state.apply(&adjudicate(orders, &state));
+        assert_eq!(
+            retreats.phase(),
+            Phase::new(Season::Fall, 1901, PhaseKind::Retreats)
+        );
+        assert_eq!(
+            retreats.dislodged_unit(province("Bel")),
+            Some(&Unit::army(Power::France))
+        );
+        assert!(retreats
+            .retreats(province("Bel"))
+            .contains(&(province("Pic"), None)));
+        assert!(!retreats
+            .retreats(province("Bel"))
+            .contains(&(province("Ruh"), None)));
+        let json = retreats.to_json();
+        assert_eq!(GameState::from_json(map, &json), Ok(retreats.clone()));
+
+        // Germany takes Belgium once the French army has retreated, and
+        // with no adjustments to make the game goes on to the Spring.
+        let orders = HashMap::from([(Power::France, vec![order("A Bel - Pic")])]);
+        let spring = retreats.apply(&adjudicate(orders, &retreats));
+        assert_eq!(
+            spring.unit_at(province("Pic")),
+            Some(&Unit::army(Power::France))
+        );
+        assert_eq!(spring.owner(province("Bel")), Some(Power::Germany));
+        assert_eq!(spring.phase(), Phase::movement(Season::Spring, 1902));
+
+        // Without a retreat, the army is disbanded, and France may build.
+        let winter = retreats.apply(&adjudicate(HashMap::new(), &retreats));
+        assert_eq!(
+            winter.phase(),
+            Phase::new(Season::Winter, 1901, PhaseKind::Adjustments)
+        );
+        assert_eq!(winter.units().count(), 2);
+        assert_eq!(winter.adjustment(Power::France), 1);
+        let builds = HashMap::from([(
+            Power::France,
+            vec![Adjustment::Build {
+                unit: UnitType::Army,
+                province: province("Par"),
+                coast: None,
+            }],
+        )]);
+        let result =
+            crate::adjudicator::adjudicate_adjustments(builds, &winter, &Default::default());
+        let spring = winter.apply_adjustments(&result);
+        assert_eq!(
+            spring.unit_at(province("Par")),
+            Some(&Unit::army(Power::France))
+        );
+        assert_eq!(spring.phase(), Phase::movement(Season::Spring, 1902));
+        assert_eq!(
+            winter.apply_adjustments(&AdjustmentResult::default()),
+            winter.apply(&adjudicate(HashMap::new(), &winter))
+        );
+    }
+}
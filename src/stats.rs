@@ -0,0 +1,244 @@
+//! Statistics over a community's games: how often each power wins or
+//! draws, how the supply centers were held over a game, and ratings for
+//! the players across a series of games.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::history::History;
+use crate::phase::Phase;
+use crate::power::Power;
+use crate::vote::Outcome;
+
+/// How one power has fared over a number of finished games.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PowerRecord {
+    pub games: usize,
+    pub solos: usize,
+    /// Games the power shared a draw in.
+    pub draws: usize,
+    /// Games the power ended with no supply centers.
+    pub eliminations: usize,
+}
+
+impl PowerRecord {
+    /// The fraction of the games the power won alone.
+    pub fn solo_rate(&self) -> f64 {
+        self.rate(self.solos)
+    }
+
+    /// The fraction of the games the power shared a draw in.
+    pub fn draw_rate(&self) -> f64 {
+        self.rate(self.draws)
+    }
+
+    fn rate(&self, count: usize) -> f64 {
+        match self.games {
+            0 => 0.0,
+            games => count as f64 / games as f64,
+        }
+    }
+}
+
+/// Each power's record over the finished games in `games`, in
+/// [`Power::ALL`] order. Games still being played are left out.
+pub fn power_records(games: &[History]) -> BTreeMap<Power, PowerRecord> {
+    let mut records: BTreeMap<Power, PowerRecord> = Power::ALL
+        .iter()
+        .map(|&power| (power, PowerRecord::default()))
+        .collect();
+    for history in games {
+        let outcome = match history.outcome() {
+            Some(outcome) => outcome,
+            None => continue,
+        };
+        let end = history.current();
+        for (&power, record) in &mut records {
+            record.games += 1;
+            match outcome {
+                Outcome::Solo(winner) if *winner == power => record.solos += 1,
+                Outcome::Draw(powers) if powers.contains(&power) => record.draws += 1,
+                _ => {}
+            }
+            if end.center_count(power) == 0 {
+                record.eliminations += 1;
+            }
+        }
+    }
+    records
+}
+
+/// The number of supply centers each power held in each position of the
+/// game, from the start, with the phase the position is in. The counts
+/// are in [`Power::ALL`] order.
+pub fn center_counts(history: &History) -> Vec<(Phase, [usize; 7])> {
+    (0..=history.turns().len())
+        .filter_map(|n| history.position(n))
+        .map(|position| {
+            let mut counts = [0; 7];
+            for (count, &power) in counts.iter_mut().zip(&Power::ALL) {
+                *count = position.center_count(power);
+            }
+            (position.phase(), counts)
+        })
+        .collect()
+}
+
+/// Elo ratings for players across a series of games.
+///
+/// Each finished game is scored as a set of head-to-head results between
+/// its players: a solo beats everyone, the powers in a draw beat those
+/// left out of it and tie with each other, and the rest tie among
+/// themselves. Each result moves the two players' ratings as a game of
+/// chess would, scaled down by the number of opponents so a game counts
+/// for as much as one chess game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ratings {
+    k: f64,
+    ratings: BTreeMap<String, f64>,
+}
+
+impl Default for Ratings {
+    fn default() -> Self {
+        Ratings::new(32.0)
+    }
+}
+
+impl Ratings {
+    /// The rating of a player yet to finish a game.
+    pub const INITIAL: f64 = 1500.0;
+
+    /// Ratings where the most a game can move a rating is `k`.
+    pub fn new(k: f64) -> Self {
+        Ratings {
+            k,
+            ratings: BTreeMap::new(),
+        }
+    }
+
+    /// `player`'s rating.
+    pub fn rating(&self, player: &str) -> f64 {
+        self.ratings.get(player).copied().unwrap_or(Self::INITIAL)
+    }
+
+    /// Every rated player's rating, by name.
+    pub fn ratings(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.ratings
+            .iter()
+            .map(|(player, &rating)| (player.as_str(), rating))
+    }
+
+    /// Updates the ratings of the players who played `history`, each
+    /// named by the power they played, if the game is over. Returns
+    /// whether it was.
+    pub fn record(&mut self, history: &History, players: &HashMap<Power, String>) -> bool {
+        match history.outcome() {
+            Some(outcome) => {
+                self.record_outcome(outcome, players);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Updates the ratings of the players of a game that ended in
+    /// `outcome`, each named by the power they played.
+    pub fn record_outcome(&mut self, outcome: &Outcome, players: &HashMap<Power, String>) {
+        let score = |power: Power| match outcome {
+            Outcome::Solo(winner) => u8::from(*winner == power),
+            Outcome::Draw(powers) => u8::from(powers.contains(&power)),
+        };
+        let mut players: Vec<_> = players.iter().collect();
+        players.sort_by_key(|&(&power, _)| power);
+        if players.len() < 2 {
+            return;
+        }
+        let k = self.k / (players.len() - 1) as f64;
+        let changes: Vec<f64> = players
+            .iter()
+            .map(|&(&power, player)| {
+                let rating = self.rating(player);
+                players
+                    .iter()
+                    .filter(|&&(&other, _)| other != power)
+                    .map(|&(&other, opponent)| {
+                        let expected =
+                            1.0 / (1.0 + 10f64.powf((self.rating(opponent) - rating) / 400.0));
+                        let actual = match score(power).cmp(&score(other)) {
+                            std::cmp::Ordering::Greater => 1.0,
+                            std::cmp::Ordering::Equal => 0.5,
+                            std::cmp::Ordering::Less => 0.0,
+                        };
+                        k * (actual - expected)
+                    })
+                    .sum()
+            })
+            .collect();
+        for ((_, player), change) in players.into_iter().zip(changes) {
+            let rating = self.rating(player) + change;
+            self.ratings.insert(player.clone(), rating);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phase::Season;
+    use crate::state::GameState;
+    use crate::vote::{Ballot, Proposal};
+
+    /// A game that ends in its first phase with `proposal`.
+    fn game(proposal: Proposal) -> History<'static> {
+        let mut history = History::new(GameState::standard());
+        let mut ballot = Ballot::new(proposal);
+        for power in Power::ALL {
+            ballot.vote(power, true);
+        }
+        history
+            .play_with_ballots(HashMap::new(), &[ballot])
+            .unwrap();
+        history
+    }
+
+    #[test]
+    fn records_and_center_counts() {
+        let solo = game(Proposal::Concede(Power::Turkey));
+        let draw = game(Proposal::Draw(vec![Power::Turkey, Power::England]));
+        let unfinished = History::new(GameState::standard());
+        let records = power_records(&[solo.clone(), draw, unfinished]);
+        let turkey = records[&Power::Turkey];
+        assert_eq!(turkey.games, 2);
+        assert_eq!(turkey.solo_rate(), 0.5);
+        assert_eq!(turkey.draw_rate(), 0.5);
+        assert_eq!(records[&Power::England].draws, 1);
+        assert_eq!(records[&Power::France].solo_rate(), 0.0);
+
+        let counts = center_counts(&solo);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].0, Phase::FIRST);
+        assert_eq!(counts[0].1, [3, 3, 3, 3, 3, 4, 3]);
+        assert_eq!(counts[1].0, Phase::movement(Season::Fall, 1901));
+    }
+
+    #[test]
+    fn ratings() {
+        let players: HashMap<Power, String> = Power::ALL
+            .iter()
+            .map(|&power| (power, power.name().to_lowercase()))
+            .collect();
+        let mut ratings = Ratings::default();
+        assert!(!ratings.record(&History::new(GameState::standard()), &players));
+        assert!(ratings.record(&game(Proposal::Concede(Power::Turkey)), &players));
+        assert!(ratings.rating("turkey") > Ratings::INITIAL);
+        assert!(ratings.rating("france") < Ratings::INITIAL);
+        assert_eq!(ratings.rating("france"), ratings.rating("russia"));
+        let total: f64 = ratings.ratings().map(|(_, rating)| rating).sum();
+        assert!((total - 7.0 * Ratings::INITIAL).abs() < 1e-9);
+
+        let before = ratings.rating("turkey");
+        ratings.record_outcome(&Outcome::Draw(vec![Power::France]), &players);
+        assert!(ratings.rating("france") > ratings.rating("russia"));
+        assert!(ratings.rating("turkey") < before);
+        assert_eq!(ratings.rating("nobody"), Ratings::INITIAL);
+    }
+}
@@ -0,0 +1,897 @@
+//! The orders players submit for a phase, which they may change as often
+//! as they like before the deadline.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::json::Value;
+use crate::map::{Map, Province};
+use crate::order::Order;
+use crate::parser::parse_order;
+use crate::phase::PhaseKind;
+use crate::power::Power;
+use crate::schedule::Deadline;
+use crate::state::{GameState, StateError};
+
+/// Each power's orders for a phase, one per unit.
+///
+/// An order for a unit that already has one replaces it, so a player can
+/// resubmit some or all of their orders at any time, or until the
+/// [deadline](Self::set_deadline) if the set has one.
+#[derive(Clone, Debug)]
+pub struct OrderSet<'m> {
+    map: &'m Map,
+    orders: BTreeMap<Power, Vec<Order<'m>>>,
+    /// Every order submitted or withdrawn in an [`Envelope`], in the
+    /// order they came.
+    trail: Vec<(Power, Envelope<Action<'m>>)>,
+    deadline: Option<Deadline>,
+    /// The orders and withdrawals refused for coming after the deadline.
+    late: Vec<(Power, Envelope<Action<'m>>)>,
+}
+
+impl<'m> OrderSet<'m> {
+    pub fn new(map: &'m Map) -> Self {
+        OrderSet {
+            map,
+            orders: BTreeMap::new(),
+            trail: Vec::new(),
+            deadline: None,
+            late: Vec::new(),
+        }
+    }
+
+    pub fn map(&self) -> &'m Map {
+        self.map
+    }
+
+    /// Closes the set to [`submit_on_time`](Self::submit_on_time) once
+    /// `deadline` [closes](Deadline::closes), grace period and all, or
+    /// opens it again with `None`.
+    pub fn set_deadline(&mut self, deadline: Option<Deadline>) {
+        self.deadline = deadline;
+    }
+
+    pub fn deadline(&self) -> Option<Deadline> {
+        self.deadline
+    }
+
+    /// Records the order in `envelope` for `power`, as
+    /// [`submit_enveloped`](Self::submit_enveloped) does, unless the
+    /// envelope's timestamp is after the deadline closed, when the order is
+    /// refused and kept among the [late](Self::late) ones instead.
+    pub fn submit_on_time(
+        &mut self,
+        power: Power,
+        envelope: Envelope<Order<'m>>,
+    ) -> Result<Option<Order<'m>>, TooLate> {
+        self.on_time(power, envelope.map(Action::Order))
+    }
+
+    /// Takes back `power`'s order for the unit in the province in
+    /// `envelope`, as [`withdraw_enveloped`](Self::withdraw_enveloped)
+    /// does, unless it is after the deadline closed, when the withdrawal
+    /// is refused and kept among the [late](Self::late) ones instead.
+    pub fn withdraw_on_time(
+        &mut self,
+        power: Power,
+        envelope: Envelope<&'m Province>,
+    ) -> Result<Option<Order<'m>>, TooLate> {
+        self.on_time(power, envelope.map(Action::Withdraw))
+    }
+
+    fn on_time(
+        &mut self,
+        power: Power,
+        envelope: Envelope<Action<'m>>,
+    ) -> Result<Option<Order<'m>>, TooLate> {
+        match self.deadline {
+            Some(deadline) if deadline.is_closed(envelope.timestamp()) => {
+                self.late.push((power, envelope));
+                Err(TooLate { deadline })
+            }
+            _ => Ok(self.record(power, envelope)),
+        }
+    }
+
+    /// The orders and withdrawals refused for coming too late, with the
+    /// power they were for, in the order they came.
+    pub fn late(&self) -> &[(Power, Envelope<Action<'m>>)] {
+        &self.late
+    }
+
+    /// Records `order` for `power`, returning the order it replaces for the
+    /// same unit, if any.
+    pub fn submit(&mut self, power: Power, order: Order<'m>) -> Option<Order<'m>> {
+        let orders = self.orders.entry(power).or_default();
+        match orders
+            .iter_mut()
+            .find(|old| old.province() == order.province())
+        {
+            Some(old) => Some(std::mem::replace(old, order)),
+            None => {
+                orders.push(order);
+                None
+            }
+        }
+    }
+
+    /// Records the order in `envelope` for `power`, as
+    /// [`submit`](Self::submit) does, and keeps the envelope in the
+    /// [audit trail](Self::trail), numbered as the unit's next
+    /// [revision](Envelope::revision).
+    ///
+    /// The deadline is not checked, so a GM can accept a late order this
+    /// way.
+    pub fn submit_enveloped(
+        &mut self,
+        power: Power,
+        envelope: Envelope<Order<'m>>,
+    ) -> Option<Order<'m>> {
+        self.record(power, envelope.map(Action::Order))
+    }
+
+    /// Takes back `power`'s order for the unit in the province in
+    /// `envelope`, as [`withdraw`](Self::withdraw) does, and keeps the
+    /// envelope in the [audit trail](Self::trail) as the unit's next
+    /// [revision](Envelope::revision).
+    ///
+    /// The deadline is not checked, so a GM can withdraw an order late
+    /// this way.
+    pub fn withdraw_enveloped(
+        &mut self,
+        power: Power,
+        envelope: Envelope<&'m Province>,
+    ) -> Option<Order<'m>> {
+        self.record(power, envelope.map(Action::Withdraw))
+    }
+
+    fn record(&mut self, power: Power, mut envelope: Envelope<Action<'m>>) -> Option<Order<'m>> {
+        let province = envelope.contents.province();
+        envelope.revision = self.revisions(power, province) + 1;
+        let replaced = match &envelope.contents {
+            Action::Order(order) => self.submit(power, order.clone()),
+            Action::Withdraw(province) => self.withdraw(power, province),
+        };
+        self.trail.push((power, envelope));
+        replaced
+    }
+
+    /// Every order submitted or withdrawn in an envelope, with the power
+    /// it was for, in the order they came, including orders since
+    /// replaced or withdrawn.
+    pub fn trail(&self) -> &[(Power, Envelope<Action<'m>>)] {
+        &self.trail
+    }
+
+    /// The envelope `power`'s order for the unit in `province` came in, if
+    /// the order standing is the last one submitted in an envelope.
+    pub fn envelope(&self, power: Power, province: &Province) -> Option<&Envelope<Action<'m>>> {
+        let order = self.get(power, province)?;
+        self.trail
+            .iter()
+            .rev()
+            .find(|(p, envelope)| *p == power && envelope.contents.province() == province)
+            .map(|(_, envelope)| envelope)
+            .filter(|envelope| envelope.contents == Action::Order(order.clone()))
+    }
+
+    /// How many orders `power` has submitted in envelopes for the unit in
+    /// `province`.
+    fn revisions(&self, power: Power, province: &Province) -> u32 {
+        self.trail
+            .iter()
+            .filter(|(p, envelope)| *p == power && envelope.contents.province() == province)
+            .count() as u32
+    }
+
+    /// Takes back `power`'s order for the unit in `province`, leaving no
+    /// record in the [audit trail](Self::trail).
+    pub fn withdraw(&mut self, power: Power, province: &Province) -> Option<Order<'m>> {
+        let orders = self.orders.get_mut(&power)?;
+        let i = orders
+            .iter()
+            .position(|order| order.province() == province)?;
+        Some(orders.remove(i))
+    }
+
+    /// `power`'s order for the unit in `province`.
+    pub fn get(&self, power: Power, province: &Province) -> Option<&Order<'m>> {
+        self.orders(power)
+            .iter()
+            .find(|order| order.province() == province)
+    }
+
+    /// `power`'s orders, in the order they were first given.
+    pub fn orders(&self, power: Power) -> &[Order<'m>] {
+        self.orders.get(&power).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every order, by power in [`Power::ALL`] order.
+    pub fn iter(&self) -> impl Iterator<Item = (Power, &Order<'m>)> {
+        self.orders
+            .iter()
+            .flat_map(|(&power, orders)| orders.iter().map(move |order| (power, order)))
+    }
+
+    /// The provinces of `power`'s units in `state` that have no order
+    /// yet, in map order: its units in a movement phase, or its dislodged
+    /// units in a retreat phase. Adjustment phases have no unit orders;
+    /// see [`Game::unassigned_adjustments`](crate::registry::Game::unassigned_adjustments)
+    /// for the builds and disbands still to make.
+    pub fn missing_orders(&self, state: &GameState<'m>, power: Power) -> Vec<&'m Province> {
+        let units: Vec<_> = match state.phase().kind {
+            PhaseKind::Movement => state.units().collect(),
+            PhaseKind::Retreats => state.dislodged().collect(),
+            PhaseKind::Adjustments => Vec::new(),
+        };
+        let mut missing: Vec<&'m Province> = units
+            .into_iter()
+            .filter(|(province, unit)| unit.power() == power && self.get(power, province).is_none())
+            .map(|(province, _)| province)
+            .collect();
+        missing.sort_by_key(|province| province.id());
+        missing
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The orders by power, as the adjudicator takes them.
+    pub fn to_orders(&self) -> HashMap<Power, Vec<Order<'m>>> {
+        self.orders
+            .iter()
+            .filter(|(_, orders)| !orders.is_empty())
+            .map(|(&power, orders)| (power, orders.clone()))
+            .collect()
+    }
+
+    /// How `newer` differs from these orders, unit by unit: by power in
+    /// [`Power::ALL`] order, then by province.
+    ///
+    /// Orders are compared in their [canonical](Order::canonicalize) forms,
+    /// so `A Par - Bur` and `Paris to Burgundy` are the same order.
+    pub fn diff(&self, newer: &OrderSet<'m>) -> Vec<OrderChange<'m>> {
+        let mut units: Vec<(Power, &'m Province)> = self
+            .iter()
+            .chain(newer.iter())
+            .map(|(power, order)| (power, order.province()))
+            .collect();
+        units.sort_by_key(|&(power, province)| (power, province.id()));
+        units.dedup();
+        let canonical = |order: &Order<'m>| order.canonicalize(self.map).into_order();
+        units
+            .into_iter()
+            .filter_map(|(power, province)| {
+                let old = self.get(power, province).map(canonical);
+                let new = newer.get(power, province).map(canonical);
+                match (old, new) {
+                    (None, Some(order)) => Some(OrderChange::Added { power, order }),
+                    (Some(order), None) => Some(OrderChange::Removed { power, order }),
+                    (Some(old), Some(new)) if old != new => {
+                        Some(OrderChange::Modified { power, old, new })
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'m> OrderSet<'m> {
+    /// The set as the members of a JSON object: `"orders"`, `"deadline"`,
+    /// `"trail"` and `"late"`, read back by [`from_value`](Self::from_value).
+    pub(crate) fn to_members(&self) -> Vec<(String, Value)> {
+        let orders = self
+            .orders
+            .iter()
+            .filter(|(_, orders)| !orders.is_empty())
+            .map(|(power, orders)| {
+                let orders = orders
+                    .iter()
+                    .map(|order| Value::String(order.to_string()))
+                    .collect();
+                (power.name().to_owned(), Value::Array(orders))
+            })
+            .collect();
+        let envelopes = |envelopes: &[(Power, Envelope<Action<'m>>)]| {
+            Value::Array(
+                envelopes
+                    .iter()
+                    .map(|(power, envelope)| envelope.to_value(*power))
+                    .collect(),
+            )
+        };
+        vec![
+            ("orders".to_owned(), Value::Object(orders)),
+            (
+                "deadline".to_owned(),
+                self.deadline.map_or(Value::Null, Deadline::to_value),
+            ),
+            ("trail".to_owned(), envelopes(&self.trail)),
+            ("late".to_owned(), envelopes(&self.late)),
+        ]
+    }
+
+    /// Reads a set from the members of a JSON object written by
+    /// [`to_members`](Self::to_members), any of which may be missing.
+    pub(crate) fn from_value(map: &'m Map, value: &Value) -> Result<Self, StateError> {
+        let mut set = OrderSet::new(map);
+        if let Some(orders) = value.get("orders") {
+            let bad = || StateError::new("\"orders\" must map powers to lists");
+            for (power, orders) in orders.as_object().ok_or_else(bad)? {
+                let power: Power = power
+                    .parse()
+                    .map_err(|e| StateError::new(format!("{}", e)))?;
+                for order in orders.as_array().ok_or_else(bad)? {
+                    let order = parse_order(map, order.as_str().ok_or_else(bad)?)
+                        .map_err(|e| StateError::new(e.to_string()))?;
+                    set.submit(power, order);
+                }
+            }
+        }
+        match value.get("deadline") {
+            None | Some(Value::Null) => {}
+            Some(deadline) => {
+                let deadline = Deadline::from_value(deadline).ok_or_else(|| {
+                    StateError::new("\"deadline\" needs a phase, and due and grace in seconds")
+                })?;
+                set.deadline = Some(deadline);
+            }
+        }
+        for (key, envelopes) in [("trail", &mut set.trail), ("late", &mut set.late)] {
+            let list = match value.get(key) {
+                None => continue,
+                Some(list) => list,
+            };
+            let list = list
+                .as_array()
+                .ok_or_else(|| StateError::new(format!("{:?} must list envelopes", key)))?;
+            for envelope in list {
+                envelopes.push(Envelope::from_value(map, envelope)?);
+            }
+        }
+        Ok(set)
+    }
+}
+
+/// An [`OrderSet`] that many threads can submit to at once, as the
+/// handlers for several players' messages do.
+///
+/// Each submission locks the set only while it records its order, and
+/// the last order to arrive for a unit is the one that stands. A snapshot
+/// or [`take`](Self::take) sees every submission made before it, and none
+/// of those made after.
+#[derive(Debug)]
+pub struct OrderBox<'m> {
+    orders: Mutex<OrderSet<'m>>,
+}
+
+impl<'m> OrderBox<'m> {
+    pub fn new(map: &'m Map) -> Self {
+        OrderBox {
+            orders: Mutex::new(OrderSet::new(map)),
+        }
+    }
+
+    /// Records `order` for `power`, returning the order it replaces for the
+    /// same unit, if any.
+    pub fn submit(&self, power: Power, order: Order<'m>) -> Option<Order<'m>> {
+        self.lock().submit(power, order)
+    }
+
+    /// Records the order in `envelope` for `power`, returning the order
+    /// it replaces. See [`OrderSet::submit_enveloped`].
+    pub fn submit_enveloped(
+        &self,
+        power: Power,
+        envelope: Envelope<Order<'m>>,
+    ) -> Option<Order<'m>> {
+        self.lock().submit_enveloped(power, envelope)
+    }
+
+    /// Records the order in `envelope` for `power` unless it is too late.
+    /// See [`OrderSet::submit_on_time`].
+    pub fn submit_on_time(
+        &self,
+        power: Power,
+        envelope: Envelope<Order<'m>>,
+    ) -> Result<Option<Order<'m>>, TooLate> {
+        self.lock().submit_on_time(power, envelope)
+    }
+
+    /// Sets or clears the deadline. See [`OrderSet::set_deadline`].
+    pub fn set_deadline(&self, deadline: Option<Deadline>) {
+        self.lock().set_deadline(deadline);
+    }
+
+    /// Takes back `power`'s order for the unit in `province`.
+    pub fn withdraw(&self, power: Power, province: &Province) -> Option<Order<'m>> {
+        self.lock().withdraw(power, province)
+    }
+
+    /// Takes back `power`'s order for the unit in the province in
+    /// `envelope`. See [`OrderSet::withdraw_enveloped`].
+    pub fn withdraw_enveloped(
+        &self,
+        power: Power,
+        envelope: Envelope<&'m Province>,
+    ) -> Option<Order<'m>> {
+        self.lock().withdraw_enveloped(power, envelope)
+    }
+
+    /// Takes back `power`'s order unless it is too late. See
+    /// [`OrderSet::withdraw_on_time`].
+    pub fn withdraw_on_time(
+        &self,
+        power: Power,
+        envelope: Envelope<&'m Province>,
+    ) -> Result<Option<Order<'m>>, TooLate> {
+        self.lock().withdraw_on_time(power, envelope)
+    }
+
+    /// The orders submitted so far.
+    pub fn snapshot(&self) -> OrderSet<'m> {
+        self.lock().clone()
+    }
+
+    /// The orders submitted so far, leaving the box empty for the next
+    /// phase.
+    pub fn take(&self) -> OrderSet<'m> {
+        let mut orders = self.lock();
+        let map = orders.map();
+        std::mem::replace(&mut *orders, OrderSet::new(map))
+    }
+
+    /// The orders, even if a thread panicked while holding the lock: an
+    /// order is recorded in one step, so the set is never left half
+    /// changed.
+    fn lock(&self) -> MutexGuard<'_, OrderSet<'m>> {
+        self.orders
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<'m> From<OrderSet<'m>> for OrderBox<'m> {
+    fn from(orders: OrderSet<'m>) -> Self {
+        OrderBox {
+            orders: Mutex::new(orders),
+        }
+    }
+}
+
+/// The error returned for an order submitted after its deadline closed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TooLate {
+    deadline: Deadline,
+}
+
+impl TooLate {
+    pub fn deadline(&self) -> Deadline {
+        self.deadline
+    }
+}
+
+impl fmt::Display for TooLate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "orders for {} have closed", self.deadline.phase())
+    }
+}
+
+impl Error for TooLate {}
+
+/// Something a player sent, such as an order, with who sent it, when, and
+/// in which message, so that a dispute over what was ordered and when can
+/// be settled from the record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Envelope<T> {
+    contents: T,
+    submitter: String,
+    timestamp: SystemTime,
+    message_id: Option<String>,
+    revision: u32,
+}
+
+impl<T> Envelope<T> {
+    /// `contents`, sent by `submitter` at `timestamp`.
+    pub fn new(contents: T, submitter: impl Into<String>, timestamp: SystemTime) -> Self {
+        Envelope {
+            contents,
+            submitter: submitter.into(),
+            timestamp,
+            message_id: None,
+            revision: 1,
+        }
+    }
+
+    /// The envelope, noting the id of the message the contents came in,
+    /// such as a chat message or an email's `Message-ID`.
+    pub fn with_message_id(self, message_id: impl Into<String>) -> Self {
+        Envelope {
+            message_id: Some(message_id.into()),
+            ..self
+        }
+    }
+
+    pub fn contents(&self) -> &T {
+        &self.contents
+    }
+
+    pub fn into_contents(self) -> T {
+        self.contents
+    }
+
+    /// Who sent the contents, by whatever id the caller knows them by.
+    pub fn submitter(&self) -> &str {
+        &self.submitter
+    }
+
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    pub fn message_id(&self) -> Option<&str> {
+        self.message_id.as_deref()
+    }
+
+    /// Which of the orders submitted or withdrawn for the same unit this
+    /// is, counting from 1. Set when the envelope is submitted to an
+    /// [`OrderSet`].
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> Envelope<U> {
+        Envelope {
+            contents: f(self.contents),
+            submitter: self.submitter,
+            timestamp: self.timestamp,
+            message_id: self.message_id,
+            revision: self.revision,
+        }
+    }
+}
+
+impl<'m> Envelope<Action<'m>> {
+    /// Writes the envelope, with the power it was for, like `{"power":
+    /// "France", "order": "A Paris - Burgundy", "submitter": "alice",
+    /// "timestamp": 1700000000, "revision": 1}`, or with `"withdraw":
+    /// "Par"` for a withdrawal, and a `"message_id"` if it has one. The
+    /// timestamp is in whole seconds since the Unix epoch.
+    fn to_value(&self, power: Power) -> Value {
+        let mut members = vec![("power".to_owned(), Value::String(power.name().to_owned()))];
+        members.push(match &self.contents {
+            Action::Order(order) => ("order".to_owned(), Value::String(order.to_string())),
+            Action::Withdraw(province) => (
+                "withdraw".to_owned(),
+                Value::String(province.abbreviation().to_owned()),
+            ),
+        });
+        members.push((
+            "submitter".to_owned(),
+            Value::String(self.submitter.clone()),
+        ));
+        let seconds = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        members.push(("timestamp".to_owned(), Value::Number(seconds as f64)));
+        if let Some(message_id) = &self.message_id {
+            members.push(("message_id".to_owned(), Value::String(message_id.clone())));
+        }
+        members.push(("revision".to_owned(), Value::Number(self.revision as f64)));
+        Value::Object(members)
+    }
+
+    /// Reads an envelope written by [`to_value`](Self::to_value).
+    fn from_value(map: &'m Map, value: &Value) -> Result<(Power, Self), StateError> {
+        let bad = || {
+            StateError::new(
+                "an envelope needs a power, an order or withdrawal, a submitter, \
+                 a timestamp in seconds and a revision",
+            )
+        };
+        let text = |key| value.get(key).and_then(Value::as_str);
+        let number = |key| match value.get(key) {
+            Some(Value::Number(n)) if *n >= 0.0 && n.fract() == 0.0 => Some(*n),
+            _ => None,
+        };
+        let power: Power = text("power")
+            .ok_or_else(bad)?
+            .parse()
+            .map_err(|e| StateError::new(format!("{}", e)))?;
+        let contents = match (text("order"), text("withdraw")) {
+            (Some(order), None) => {
+                Action::Order(parse_order(map, order).map_err(|e| StateError::new(e.to_string()))?)
+            }
+            (None, Some(province)) => Action::Withdraw(
+                map.find_by_abbreviation(province)
+                    .ok_or_else(|| StateError::new(format!("no province {:?}", province)))?,
+            ),
+            _ => return Err(bad()),
+        };
+        let envelope = Envelope {
+            contents,
+            submitter: text("submitter").ok_or_else(bad)?.to_owned(),
+            timestamp: UNIX_EPOCH
+                + Duration::from_secs(number("timestamp").ok_or_else(bad)? as u64),
+            message_id: text("message_id").map(str::to_owned),
+            revision: number("revision").ok_or_else(bad)? as u32,
+        };
+        Ok((power, envelope))
+    }
+}
+
+/// What an envelope in an [`OrderSet`]'s audit trail asked for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action<'m> {
+    /// An order for a unit, replacing any it had.
+    Order(Order<'m>),
+    /// Taking back the order for the unit in the province.
+    Withdraw(&'m Province),
+}
+
+impl<'m> Action<'m> {
+    /// The province of the unit the action is for.
+    pub fn province(&self) -> &'m Province {
+        match self {
+            Action::Order(order) => order.province(),
+            Action::Withdraw(province) => province,
+        }
+    }
+}
+
+impl<'m> fmt::Display for Action<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::Order(order) => write!(f, "{}", order),
+            Action::Withdraw(province) => write!(f, "withdraw the order for {}", province),
+        }
+    }
+}
+
+/// How one unit's order changed between two submissions.
+///
+/// Displayed for confirming the change to the player, as in `changed:
+/// A Paris now moves to Picardy (was Burgundy)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrderChange<'m> {
+    Added {
+        power: Power,
+        order: Order<'m>,
+    },
+    Removed {
+        power: Power,
+        order: Order<'m>,
+    },
+    Modified {
+        power: Power,
+        old: Order<'m>,
+        new: Order<'m>,
+    },
+}
+
+impl<'m> OrderChange<'m> {
+    pub fn power(&self) -> Power {
+        match self {
+            OrderChange::Added { power, .. }
+            | OrderChange::Removed { power, .. }
+            | OrderChange::Modified { power, .. } => *power,
+        }
+    }
+
+    /// The province of the unit whose order changed.
+    pub fn province(&self) -> &'m Province {
+        match self {
+            OrderChange::Added { order, .. } | OrderChange::Removed { order, .. } => {
+                order.province()
+            }
+            OrderChange::Modified { new, .. } => new.province(),
+        }
+    }
+}
+
+impl<'m> fmt::Display for OrderChange<'m> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrderChange::Added { order, .. } => write!(f, "added: {}", order),
+            OrderChange::Removed { order, .. } => write!(f, "removed: {}", order),
+            OrderChange::Modified {
+                old: Order::Move(old),
+                new: Order::Move(new),
+                ..
+            } if old.unit() == new.unit() => {
+                if let Some(unit) = new.unit() {
+                    write!(f, "changed: {} ", unit)?;
+                } else {
+                    f.write_str("changed: ")?;
+                }
+                write!(
+                    f,
+                    "{} now moves to {} (was {})",
+                    new.province(),
+                    new.destination(),
+                    old.destination()
+                )
+            }
+            OrderChange::Modified { old, new, .. } => {
+                write!(f, "changed: {} (was {})", new, old)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::parser::parse_order;
+    use crate::phase::Phase;
+    use crate::schedule::Cadence;
+
+    #[test]
+    fn diff() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let mut first = OrderSet::new(map);
+        first.submit(Power::France, order("A Par - Bur"));
+        first.submit(Power::France, order("A Mar H"));
+        first.submit(Power::France, order("F Bre - Mao"));
+        first.submit(Power::Germany, order("A Mun - Bur"));
+
+        let mut second = first.clone();
+        assert_eq!(
+            second.submit(Power::France, order("A Paris to Picardy")),
+            Some(order("A Par - Bur"))
+        );
+        second.submit(Power::France, order("Marseilles S Paris - Burgundy"));
+        second.withdraw(Power::Germany, map.find_by_abbreviation("Mun").unwrap());
+        second.submit(Power::Germany, order("F Kie - Den"));
+        // The same order, written differently, is no change.
+        second.submit(Power::France, order("Brest - Mid-Atlantic"));
+        assert_eq!(second.len(), 4);
+
+        let changes: Vec<_> = first.diff(&second).iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            [
+                "changed: A Marseilles S A Paris - Burgundy (was A Marseilles H)",
+                "changed: A Paris now moves to Picardy (was Burgundy)",
+                "added: F Kiel - Denmark",
+                "removed: A Munich - Burgundy",
+            ]
+        );
+        assert!(second.diff(&second).is_empty());
+    }
+
+    #[test]
+    fn audit_trail() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let paris = map.find_by_abbreviation("Par").unwrap();
+        let start = SystemTime::UNIX_EPOCH;
+        let mut orders = OrderSet::new(map);
+        orders.submit_enveloped(
+            Power::France,
+            Envelope::new(order("A Par - Bur"), "alice", start).with_message_id("m1"),
+        );
+        orders.submit_enveloped(
+            Power::France,
+            Envelope::new(order("A Mar H"), "alice", start),
+        );
+        let later = start + std::time::Duration::from_secs(60);
+        assert_eq!(
+            orders.submit_enveloped(
+                Power::France,
+                Envelope::new(order("A Par - Pic"), "alice", later).with_message_id("m2"),
+            ),
+            Some(order("A Par - Bur"))
+        );
+
+        let envelope = orders.envelope(Power::France, paris).unwrap();
+        assert_eq!(envelope.contents(), &Action::Order(order("A Par - Pic")));
+        assert_eq!(envelope.timestamp(), later);
+        assert_eq!(envelope.message_id(), Some("m2"));
+        assert_eq!(envelope.revision(), 2);
+        let revisions: Vec<u32> = orders.trail().iter().map(|(_, e)| e.revision()).collect();
+        assert_eq!(revisions, [1, 1, 2]);
+
+        // An order given without an envelope has none.
+        orders.submit(Power::France, order("A Par H"));
+        assert_eq!(orders.envelope(Power::France, paris), None);
+        assert_eq!(orders.trail().len(), 3);
+
+        // A withdrawal in an envelope is the unit's next revision.
+        let marseilles = map.find_by_abbreviation("Mar").unwrap();
+        assert_eq!(
+            orders.withdraw_enveloped(Power::France, Envelope::new(marseilles, "alice", later)),
+            Some(order("A Mar H"))
+        );
+        let (_, withdrawal) = orders.trail().last().unwrap();
+        assert_eq!(withdrawal.contents(), &Action::Withdraw(marseilles));
+        assert_eq!(withdrawal.revision(), 2);
+        assert_eq!(
+            withdrawal.contents().to_string(),
+            "withdraw the order for Marseilles"
+        );
+
+        let saved = Value::Object(orders.to_members());
+        let restored = OrderSet::from_value(map, &saved).unwrap();
+        assert_eq!(restored.trail(), orders.trail());
+        assert_eq!(restored.orders(Power::France), orders.orders(Power::France));
+        assert_eq!(Value::Object(restored.to_members()), saved);
+    }
+
+    #[test]
+    fn deadline() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let start = SystemTime::UNIX_EPOCH;
+        let cadence = Cadence {
+            grace: Duration::from_secs(60),
+            ..Cadence::default()
+        };
+        let deadline = cadence.deadline(Phase::FIRST, start);
+        let mut orders = OrderSet::new(map);
+        orders.set_deadline(Some(deadline));
+
+        let in_grace = deadline.due() + Duration::from_secs(30);
+        let on_time = Envelope::new(order("A Par - Bur"), "alice", in_grace);
+        assert_eq!(orders.submit_on_time(Power::France, on_time), Ok(None));
+        let late = Envelope::new(order("A Par - Pic"), "alice", deadline.closes());
+        let error = orders.submit_on_time(Power::France, late).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "orders for Spring 1901 Movement have closed"
+        );
+        assert_eq!(orders.late().len(), 1);
+        assert_eq!(orders.orders(Power::France), [order("A Par - Bur")]);
+
+        // The GM can still put a late order in.
+        let late = Envelope::new(order("A Par - Pic"), "gm", deadline.closes());
+        orders.submit_enveloped(Power::France, late);
+        assert_eq!(orders.orders(Power::France), [order("A Par - Pic")]);
+    }
+
+    #[test]
+    fn concurrent_submissions() {
+        let map = Map::standard();
+        let order = |text| parse_order(map, text).unwrap();
+        let orders = OrderBox::new(map);
+        let moves = ["A Par - Bur", "A Par - Pic", "A Par - Gas"];
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        for text in moves {
+                            orders.submit(Power::France, order(text));
+                        }
+                        orders.submit(Power::Germany, order("A Mun - Ruh"));
+                    }
+                });
+            }
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    assert!(orders.snapshot().len() <= 2);
+                }
+            });
+        });
+
+        // Whichever thread was last, each unit has one order, and Paris's
+        // is the last of the moves.
+        let taken = orders.take();
+        assert_eq!(taken.len(), 2);
+        assert_eq!(taken.orders(Power::France), [order("A Par - Gas")]);
+        assert!(orders.snapshot().is_empty());
+    }
+}
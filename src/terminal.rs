@@ -0,0 +1,204 @@
+//! Text summaries of game states, for following a game in a terminal.
+//!
+//! A summary starts with the phase, then a line for each power still in
+//! the game: its supply center count and units, any units dislodged in a
+//! retreat phase, and the builds or disbands due in an adjustment phase.
+//! Below that the board is listed region by region, showing only the
+//! provinces with a supply center or a unit. A center is marked with `*`
+//! and the initial of its owner, if any, and a unit is written with its
+//! type and its power's initial:
+//!
+//! ```text
+//! Low Countries  Bel*G AG  Hol*
+//! ```
+//!
+//! is a German army in German-owned Belgium, and an unowned Holland.
+//! Provinces in no region are listed last, as "Elsewhere". With the
+//! `colored` feature, [`coloured_summary`] also writes each power's names
+//! and letters in its colour.
+
+use std::fmt::Write;
+
+#[cfg(feature = "colored")]
+use colored::{Color, Colorize};
+
+use crate::map::Province;
+use crate::phase::PhaseKind;
+use crate::power::Power;
+use crate::state::{unit_text, GameState};
+
+/// How wide a summary tries to keep its lines.
+const WIDTH: usize = 80;
+
+/// Summarizes `state` as plain text.
+pub fn summary(state: &GameState) -> String {
+    write_summary(state, &|text, _| text.to_owned())
+}
+
+/// Summarizes `state` with ANSI colour codes, for printing to a terminal.
+#[cfg(feature = "colored")]
+pub fn coloured_summary(state: &GameState) -> String {
+    write_summary(state, &|text, power| {
+        text.color(terminal_colour(power)).to_string()
+    })
+}
+
+/// The terminal colour closest to each power's colour on the board.
+#[cfg(feature = "colored")]
+fn terminal_colour(power: Power) -> Color {
+    match power {
+        Power::Austria => Color::Red,
+        Power::England => Color::Blue,
+        Power::France => Color::BrightCyan,
+        Power::Germany => Color::BrightBlack,
+        Power::Italy => Color::Green,
+        Power::Russia => Color::Magenta,
+        Power::Turkey => Color::Yellow,
+    }
+}
+
+/// Writes the summary, passing each power's name or initial through
+/// `paint` with the power.
+fn write_summary(state: &GameState, paint: &dyn Fn(&str, Power) -> String) -> String {
+    let map = state.map();
+    let mut text = format!("{}\n\n", state.phase());
+
+    let name_width = Power::ALL.iter().map(|power| power.name().len()).max();
+    for power in Power::ALL {
+        let units: Vec<String> = state
+            .units()
+            .filter(|(_, unit)| unit.power() == power)
+            .map(|(province, unit)| unit_text(map, province, unit))
+            .collect();
+        let centers = state.center_count(power);
+        if units.is_empty() && centers == 0 {
+            continue;
+        }
+        let name = format!("{:1$}", power.name(), name_width.unwrap_or(0));
+        let _ = write!(
+            text,
+            "{}  {:2} {}  {}",
+            paint(&name, power),
+            centers,
+            if centers == 1 { "center " } else { "centers" },
+            units.join(", ")
+        );
+        let dislodged: Vec<String> = state
+            .dislodged()
+            .filter(|(_, unit)| unit.power() == power)
+            .map(|(province, unit)| unit_text(map, province, unit))
+            .collect();
+        if !dislodged.is_empty() {
+            let _ = write!(text, "; dislodged {}", dislodged.join(", "));
+        }
+        if state.phase().kind == PhaseKind::Adjustments {
+            match state.adjustment(power) {
+                0 => {}
+                n if n > 0 => {
+                    let _ = write!(text, "; builds {}", n);
+                }
+                n => {
+                    let _ = write!(text, "; disbands {}", -n);
+                }
+            }
+        }
+        text.push('\n');
+    }
+
+    let mut groups: Vec<(&str, Vec<&Province>)> = map
+        .regions()
+        .map(|region| (region, map.provinces_in_region(region).collect()))
+        .collect();
+    let elsewhere = map
+        .provinces()
+        .filter(|province| province.regions().next().is_none())
+        .collect();
+    groups.push(("Elsewhere", elsewhere));
+    let label_width = groups.iter().map(|(label, _)| label.len()).max();
+    let label_width = label_width.unwrap_or(0) + 2;
+    text.push('\n');
+    for (label, provinces) in groups {
+        let cells: Vec<(String, usize)> = provinces
+            .into_iter()
+            .filter_map(|province| cell(state, province, paint))
+            .collect();
+        if cells.is_empty() {
+            continue;
+        }
+        let _ = write!(text, "{:1$}", label, label_width);
+        let mut column = label_width;
+        for (i, (cell, width)) in cells.into_iter().enumerate() {
+            if i > 0 && column + 2 + width > WIDTH {
+                let _ = write!(text, "\n{:1$}", "", label_width);
+                column = label_width;
+            } else if i > 0 {
+                text.push_str("  ");
+                column += 2;
+            }
+            text.push_str(&cell);
+            column += width;
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// How `province` is listed, and its width without colour codes, or
+/// `None` if it has neither a supply center nor a unit.
+fn cell(
+    state: &GameState,
+    province: &Province,
+    paint: &dyn Fn(&str, Power) -> String,
+) -> Option<(String, usize)> {
+    let unit = state.unit_at(province);
+    if !province.is_supply_center() && unit.is_none() {
+        return None;
+    }
+    let initial = |power: Power| paint(&power.name()[..1], power);
+    let mut cell = province.abbreviation().to_owned();
+    let mut width = cell.len();
+    if province.is_supply_center() {
+        cell.push('*');
+        width += 1;
+        if let Some(owner) = state.owner(province) {
+            cell.push_str(&initial(owner));
+            width += 1;
+        }
+    }
+    if let Some(unit) = unit {
+        let letter = match unit.kind(state.map()) {
+            Some(kind) => kind.abbreviation().to_owned(),
+            None => unit.unit_type().to_string(),
+        };
+        let _ = write!(cell, " {}{}", letter, initial(unit.power()));
+        width += 2 + letter.len();
+    }
+    Some((cell, width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_start() {
+        let text = summary(&GameState::standard());
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Spring 1901 Movement");
+        assert_eq!(lines[2], "Austria   3 centers  A Bud, F Tri, A Vie");
+        assert!(lines.contains(&"Low Countries  Bel*  Hol*"));
+        assert!(lines.contains(&"Iberia         Por*  Spa*"));
+        assert!(lines.iter().all(|line| line.len() <= WIDTH));
+        assert!(text.contains("StP*R FR"));
+        assert!(!text.contains('\u{1b}'));
+    }
+
+    #[cfg(feature = "colored")]
+    #[test]
+    fn coloured() {
+        colored::control::set_override(true);
+        let text = coloured_summary(&GameState::standard());
+        assert!(text.contains(&"Austria".red().to_string()));
+        assert_eq!(text.lines().next(), Some("Spring 1901 Movement"));
+    }
+}
@@ -0,0 +1,104 @@
+//! Tracing of the parser and adjudicator, for diagnosing engine bugs in
+//! complicated positions from a bot's logs.
+//!
+//! With the `trace-adjudication` feature, `trace_span!` and
+//! `trace_event!` are `tracing`'s `debug_span!` and `trace!`: parsing,
+//! each order's legality check and each adjudication get a span, and the
+//! resolver records every decision it settles, every strength it works
+//! out, every support it finds cut and every unit it finds dislodged.
+//! Without the feature they compile to nothing, arguments and all.
+
+/// Enters a span until the end of the enclosing block, taking the
+/// arguments of `tracing::debug_span!`.
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "trace-adjudication")]
+        let _span = tracing::debug_span!($($arg)*).entered();
+    };
+}
+
+/// Records an event, taking the arguments of `tracing::trace!`.
+#[allow(unused_macros)]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "trace-adjudication")]
+        tracing::trace!($($arg)*);
+    };
+}
+
+#[cfg(all(test, feature = "trace-adjudication"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::adjudicator::adjudicate;
+    use crate::map::Map;
+    use crate::parser::parse_order;
+    use crate::power::Power;
+    use crate::state::{GameState, Unit};
+
+    /// Keeps the message of every event.
+    #[derive(Clone, Default)]
+    struct Messages(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Messages {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "message" {
+                self.0.lock().unwrap().push(format!("{:?}", value));
+            }
+        }
+    }
+
+    impl Subscriber for Messages {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event) {
+            event.record(&mut self.clone());
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn adjudication_is_traced() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut state = GameState::new(map);
+        state.place_unit(province("Par"), Unit::army(Power::France));
+        state.place_unit(province("Bur"), Unit::army(Power::Germany));
+        state.place_unit(province("Mar"), Unit::army(Power::France));
+        state.place_unit(province("Gas"), Unit::army(Power::Germany));
+        let order = |text| parse_order(map, text).unwrap();
+        let orders = HashMap::from([
+            (
+                Power::France,
+                vec![order("A Par - Bur"), order("A Mar S A Par - Bur")],
+            ),
+            (Power::Germany, vec![order("A Gas - Mar")]),
+        ]);
+
+        let messages = Messages::default();
+        tracing::subscriber::with_default(messages.clone(), || adjudicate(orders, &state));
+        let messages = messages.0.lock().unwrap();
+        assert!(messages.iter().any(|m| m == "support cut"));
+        assert!(messages.iter().any(|m| m == "move strengths"));
+        assert!(!messages.iter().any(|m| m == "dislodged"));
+    }
+}
@@ -0,0 +1,167 @@
+//! Draw votes and concessions, and how a game ends.
+//!
+//! Organized games rarely end with a power holding a majority of the
+//! supply centers on the board. More often the survivors agree to stop:
+//! a draw shared by all of them (DIAS), a draw shared by some of them, or
+//! a concession to one of them.
+
+use std::collections::HashMap;
+
+use crate::power::Power;
+use crate::state::GameState;
+
+/// A way of ending the game early, put to a vote.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Proposal {
+    /// A draw including all survivors.
+    Dias,
+    /// A draw shared by the named powers only.
+    Draw(Vec<Power>),
+    /// A concession of the game to one power.
+    Concede(Power),
+}
+
+/// How a game ended.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The power won alone, by holding a majority of the supply centers
+    /// or by the others conceding.
+    Solo(Power),
+    /// The powers shared a draw, in [`Power::ALL`] order.
+    Draw(Vec<Power>),
+}
+
+/// The votes cast on one proposal.
+///
+/// A proposal passes when every surviving power that still has a player
+/// votes for it. Powers in civil disorder have no vote, but survivors
+/// among them share a DIAS.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ballot {
+    proposal: Proposal,
+    votes: HashMap<Power, bool>,
+}
+
+impl Ballot {
+    pub fn new(proposal: Proposal) -> Self {
+        Ballot {
+            proposal,
+            votes: HashMap::new(),
+        }
+    }
+
+    pub fn proposal(&self) -> &Proposal {
+        &self.proposal
+    }
+
+    /// Records `power`'s vote, replacing any it cast before.
+    pub fn vote(&mut self, power: Power, yes: bool) {
+        self.votes.insert(power, yes);
+    }
+
+    /// How `power` voted, if it has.
+    pub fn vote_of(&self, power: Power) -> Option<bool> {
+        self.votes.get(&power).copied()
+    }
+
+    /// The powers whose votes count in `state`.
+    pub fn voters(state: &GameState) -> Vec<Power> {
+        state
+            .survivors()
+            .into_iter()
+            .filter(|&power| !state.in_civil_disorder(power))
+            .collect()
+    }
+
+    /// How the game ends if the proposal has passed in `state`.
+    ///
+    /// A draw names only the powers that are still in the game, and a
+    /// proposal for an eliminated power, or with no one left to vote,
+    /// never passes.
+    pub fn outcome(&self, state: &GameState) -> Option<Outcome> {
+        let voters = Ballot::voters(state);
+        let passed = voters
+            .iter()
+            .all(|&power| self.vote_of(power) == Some(true));
+        if voters.is_empty() || !passed {
+            return None;
+        }
+        let survivors = state.survivors();
+        match &self.proposal {
+            Proposal::Dias => Some(Outcome::Draw(survivors)),
+            Proposal::Draw(powers) => {
+                let powers: Vec<Power> = survivors
+                    .into_iter()
+                    .filter(|power| powers.contains(power))
+                    .collect();
+                if powers.is_empty() {
+                    None
+                } else {
+                    Some(Outcome::Draw(powers))
+                }
+            }
+            Proposal::Concede(power) => {
+                if survivors.contains(power) {
+                    Some(Outcome::Solo(*power))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Map;
+    use crate::state::Unit;
+
+    fn state() -> GameState<'static> {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let mut state = GameState::new(map);
+        state.place_unit(province("Par"), Unit::army(Power::France));
+        state.place_unit(province("Ber"), Unit::army(Power::Germany));
+        state.set_owner(province("Mos"), Power::Russia);
+        state
+    }
+
+    #[test]
+    fn every_voter_must_agree() {
+        let state = state();
+        let mut ballot = Ballot::new(Proposal::Dias);
+        ballot.vote(Power::France, true);
+        ballot.vote(Power::Germany, true);
+        assert_eq!(ballot.outcome(&state), None);
+        ballot.vote(Power::Russia, false);
+        assert_eq!(ballot.outcome(&state), None);
+        ballot.vote(Power::Russia, true);
+        let survivors = vec![Power::France, Power::Germany, Power::Russia];
+        assert_eq!(ballot.outcome(&state), Some(Outcome::Draw(survivors)));
+    }
+
+    #[test]
+    fn draws_and_concessions() {
+        let mut state = state();
+        state.set_civil_disorder(Power::Russia, true);
+        assert_eq!(Ballot::voters(&state), [Power::France, Power::Germany]);
+
+        let mut draw = Ballot::new(Proposal::Draw(vec![Power::Germany, Power::France]));
+        let mut concession = Ballot::new(Proposal::Concede(Power::Germany));
+        let mut eliminated = Ballot::new(Proposal::Concede(Power::Italy));
+        for ballot in [&mut draw, &mut concession, &mut eliminated] {
+            ballot.vote(Power::France, true);
+            ballot.vote(Power::Germany, true);
+        }
+        assert_eq!(
+            draw.outcome(&state),
+            Some(Outcome::Draw(vec![Power::France, Power::Germany]))
+        );
+        assert_eq!(
+            concession.outcome(&state),
+            Some(Outcome::Solo(Power::Germany))
+        );
+        assert_eq!(eliminated.outcome(&state), None);
+    }
+}
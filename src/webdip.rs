@@ -0,0 +1,244 @@
+//! Positions from webDiplomacy, so players can bring an online game in
+//! for analysis.
+//!
+//! The format is the board data webDiplomacy's API gives for a game, with
+//! territories and countries by name rather than by the variant's ids:
+//!
+//! ```json
+//! {
+//!   "turn": 2,
+//!   "phase": "Retreats",
+//!   "units": [
+//!     {"country": "France", "unitType": "Army", "terr": "Paris"},
+//!     {"country": "Russia", "unitType": "Fleet", "terr": "St. Petersburg (South Coast)"},
+//!     {"country": "Austria", "unitType": "Army", "terr": "Trieste", "retreating": "Yes"}
+//!   ],
+//!   "centers": [{"country": "France", "terr": "Paris"}],
+//!   "standoffs": ["Tyrolia"],
+//!   "occupiedFrom": {"Trieste": "Venice"}
+//! }
+//! ```
+//!
+//! Turn 0 is Spring 1901, turn 1 Autumn 1901, and so on, and the phase is
+//! one of "Pre-game", "Diplomacy", "Retreats" and "Builds". Countries may
+//! also be given by `"countryID"`, numbered as on the classic map, from
+//! England (1) to Russia (7). Retreating units are dislodged, and may
+//! retreat anywhere but where their attackers came from, as given by
+//! `"occupiedFrom"`, and where there were standoffs.
+
+use crate::json::{self, Value};
+use crate::map::{Coast, Map, Province, ProvinceSet};
+use crate::phase::{Phase, PhaseKind, Season};
+use crate::power::Power;
+use crate::state::{GameState, StateError, Unit};
+
+/// The classic map's countries, by webDiplomacy's `countryID` less one.
+const COUNTRIES: [Power; 7] = [
+    Power::England,
+    Power::France,
+    Power::Italy,
+    Power::Germany,
+    Power::Austria,
+    Power::Turkey,
+    Power::Russia,
+];
+
+/// Reads a webDiplomacy position for `map`.
+pub fn import<'m>(map: &'m Map, text: &str) -> Result<GameState<'m>, StateError> {
+    let value = json::parse(text).map_err(|e| StateError::new(e.to_string()))?;
+    let mut state = GameState::new(map);
+    let turn = match value.get("turn") {
+        Some(Value::Number(turn)) if turn.fract() == 0.0 && (0.0..65535.0).contains(turn) => {
+            *turn as u16
+        }
+        _ => return Err(StateError::new("\"turn\" must be a turn number")),
+    };
+    let season = if turn % 2 == 0 {
+        Season::Spring
+    } else {
+        Season::Fall
+    };
+    let year = 1901 + turn / 2;
+    state.set_phase(match value.get("phase").and_then(Value::as_str) {
+        Some("Pre-game") | Some("Diplomacy") => Phase::movement(season, year),
+        Some("Retreats") => Phase::new(season, year, PhaseKind::Retreats),
+        Some("Builds") => Phase::new(Season::Winter, year, PhaseKind::Adjustments),
+        _ => return Err(StateError::new("unknown \"phase\"")),
+    });
+
+    let mut dislodged = Vec::new();
+    for entry in list(&value, "units")? {
+        let power = country(entry)?;
+        let (province, coast) = territory(map, field(entry, "terr")?)?;
+        let unit_type = field(entry, "unitType")?;
+        let unit = match (unit_type, coast) {
+            ("Army", None) => Unit::army(power),
+            ("Fleet", None) if !province.is_bicoastal() => Unit::fleet(power),
+            ("Fleet", Some(coast)) => Unit::fleet_on(power, coast),
+            _ => {
+                return Err(StateError::new(format!(
+                    "no {} can be in {}",
+                    unit_type, province
+                )))
+            }
+        };
+        if entry.get("retreating").and_then(Value::as_str) == Some("Yes") {
+            dislodged.push((province, unit));
+        } else if state.place_unit(province, unit).is_some() {
+            return Err(StateError::new(format!("two units in {}", province)));
+        }
+    }
+    for entry in list(&value, "centers")? {
+        let power = country(entry)?;
+        let (province, _) = territory(map, field(entry, "terr")?)?;
+        if !province.is_supply_center() {
+            return Err(StateError::new(format!(
+                "{} is not a supply center",
+                province
+            )));
+        }
+        state.set_owner(province, power);
+    }
+
+    let mut standoffs = ProvinceSet::new(map);
+    for terr in list(&value, "standoffs")? {
+        let terr = terr
+            .as_str()
+            .ok_or_else(|| StateError::new("\"standoffs\" must list territories"))?;
+        standoffs.insert(territory(map, terr)?.0);
+    }
+    let occupied_from = match value.get("occupiedFrom") {
+        None => &[][..],
+        Some(members) => members.as_object().ok_or_else(|| {
+            StateError::new("\"occupiedFrom\" must map territories to territories")
+        })?,
+    };
+    for (province, unit) in dislodged {
+        let mut attacker = None;
+        for (to, from) in occupied_from {
+            if territory(map, to)?.0 == province {
+                let from = from.as_str().ok_or_else(|| {
+                    StateError::new("\"occupiedFrom\" must map territories to territories")
+                })?;
+                attacker = Some(territory(map, from)?.0);
+            }
+        }
+        state.dislodge(province, unit, attacker, standoffs);
+    }
+    Ok(state)
+}
+
+/// The array `key` of `value`, or nothing if it is missing.
+fn list<'v>(value: &'v Value, key: &str) -> Result<&'v [Value], StateError> {
+    match value.get(key) {
+        None => Ok(&[]),
+        Some(list) => list
+            .as_array()
+            .ok_or_else(|| StateError::new(format!("{:?} must be a list", key))),
+    }
+}
+
+fn field<'v>(value: &'v Value, key: &str) -> Result<&'v str, StateError> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| StateError::new(format!("expected {:?} in {}", key, value)))
+}
+
+/// The power named by `"country"`, or numbered by `"countryID"`.
+fn country(value: &Value) -> Result<Power, StateError> {
+    if let Some(Value::Number(id)) = value.get("countryID") {
+        return COUNTRIES
+            .iter()
+            .zip(1..)
+            .find(|&(_, n)| f64::from(n) == *id)
+            .map(|(&power, _)| power)
+            .ok_or_else(|| StateError::new(format!("unknown countryID {}", id)));
+    }
+    field(value, "country")?
+        .parse()
+        .map_err(|e| StateError::new(format!("{}", e)))
+}
+
+/// Reads a territory like "Paris" or "Spain (North Coast)".
+fn territory<'m>(map: &'m Map, text: &str) -> Result<(&'m Province, Option<Coast>), StateError> {
+    let unknown = || StateError::new(format!("unknown territory {:?}", text));
+    let (name, coast) = match text.strip_suffix(')').and_then(|t| t.split_once(" (")) {
+        Some((name, coast)) => {
+            let coast = [Coast::North, Coast::South, Coast::East, Coast::West]
+                .iter()
+                .copied()
+                .find(|c| c.name().eq_ignore_ascii_case(coast))
+                .ok_or_else(unknown)?;
+            (name, Some(coast))
+        }
+        None => (text, None),
+    };
+    let province = map.province_named(name.trim()).ok_or_else(unknown)?;
+    match coast {
+        Some(coast) if !province.coasts().contains(&coast) => Err(unknown()),
+        _ => Ok((province, coast)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_retreats() {
+        let map = Map::standard();
+        let province = |abbreviation| map.find_by_abbreviation(abbreviation).unwrap();
+        let state = import(
+            map,
+            r#"{
+                "turn": 3,
+                "phase": "Retreats",
+                "units": [
+                    {"countryID": 3, "unitType": "Army", "terr": "Trieste"},
+                    {"countryID": 3, "unitType": "Army", "terr": "Tyrolia"},
+                    {"country": "Russia", "unitType": "Fleet", "terr": "St. Petersburg (North Coast)"},
+                    {"country": "Austria", "unitType": "Army", "terr": "Trieste", "retreating": "Yes"}
+                ],
+                "centers": [{"country": "Austria", "terr": "Vienna"}, {"countryID": 7, "terr": "Sevastopol"}],
+                "standoffs": ["Budapest"],
+                "occupiedFrom": {"Trieste": "Venice"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            state.phase(),
+            Phase::new(Season::Fall, 1902, PhaseKind::Retreats)
+        );
+        assert_eq!(
+            state.unit_at(province("Tri")),
+            Some(&Unit::army(Power::Italy))
+        );
+        assert_eq!(
+            state.unit_at(province("StP")),
+            Some(&Unit::fleet_on(Power::Russia, Coast::North))
+        );
+        assert_eq!(state.owner(province("Sev")), Some(Power::Russia));
+        assert_eq!(
+            state.dislodged_unit(province("Tri")),
+            Some(&Unit::army(Power::Austria))
+        );
+        let mut retreats: Vec<_> = state
+            .retreats(province("Tri"))
+            .iter()
+            .map(|(p, _)| p.abbreviation())
+            .collect();
+        retreats.sort_unstable();
+        assert_eq!(retreats, ["Alb", "Ser", "Vie"]);
+
+        let bad = [
+            r#"{"turn": 0, "phase": "Finished"}"#,
+            r#"{"turn": 0, "phase": "Diplomacy", "units": [{"country": "Russia", "unitType": "Fleet", "terr": "St. Petersburg"}]}"#,
+            r#"{"turn": 0, "phase": "Diplomacy", "centers": [{"countryID": 8, "terr": "Paris"}]}"#,
+            r#"{"turn": 0, "phase": "Diplomacy", "centers": [{"country": "France", "terr": "Picardy"}]}"#,
+        ];
+        for text in bad {
+            assert!(import(map, text).is_err(), "{}", text);
+        }
+    }
+}